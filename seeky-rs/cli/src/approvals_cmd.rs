@@ -0,0 +1,118 @@
+//! `seeky approvals`: list and remove the approval rules saved in
+//! `$SEEKY_HOME/approvals.toml` and in the project's `.seeky/approvals.toml`.
+
+use clap::Parser;
+use seeky_core::approval_rules::ApprovalRules;
+use seeky_core::approval_rules::RuleDecision;
+use seeky_core::approval_rules::RuleSource;
+use seeky_core::approval_rules::SavedRule;
+use seeky_core::config::find_seeky_home;
+
+#[derive(Debug, Parser)]
+pub struct ApprovalsCli {
+    #[command(subcommand)]
+    pub cmd: ApprovalsCommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum ApprovalsCommand {
+    /// List the approval rules of the user and of the project in the current
+    /// directory.
+    List {
+        /// Print the rules as JSON.
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+
+    /// Remove an approval rule.
+    Remove {
+        /// Number of the rule, as printed by `seeky approvals list`.
+        #[arg(value_parser = clap::value_parser!(u64).range(1..))]
+        number: u64,
+    },
+}
+
+pub fn run_main(cli: ApprovalsCli) -> anyhow::Result<()> {
+    let seeky_home = find_seeky_home()?;
+    let rules = ApprovalRules::new(&seeky_home, &std::env::current_dir()?);
+
+    match cli.cmd {
+        ApprovalsCommand::List { json } => {
+            let saved = rules.load()?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&saved)?);
+            } else if saved.is_empty() {
+                println!(
+                    "No approval rules in {} or {}.",
+                    rules.file(RuleSource::User).display(),
+                    rules.file(RuleSource::Project).display()
+                );
+            } else {
+                print_table(&saved);
+            }
+        }
+        ApprovalsCommand::Remove { number } => {
+            let index = usize::try_from(number - 1)?;
+            let removed = rules.remove(index)?;
+            println!(
+                "Removed rule #{number} ({}) from {}",
+                removed.rule.pattern,
+                rules.file(removed.source).display()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn print_table(rules: &[SavedRule]) {
+    let rows: Vec<[String; 5]> = rules
+        .iter()
+        .enumerate()
+        .map(|(index, saved)| {
+            [
+                (index + 1).to_string(),
+                match saved.rule.decision {
+                    _ if saved.is_ignored() => "allow (ignored)",
+                    RuleDecision::Allow => "allow",
+                    RuleDecision::Deny => "deny",
+                }
+                .to_string(),
+                saved.rule.pattern.to_string(),
+                saved
+                    .rule
+                    .cwd
+                    .as_ref()
+                    .map(|cwd| cwd.display().to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                match saved.source {
+                    RuleSource::User => "user",
+                    RuleSource::Project => "project",
+                }
+                .to_string(),
+            ]
+        })
+        .collect();
+
+    let header = ["#", "DECISION", "COMMAND", "CWD", "SOURCE"];
+    let mut widths = header.map(|h| h.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: [&str; 5]| {
+        cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    println!("{}", format_row(header));
+    for row in &rows {
+        println!("{}", format_row(row.each_ref().map(String::as_str)));
+    }
+}
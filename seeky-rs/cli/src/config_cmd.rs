@@ -0,0 +1,189 @@
+//! `seeky config`: inspect and edit `config.toml`.
+
+use std::path::Path;
+
+use clap::Parser;
+use seeky_common::CliConfigOverrides;
+use seeky_core::config::Config;
+use seeky_core::config::ConfigOverrides;
+use seeky_core::config::config_toml_path;
+use seeky_core::config::find_seeky_home;
+use seeky_core::config_edit::get_config_value;
+use seeky_core::config_edit::set_config_value;
+use seeky_core::config_edit::validate_config_toml;
+use serde_json::Value as JsonValue;
+
+#[derive(Debug, Parser)]
+pub struct ConfigCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub cmd: ConfigCommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum ConfigCommand {
+    /// Print the effective configuration after merging config.toml, the
+    /// selected profile and any `-c` overrides.
+    Show {
+        /// Print the configuration as JSON.
+        #[arg(long, default_value_t = false)]
+        json: bool,
+
+        /// Configuration profile from config.toml to apply.
+        #[arg(long = "profile", short = 'p')]
+        config_profile: Option<String>,
+    },
+
+    /// Check config.toml for errors and unknown keys.
+    Validate,
+
+    /// Print the value of a (dotted) key as written in config.toml.
+    Get {
+        /// Key to look up, e.g. `model` or `tui.disable_mouse_capture`.
+        key: String,
+    },
+
+    /// Set a (dotted) key in config.toml, preserving comments and formatting.
+    Set {
+        /// Key to set, e.g. `model` or `history.persistence`.
+        key: String,
+
+        /// TOML value; bare words are treated as strings.
+        value: String,
+    },
+
+    /// Print the path to config.toml.
+    Path,
+}
+
+pub fn run_main(cli: ConfigCli) -> anyhow::Result<()> {
+    let ConfigCli {
+        config_overrides,
+        cmd,
+    } = cli;
+    let config_path = config_toml_path(&find_seeky_home()?);
+
+    match cmd {
+        ConfigCommand::Show {
+            json,
+            config_profile,
+        } => {
+            let cli_overrides = config_overrides
+                .parse_overrides()
+                .map_err(|e| anyhow::anyhow!("Error parsing -c overrides: {e}"))?;
+            let overrides = ConfigOverrides {
+                config_profile,
+                ..Default::default()
+            };
+            let config = Config::load_with_cli_overrides(cli_overrides, overrides)?;
+            let mut value = serde_json::to_value(&config)?;
+            redact_env_values(&mut value);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&value)?);
+            } else {
+                let mut lines = Vec::new();
+                flatten_json("", &value, &mut lines);
+                for (key, value) in lines {
+                    println!("{key} = {value}");
+                }
+            }
+        }
+        ConfigCommand::Validate => {
+            let contents = read_config_toml(&config_path)?;
+            let validation = validate_config_toml(&contents);
+            let path = config_path.display();
+            for warning in &validation.warnings {
+                eprintln!("{path}:{warning} (warning)");
+            }
+            for error in &validation.errors {
+                eprintln!("{path}:{error}");
+            }
+            if !validation.is_valid() {
+                std::process::exit(1);
+            }
+            println!("{path} is valid");
+        }
+        ConfigCommand::Get { key } => {
+            let contents = read_config_toml(&config_path)?;
+            match get_config_value(&contents, &key).map_err(anyhow::Error::msg)? {
+                Some(value) => println!("{value}"),
+                None => {
+                    eprintln!("`{key}` is not set in {}", config_path.display());
+                    std::process::exit(1);
+                }
+            }
+        }
+        ConfigCommand::Set { key, value } => {
+            let contents = read_config_toml(&config_path)?;
+            let updated = set_config_value(&contents, &key, &value).map_err(anyhow::Error::msg)?;
+            if let Some(parent) = config_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&config_path, updated)?;
+        }
+        ConfigCommand::Path => {
+            println!("{}", config_path.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Hides the values of the environment variables set for MCP servers and
+/// shell commands, which often hold tokens, keeping their names.
+fn redact_env_values(config: &mut JsonValue) {
+    if let Some(servers) = config
+        .get_mut("mcp_servers")
+        .and_then(JsonValue::as_object_mut)
+    {
+        for env in servers
+            .values_mut()
+            .filter_map(|server| server.get_mut("env"))
+        {
+            redact_values(env);
+        }
+    }
+    if let Some(set) = config.pointer_mut("/shell_environment_policy/set") {
+        redact_values(set);
+    }
+}
+
+fn redact_values(env: &mut JsonValue) {
+    if let Some(env) = env.as_object_mut() {
+        for value in env.values_mut() {
+            *value = JsonValue::String("[REDACTED]".to_string());
+        }
+    }
+}
+
+/// Reads config.toml, treating a missing file as empty.
+fn read_config_toml(path: &Path) -> anyhow::Result<String> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(anyhow::anyhow!("failed to read {}: {e}", path.display())),
+    }
+}
+
+/// Flattens nested JSON objects into `(dotted.key, value)` pairs. Arrays and
+/// scalars are kept as leaf values.
+fn flatten_json(prefix: &str, value: &JsonValue, out: &mut Vec<(String, String)>) {
+    match value {
+        JsonValue::Object(map) if !map.is_empty() => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            for key in keys {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten_json(&path, &map[key], out);
+            }
+        }
+        JsonValue::Null => {}
+        _ => out.push((prefix.to_string(), value.to_string())),
+    }
+}
@@ -75,8 +75,15 @@ async fn run_command_under_sandbox(
 
     let mut child = match sandbox_type {
         SandboxType::Seatbelt => {
-            spawn_command_under_seatbelt(command, &config.sandbox_policy, cwd, stdio_policy, env)
-                .await?
+            spawn_command_under_seatbelt(
+                command,
+                &config.sandbox_policy,
+                &config.sandbox.limits,
+                cwd,
+                stdio_policy,
+                env,
+            )
+            .await?
         }
         SandboxType::Landlock => {
             #[expect(clippy::expect_used)]
@@ -87,6 +94,8 @@ async fn run_command_under_sandbox(
                 seeky_linux_sandbox_exe,
                 command,
                 &config.sandbox_policy,
+                config.sandbox.linux_backend,
+                &config.sandbox.limits,
                 cwd,
                 stdio_policy,
                 env,
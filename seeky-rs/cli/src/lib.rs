@@ -1,6 +1,11 @@
+pub mod approvals_cmd;
+pub mod config_cmd;
 pub mod debug_sandbox;
 mod exit_status;
 pub mod proto;
+pub mod sandbox_cmd;
+pub mod schema_cmd;
+pub mod sessions_cmd;
 
 use clap::Parser;
 use seeky_common::SandboxPermissionOption;
@@ -1,7 +1,13 @@
 use clap::Parser;
 use seeky_cli::LandlockCommand;
 use seeky_cli::SeatbeltCommand;
+use seeky_cli::approvals_cmd::ApprovalsCli;
+use seeky_cli::config_cmd::ConfigCli;
 use seeky_cli::proto;
+use seeky_cli::sandbox_cmd::SandboxCli;
+use seeky_cli::schema_cmd::SchemaCli;
+use seeky_cli::sessions_cmd::SessionsCli;
+use seeky_common::CliConfigOverrides;
 use seeky_exec::Cli as ExecCli;
 use seeky_tui::Cli as TuiCli;
 use std::path::PathBuf;
@@ -33,12 +39,28 @@ enum Subcommand {
     Exec(ExecCli),
 
     /// Experimental: run Seeky as an MCP server.
-    Mcp,
+    Mcp(McpCli),
 
     /// Run the Protocol stream via stdin/stdout
     #[clap(visible_alias = "p")]
     Proto(ProtoCli),
 
+    /// Inspect, validate and edit config.toml.
+    Config(ConfigCli),
+
+    /// Generate the JSON Schema and TypeScript definitions for config.toml or
+    /// the protocol.
+    Schema(SchemaCli),
+
+    /// List, inspect, export and prune saved sessions.
+    Sessions(SessionsCli),
+
+    /// Check the sandbox support of this machine and the effective policy.
+    Sandbox(SandboxCli),
+
+    /// List and remove saved approval rules.
+    Approvals(ApprovalsCli),
+
     /// Internal debugging commands.
     Debug(DebugArgs),
 }
@@ -58,6 +80,12 @@ enum DebugCommand {
     Landlock(LandlockCommand),
 }
 
+#[derive(Debug, Parser)]
+struct McpCli {
+    #[clap(flatten)]
+    config_overrides: CliConfigOverrides,
+}
+
 #[derive(Debug, Parser)]
 struct ReplProto {}
 
@@ -70,20 +98,51 @@ fn main() -> anyhow::Result<()> {
 
 async fn cli_main(seeky_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()> {
     let cli = MultitoolCli::parse();
+    // `-c` overrides given before the subcommand are parsed as part of the
+    // interactive CLI, so forward them to whichever front-end actually runs.
+    let root_config_overrides = cli.interactive.config_overrides.clone();
 
     match cli.subcommand {
         None => {
             seeky_tui::run_main(cli.interactive, seeky_linux_sandbox_exe)?;
         }
-        Some(Subcommand::Exec(exec_cli)) => {
+        Some(Subcommand::Exec(mut exec_cli)) => {
+            prepend_config_overrides(&mut exec_cli.config_overrides, root_config_overrides);
             seeky_exec::run_main(exec_cli, seeky_linux_sandbox_exe).await?;
         }
-        Some(Subcommand::Mcp) => {
-            seeky_mcp_server::run_main(seeky_linux_sandbox_exe).await?;
+        Some(Subcommand::Mcp(mut mcp_cli)) => {
+            prepend_config_overrides(&mut mcp_cli.config_overrides, root_config_overrides);
+            let cli_overrides = mcp_cli
+                .config_overrides
+                .parse_overrides()
+                .map_err(|e| anyhow::anyhow!("Error parsing -c overrides: {e}"))?;
+            seeky_mcp_server::run_main(seeky_linux_sandbox_exe, cli_overrides).await?;
         }
-        Some(Subcommand::Proto(proto_cli)) => {
+        Some(Subcommand::Proto(mut proto_cli)) => {
+            prepend_config_overrides(&mut proto_cli.config_overrides, root_config_overrides);
             proto::run_main(proto_cli).await?;
         }
+        Some(Subcommand::Config(mut config_cli)) => {
+            prepend_config_overrides(&mut config_cli.config_overrides, root_config_overrides);
+            seeky_cli::config_cmd::run_main(config_cli)?;
+        }
+        Some(Subcommand::Schema(schema_cli)) => {
+            seeky_cli::schema_cmd::run_main(schema_cli)?;
+        }
+        Some(Subcommand::Sessions(sessions_cli)) => {
+            seeky_cli::sessions_cmd::run_main(sessions_cli)?;
+        }
+        Some(Subcommand::Approvals(approvals_cli)) => {
+            seeky_cli::approvals_cmd::run_main(approvals_cli)?;
+        }
+        Some(Subcommand::Sandbox(mut sandbox_cli)) => {
+            match &mut sandbox_cli.cmd {
+                seeky_cli::sandbox_cmd::SandboxCommand::Check(check) => {
+                    prepend_config_overrides(&mut check.config_overrides, root_config_overrides);
+                }
+            }
+            seeky_cli::sandbox_cmd::run_main(sandbox_cli, seeky_linux_sandbox_exe).await?;
+        }
         Some(Subcommand::Debug(debug_args)) => match debug_args.cmd {
             DebugCommand::Seatbelt(seatbelt_command) => {
                 seeky_cli::debug_sandbox::run_command_under_seatbelt(
@@ -104,3 +163,14 @@ async fn cli_main(seeky_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
 
     Ok(())
 }
+
+/// Prepends the root-level `-c` overrides to those of a subcommand so that
+/// the subcommand's own overrides take precedence.
+fn prepend_config_overrides(
+    subcommand_overrides: &mut CliConfigOverrides,
+    root_overrides: CliConfigOverrides,
+) {
+    subcommand_overrides
+        .raw_overrides
+        .splice(0..0, root_overrides.raw_overrides);
+}
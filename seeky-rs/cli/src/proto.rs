@@ -2,6 +2,7 @@ use std::io::IsTerminal;
 use std::sync::Arc;
 
 use clap::Parser;
+use seeky_common::CliConfigOverrides;
 use seeky_core::Seeky;
 use seeky_core::config::Config;
 use seeky_core::config::ConfigOverrides;
@@ -13,9 +14,12 @@ use tracing::error;
 use tracing::info;
 
 #[derive(Debug, Parser)]
-pub struct ProtoCli {}
+pub struct ProtoCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+}
 
-pub async fn run_main(_opts: ProtoCli) -> anyhow::Result<()> {
+pub async fn run_main(opts: ProtoCli) -> anyhow::Result<()> {
     if std::io::stdin().is_terminal() {
         anyhow::bail!("Protocol mode expects stdin to be a pipe, not a terminal");
     }
@@ -24,7 +28,11 @@ pub async fn run_main(_opts: ProtoCli) -> anyhow::Result<()> {
         .with_writer(std::io::stderr)
         .init();
 
-    let config = Config::load_with_overrides(ConfigOverrides::default())?;
+    let ProtoCli { config_overrides } = opts;
+    let cli_overrides = config_overrides
+        .parse_overrides()
+        .map_err(|e| anyhow::anyhow!("Error parsing -c overrides: {e}"))?;
+    let config = Config::load_with_cli_overrides(cli_overrides, ConfigOverrides::default())?;
     let ctrl_c = notify_on_sigint();
     let (seeky, _init_id) = Seeky::spawn(config, ctrl_c.clone()).await?;
     let seeky = Arc::new(seeky);
@@ -0,0 +1,388 @@
+//! `seeky sandbox check`: reports which sandbox Seeky would use, what the
+//! kernel supports, and whether commands run under the effective policy can
+//! actually do what the policy says they can.
+
+use std::net::TcpListener;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use clap::Parser;
+use seeky_common::CliConfigOverrides;
+use seeky_common::SandboxPermissionOption;
+use seeky_core::config::Config;
+use seeky_core::config::ConfigOverrides;
+use seeky_core::config_types::LinuxSandboxBackend;
+use seeky_core::exec::ExecParams;
+use seeky_core::exec::SandboxType;
+use seeky_core::exec::process_exec_tool_call;
+use seeky_core::exec_env::create_env;
+use seeky_core::protocol::SandboxPolicy;
+use serde_json::json;
+use tokio::sync::Notify;
+
+use crate::debug_sandbox::create_sandbox_policy;
+
+/// Probes that take longer than this are reported as denied.
+const PROBE_TIMEOUT_MS: u64 = 10_000;
+
+#[derive(Debug, Parser)]
+pub struct SandboxCli {
+    #[command(subcommand)]
+    pub cmd: SandboxCommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum SandboxCommand {
+    /// Report the sandbox support of this machine and probe the effective
+    /// policy with a few commands.
+    Check(CheckCommand),
+}
+
+#[derive(Debug, Parser)]
+pub struct CheckCommand {
+    /// Check the policy used by `--full-auto` (network-disabled sandbox that
+    /// can write to cwd and TMPDIR).
+    #[arg(long = "full-auto", default_value_t = false)]
+    pub full_auto: bool,
+
+    #[clap(flatten)]
+    pub sandbox: SandboxPermissionOption,
+
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// Print the report as JSON.
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+}
+
+/// A command run under the sandbox and whether the policy allows it.
+struct Probe {
+    name: &'static str,
+    target: String,
+    expected: bool,
+    allowed: bool,
+}
+
+impl Probe {
+    fn matches(&self) -> bool {
+        self.expected == self.allowed
+    }
+}
+
+pub async fn run_main(
+    cli: SandboxCli,
+    seeky_linux_sandbox_exe: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    match cli.cmd {
+        SandboxCommand::Check(command) => run_check(command, seeky_linux_sandbox_exe).await,
+    }
+}
+
+async fn run_check(
+    command: CheckCommand,
+    seeky_linux_sandbox_exe: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let CheckCommand {
+        full_auto,
+        sandbox,
+        config_overrides,
+        json,
+    } = command;
+    let cli_overrides = config_overrides
+        .parse_overrides()
+        .map_err(|e| anyhow::anyhow!("Error parsing -c overrides: {e}"))?;
+    // Without `-s` or `--full-auto`, check the policy from config.toml.
+    let sandbox_policy = (full_auto || sandbox.permissions.is_some())
+        .then(|| create_sandbox_policy(full_auto, sandbox));
+    let config = Config::load_with_cli_overrides(
+        cli_overrides,
+        ConfigOverrides {
+            sandbox_policy,
+            seeky_linux_sandbox_exe,
+            ..Default::default()
+        },
+    )?;
+    let cwd = std::env::current_dir()?;
+    let policy = &config.sandbox_policy;
+    let sandbox_type = sandbox_type(config.sandbox.linux_backend);
+
+    let kernel = kernel_support();
+    let writable_roots = if policy.has_full_disk_write_access() {
+        None
+    } else {
+        Some(policy.get_writable_roots_with_cwd(&cwd))
+    };
+    let readable_roots = if policy.has_full_disk_read_access() {
+        None
+    } else {
+        Some(policy.get_readable_roots_with_cwd(&cwd))
+    };
+    let protected_paths = policy
+        .get_protected_paths_with_cwd(&cwd)
+        .into_iter()
+        .map(|protected| protected.path)
+        .collect::<Vec<_>>();
+    let network = if policy.has_full_network_access() {
+        "full".to_string()
+    } else {
+        let domains = policy.get_allowed_network_domains();
+        if domains.is_empty() {
+            "disabled".to_string()
+        } else {
+            format!("proxy ({})", domains.join(", "))
+        }
+    };
+
+    let probes = run_probes(&config, sandbox_type, &cwd).await?;
+
+    if json {
+        let report = json!({
+            "platform": std::env::consts::OS,
+            "sandbox": sandbox_name(sandbox_type),
+            "landlock_abi": kernel.landlock_abi,
+            "seccomp": kernel.seccomp,
+            "user_namespaces": kernel.user_namespaces,
+            "cwd": cwd,
+            "writable_roots": writable_roots,
+            "readable_roots": readable_roots,
+            "protected_paths": protected_paths,
+            "network": network,
+            "probes": probes.iter().map(|probe| json!({
+                "name": probe.name,
+                "target": probe.target,
+                "expected": probe.expected,
+                "allowed": probe.allowed,
+                "ok": probe.matches(),
+            })).collect::<Vec<_>>(),
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("platform: {}", std::env::consts::OS);
+        println!("sandbox: {}", sandbox_name(sandbox_type));
+        if cfg!(target_os = "linux") {
+            match kernel.landlock_abi {
+                Some(version) => println!("landlock abi: {version}"),
+                None => println!("landlock abi: unsupported"),
+            }
+            println!("seccomp: {}", availability(kernel.seccomp));
+            println!("user namespaces: {}", availability(kernel.user_namespaces));
+        }
+        println!("cwd: {}", cwd.display());
+        println!(
+            "writable roots: {}",
+            format_roots(writable_roots.as_deref())
+        );
+        println!(
+            "readable roots: {}",
+            format_roots(readable_roots.as_deref())
+        );
+        println!("network: {network}");
+        println!();
+        println!("probes:");
+        for probe in &probes {
+            println!(
+                "  {:<8} {:<40} {:<8} (expected {}){}",
+                probe.name,
+                probe.target,
+                verdict(probe.allowed),
+                verdict(probe.expected),
+                if probe.matches() { "" } else { "  MISMATCH" }
+            );
+        }
+    }
+
+    let mismatches = probes.iter().filter(|probe| !probe.matches()).count();
+    if mismatches > 0 {
+        anyhow::bail!("{mismatches} probe(s) did not match the sandbox policy");
+    }
+    Ok(())
+}
+
+/// The sandbox `seeky` uses for commands it runs without approval.
+fn sandbox_type(linux_backend: LinuxSandboxBackend) -> SandboxType {
+    if cfg!(target_os = "macos") {
+        SandboxType::MacosSeatbelt
+    } else if cfg!(target_os = "linux") {
+        match linux_backend {
+            LinuxSandboxBackend::Landlock => SandboxType::LinuxSeccomp,
+            LinuxSandboxBackend::Namespaces => SandboxType::LinuxNamespaces,
+        }
+    } else {
+        SandboxType::None
+    }
+}
+
+fn sandbox_name(sandbox_type: SandboxType) -> &'static str {
+    match sandbox_type {
+        SandboxType::None => "none",
+        SandboxType::MacosSeatbelt => "seatbelt",
+        SandboxType::LinuxSeccomp => "landlock",
+        SandboxType::LinuxNamespaces => "namespaces",
+    }
+}
+
+struct KernelSupport {
+    landlock_abi: Option<i32>,
+    seccomp: bool,
+    user_namespaces: bool,
+}
+
+#[cfg(target_os = "linux")]
+fn kernel_support() -> KernelSupport {
+    KernelSupport {
+        landlock_abi: seeky_linux_sandbox::landlock_abi_version(),
+        seccomp: seeky_linux_sandbox::seccomp_filter_available(),
+        user_namespaces: seeky_linux_sandbox::user_namespaces_available(),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn kernel_support() -> KernelSupport {
+    KernelSupport {
+        landlock_abi: None,
+        seccomp: false,
+        user_namespaces: false,
+    }
+}
+
+async fn run_probes(
+    config: &Config,
+    sandbox_type: SandboxType,
+    cwd: &Path,
+) -> anyhow::Result<Vec<Probe>> {
+    let policy = &config.sandbox_policy;
+    let mut probes = Vec::new();
+
+    let mut folders = vec![("cwd", cwd.to_path_buf())];
+    if let Some(home) = std::env::var_os("HOME") {
+        folders.push(("home", PathBuf::from(home)));
+    }
+    folders.push(("tmp", PathBuf::from("/tmp")));
+    // Protected paths that do not exist yet have nowhere to write a probe to.
+    folders.extend(
+        policy
+            .get_protected_paths_with_cwd(cwd)
+            .into_iter()
+            .filter(|protected| protected.path.is_dir())
+            .map(|protected| ("protect", protected.path)),
+    );
+    for (name, folder) in folders {
+        let file = folder.join(format!(".seeky-sandbox-probe-{}", std::process::id()));
+        let allowed = run_probe(
+            config,
+            sandbox_type,
+            cwd,
+            r#"echo probe > "$1" && rm -f "$1""#,
+            &file.to_string_lossy(),
+        )
+        .await;
+        // The probe may have been killed between writing and removing.
+        let _ = std::fs::remove_file(&file);
+        probes.push(Probe {
+            name,
+            target: format!("write {}", folder.display()),
+            expected: is_writable(policy, sandbox_type, cwd, &folder),
+            allowed,
+        });
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let address = listener.local_addr()?;
+    let allowed = run_probe(
+        config,
+        sandbox_type,
+        cwd,
+        r#"exec 3<>"/dev/tcp/127.0.0.1/$1""#,
+        &address.port().to_string(),
+    )
+    .await;
+    probes.push(Probe {
+        name: "network",
+        target: format!("connect {address}"),
+        expected: policy.has_full_network_access(),
+        allowed,
+    });
+
+    Ok(probes)
+}
+
+/// Runs `script` with `arg` as `$1` under the sandbox and returns whether it
+/// succeeded.
+async fn run_probe(
+    config: &Config,
+    sandbox_type: SandboxType,
+    cwd: &Path,
+    script: &str,
+    arg: &str,
+) -> bool {
+    let params = ExecParams {
+        command: vec![
+            "bash".to_string(),
+            "-c".to_string(),
+            script.to_string(),
+            "probe".to_string(),
+            arg.to_string(),
+        ],
+        cwd: cwd.to_path_buf(),
+        timeout_ms: Some(PROBE_TIMEOUT_MS),
+        env: create_env(&config.shell_environment_policy),
+        limits: config.sandbox.limits.clone(),
+    };
+    let output = process_exec_tool_call(
+        params,
+        sandbox_type,
+        Arc::new(Notify::new()),
+        &config.sandbox_policy,
+        &config.seeky_linux_sandbox_exe,
+    )
+    .await;
+    matches!(output, Ok(output) if output.exit_code == 0)
+}
+
+fn is_writable(
+    policy: &SandboxPolicy,
+    sandbox_type: SandboxType,
+    cwd: &Path,
+    folder: &Path,
+) -> bool {
+    // The namespace backend mounts a private tmpfs on `/tmp` that is
+    // discarded when the command exits.
+    let private_tmp = sandbox_type == SandboxType::LinuxNamespaces && folder.starts_with("/tmp");
+    let protected = policy
+        .get_protected_paths_with_cwd(cwd)
+        .iter()
+        .any(|protected| protected.protects(folder));
+    !protected
+        && (private_tmp
+            || policy.has_full_disk_write_access()
+            || policy
+                .get_writable_roots_with_cwd(cwd)
+                .iter()
+                .any(|root| folder.starts_with(root)))
+}
+
+fn format_roots(roots: Option<&[PathBuf]>) -> String {
+    match roots {
+        None => "everything".to_string(),
+        Some([]) => "none".to_string(),
+        Some(roots) => roots
+            .iter()
+            .map(|root| root.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+fn availability(available: bool) -> &'static str {
+    if available {
+        "available"
+    } else {
+        "unavailable"
+    }
+}
+
+fn verdict(allowed: bool) -> &'static str {
+    if allowed { "allowed" } else { "denied" }
+}
@@ -0,0 +1,57 @@
+//! `seeky schema`: print or write the JSON Schema and TypeScript definitions
+//! for `config.toml` and the protocol.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use clap::ValueEnum;
+use seeky_core::schema::SchemaKind;
+use seeky_core::schema::json_schema;
+use seeky_core::schema::schema_files;
+
+#[derive(Debug, Parser)]
+pub struct SchemaCli {
+    /// Which schema to generate.
+    #[arg(value_enum)]
+    pub kind: SchemaKindArg,
+
+    /// Directory to write `<kind>.schema.json` and `<kind>.ts` to. If not
+    /// specified, the JSON Schema is printed to stdout.
+    #[arg(long = "out", value_name = "DIR")]
+    pub out_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SchemaKindArg {
+    /// The contents of config.toml.
+    Config,
+    /// Submissions and events exchanged with a Seeky session.
+    Protocol,
+}
+
+impl From<SchemaKindArg> for SchemaKind {
+    fn from(value: SchemaKindArg) -> Self {
+        match value {
+            SchemaKindArg::Config => SchemaKind::Config,
+            SchemaKindArg::Protocol => SchemaKind::Protocol,
+        }
+    }
+}
+
+pub fn run_main(cli: SchemaCli) -> anyhow::Result<()> {
+    let kind = SchemaKind::from(cli.kind);
+    match cli.out_dir {
+        Some(out_dir) => {
+            std::fs::create_dir_all(&out_dir)?;
+            for (file_name, contents) in schema_files(kind)? {
+                let path = out_dir.join(file_name);
+                std::fs::write(&path, contents)?;
+                println!("Wrote {}", path.display());
+            }
+        }
+        None => {
+            println!("{}", serde_json::to_string_pretty(&json_schema(kind))?);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,235 @@
+//! `seeky sessions`: list, inspect, export and prune the rollouts saved
+//! under `$SEEKY_HOME/sessions`.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::Parser;
+use clap::ValueEnum;
+use seeky_core::config::find_seeky_home;
+use seeky_core::rollout::SessionLog;
+use seeky_core::sessions::ExportFormat;
+use seeky_core::sessions::SessionSummary;
+use seeky_core::sessions::export_session;
+use seeky_core::sessions::find_session;
+use seeky_core::sessions::list_sessions;
+use seeky_core::sessions::prune_sessions;
+
+#[derive(Debug, Parser)]
+pub struct SessionsCli {
+    #[command(subcommand)]
+    pub cmd: SessionsCommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum SessionsCommand {
+    /// List saved sessions, newest first.
+    List {
+        /// Only list sessions whose transcript contains this text
+        /// (case-insensitive).
+        #[arg(long, short = 's')]
+        search: Option<String>,
+
+        /// Maximum number of sessions to list.
+        #[arg(long, short = 'n')]
+        limit: Option<usize>,
+
+        /// Print the sessions as JSON.
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+
+    /// Print the transcript of a session, including commands and diffs.
+    Show {
+        /// Session id (or a unique prefix of it), or a path to a rollout file.
+        id: String,
+    },
+
+    /// Export a session as Markdown, HTML or JSON.
+    Export {
+        /// Session id (or a unique prefix of it), or a path to a rollout file.
+        id: String,
+
+        #[arg(long, short = 'f', value_enum, default_value_t = ExportFormatArg::Markdown)]
+        format: ExportFormatArg,
+
+        /// File to write to. If not specified, the export is printed to
+        /// stdout.
+        #[arg(long, short = 'o', value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Delete sessions that have not been updated for a while.
+    Prune {
+        /// Minimum age of the sessions to delete, e.g. `30d`, `12h` or `2w`.
+        #[arg(long, value_name = "AGE", value_parser = parse_age)]
+        older_than: Duration,
+
+        /// Print the sessions that would be deleted without deleting them.
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ExportFormatArg {
+    Markdown,
+    Html,
+    Json,
+}
+
+impl From<ExportFormatArg> for ExportFormat {
+    fn from(value: ExportFormatArg) -> Self {
+        match value {
+            ExportFormatArg::Markdown => ExportFormat::Markdown,
+            ExportFormatArg::Html => ExportFormat::Html,
+            ExportFormatArg::Json => ExportFormat::Json,
+        }
+    }
+}
+
+pub fn run_main(cli: SessionsCli) -> anyhow::Result<()> {
+    let seeky_home = find_seeky_home()?;
+
+    match cli.cmd {
+        SessionsCommand::List {
+            search,
+            limit,
+            json,
+        } => {
+            let mut sessions = list_sessions(&seeky_home, search.as_deref())?;
+            if let Some(limit) = limit {
+                sessions.truncate(limit);
+            }
+            if json {
+                println!("{}", serde_json::to_string_pretty(&sessions)?);
+            } else if sessions.is_empty() {
+                println!("No sessions found.");
+            } else {
+                print_table(&sessions);
+            }
+        }
+        SessionsCommand::Show { id } => {
+            let log = SessionLog::read(&find_session(&seeky_home, &id)?)?;
+            print!("{}", export_session(&log, ExportFormat::Markdown)?);
+        }
+        SessionsCommand::Export { id, format, output } => {
+            let log = SessionLog::read(&find_session(&seeky_home, &id)?)?;
+            let contents = export_session(&log, format.into())?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, contents)?;
+                    println!("Wrote {}", path.display());
+                }
+                None => print!("{contents}"),
+            }
+        }
+        SessionsCommand::Prune {
+            older_than,
+            dry_run,
+        } => {
+            let pruned = prune_sessions(&seeky_home, older_than, dry_run)?;
+            for path in &pruned {
+                println!("{}", path.display());
+            }
+            let verb = if dry_run { "Would delete" } else { "Deleted" };
+            println!("{verb} {} session(s).", pruned.len());
+        }
+    }
+    Ok(())
+}
+
+fn print_table(sessions: &[SessionSummary]) {
+    const PROMPT_WIDTH: usize = 60;
+
+    let rows: Vec<[String; 6]> = sessions
+        .iter()
+        .map(|session| {
+            let prompt = session
+                .first_prompt
+                .as_deref()
+                .unwrap_or_default()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            [
+                session.id.to_string()[..8].to_string(),
+                session
+                    .started_at
+                    .get(..16)
+                    .unwrap_or(&session.started_at)
+                    .replace('T', " "),
+                session
+                    .duration_secs
+                    .map(format_duration)
+                    .unwrap_or_else(|| "-".to_string()),
+                session.model.clone().unwrap_or_else(|| "-".to_string()),
+                session
+                    .cwd
+                    .as_ref()
+                    .map(|cwd| cwd.display().to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                truncate(&prompt, PROMPT_WIDTH),
+            ]
+        })
+        .collect();
+
+    let header = ["ID", "STARTED (UTC)", "DURATION", "MODEL", "CWD", "PROMPT"];
+    let mut widths = header.map(|h| h.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: [&str; 6]| {
+        cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    println!("{}", format_row(header));
+    for row in &rows {
+        println!("{}", format_row(row.each_ref().map(String::as_str)));
+    }
+}
+
+fn format_duration(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
+fn truncate(s: &str, max_chars: usize) -> String {
+    if s.chars().count() <= max_chars {
+        s.to_string()
+    } else {
+        let truncated: String = s.chars().take(max_chars.saturating_sub(1)).collect();
+        format!("{truncated}…")
+    }
+}
+
+/// Parses an age such as `30d`, `12h`, `45m`, `90s` or `2w`.
+fn parse_age(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (amount, unit) = s.split_at(split);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("invalid age `{s}`; expected e.g. `30d`"))?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" | "" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("invalid unit in `{s}`; use s, m, h, d or w")),
+    };
+    Ok(Duration::from_secs(amount.saturating_mul(unit_secs)))
+}
@@ -49,10 +49,13 @@ pub struct SandboxPermissionOption {
     ///       -s disk-write-platform-global-temp-folder
     /// ```
     ///
-    /// Note disk-write-folder takes a value:
+    /// Note disk-write-folder, disk-read-folder and network-allow-domains take
+    /// a value:
     ///
     /// ```shell
     ///     -s disk-write-folder=$HOME/.pyenv/shims
+    ///     -s disk-read-folder=$HOME/.cargo/registry
+    ///     -s network-allow-domains=crates.io,*.crates.io
     /// ```
     ///
     /// These permissions are quite broad and should be used with caution:
@@ -66,7 +69,8 @@ pub struct SandboxPermissionOption {
 }
 
 /// Custom value-parser so we can keep the CLI surface small *and*
-/// still handle the parameterised `disk-write-folder` case.
+/// still handle the parameterised `disk-write-folder`, `disk-read-folder` and
+/// `network-allow-domains` cases.
 fn parse_sandbox_permission(raw: &str) -> std::io::Result<SandboxPermission> {
     let base_path = std::env::current_dir()?;
     parse_sandbox_permission_with_base_path(raw, base_path)
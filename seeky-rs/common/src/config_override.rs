@@ -0,0 +1,127 @@
+//! Support for the generic `-c key=value` CLI option that lets users override
+//! any field of `config.toml` from the command line.
+//! Available when the `cli` feature is enabled for the crate.
+
+use clap::ArgAction;
+use clap::Parser;
+use toml::Value as TomlValue;
+
+#[derive(Parser, Debug, Default, Clone)]
+pub struct CliConfigOverrides {
+    /// Override a configuration value that would otherwise be loaded from
+    /// `~/.seeky/config.toml`. Use a dotted path (`foo.bar.baz`) to override
+    /// nested values. The `value` portion is parsed as TOML. If it fails to
+    /// parse as TOML, the raw string is used as a literal.
+    ///
+    /// ```shell
+    /// seeky -c model=o3 \
+    ///       -c 'sandbox_permissions=["disk-full-read-access"]' \
+    ///       -c tui.disable_mouse_capture=true
+    /// ```
+    ///
+    /// Can be specified multiple times.
+    #[arg(
+        short = 'c',
+        long = "config",
+        value_name = "key=value",
+        action = ArgAction::Append
+    )]
+    pub raw_overrides: Vec<String>,
+}
+
+impl CliConfigOverrides {
+    /// Parses each raw `key=value` override into a dotted path and the TOML
+    /// value to store there.
+    pub fn parse_overrides(&self) -> Result<Vec<(String, TomlValue)>, String> {
+        self.raw_overrides
+            .iter()
+            .map(|raw| parse_override(raw))
+            .collect()
+    }
+}
+
+fn parse_override(raw: &str) -> Result<(String, TomlValue), String> {
+    let Some((key, value)) = raw.split_once('=') else {
+        return Err(format!("invalid override (missing '='): {raw}"));
+    };
+
+    let key = key.trim();
+    if key.is_empty() || key.split('.').any(str::is_empty) {
+        return Err(format!("invalid override key: {raw}"));
+    }
+
+    let value = value.trim();
+    let value = parse_toml_value(value).unwrap_or_else(|| {
+        // Allow unquoted strings such as `-c model=o3` by falling back to the
+        // raw input, minus any surrounding quotes.
+        let trimmed = value.trim_matches(|c| c == '"' || c == '\'');
+        TomlValue::String(trimmed.to_string())
+    });
+    Ok((key.to_string(), value))
+}
+
+/// Parses `raw` as the right-hand side of a TOML assignment.
+fn parse_toml_value(raw: &str) -> Option<TomlValue> {
+    const SENTINEL_KEY: &str = "_seeky_override_";
+    let wrapped = format!("{SENTINEL_KEY} = {raw}");
+    let mut table: toml::Table = toml::from_str(&wrapped).ok()?;
+    table.remove(SENTINEL_KEY)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    fn parse(raw: &[&str]) -> Result<Vec<(String, TomlValue)>, String> {
+        CliConfigOverrides {
+            raw_overrides: raw.iter().map(|s| s.to_string()).collect(),
+        }
+        .parse_overrides()
+    }
+
+    #[test]
+    fn parses_toml_values() {
+        let overrides = parse(&[
+            "project_doc_max_bytes=1024",
+            "tui.disable_mouse_capture=true",
+            r#"notify=["notify-send", "Seeky"]"#,
+            r#"mcp_servers.docs={ command = "docs-server" }"#,
+        ])
+        .unwrap();
+
+        assert_eq!(overrides[0].0, "project_doc_max_bytes");
+        assert_eq!(overrides[0].1, TomlValue::Integer(1024));
+        assert_eq!(overrides[1].0, "tui.disable_mouse_capture");
+        assert_eq!(overrides[1].1, TomlValue::Boolean(true));
+        assert_eq!(
+            overrides[2].1,
+            TomlValue::Array(vec![
+                TomlValue::String("notify-send".to_string()),
+                TomlValue::String("Seeky".to_string()),
+            ])
+        );
+        assert_eq!(
+            overrides[3].1.get("command").and_then(TomlValue::as_str),
+            Some("docs-server")
+        );
+    }
+
+    #[test]
+    fn falls_back_to_string_literal() {
+        let overrides = parse(&["model=o3", "cwd=/tmp/some dir", "model='o4-mini'"]).unwrap();
+        assert_eq!(overrides[0].1, TomlValue::String("o3".to_string()));
+        assert_eq!(
+            overrides[1].1,
+            TomlValue::String("/tmp/some dir".to_string())
+        );
+        assert_eq!(overrides[2].1, TomlValue::String("o4-mini".to_string()));
+    }
+
+    #[test]
+    fn rejects_malformed_overrides() {
+        assert!(parse(&["model"]).is_err());
+        assert!(parse(&["=o3"]).is_err());
+        assert!(parse(&["tui..disable_mouse_capture=true"]).is_err());
+    }
+}
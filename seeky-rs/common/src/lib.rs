@@ -1,6 +1,9 @@
 #[cfg(feature = "cli")]
 mod approval_mode_cli_arg;
 
+#[cfg(feature = "cli")]
+mod config_override;
+
 #[cfg(feature = "elapsed")]
 pub mod elapsed;
 
@@ -8,3 +11,5 @@ pub mod elapsed;
 pub use approval_mode_cli_arg::ApprovalModeCliArg;
 #[cfg(feature = "cli")]
 pub use approval_mode_cli_arg::SandboxPermissionOption;
+#[cfg(feature = "cli")]
+pub use config_override::CliConfigOverrides;
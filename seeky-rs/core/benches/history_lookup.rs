@@ -0,0 +1,123 @@
+//! Measures `GetHistoryEntryRequest`-style lookups and reverse searches
+//! against a history file with 100k entries, compared with reading the file
+//! line by line from the start.
+//!
+//! Run with `cargo bench -p seeky-core --bench history_lookup`.
+
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+
+use seeky_core::config::Config;
+use seeky_core::config::ConfigOverrides;
+use seeky_core::config::ConfigToml;
+use seeky_core::message_history::HistoryEntry;
+use seeky_core::message_history::HistorySearchMode;
+use seeky_core::message_history::lookup;
+use seeky_core::message_history::search;
+
+const ENTRIES: usize = 100_000;
+const LOOKUPS: usize = 1_000;
+
+fn main() -> std::io::Result<()> {
+    let seeky_home = tempfile::TempDir::new()?;
+    let config = Config::load_from_base_config_with_overrides(
+        ConfigToml::default(),
+        ConfigOverrides::default(),
+        seeky_home.path().to_path_buf(),
+    )?;
+
+    let path = seeky_home.path().join("history.jsonl");
+    write_history(&path)?;
+    let log_id = log_id(&path)?;
+    println!(
+        "history: {ENTRIES} entries, {} bytes",
+        std::fs::metadata(&path)?.len()
+    );
+
+    let start = Instant::now();
+    lookup(log_id, 0, &config);
+    report("first lookup (builds index)", start.elapsed(), 1);
+
+    // Walk backwards from the newest entry, like Up-arrow navigation does.
+    let start = Instant::now();
+    for offset in (ENTRIES - LOOKUPS..ENTRIES).rev() {
+        lookup(log_id, offset, &config);
+    }
+    report("indexed lookup", start.elapsed(), LOOKUPS);
+
+    let start = Instant::now();
+    for offset in (ENTRIES - 10..ENTRIES).rev() {
+        linear_lookup(&path, offset)?;
+    }
+    report("linear scan lookup", start.elapsed(), 10);
+
+    let start = Instant::now();
+    let found = search(
+        log_id,
+        "needle",
+        HistorySearchMode::Substring,
+        None,
+        &config,
+    );
+    report("search, no match (full scan)", start.elapsed(), 1);
+    assert!(found.is_none());
+
+    let start = Instant::now();
+    search(
+        log_id,
+        "prompt 99",
+        HistorySearchMode::Prefix,
+        None,
+        &config,
+    );
+    report("search, recent match", start.elapsed(), 1);
+
+    Ok(())
+}
+
+fn write_history(path: &Path) -> std::io::Result<()> {
+    let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+    for i in 0..ENTRIES {
+        let entry = HistoryEntry {
+            session_id: "67e55044-10b1-426f-9247-bb680e5fe0c8".to_string(),
+            ts: 1_700_000_000 + i as u64,
+            text: format!("prompt {i}: please refactor the module and run the tests"),
+        };
+        serde_json::to_writer(&mut out, &entry)?;
+        out.write_all(b"\n")?;
+    }
+    out.flush()
+}
+
+/// The lookup strategy used before the index was introduced.
+fn linear_lookup(path: &Path, offset: usize) -> std::io::Result<Option<HistoryEntry>> {
+    let reader = BufReader::new(std::fs::File::open(path)?);
+    for (idx, line) in reader.lines().enumerate() {
+        if idx == offset {
+            return Ok(serde_json::from_str(&line?).ok());
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(unix)]
+fn log_id(path: &Path) -> std::io::Result<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Ok(std::fs::metadata(path)?.ino())
+}
+
+#[cfg(not(unix))]
+fn log_id(_path: &Path) -> std::io::Result<u64> {
+    Ok(0)
+}
+
+fn report(name: &str, elapsed: Duration, iterations: usize) {
+    println!(
+        "{name:<30} {:>12.3?} per iteration ({iterations} iterations)",
+        elapsed / iterations as u32
+    );
+}
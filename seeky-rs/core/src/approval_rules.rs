@@ -0,0 +1,518 @@
+//! Approval rules that outlive a session: commands that are always allowed to
+//! run without asking, or always rejected. They are read from
+//! `$SEEKY_HOME/approvals.toml` and from `.seeky/approvals.toml` in the
+//! project, of which only the `deny` rules apply:
+//!
+//! ```toml
+//! [[rules]]
+//! prefix = ["cargo", "test"]
+//! cwd = "/home/me/src/app"
+//! decision = "allow"
+//!
+//! [[rules]]
+//! glob = "git push *"
+//! decision = "deny"
+//! ```
+//!
+//! A `prefix` rule matches commands whose arguments start with the given ones,
+//! and a `glob` rule matches the arguments joined by spaces, with `*` and `?`
+//! as wildcards. A rule with a `cwd` only applies in that directory and below;
+//! a relative `cwd` in the project file is resolved against the project root.
+//! The parts of a `bash -lc` script are matched one by one: the script is
+//! denied if any part is, and allowed if every part is. An allowed command is
+//! still run in the sandbox, if there is one.
+//!
+//! The files are read again for every command, so edits take effect right
+//! away. Answering an approval request with
+//! [`ReviewDecision::ApprovedAlways`](crate::protocol::ReviewDecision::ApprovedAlways)
+//! adds an `allow` rule for the command to the user's file.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+use toml_edit::ArrayOfTables;
+use toml_edit::DocumentMut;
+use toml_edit::Item;
+use toml_edit::Table;
+use toml_edit::value;
+use wildmatch::WildMatchPattern;
+
+use crate::is_safe_command::exec_commands;
+use crate::util::find_git_root;
+
+pub const APPROVALS_FILENAME: &str = "approvals.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleDecision {
+    Allow,
+    Deny,
+}
+
+/// Which commands a rule applies to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RulePattern {
+    /// The command's arguments start with these.
+    Prefix(Vec<String>),
+    /// The command's arguments joined by spaces match this pattern.
+    Glob(String),
+}
+
+impl RulePattern {
+    fn matches(&self, argv: &[String]) -> bool {
+        match self {
+            RulePattern::Prefix(prefix) => argv.starts_with(prefix),
+            RulePattern::Glob(glob) => {
+                WildMatchPattern::<'*', '?'>::new(glob).matches(&argv.join(" "))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for RulePattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RulePattern::Prefix(prefix) => write!(f, "{} …", prefix.join(" ")),
+            RulePattern::Glob(glob) => write!(f, "{glob}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawApprovalRule")]
+pub struct ApprovalRule {
+    #[serde(flatten)]
+    pub pattern: RulePattern,
+    /// Only apply to commands run in this directory or below.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    pub decision: RuleDecision,
+}
+
+/// An [`ApprovalRule`] as written in the file, before it is checked to have
+/// exactly one pattern.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawApprovalRule {
+    prefix: Option<Vec<String>>,
+    glob: Option<String>,
+    cwd: Option<PathBuf>,
+    decision: RuleDecision,
+}
+
+impl TryFrom<RawApprovalRule> for ApprovalRule {
+    type Error = &'static str;
+
+    fn try_from(raw: RawApprovalRule) -> Result<Self, Self::Error> {
+        let pattern = match (raw.prefix, raw.glob) {
+            (Some(prefix), None) if !prefix.is_empty() => RulePattern::Prefix(prefix),
+            (None, Some(glob)) => RulePattern::Glob(glob),
+            _ => return Err("a rule needs either a non-empty `prefix` or a `glob`"),
+        };
+        Ok(Self {
+            pattern,
+            cwd: raw.cwd,
+            decision: raw.decision,
+        })
+    }
+}
+
+impl ApprovalRule {
+    /// The rule saved when the user always approves `command` in `cwd`: a
+    /// `bash -lc` script of a single plain command is saved as that command.
+    pub(crate) fn allow_command(command: &[String], cwd: &Path) -> Self {
+        let prefix = match exec_commands(command) {
+            Some(mut commands) if commands.len() == 1 => commands.remove(0),
+            _ => command.to_vec(),
+        };
+        Self {
+            pattern: RulePattern::Prefix(prefix),
+            cwd: Some(cwd.to_path_buf()),
+            decision: RuleDecision::Allow,
+        }
+    }
+
+    fn matches(&self, argv: &[String], cwd: &Path) -> bool {
+        self.cwd.as_ref().is_none_or(|scope| cwd.starts_with(scope)) && self.pattern.matches(argv)
+    }
+}
+
+/// Which file a rule comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleSource {
+    User,
+    Project,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedRule {
+    pub source: RuleSource,
+    #[serde(flatten)]
+    pub rule: ApprovalRule,
+}
+
+impl SavedRule {
+    /// Whether the rule is an `allow` rule of the project file. Those are
+    /// ignored: anyone who can commit to the repository could otherwise
+    /// approve their own commands.
+    pub fn is_ignored(&self) -> bool {
+        self.source == RuleSource::Project && self.rule.decision == RuleDecision::Allow
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ApprovalsFile {
+    #[serde(default)]
+    rules: Vec<ApprovalRule>,
+}
+
+/// The approval files of a user and a project.
+#[derive(Debug, Clone)]
+pub struct ApprovalRules {
+    user_file: PathBuf,
+    project_root: PathBuf,
+}
+
+impl ApprovalRules {
+    /// The project is the git repository `cwd` is in, or `cwd` itself.
+    pub fn new(seeky_home: &Path, cwd: &Path) -> Self {
+        Self {
+            user_file: seeky_home.join(APPROVALS_FILENAME),
+            project_root: find_git_root(cwd).unwrap_or_else(|| cwd.to_path_buf()),
+        }
+    }
+
+    pub fn project_root(&self) -> &Path {
+        &self.project_root
+    }
+
+    pub fn file(&self, source: RuleSource) -> PathBuf {
+        match source {
+            RuleSource::User => self.user_file.clone(),
+            RuleSource::Project => self.project_root.join(".seeky").join(APPROVALS_FILENAME),
+        }
+    }
+
+    /// The rules of the user's file followed by those of the project's.
+    /// Missing files have no rules.
+    pub fn load(&self) -> std::io::Result<Vec<SavedRule>> {
+        let mut rules = Vec::new();
+        for source in [RuleSource::User, RuleSource::Project] {
+            for mut rule in read_rules(&self.file(source))? {
+                if source == RuleSource::Project {
+                    rule.cwd = rule.cwd.map(|cwd| self.project_root.join(cwd));
+                }
+                rules.push(SavedRule { source, rule });
+            }
+        }
+        Ok(rules)
+    }
+
+    /// Appends `rule` to the user's file, keeping its comments and
+    /// formatting.
+    pub fn add(&self, rule: &ApprovalRule) -> std::io::Result<()> {
+        let mut document = read_document(&self.user_file)?;
+        let rules = rules_table(&mut document, &self.user_file)?;
+        rules.push(rule_to_table(rule));
+        write_document(&self.user_file, &document)
+    }
+
+    /// Removes the rule at `index` in the list returned by [`Self::load`] and
+    /// returns it.
+    pub fn remove(&self, index: usize) -> std::io::Result<SavedRule> {
+        let rules = self.load()?;
+        let Some(removed) = rules.get(index).cloned() else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("there is no approval rule #{}", index + 1),
+            ));
+        };
+        // Position of the rule within its own file.
+        let position = rules[..index]
+            .iter()
+            .filter(|rule| rule.source == removed.source)
+            .count();
+
+        let path = self.file(removed.source);
+        let mut document = read_document(&path)?;
+        rules_table(&mut document, &path)?.remove(position);
+        write_document(&path, &document)?;
+        Ok(removed)
+    }
+}
+
+/// What the rules say about running `command` in `cwd`. Deny rules win over
+/// allow rules, and ignored rules do not count.
+pub(crate) fn decide(rules: &[SavedRule], command: &[String], cwd: &Path) -> Option<RuleDecision> {
+    let parts = exec_commands(command).unwrap_or_default();
+    let matched = |argv: &[String], decision: RuleDecision| {
+        rules.iter().any(|saved| {
+            !saved.is_ignored() && saved.rule.decision == decision && saved.rule.matches(argv, cwd)
+        })
+    };
+
+    if matched(command, RuleDecision::Deny)
+        || parts.iter().any(|part| matched(part, RuleDecision::Deny))
+    {
+        Some(RuleDecision::Deny)
+    } else if matched(command, RuleDecision::Allow)
+        || (!parts.is_empty() && parts.iter().all(|part| matched(part, RuleDecision::Allow)))
+    {
+        Some(RuleDecision::Allow)
+    } else {
+        None
+    }
+}
+
+fn invalid_data(path: &Path, e: impl std::fmt::Display) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("{}: {e}", path.display()),
+    )
+}
+
+fn read_to_string(path: &Path) -> std::io::Result<String> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e),
+    }
+}
+
+fn read_rules(path: &Path) -> std::io::Result<Vec<ApprovalRule>> {
+    let contents = read_to_string(path)?;
+    let file: ApprovalsFile = toml::from_str(&contents).map_err(|e| invalid_data(path, e))?;
+    Ok(file.rules)
+}
+
+fn read_document(path: &Path) -> std::io::Result<DocumentMut> {
+    read_to_string(path)?
+        .parse::<DocumentMut>()
+        .map_err(|e| invalid_data(path, e))
+}
+
+fn write_document(path: &Path, document: &DocumentMut) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, document.to_string())
+}
+
+fn rules_table<'a>(
+    document: &'a mut DocumentMut,
+    path: &Path,
+) -> std::io::Result<&'a mut ArrayOfTables> {
+    document
+        .entry("rules")
+        .or_insert_with(|| Item::ArrayOfTables(ArrayOfTables::new()))
+        .as_array_of_tables_mut()
+        .ok_or_else(|| invalid_data(path, "`rules` must be an array of tables"))
+}
+
+fn rule_to_table(rule: &ApprovalRule) -> Table {
+    let mut table = Table::new();
+    match &rule.pattern {
+        RulePattern::Prefix(prefix) => {
+            table["prefix"] = value(prefix.iter().collect::<toml_edit::Array>());
+        }
+        RulePattern::Glob(glob) => table["glob"] = value(glob),
+    }
+    if let Some(cwd) = &rule.cwd {
+        table["cwd"] = value(cwd.to_string_lossy().as_ref());
+    }
+    table["decision"] = value(match rule.decision {
+        RuleDecision::Allow => "allow",
+        RuleDecision::Deny => "deny",
+    });
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn rule(pattern: RulePattern, cwd: Option<&str>, decision: RuleDecision) -> SavedRule {
+        SavedRule {
+            source: RuleSource::User,
+            rule: ApprovalRule {
+                pattern,
+                cwd: cwd.map(PathBuf::from),
+                decision,
+            },
+        }
+    }
+
+    #[test]
+    fn deny_rules_win_and_scripts_are_matched_by_part() {
+        let rules = [
+            rule(
+                RulePattern::Prefix(strings(&["cargo", "test"])),
+                Some("/repo"),
+                RuleDecision::Allow,
+            ),
+            rule(
+                RulePattern::Glob("npm run *".to_string()),
+                None,
+                RuleDecision::Allow,
+            ),
+            rule(
+                RulePattern::Glob("npm run publish*".to_string()),
+                None,
+                RuleDecision::Deny,
+            ),
+        ];
+        let decide =
+            |command: &[&str], cwd: &str| decide(&rules, &strings(command), Path::new(cwd));
+
+        assert_eq!(
+            Some(RuleDecision::Allow),
+            decide(&["cargo", "test", "-p", "core"], "/repo/core")
+        );
+        assert_eq!(None, decide(&["cargo", "test"], "/elsewhere"));
+        assert_eq!(None, decide(&["cargo", "build"], "/repo"));
+        assert_eq!(
+            Some(RuleDecision::Allow),
+            decide(&["npm", "run", "lint"], "/")
+        );
+        assert_eq!(
+            Some(RuleDecision::Deny),
+            decide(&["npm", "run", "publish", "--dry-run"], "/")
+        );
+        assert_eq!(
+            Some(RuleDecision::Allow),
+            decide(&["bash", "-lc", "cargo test && npm run lint"], "/repo")
+        );
+        assert_eq!(
+            None,
+            decide(&["bash", "-lc", "cargo test && rm -rf target"], "/repo")
+        );
+        assert_eq!(
+            Some(RuleDecision::Deny),
+            decide(&["bash", "-lc", "npm run lint; npm run publish"], "/repo")
+        );
+    }
+
+    #[test]
+    fn project_rules_can_only_deny() {
+        let project_rule = |glob: &str, decision| SavedRule {
+            source: RuleSource::Project,
+            rule: ApprovalRule {
+                pattern: RulePattern::Glob(glob.to_string()),
+                cwd: None,
+                decision,
+            },
+        };
+        let rules = [
+            project_rule("*", RuleDecision::Allow),
+            project_rule("git push *", RuleDecision::Deny),
+        ];
+        let decide = |command: &[&str]| decide(&rules, &strings(command), Path::new("/repo"));
+
+        assert_eq!(None, decide(&["rm", "-rf", "/"]));
+        assert_eq!(
+            Some(RuleDecision::Deny),
+            decide(&["git", "push", "origin", "main"])
+        );
+    }
+
+    #[test]
+    fn rules_are_added_listed_and_removed() {
+        let seeky_home = tempfile::tempdir().unwrap();
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::create_dir(repo.path().join(".git")).unwrap();
+        std::fs::create_dir(repo.path().join(".seeky")).unwrap();
+        std::fs::write(
+            repo.path().join(".seeky").join(APPROVALS_FILENAME),
+            r#"
+[[rules]]
+glob = "make *"
+cwd = "src"
+decision = "allow"
+"#,
+        )
+        .unwrap();
+        let user_file = seeky_home.path().join(APPROVALS_FILENAME);
+        std::fs::write(&user_file, "# my approvals\n").unwrap();
+        let rules = ApprovalRules::new(seeky_home.path(), repo.path());
+
+        let approved =
+            ApprovalRule::allow_command(&strings(&["bash", "-lc", "npm test"]), repo.path());
+        rules.add(&approved).unwrap();
+        let denied = ApprovalRule {
+            pattern: RulePattern::Glob("git push *".to_string()),
+            cwd: None,
+            decision: RuleDecision::Deny,
+        };
+        rules.add(&denied).unwrap();
+
+        let saved = rules.load().unwrap();
+        assert_eq!(
+            vec![
+                SavedRule {
+                    source: RuleSource::User,
+                    rule: ApprovalRule {
+                        pattern: RulePattern::Prefix(strings(&["npm", "test"])),
+                        cwd: Some(repo.path().to_path_buf()),
+                        decision: RuleDecision::Allow,
+                    },
+                },
+                SavedRule {
+                    source: RuleSource::User,
+                    rule: denied.clone(),
+                },
+                SavedRule {
+                    source: RuleSource::Project,
+                    rule: ApprovalRule {
+                        pattern: RulePattern::Glob("make *".to_string()),
+                        cwd: Some(repo.path().join("src")),
+                        decision: RuleDecision::Allow,
+                    },
+                },
+            ],
+            saved
+        );
+
+        assert_eq!(saved[0], rules.remove(0).unwrap());
+        assert_eq!(saved[2], rules.remove(1).unwrap());
+        assert_eq!(
+            vec![SavedRule {
+                source: RuleSource::User,
+                rule: denied,
+            }],
+            rules.load().unwrap()
+        );
+        assert!(
+            std::fs::read_to_string(&user_file)
+                .unwrap()
+                .contains("# my approvals\n")
+        );
+        assert!(rules.remove(1).is_err());
+    }
+
+    #[test]
+    fn a_rule_needs_exactly_one_pattern() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(APPROVALS_FILENAME);
+        for contents in [
+            "[[rules]]\ndecision = \"allow\"\n",
+            "[[rules]]\nprefix = [\"ls\"]\nglob = \"ls *\"\ndecision = \"allow\"\n",
+            "[[rules]]\nprefix = []\ndecision = \"allow\"\n",
+            "[[rules]]\nprefx = [\"ls\"]\ndecision = \"allow\"\n",
+        ] {
+            std::fs::write(&path, contents).unwrap();
+            let e = read_rules(&path).unwrap_err();
+            assert_eq!(std::io::ErrorKind::InvalidData, e.kind(), "{contents}");
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use crate::config_types::ReasoningEffort;
+use crate::config_types::ReasoningSummary;
 use crate::error::Result;
 use crate::models::ResponseItem;
 use futures::Stream;
@@ -52,21 +54,29 @@ pub enum ResponseEvent {
 
 #[derive(Debug, Serialize)]
 pub(crate) struct Reasoning {
-    pub(crate) effort: &'static str,
+    pub(crate) effort: ReasoningEffort,
+    /// A summary of the reasoning performed by the model. This can be useful
+    /// for debugging and understanding the model's reasoning process.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(crate) summary: Option<Summary>,
+    pub(crate) summary: Option<ReasoningSummary>,
 }
 
-/// A summary of the reasoning performed by the model. This can be useful for
-/// debugging and understanding the model's reasoning process.
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "lowercase")]
-pub(crate) enum Summary {
-    Auto,
-    #[allow(dead_code)] // Will go away once this is configurable.
-    Concise,
-    #[allow(dead_code)] // Will go away once this is configurable.
-    Detailed,
+/// Builds the `reasoning` parameter for a Responses API request, or `None` if
+/// reasoning has been disabled via [`ReasoningEffort::None`].
+pub(crate) fn create_reasoning_param(
+    effort: ReasoningEffort,
+    summary: ReasoningSummary,
+) -> Option<Reasoning> {
+    if effort == ReasoningEffort::None {
+        return None;
+    }
+    Some(Reasoning {
+        effort,
+        summary: match summary {
+            ReasoningSummary::None => None,
+            summary => Some(summary),
+        },
+    })
 }
 
 #[derive(Debug, Serialize)]
@@ -80,6 +90,7 @@ pub(crate) struct Payload<'a> {
     pub(crate) tools: &'a [serde_json::Value],
     pub(crate) tool_choice: &'static str,
     pub(crate) parallel_tool_calls: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) reasoning: Option<Reasoning>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) previous_response_id: Option<String>,
@@ -1,6 +1,21 @@
 use crate::config_profile::ConfigProfile;
+use crate::config_profile::merge_audit;
+use crate::config_profile::merge_history;
+use crate::config_profile::merge_redaction;
+use crate::config_profile::merge_sandbox;
+use crate::config_profile::merge_shell_environment_policy;
+use crate::config_profile::merge_tables;
+use crate::config_types::Audit;
+use crate::config_types::AuditToml;
 use crate::config_types::History;
+use crate::config_types::HistoryToml;
 use crate::config_types::McpServerConfig;
+use crate::config_types::ReasoningEffort;
+use crate::config_types::ReasoningSummary;
+use crate::config_types::Redaction;
+use crate::config_types::RedactionToml;
+use crate::config_types::Sandbox;
+use crate::config_types::SandboxToml;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::ShellEnvironmentPolicyToml;
 use crate::config_types::Tui;
@@ -11,11 +26,16 @@ use crate::model_provider_info::built_in_model_providers;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPermission;
 use crate::protocol::SandboxPolicy;
+use crate::redaction::Redactor;
+use crate::util::find_git_root;
 use dirs::home_dir;
+use schemars::JsonSchema;
 use serde::Deserialize;
+use serde::Serialize;
 use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
+use toml::Value as TomlValue;
 
 /// Maximum number of bytes of the documentation that will be embedded. Larger
 /// files are *silently truncated* to this size so we do not take up too much of
@@ -23,7 +43,7 @@ use std::path::PathBuf;
 pub(crate) const PROJECT_DOC_MAX_BYTES: usize = 32 * 1024; // 32 KiB
 
 /// Application configuration loaded from disk and merged with overrides.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Config {
     /// Optional override of model selection.
     pub model: String,
@@ -92,6 +112,23 @@ pub struct Config {
     /// Settings that govern if and what will be written to `~/.seeky/history.jsonl`.
     pub history: History,
 
+    /// Settings for scrubbing secrets from tool output, history and rollouts.
+    pub redaction: Redaction,
+
+    /// Settings for the audit log of commands and patches.
+    pub audit: Audit,
+
+    /// `.policy` files layered on the built-in `default.policy` of
+    /// `seeky-execpolicy` to decide which commands are safe, in order.
+    pub exec_policy_files: Vec<PathBuf>,
+
+    /// `.policy` files of the project, layered after `exec_policy_files`.
+    /// Being part of the repository, they can only forbid commands.
+    pub project_exec_policy_files: Vec<PathBuf>,
+
+    /// Settings for the sandbox in which commands are executed.
+    pub sandbox: Sandbox,
+
     /// Optional URI-based file opener. If set, citations to files in the model
     /// output will be hyperlinked using the specified URI scheme.
     pub file_opener: UriBasedFileOpener,
@@ -99,8 +136,15 @@ pub struct Config {
     /// Collection of settings that are specific to the TUI.
     pub tui: Tui,
 
+    /// How much effort reasoning models should spend before responding.
+    pub model_reasoning_effort: ReasoningEffort,
+
+    /// Level of detail requested for reasoning summaries.
+    pub model_reasoning_summary: ReasoningSummary,
+
     /// Path to the `seeky-linux-sandbox` executable. This must be set if
-    /// [`crate::exec::SandboxType::LinuxSeccomp`] is used. Note that this
+    /// [`crate::exec::SandboxType::LinuxSeccomp`] or
+    /// [`crate::exec::SandboxType::LinuxNamespaces`] is used. Note that this
     /// cannot be set in the config file: it must be set in code via
     /// [`ConfigOverrides`].
     ///
@@ -109,7 +153,7 @@ pub struct Config {
 }
 
 /// Base config deserialized from ~/.seeky/config.toml.
-#[derive(Deserialize, Debug, Clone, Default)]
+#[derive(Deserialize, Debug, Clone, Default, JsonSchema)]
 pub struct ConfigToml {
     /// Optional override of model selection.
     pub model: Option<String>,
@@ -127,6 +171,7 @@ pub struct ConfigToml {
     // the key is omitted from the TOML. Without it, Serde treats the field as
     // required because we supply a custom deserializer.
     #[serde(default, deserialize_with = "deserialize_sandbox_permissions")]
+    #[schemars(with = "Option<Vec<String>>")]
     pub sandbox_permissions: Option<Vec<SandboxPermission>>,
 
     /// Disable server-side response storage (sends the full conversation
@@ -161,7 +206,24 @@ pub struct ConfigToml {
 
     /// Settings that govern if and what will be written to `~/.seeky/history.jsonl`.
     #[serde(default)]
-    pub history: Option<History>,
+    pub history: Option<HistoryToml>,
+
+    /// Settings for scrubbing secrets from tool output, history and rollouts.
+    #[serde(default)]
+    pub redaction: Option<RedactionToml>,
+
+    /// Settings for the audit log of commands and patches.
+    #[serde(default)]
+    pub audit: Option<AuditToml>,
+
+    /// Additional `.policy` files for command safety, loaded after the ones
+    /// in `$SEEKY_HOME/policies`. Relative paths are resolved against
+    /// `$SEEKY_HOME`.
+    pub exec_policy_files: Option<Vec<PathBuf>>,
+
+    /// Settings for the sandbox in which commands are executed.
+    #[serde(default)]
+    pub sandbox: Option<SandboxToml>,
 
     /// Optional URI-based file opener. If set, citations to files in the model
     /// output will be hyperlinked using the specified URI scheme.
@@ -169,32 +231,111 @@ pub struct ConfigToml {
 
     /// Collection of settings that are specific to the TUI.
     pub tui: Option<Tui>,
+
+    /// How much effort reasoning models should spend before responding.
+    pub model_reasoning_effort: Option<ReasoningEffort>,
+
+    /// Level of detail requested for reasoning summaries.
+    pub model_reasoning_summary: Option<ReasoningSummary>,
 }
 
-impl ConfigToml {
-    /// Attempt to parse the file at `~/.seeky/config.toml`. If it does not
-    /// exist, return a default config. Though if it exists and cannot be
-    /// parsed, report that to the user and force them to fix it.
-    fn load_from_toml(seeky_home: &Path) -> std::io::Result<Self> {
-        let config_toml_path = seeky_home.join("config.toml");
-        match std::fs::read_to_string(&config_toml_path) {
-            Ok(contents) => toml::from_str::<Self>(&contents).map_err(|e| {
-                tracing::error!("Failed to parse config.toml: {e}");
-                std::io::Error::new(std::io::ErrorKind::InvalidData, e)
-            }),
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
-                tracing::info!("config.toml not found, using defaults");
-                Ok(Self::default())
+/// Attempt to parse the file at `~/.seeky/config.toml` as a generic TOML
+/// value so that `-c key=value` overrides can be layered on top of it before
+/// it is deserialized into a [`ConfigToml`]. If the file does not exist,
+/// return an empty table. Though if it exists and cannot be parsed, report
+/// that to the user and force them to fix it.
+fn load_config_as_toml(seeky_home: &Path) -> std::io::Result<TomlValue> {
+    let config_toml_path = config_toml_path(seeky_home);
+    match std::fs::read_to_string(&config_toml_path) {
+        Ok(contents) => toml::from_str::<TomlValue>(&contents).map_err(|e| {
+            tracing::error!("Failed to parse config.toml: {e}");
+            std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            tracing::info!("config.toml not found, using defaults");
+            Ok(TomlValue::Table(Default::default()))
+        }
+        Err(e) => {
+            tracing::error!("Failed to read config.toml: {e}");
+            Err(e)
+        }
+    }
+}
+
+/// Sets the value at the dotted `path` (e.g. `tui.disable_mouse_capture`)
+/// inside `root`, creating intermediate tables as needed. Any non-table value
+/// found along the way is replaced by a table.
+fn apply_toml_override(root: &mut TomlValue, path: &str, value: TomlValue) {
+    let mut current = root;
+    let mut segments = path.split('.').peekable();
+    while let Some(segment) = segments.next() {
+        if !current.is_table() {
+            *current = TomlValue::Table(Default::default());
+        }
+        let TomlValue::Table(table) = current else {
+            unreachable!("value was just replaced with a table");
+        };
+
+        if segments.peek().is_none() {
+            table.insert(segment.to_string(), value);
+            return;
+        }
+        current = table
+            .entry(segment.to_string())
+            .or_insert_with(|| TomlValue::Table(Default::default()));
+    }
+}
+
+/// Deserializes `value` into a [`ConfigToml`], also returning the dotted paths
+/// of any keys that were present in `value` but not recognized.
+fn deserialize_config_toml(value: TomlValue) -> Result<(ConfigToml, Vec<String>), toml::de::Error> {
+    let mut unknown_keys = Vec::new();
+    let cfg = serde_ignored::deserialize(value, |path| {
+        unknown_keys.push(format_ignored_path(&path));
+    })?;
+    Ok((cfg, unknown_keys))
+}
+
+/// Same as [`deserialize_config_toml`], but parses `contents` directly so that
+/// errors carry the span of the offending value in the source text.
+pub(crate) fn deserialize_config_toml_str(
+    contents: &str,
+) -> Result<(ConfigToml, Vec<String>), toml::de::Error> {
+    let mut unknown_keys = Vec::new();
+    let cfg = serde_ignored::deserialize(toml::Deserializer::new(contents), |path| {
+        unknown_keys.push(format_ignored_path(&path));
+    })?;
+    Ok((cfg, unknown_keys))
+}
+
+/// Renders a [`serde_ignored::Path`] in the same dotted form used by `-c`,
+/// dropping the `?` markers that serde_ignored emits for `Option`s.
+fn format_ignored_path(path: &serde_ignored::Path) -> String {
+    use serde_ignored::Path;
+
+    fn collect(path: &Path, segments: &mut Vec<String>) {
+        match path {
+            Path::Root => {}
+            Path::Seq { parent, index } => {
+                collect(parent, segments);
+                segments.push(index.to_string());
             }
-            Err(e) => {
-                tracing::error!("Failed to read config.toml: {e}");
-                Err(e)
+            Path::Map { parent, key } => {
+                collect(parent, segments);
+                segments.push(key.clone());
             }
+            Path::Some { parent }
+            | Path::NewtypeStruct { parent }
+            | Path::NewtypeVariant { parent } => collect(parent, segments),
         }
     }
+
+    let mut segments = Vec::new();
+    collect(path, &mut segments);
+    segments.join(".")
 }
 
-fn deserialize_sandbox_permissions<'de, D>(
+pub(crate) fn deserialize_sandbox_permissions<'de, D>(
     deserializer: D,
 ) -> Result<Option<Vec<SandboxPermission>>, D::Error>
 where
@@ -238,14 +379,40 @@ impl Config {
     /// ~/.seeky/config.toml, ~/.seeky/instructions.md, embedded defaults, and
     /// any values provided in `overrides` (highest precedence).
     pub fn load_with_overrides(overrides: ConfigOverrides) -> std::io::Result<Self> {
+        Self::load_with_cli_overrides(Vec::new(), overrides)
+    }
+
+    /// Like [`Config::load_with_overrides`], but first applies the generic
+    /// `-c key=value` overrides from the command line to the contents of
+    /// `config.toml`. Each override is a dotted path into [`ConfigToml`] and
+    /// the value to store there. Overrides that do not correspond to a known
+    /// config key are reported as an error.
+    pub fn load_with_cli_overrides(
+        cli_overrides: Vec<(String, TomlValue)>,
+        overrides: ConfigOverrides,
+    ) -> std::io::Result<Self> {
         // Resolve the directory that stores Seeky state (e.g. ~/.seeky or the
         // value of $SEEKY_HOME) so we can embed it into the resulting
         // `Config` instance.
         let seeky_home = find_seeky_home()?;
 
-        let cfg: ConfigToml = ConfigToml::load_from_toml(&seeky_home)?;
+        let mut root_value = load_config_as_toml(&seeky_home)?;
+        let override_keys: Vec<String> = cli_overrides.iter().map(|(k, _)| k.clone()).collect();
+        for (path, value) in cli_overrides {
+            apply_toml_override(&mut root_value, &path, value);
+        }
+
+        let (cfg, unknown_keys) = deserialize_config_toml(root_value).map_err(|e| {
+            tracing::error!("Failed to parse config.toml: {e}");
+            std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+        })?;
         tracing::warn!("Config parsed from config.toml: {cfg:?}");
 
+        check_overrides_are_known(&override_keys, &unknown_keys)?;
+        for key in &unknown_keys {
+            tracing::warn!("Ignoring unknown key in config.toml: `{key}`");
+        }
+
         Self::load_from_base_config_with_overrides(cfg, overrides, seeky_home)
     }
 
@@ -271,24 +438,19 @@ impl Config {
         } = overrides;
 
         let config_profile = match config_profile_key.or(cfg.profile) {
-            Some(key) => cfg
-                .profiles
-                .get(&key)
-                .ok_or_else(|| {
-                    std::io::Error::new(
-                        std::io::ErrorKind::NotFound,
-                        format!("config profile `{key}` not found"),
-                    )
-                })?
-                .clone(),
+            Some(key) => ConfigProfile::resolve(&cfg.profiles, &key)?,
             None => ConfigProfile::default(),
         };
 
         let sandbox_policy = match sandbox_policy {
             Some(sandbox_policy) => sandbox_policy,
             None => {
-                // Derive a SandboxPolicy from the permissions in the config.
-                match cfg.sandbox_permissions {
+                // Derive a SandboxPolicy from the permissions in the profile
+                // or the config.
+                match config_profile
+                    .sandbox_permissions
+                    .or(cfg.sandbox_permissions)
+                {
                     // Note this means the user can explicitly set permissions
                     // to the empty list in the config file, granting it no
                     // permissions whatsoever.
@@ -298,6 +460,13 @@ impl Config {
                 }
             }
         };
+        let sandbox: Sandbox = merge_tables(config_profile.sandbox, cfg.sandbox, merge_sandbox)
+            .unwrap_or_default()
+            .into();
+        let sandbox_policy = match &sandbox.protected_paths {
+            Some(protected_paths) => sandbox_policy.with_protected_paths(protected_paths.clone()),
+            None => sandbox_policy,
+        };
 
         let mut model_providers = built_in_model_providers();
         // Merge user-defined providers into the built-in list.
@@ -319,7 +488,23 @@ impl Config {
             })?
             .clone();
 
-        let shell_environment_policy = cfg.shell_environment_policy.into();
+        let mut shell_environment_policy: ShellEnvironmentPolicy = match config_profile
+            .shell_environment_policy
+        {
+            Some(policy) => merge_shell_environment_policy(policy, cfg.shell_environment_policy),
+            None => cfg.shell_environment_policy,
+        }
+        .into();
+
+        let mut mcp_servers = cfg.mcp_servers;
+        mcp_servers.extend(config_profile.mcp_servers);
+        for name in &config_profile.disabled_mcp_servers {
+            mcp_servers.remove(name);
+        }
+
+        // Instructions from the profile take precedence over the contents of
+        // instructions.md.
+        let instructions = config_profile.instructions.or(instructions);
 
         let resolved_cwd = {
             use std::env;
@@ -340,7 +525,33 @@ impl Config {
             }
         };
 
-        let history = cfg.history.unwrap_or_default();
+        // Env files belong to the project, not to the directory Seeky was
+        // started in.
+        let project_root = find_git_root(&resolved_cwd).unwrap_or_else(|| resolved_cwd.clone());
+        for path in &mut shell_environment_policy.env_files {
+            if path.is_relative() {
+                *path = project_root.join(&*path);
+            }
+        }
+
+        let exec_policy_files = exec_policy_files(&seeky_home, cfg.exec_policy_files)?;
+        let project_exec_policy_files =
+            policy_files_in(&project_root.join(".seeky").join("policies"))?;
+
+        let history = merge_tables(config_profile.history, cfg.history, merge_history)
+            .unwrap_or_default()
+            .into();
+
+        let redaction: Redaction =
+            merge_tables(config_profile.redaction, cfg.redaction, merge_redaction)
+                .unwrap_or_default()
+                .into();
+        Redactor::new(&redaction).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("invalid pattern in `redaction.patterns`: {e}"),
+            )
+        })?;
 
         let config = Self {
             model: model
@@ -360,15 +571,36 @@ impl Config {
                 .or(config_profile.disable_response_storage)
                 .or(cfg.disable_response_storage)
                 .unwrap_or(false),
-            notify: cfg.notify,
+            notify: config_profile.notify.or(cfg.notify),
             instructions,
-            mcp_servers: cfg.mcp_servers,
+            mcp_servers,
             model_providers,
-            project_doc_max_bytes: cfg.project_doc_max_bytes.unwrap_or(PROJECT_DOC_MAX_BYTES),
+            project_doc_max_bytes: config_profile
+                .project_doc_max_bytes
+                .or(cfg.project_doc_max_bytes)
+                .unwrap_or(PROJECT_DOC_MAX_BYTES),
             seeky_home,
             history,
-            file_opener: cfg.file_opener.unwrap_or(UriBasedFileOpener::VsCode),
-            tui: cfg.tui.unwrap_or_default(),
+            redaction,
+            audit: merge_tables(config_profile.audit, cfg.audit, merge_audit)
+                .unwrap_or_default()
+                .into(),
+            exec_policy_files,
+            project_exec_policy_files,
+            sandbox,
+            file_opener: config_profile
+                .file_opener
+                .or(cfg.file_opener)
+                .unwrap_or(UriBasedFileOpener::VsCode),
+            tui: config_profile.tui.or(cfg.tui).unwrap_or_default(),
+            model_reasoning_effort: config_profile
+                .model_reasoning_effort
+                .or(cfg.model_reasoning_effort)
+                .unwrap_or_default(),
+            model_reasoning_summary: config_profile
+                .model_reasoning_summary
+                .or(cfg.model_reasoning_summary)
+                .unwrap_or_default(),
             seeky_linux_sandbox_exe,
         };
         Ok(config)
@@ -392,6 +624,76 @@ impl Config {
     }
 }
 
+/// The user's `.policy` files for command safety, in the order they are
+/// layered: `$SEEKY_HOME/policies/*.policy`, then `exec_policy_files` from
+/// `config.toml`.
+fn exec_policy_files(
+    seeky_home: &Path,
+    configured: Option<Vec<PathBuf>>,
+) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = policy_files_in(&seeky_home.join("policies"))?;
+    files.extend(
+        configured
+            .unwrap_or_default()
+            .into_iter()
+            .map(|path| seeky_home.join(path)),
+    );
+    Ok(files)
+}
+
+/// The `*.policy` files in `dir`, sorted by name.
+fn policy_files_in(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "policy") && path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Returns an error naming every `-c` override in `override_keys` that did not
+/// map onto a field of [`ConfigToml`] (i.e., appears in `unknown_keys`).
+fn check_overrides_are_known(
+    override_keys: &[String],
+    unknown_keys: &[String],
+) -> std::io::Result<()> {
+    let rejected: Vec<&str> = override_keys
+        .iter()
+        .filter(|key| {
+            unknown_keys.iter().any(|unknown| {
+                unknown == *key
+                    || unknown.starts_with(&format!("{key}."))
+                    || key.starts_with(&format!("{unknown}."))
+            })
+        })
+        .map(String::as_str)
+        .collect();
+
+    if rejected.is_empty() {
+        Ok(())
+    } else {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "unknown configuration key(s) passed via -c: {}",
+                rejected
+                    .iter()
+                    .map(|key| format!("`{key}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ))
+    }
+}
+
 fn default_model() -> String {
     OPENAI_DEFAULT_MODEL.to_string()
 }
@@ -404,7 +706,7 @@ fn default_model() -> String {
 ///   function will Err if the path does not exist.
 /// - If `SEEKY_HOME` is not set, this function does not verify that the
 ///   directory exists.
-fn find_seeky_home() -> std::io::Result<PathBuf> {
+pub fn find_seeky_home() -> std::io::Result<PathBuf> {
     // Honor the `SEEKY_HOME` environment variable when it is set to allow users
     // (and tests) to override the default location.
     if let Ok(val) = std::env::var("SEEKY_HOME") {
@@ -423,6 +725,12 @@ fn find_seeky_home() -> std::io::Result<PathBuf> {
     Ok(p)
 }
 
+/// Returns the path to `config.toml` inside `seeky_home`. Does not verify that
+/// the file exists.
+pub fn config_toml_path(seeky_home: &Path) -> PathBuf {
+    seeky_home.join("config.toml")
+}
+
 /// Returns the path to the folder where Seeky logs are stored. Does not verify
 /// that the directory exists.
 pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
@@ -438,24 +746,29 @@ pub fn parse_sandbox_permission_with_base_path(
     use SandboxPermission::*;
 
     if let Some(path) = raw.strip_prefix("disk-write-folder=") {
-        return if path.is_empty() {
+        let folder = parse_permission_folder("disk-write-folder", path, base_path)?;
+        return Ok(DiskWriteFolder { folder });
+    }
+
+    if let Some(path) = raw.strip_prefix("disk-read-folder=") {
+        let folder = parse_permission_folder("disk-read-folder", path, base_path)?;
+        return Ok(DiskReadFolder { folder });
+    }
+
+    if let Some(domains) = raw.strip_prefix("network-allow-domains=") {
+        let domains: Vec<String> = domains
+            .split(',')
+            .map(str::trim)
+            .filter(|domain| !domain.is_empty())
+            .map(String::from)
+            .collect();
+        return if domains.is_empty() {
             Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
-                "--sandbox-permission disk-write-folder=<PATH> requires a non-empty PATH",
+                "--sandbox-permission network-allow-domains=<DOMAINS> requires a comma-separated list of domains",
             ))
         } else {
-            use path_absolutize::*;
-
-            let file = PathBuf::from(path);
-            let absolute_path = if file.is_relative() {
-                file.absolutize_from(base_path)
-            } else {
-                file.absolutize()
-            }
-            .map(|path| path.into_owned())?;
-            Ok(DiskWriteFolder {
-                folder: absolute_path,
-            })
+            Ok(NetworkAllowDomains { domains })
         };
     }
 
@@ -475,10 +788,33 @@ pub fn parse_sandbox_permission_with_base_path(
     }
 }
 
+/// Resolves the `<PATH>` of a parameterised permission such as
+/// `disk-write-folder=<PATH>` against `base_path`.
+fn parse_permission_folder(name: &str, path: &str, base_path: PathBuf) -> std::io::Result<PathBuf> {
+    use path_absolutize::*;
+
+    if path.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("--sandbox-permission {name}=<PATH> requires a non-empty PATH"),
+        ));
+    }
+
+    let file = PathBuf::from(path);
+    let absolute_path = if file.is_relative() {
+        file.absolutize_from(base_path)
+    } else {
+        file.absolutize()
+    }?;
+    Ok(absolute_path.into_owned())
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::expect_used, clippy::unwrap_used)]
     use crate::config_types::HistoryPersistence;
+    use crate::config_types::LinuxSandboxBackend;
+    use crate::config_types::ResourceLimits;
 
     use super::*;
     use pretty_assertions::assert_eq;
@@ -530,9 +866,10 @@ persistence = "save-all"
             toml::from_str::<ConfigToml>(history_with_persistence)
                 .expect("TOML deserialization should succeed");
         assert_eq!(
-            Some(History {
-                persistence: HistoryPersistence::SaveAll,
+            Some(HistoryToml {
+                persistence: Some(HistoryPersistence::SaveAll),
                 max_bytes: None,
+                max_age_days: None,
             }),
             history_with_persistence_cfg.history
         );
@@ -546,14 +883,145 @@ persistence = "none"
             toml::from_str::<ConfigToml>(history_no_persistence)
                 .expect("TOML deserialization should succeed");
         assert_eq!(
-            Some(History {
-                persistence: HistoryPersistence::None,
+            Some(HistoryToml {
+                persistence: Some(HistoryPersistence::None),
                 max_bytes: None,
+                max_age_days: None,
             }),
             history_no_persistence_cfg.history
         );
     }
 
+    #[test]
+    fn test_sandbox_limits() {
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+            [sandbox.limits]
+            cpu_seconds = 60
+            memory_bytes = 4294967296
+            max_processes = 512
+        "#,
+        )
+        .expect("TOML deserialization should succeed");
+
+        let limits = cfg
+            .sandbox
+            .and_then(|sandbox| sandbox.limits)
+            .expect("sandbox limits should be parsed");
+        assert_eq!(
+            ResourceLimits {
+                cpu_seconds: Some(60),
+                memory_bytes: Some(4 * 1024 * 1024 * 1024),
+                file_size_bytes: None,
+                max_processes: Some(512),
+                open_files: None,
+            },
+            limits
+        );
+    }
+
+    #[test]
+    fn test_sandbox_protected_paths() -> std::io::Result<()> {
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+            sandbox_permissions = ["disk-write-cwd"]
+
+            [sandbox]
+            protected_paths = [".git", ".env"]
+        "#,
+        )
+        .expect("TOML deserialization should succeed");
+
+        let seeky_home = TempDir::new()?;
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            seeky_home.path().to_path_buf(),
+        )?;
+        assert_eq!(
+            vec![".git".to_string(), ".env".to_string()],
+            config.sandbox_policy.protected_paths()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_env_files_are_relative_to_the_git_root() -> std::io::Result<()> {
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+            [shell_environment_policy]
+            env_files = [".seeky/env", "/etc/seeky.env"]
+        "#,
+        )
+        .expect("TOML deserialization should succeed");
+        let repo = TempDir::new()?;
+        std::fs::create_dir(repo.path().join(".git"))?;
+        let cwd = repo.path().join("crates").join("app");
+        std::fs::create_dir_all(&cwd)?;
+
+        let seeky_home = TempDir::new()?;
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                cwd: Some(cwd),
+                ..Default::default()
+            },
+            seeky_home.path().to_path_buf(),
+        )?;
+        assert_eq!(
+            vec![
+                repo.path().join(".seeky").join("env"),
+                PathBuf::from("/etc/seeky.env"),
+            ],
+            config.shell_environment_policy.env_files
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_exec_policy_files_are_layered_in_order() -> std::io::Result<()> {
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+            exec_policy_files = ["team.policy", "/etc/seeky/site.policy"]
+        "#,
+        )
+        .expect("TOML deserialization should succeed");
+        let seeky_home = TempDir::new()?;
+        let user_policies = seeky_home.path().join("policies");
+        std::fs::create_dir(&user_policies)?;
+        std::fs::write(user_policies.join("b.policy"), "")?;
+        std::fs::write(user_policies.join("a.policy"), "")?;
+        std::fs::write(user_policies.join("notes.txt"), "")?;
+        let repo = TempDir::new()?;
+        std::fs::create_dir(repo.path().join(".git"))?;
+        let project_policies = repo.path().join(".seeky").join("policies");
+        std::fs::create_dir_all(&project_policies)?;
+        std::fs::write(project_policies.join("repo.policy"), "")?;
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                cwd: Some(repo.path().to_path_buf()),
+                ..Default::default()
+            },
+            seeky_home.path().to_path_buf(),
+        )?;
+        assert_eq!(
+            vec![
+                user_policies.join("a.policy"),
+                user_policies.join("b.policy"),
+                seeky_home.path().join("team.policy"),
+                PathBuf::from("/etc/seeky/site.policy"),
+            ],
+            config.exec_policy_files
+        );
+        assert_eq!(
+            vec![project_policies.join("repo.policy")],
+            config.project_exec_policy_files
+        );
+        Ok(())
+    }
+
     /// Deserializing a TOML string containing an *invalid* permission should
     /// fail with a helpful error rather than silently defaulting or
     /// succeeding.
@@ -570,6 +1038,348 @@ persistence = "none"
         assert!(msg.contains("not-a-real-permission"));
     }
 
+    #[test]
+    fn test_parameterised_sandbox_permissions() {
+        let base_path = PathBuf::from("/workspace/project");
+        assert_eq!(
+            SandboxPermission::DiskReadFolder {
+                folder: PathBuf::from("/workspace/vendor"),
+            },
+            parse_sandbox_permission_with_base_path(
+                "disk-read-folder=../vendor",
+                base_path.clone()
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            SandboxPermission::NetworkAllowDomains {
+                domains: vec!["crates.io".to_string(), "*.crates.io".to_string()],
+            },
+            parse_sandbox_permission_with_base_path(
+                "network-allow-domains=crates.io, *.crates.io,",
+                base_path.clone()
+            )
+            .unwrap()
+        );
+        assert!(
+            parse_sandbox_permission_with_base_path("disk-read-folder=", base_path.clone())
+                .is_err()
+        );
+        assert!(
+            parse_sandbox_permission_with_base_path("network-allow-domains=,", base_path).is_err()
+        );
+    }
+
+    #[test]
+    fn test_apply_toml_override_creates_nested_tables() {
+        let mut root: TomlValue = toml::from_str(
+            r#"
+model = "o3"
+tui = "not-a-table"
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+
+        apply_toml_override(
+            &mut root,
+            "shell_environment_policy.ignore_default_excludes",
+            TomlValue::Boolean(true),
+        );
+        apply_toml_override(
+            &mut root,
+            "tui.disable_mouse_capture",
+            TomlValue::Boolean(true),
+        );
+        apply_toml_override(&mut root, "model", TomlValue::String("o4-mini".to_string()));
+
+        let (cfg, unknown_keys) =
+            deserialize_config_toml(root).expect("overridden config should deserialize");
+        assert!(unknown_keys.is_empty());
+        assert_eq!(Some("o4-mini".to_string()), cfg.model);
+        assert_eq!(
+            Some(true),
+            cfg.shell_environment_policy.ignore_default_excludes
+        );
+        assert_eq!(
+            Some(Tui {
+                disable_mouse_capture: true
+            }),
+            cfg.tui
+        );
+    }
+
+    #[test]
+    fn test_unknown_cli_overrides_are_rejected() {
+        let mut root = TomlValue::Table(Default::default());
+        let overrides = [
+            ("mdoel", TomlValue::String("o3".to_string())),
+            ("tui.no_such_option", TomlValue::Boolean(true)),
+            ("tui.disable_mouse_capture", TomlValue::Boolean(true)),
+            (
+                "mcp_servers.docs.command",
+                TomlValue::String("docs".to_string()),
+            ),
+        ];
+        for (key, value) in overrides.clone() {
+            apply_toml_override(&mut root, key, value);
+        }
+
+        let (cfg, unknown_keys) =
+            deserialize_config_toml(root).expect("config should still deserialize");
+        assert_eq!(
+            vec!["mdoel".to_string(), "tui.no_such_option".to_string()],
+            {
+                let mut sorted = unknown_keys.clone();
+                sorted.sort();
+                sorted
+            }
+        );
+        assert_eq!(
+            "docs",
+            cfg.mcp_servers
+                .get("docs")
+                .expect("docs server should be defined")
+                .command
+        );
+
+        let override_keys: Vec<String> = overrides.iter().map(|(k, _)| k.to_string()).collect();
+        let err = check_overrides_are_known(&override_keys, &unknown_keys)
+            .expect_err("unknown keys should be rejected");
+        assert_eq!(std::io::ErrorKind::InvalidInput, err.kind());
+        assert_eq!(
+            "unknown configuration key(s) passed via -c: `mdoel`, `tui.no_such_option`",
+            err.to_string()
+        );
+
+        // Unknown keys that only come from config.toml are not an error.
+        assert!(check_overrides_are_known(&["model".to_string()], &unknown_keys).is_ok());
+    }
+
+    /// Profiles can set any option and inherit from another profile via
+    /// `extends`; values set closer to the selected profile win.
+    #[test]
+    fn test_profile_extends_and_overrides_all_options() -> std::io::Result<()> {
+        let toml = r#"
+sandbox_permissions = ["disk-full-read-access"]
+project_doc_max_bytes = 1000
+
+[shell_environment_policy]
+inherit = "All"
+exclude = ["AWS_*"]
+
+[mcp_servers.docs]
+command = "docs-server"
+
+[mcp_servers.browser]
+command = "browser-server"
+
+[profiles.ci]
+approval_policy = "never"
+sandbox_permissions = ["disk-full-read-access", "disk-write-cwd"]
+disabled_mcp_servers = ["browser"]
+model_reasoning_effort = "low"
+notify = ["notify-send", "Seeky"]
+
+[profiles.ci.shell_environment_policy]
+set = { CI = "1" }
+
+[profiles.review]
+extends = "ci"
+instructions = "Only review, never edit."
+model_reasoning_summary = "detailed"
+project_doc_max_bytes = 2000
+
+[profiles.review.mcp_servers.linter]
+command = "lint-server"
+
+[profiles.review.tui]
+disable_mouse_capture = true
+"#;
+        let cfg: ConfigToml = toml::from_str(toml).expect("TOML deserialization should succeed");
+        let cwd = TempDir::new()?;
+        std::fs::write(cwd.path().join(".git"), "gitdir: nowhere")?;
+        let seeky_home = TempDir::new()?;
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                config_profile: Some("review".to_string()),
+                cwd: Some(cwd.path().to_path_buf()),
+                ..Default::default()
+            },
+            seeky_home.path().to_path_buf(),
+        )?;
+
+        assert_eq!(AskForApproval::Never, config.approval_policy);
+        assert_eq!(
+            SandboxPolicy::from(vec![
+                SandboxPermission::DiskFullReadAccess,
+                SandboxPermission::DiskWriteCwd,
+            ]),
+            config.sandbox_policy
+        );
+        assert_eq!(
+            ShellEnvironmentPolicy::from(ShellEnvironmentPolicyToml {
+                inherit: Some(crate::config_types::ShellEnvironmentPolicyInherit::All),
+                exclude: Some(vec!["AWS_*".to_string()]),
+                r#set: Some(HashMap::from([("CI".to_string(), "1".to_string())])),
+                ..Default::default()
+            }),
+            config.shell_environment_policy
+        );
+        let mut mcp_server_names: Vec<&str> =
+            config.mcp_servers.keys().map(String::as_str).collect();
+        mcp_server_names.sort();
+        assert_eq!(vec!["docs", "linter"], mcp_server_names);
+        assert_eq!(
+            Some("Only review, never edit.".to_string()),
+            config.instructions
+        );
+        assert_eq!(
+            Some(vec!["notify-send".to_string(), "Seeky".to_string()]),
+            config.notify
+        );
+        assert_eq!(2000, config.project_doc_max_bytes);
+        assert_eq!(ReasoningEffort::Low, config.model_reasoning_effort);
+        assert_eq!(ReasoningSummary::Detailed, config.model_reasoning_summary);
+        assert!(config.tui.disable_mouse_capture);
+        Ok(())
+    }
+
+    #[test]
+    fn test_profile_tables_override_individual_fields() -> std::io::Result<()> {
+        let toml = r#"
+[history]
+persistence = "none"
+max_age_days = 30
+
+[redaction]
+enabled = false
+
+[sandbox]
+linux_backend = "namespaces"
+limits = { cpu_seconds = 60, memory_bytes = 1024 }
+
+[profiles.base.history]
+max_bytes = 1000
+
+[profiles.strict]
+extends = "base"
+
+[profiles.strict.redaction]
+patterns = ["secret-[0-9]+"]
+
+[profiles.strict.sandbox.limits]
+memory_bytes = 2048
+"#;
+        let cfg: ConfigToml = toml::from_str(toml).expect("TOML deserialization should succeed");
+        let seeky_home = TempDir::new()?;
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                config_profile: Some("strict".to_string()),
+                cwd: Some(seeky_home.path().to_path_buf()),
+                ..Default::default()
+            },
+            seeky_home.path().to_path_buf(),
+        )?;
+
+        assert_eq!(
+            History {
+                persistence: HistoryPersistence::None,
+                max_bytes: Some(1000),
+                max_age_days: Some(30),
+            },
+            config.history
+        );
+        assert_eq!(
+            Redaction {
+                enabled: false,
+                patterns: vec!["secret-[0-9]+".to_string()],
+            },
+            config.redaction
+        );
+        assert_eq!(
+            LinuxSandboxBackend::Namespaces,
+            config.sandbox.linux_backend
+        );
+        assert_eq!(
+            ResourceLimits {
+                cpu_seconds: Some(60),
+                memory_bytes: Some(2048),
+                ..Default::default()
+            },
+            config.sandbox.limits
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_profile_instructions_take_precedence_over_instructions_md() -> std::io::Result<()> {
+        let toml = r#"
+instructions = "Ignored top-level instructions."
+
+[profiles.review]
+instructions = "Only review, never edit."
+
+[profiles.plain]
+model = "o3"
+"#;
+        let seeky_home = TempDir::new()?;
+        std::fs::write(
+            seeky_home.path().join("instructions.md"),
+            "Instructions from the file.",
+        )?;
+        let load = |profile: &str| {
+            let cfg: ConfigToml =
+                toml::from_str(toml).expect("TOML deserialization should succeed");
+            Config::load_from_base_config_with_overrides(
+                cfg,
+                ConfigOverrides {
+                    config_profile: Some(profile.to_string()),
+                    cwd: Some(seeky_home.path().to_path_buf()),
+                    ..Default::default()
+                },
+                seeky_home.path().to_path_buf(),
+            )
+        };
+
+        assert_eq!(
+            Some("Only review, never edit.".to_string()),
+            load("review")?.instructions
+        );
+        assert_eq!(
+            Some("Instructions from the file.".to_string()),
+            load("plain")?.instructions
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_profile_extends_cycle_is_an_error() {
+        let toml = r#"
+[profiles.a]
+extends = "b"
+
+[profiles.b]
+extends = "a"
+
+[profiles.c]
+extends = "missing"
+"#;
+        let cfg: ConfigToml = toml::from_str(toml).expect("TOML deserialization should succeed");
+
+        let err = ConfigProfile::resolve(&cfg.profiles, "a").expect_err("cycle should be detected");
+        assert_eq!(std::io::ErrorKind::InvalidInput, err.kind());
+        assert!(err.to_string().contains("cycle"));
+
+        let err =
+            ConfigProfile::resolve(&cfg.profiles, "c").expect_err("missing parent should fail");
+        assert_eq!(std::io::ErrorKind::NotFound, err.kind());
+        assert_eq!("config profile `missing` not found", err.to_string());
+    }
+
     struct PrecedenceTestFixture {
         cwd: TempDir,
         seeky_home: TempDir,
@@ -708,8 +1518,15 @@ disable_response_storage = true
                 project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
                 seeky_home: fixture.seeky_home(),
                 history: History::default(),
+                redaction: Redaction::default(),
+                audit: Audit::default(),
+                exec_policy_files: Vec::new(),
+                project_exec_policy_files: Vec::new(),
+                sandbox: Sandbox::default(),
                 file_opener: UriBasedFileOpener::VsCode,
                 tui: Tui::default(),
+                model_reasoning_effort: ReasoningEffort::default(),
+                model_reasoning_summary: ReasoningSummary::default(),
                 seeky_linux_sandbox_exe: None,
             },
             o3_profile_config
@@ -747,8 +1564,15 @@ disable_response_storage = true
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             seeky_home: fixture.seeky_home(),
             history: History::default(),
+            redaction: Redaction::default(),
+            audit: Audit::default(),
+            exec_policy_files: Vec::new(),
+            project_exec_policy_files: Vec::new(),
+            sandbox: Sandbox::default(),
             file_opener: UriBasedFileOpener::VsCode,
             tui: Tui::default(),
+            model_reasoning_effort: ReasoningEffort::default(),
+            model_reasoning_summary: ReasoningSummary::default(),
             seeky_linux_sandbox_exe: None,
         };
 
@@ -801,8 +1625,15 @@ disable_response_storage = true
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             seeky_home: fixture.seeky_home(),
             history: History::default(),
+            redaction: Redaction::default(),
+            audit: Audit::default(),
+            exec_policy_files: Vec::new(),
+            project_exec_policy_files: Vec::new(),
+            sandbox: Sandbox::default(),
             file_opener: UriBasedFileOpener::VsCode,
             tui: Tui::default(),
+            model_reasoning_effort: ReasoningEffort::default(),
+            model_reasoning_summary: ReasoningSummary::default(),
             seeky_linux_sandbox_exe: None,
         };
 
@@ -0,0 +1,302 @@
+//! Helpers for inspecting and editing `config.toml` in place, as used by the
+//! `seeky config` subcommand. Edits go through `toml_edit` so that comments
+//! and formatting in the user's file are preserved.
+
+use std::ops::Range;
+
+use toml_edit::DocumentMut;
+use toml_edit::ImDocument;
+use toml_edit::Item;
+use toml_edit::TableLike;
+
+use crate::config::deserialize_config_toml_str;
+
+/// A problem found in `config.toml`, with a 1-based location when known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigDiagnostic {
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "{line}:{column}: {}", self.message),
+            _ => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Result of [`validate_config_toml`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigValidation {
+    /// Problems that prevent Seeky from loading the file.
+    pub errors: Vec<ConfigDiagnostic>,
+    /// Problems that Seeky tolerates, such as unknown keys.
+    pub warnings: Vec<ConfigDiagnostic>,
+}
+
+impl ConfigValidation {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Checks that `contents` is valid TOML that deserializes into a
+/// [`ConfigToml`], reporting unknown keys as warnings.
+pub fn validate_config_toml(contents: &str) -> ConfigValidation {
+    let mut validation = ConfigValidation::default();
+
+    let document = match ImDocument::parse(contents) {
+        Ok(document) => document,
+        Err(e) => {
+            validation.errors.push(diagnostic_at(
+                contents,
+                e.span(),
+                e.message().trim().to_string(),
+            ));
+            return validation;
+        }
+    };
+
+    match deserialize_config_toml_str(contents) {
+        Ok((_, unknown_keys)) => {
+            for key in unknown_keys {
+                let span = find_key_span(document.as_table(), &key);
+                validation.warnings.push(diagnostic_at(
+                    contents,
+                    span,
+                    format!("unknown configuration key `{key}`"),
+                ));
+            }
+        }
+        Err(e) => {
+            validation
+                .errors
+                .push(diagnostic_at(contents, e.span(), e.message().to_string()));
+        }
+    }
+
+    validation
+}
+
+/// Returns the value stored at the dotted `key` in `contents`, rendered as
+/// TOML, or `None` if the key is not set.
+pub fn get_config_value(contents: &str, key: &str) -> Result<Option<String>, String> {
+    let document = contents
+        .parse::<DocumentMut>()
+        .map_err(|e| format!("failed to parse config.toml: {e}"))?;
+
+    let mut item = document.as_item();
+    for segment in key.split('.') {
+        match item.get(segment) {
+            Some(next) => item = next,
+            None => return Ok(None),
+        }
+    }
+
+    let rendered = match item {
+        Item::None => return Ok(None),
+        Item::Value(value) => {
+            let mut value = value.clone();
+            value.decor_mut().clear();
+            value.to_string()
+        }
+        Item::Table(table) => table.to_string(),
+        Item::ArrayOfTables(array) => array.to_string(),
+    };
+    Ok(Some(rendered.trim().to_string()))
+}
+
+/// Sets the dotted `key` in `contents` to `raw_value` and returns the updated
+/// file. `raw_value` is parsed as a TOML value, falling back to a string
+/// literal. Comments and formatting elsewhere in the file are preserved.
+///
+/// The edit is rejected if the resulting file would not be a valid
+/// `config.toml` or if `key` is not a known configuration key.
+pub fn set_config_value(contents: &str, key: &str, raw_value: &str) -> Result<String, String> {
+    let mut document = contents
+        .parse::<DocumentMut>()
+        .map_err(|e| format!("failed to parse config.toml: {e}"))?;
+
+    let segments: Vec<&str> = key.split('.').collect();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return Err(format!("invalid key: `{key}`"));
+    }
+
+    let value = raw_value
+        .trim()
+        .parse::<toml_edit::Value>()
+        .unwrap_or_else(|_| toml_edit::Value::from(raw_value));
+
+    let Some((last, parents)) = segments.split_last() else {
+        return Err(format!("invalid key: `{key}`"));
+    };
+    let mut table: &mut dyn TableLike = document.as_table_mut();
+    for segment in parents {
+        let entry = table
+            .entry(segment)
+            .or_insert_with(|| Item::Table(toml_edit::Table::new()));
+        table = entry
+            .as_table_like_mut()
+            .ok_or_else(|| format!("`{segment}` in `{key}` is not a table"))?;
+    }
+    match table.get_mut(last) {
+        Some(existing @ Item::Value(_)) => {
+            // Keep the existing key and its decor (e.g. trailing comments).
+            let decor = existing.as_value().map(|v| v.decor().clone());
+            *existing = Item::Value(value);
+            if let (Some(decor), Some(new_value)) = (decor, existing.as_value_mut()) {
+                *new_value.decor_mut() = decor;
+            }
+        }
+        _ => {
+            table.insert(last, Item::Value(value));
+        }
+    }
+
+    let updated = document.to_string();
+    let validation = validate_config_toml(&updated);
+    if let Some(error) = validation.errors.first() {
+        return Err(format!("refusing to write invalid config.toml: {error}"));
+    }
+    if validation
+        .warnings
+        .iter()
+        .any(|warning| warning.message == format!("unknown configuration key `{key}`"))
+    {
+        return Err(format!("unknown configuration key `{key}`"));
+    }
+    Ok(updated)
+}
+
+fn diagnostic_at(contents: &str, span: Option<Range<usize>>, message: String) -> ConfigDiagnostic {
+    let (line, column) = match span {
+        Some(span) => {
+            let (line, column) = line_and_column(contents, span.start);
+            (Some(line), Some(column))
+        }
+        None => (None, None),
+    };
+    ConfigDiagnostic {
+        line,
+        column,
+        message,
+    }
+}
+
+/// Converts a byte offset into a 1-based (line, column) pair.
+fn line_and_column(contents: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(contents.len());
+    let before = &contents[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+/// Locates the span of the (last segment of the) dotted `key` in `table`.
+fn find_key_span(table: &dyn TableLike, key: &str) -> Option<Range<usize>> {
+    let mut table = table;
+    let mut segments = key.split('.').peekable();
+    while let Some(segment) = segments.next() {
+        if segments.peek().is_none() {
+            return table.key(segment).and_then(|k| k.span());
+        }
+        table = table.get(segment)?.as_table_like()?;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const CONFIG: &str = r#"# Default model.
+model = "o3" # keep this comment
+
+[tui]
+disable_mouse_capture = false
+"#;
+
+    #[test]
+    fn validate_reports_parse_errors_with_location() {
+        let validation = validate_config_toml("model = \"o3\"\napproval_policy = \n");
+        assert!(!validation.is_valid());
+        assert_eq!(Some(2), validation.errors[0].line);
+        assert_eq!(Some(19), validation.errors[0].column);
+    }
+
+    #[test]
+    fn validate_reports_type_errors_with_location() {
+        let validation = validate_config_toml("model = \"o3\"\napproval_policy = \"sometimes\"\n");
+        assert!(!validation.is_valid());
+        assert_eq!(Some(2), validation.errors[0].line);
+        assert!(validation.errors[0].message.contains("sometimes"));
+    }
+
+    #[test]
+    fn validate_warns_about_unknown_keys() {
+        let validation = validate_config_toml(
+            "mdoel = \"o3\"\n\n[tui]\nmouse = true\ndisable_mouse_capture = false\n",
+        );
+        assert!(validation.is_valid());
+        assert_eq!(
+            vec![
+                "1:1: unknown configuration key `mdoel`".to_string(),
+                "4:1: unknown configuration key `tui.mouse`".to_string(),
+            ],
+            validation
+                .warnings
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn get_returns_values_and_tables() {
+        assert_eq!(
+            Some("\"o3\"".to_string()),
+            get_config_value(CONFIG, "model").unwrap()
+        );
+        assert_eq!(
+            Some("false".to_string()),
+            get_config_value(CONFIG, "tui.disable_mouse_capture").unwrap()
+        );
+        assert_eq!(None, get_config_value(CONFIG, "approval_policy").unwrap());
+    }
+
+    #[test]
+    fn set_preserves_comments() {
+        let updated = set_config_value(CONFIG, "model", "o4-mini").unwrap();
+        assert_eq!(
+            r#"# Default model.
+model = "o4-mini" # keep this comment
+
+[tui]
+disable_mouse_capture = false
+"#,
+            updated
+        );
+
+        let updated = set_config_value(&updated, "tui.disable_mouse_capture", "true").unwrap();
+        assert!(updated.contains("disable_mouse_capture = true"));
+
+        let updated = set_config_value(&updated, "history.persistence", "none").unwrap();
+        assert!(updated.contains("[history]\npersistence = \"none\""));
+    }
+
+    #[test]
+    fn set_rejects_invalid_edits() {
+        assert!(set_config_value(CONFIG, "approval_policy", "sometimes").is_err());
+        assert_eq!(
+            Err("unknown configuration key `mdoel`".to_string()),
+            set_config_value(CONFIG, "mdoel", "o3")
+        );
+        assert!(set_config_value(CONFIG, "model.name", "o3").is_err());
+    }
+}
@@ -1,15 +1,228 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use schemars::JsonSchema;
 use serde::Deserialize;
 
+use crate::config::deserialize_sandbox_permissions;
+use crate::config_types::AuditToml;
+use crate::config_types::HistoryToml;
+use crate::config_types::McpServerConfig;
+use crate::config_types::ReasoningEffort;
+use crate::config_types::ReasoningSummary;
+use crate::config_types::RedactionToml;
+use crate::config_types::ResourceLimits;
+use crate::config_types::SandboxToml;
+use crate::config_types::ShellEnvironmentPolicyToml;
+use crate::config_types::Tui;
+use crate::config_types::UriBasedFileOpener;
 use crate::protocol::AskForApproval;
+use crate::protocol::SandboxPermission;
 
 /// Collection of common configuration options that a user can define as a unit
+/// in `config.toml`. Every field is optional: anything left unset falls back
+/// to the profile named by `extends` (if any) and then to the top-level value
 /// in `config.toml`.
-#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, JsonSchema)]
 pub struct ConfigProfile {
+    /// Name of another profile whose values this profile inherits. Values set
+    /// directly on this profile take precedence over inherited ones.
+    pub extends: Option<String>,
+
     pub model: Option<String>,
     /// The key in the `model_providers` map identifying the
     /// [`ModelProviderInfo`] to use.
     pub model_provider: Option<String>,
     pub approval_policy: Option<AskForApproval>,
     pub disable_response_storage: Option<bool>,
+
+    #[serde(default, deserialize_with = "deserialize_sandbox_permissions")]
+    #[schemars(with = "Option<Vec<String>>")]
+    pub sandbox_permissions: Option<Vec<SandboxPermission>>,
+
+    /// Individual fields set here override the corresponding fields of the
+    /// top-level `shell_environment_policy`.
+    pub shell_environment_policy: Option<ShellEnvironmentPolicyToml>,
+
+    pub instructions: Option<String>,
+
+    /// MCP servers to add to (or replace in) the top-level `mcp_servers` map.
+    #[serde(default)]
+    pub mcp_servers: HashMap<String, McpServerConfig>,
+
+    /// Names of MCP servers from the top-level `mcp_servers` map (or from an
+    /// inherited profile) that should not be started with this profile.
+    #[serde(default)]
+    pub disabled_mcp_servers: Vec<String>,
+
+    pub notify: Option<Vec<String>>,
+    pub project_doc_max_bytes: Option<usize>,
+    pub model_reasoning_effort: Option<ReasoningEffort>,
+    pub model_reasoning_summary: Option<ReasoningSummary>,
+    /// Like `shell_environment_policy`, the `history`, `redaction`, `audit`
+    /// and `sandbox` tables override the inherited ones field by field.
+    pub history: Option<HistoryToml>,
+    pub redaction: Option<RedactionToml>,
+    pub audit: Option<AuditToml>,
+    pub sandbox: Option<SandboxToml>,
+    pub file_opener: Option<UriBasedFileOpener>,
+    pub tui: Option<Tui>,
+}
+
+impl ConfigProfile {
+    /// Returns the profile named `key` from `profiles` with its `extends`
+    /// chain flattened, so the result no longer refers to other profiles.
+    pub fn resolve(
+        profiles: &HashMap<String, ConfigProfile>,
+        key: &str,
+    ) -> std::io::Result<ConfigProfile> {
+        let mut chain = Vec::new();
+        let mut seen = HashSet::new();
+        let mut next = Some(key);
+        while let Some(name) = next {
+            if !seen.insert(name) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "config profile `{key}` has a cycle in its `extends` chain at `{name}`"
+                    ),
+                ));
+            }
+            let profile = profiles.get(name).ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("config profile `{name}` not found"),
+                )
+            })?;
+            chain.push(profile);
+            next = profile.extends.as_deref();
+        }
+
+        // Apply the most distant ancestor first so that each profile in the
+        // chain overrides the ones it extends.
+        let resolved = chain
+            .into_iter()
+            .rev()
+            .fold(ConfigProfile::default(), |base, profile| {
+                profile.clone().merge_onto(base)
+            });
+        Ok(resolved)
+    }
+
+    /// Layers `self` on top of `base`: fields set on `self` win.
+    fn merge_onto(self, base: ConfigProfile) -> ConfigProfile {
+        let mut mcp_servers = base.mcp_servers;
+        let mut disabled_mcp_servers = base.disabled_mcp_servers;
+        // Re-adding a server in a derived profile re-enables it.
+        disabled_mcp_servers.retain(|name| !self.mcp_servers.contains_key(name));
+        mcp_servers.extend(self.mcp_servers);
+        disabled_mcp_servers.extend(self.disabled_mcp_servers);
+
+        ConfigProfile {
+            extends: None,
+            model: self.model.or(base.model),
+            model_provider: self.model_provider.or(base.model_provider),
+            approval_policy: self.approval_policy.or(base.approval_policy),
+            disable_response_storage: self
+                .disable_response_storage
+                .or(base.disable_response_storage),
+            sandbox_permissions: self.sandbox_permissions.or(base.sandbox_permissions),
+            shell_environment_policy: merge_tables(
+                self.shell_environment_policy,
+                base.shell_environment_policy,
+                merge_shell_environment_policy,
+            ),
+            instructions: self.instructions.or(base.instructions),
+            mcp_servers,
+            disabled_mcp_servers,
+            notify: self.notify.or(base.notify),
+            project_doc_max_bytes: self.project_doc_max_bytes.or(base.project_doc_max_bytes),
+            model_reasoning_effort: self.model_reasoning_effort.or(base.model_reasoning_effort),
+            model_reasoning_summary: self
+                .model_reasoning_summary
+                .or(base.model_reasoning_summary),
+            history: merge_tables(self.history, base.history, merge_history),
+            redaction: merge_tables(self.redaction, base.redaction, merge_redaction),
+            audit: merge_tables(self.audit, base.audit, merge_audit),
+            sandbox: merge_tables(self.sandbox, base.sandbox, merge_sandbox),
+            file_opener: self.file_opener.or(base.file_opener),
+            tui: self.tui.or(base.tui),
+        }
+    }
+}
+
+/// Layers `table` on top of `base` with `merge` if both are set.
+pub(crate) fn merge_tables<T>(
+    table: Option<T>,
+    base: Option<T>,
+    merge: fn(T, T) -> T,
+) -> Option<T> {
+    match (table, base) {
+        (Some(table), Some(base)) => Some(merge(table, base)),
+        (table, base) => table.or(base),
+    }
+}
+
+/// Layers the fields set in `policy` on top of `base`.
+pub(crate) fn merge_shell_environment_policy(
+    policy: ShellEnvironmentPolicyToml,
+    base: ShellEnvironmentPolicyToml,
+) -> ShellEnvironmentPolicyToml {
+    ShellEnvironmentPolicyToml {
+        inherit: policy.inherit.or(base.inherit),
+        ignore_default_excludes: policy
+            .ignore_default_excludes
+            .or(base.ignore_default_excludes),
+        exclude: policy.exclude.or(base.exclude),
+        env_files: policy.env_files.or(base.env_files),
+        r#set: policy.r#set.or(base.r#set),
+        include_only: policy.include_only.or(base.include_only),
+        secrets: policy.secrets.or(base.secrets),
+        snapshot_login_shell: policy.snapshot_login_shell.or(base.snapshot_login_shell),
+        use_user_shell: policy.use_user_shell.or(base.use_user_shell),
+    }
+}
+
+/// Layers the fields set in `history` on top of `base`.
+pub(crate) fn merge_history(history: HistoryToml, base: HistoryToml) -> HistoryToml {
+    HistoryToml {
+        persistence: history.persistence.or(base.persistence),
+        max_bytes: history.max_bytes.or(base.max_bytes),
+        max_age_days: history.max_age_days.or(base.max_age_days),
+    }
+}
+
+/// Layers the fields set in `redaction` on top of `base`.
+pub(crate) fn merge_redaction(redaction: RedactionToml, base: RedactionToml) -> RedactionToml {
+    RedactionToml {
+        enabled: redaction.enabled.or(base.enabled),
+        patterns: redaction.patterns.or(base.patterns),
+    }
+}
+
+/// Layers the fields set in `audit` on top of `base`.
+pub(crate) fn merge_audit(audit: AuditToml, base: AuditToml) -> AuditToml {
+    AuditToml {
+        enabled: audit.enabled.or(base.enabled),
+        per_project: audit.per_project.or(base.per_project),
+    }
+}
+
+/// Layers the fields set in `sandbox`, and in its `limits`, on top of `base`.
+pub(crate) fn merge_sandbox(sandbox: SandboxToml, base: SandboxToml) -> SandboxToml {
+    SandboxToml {
+        linux_backend: sandbox.linux_backend.or(base.linux_backend),
+        limits: merge_tables(sandbox.limits, base.limits, merge_resource_limits),
+        protected_paths: sandbox.protected_paths.or(base.protected_paths),
+    }
+}
+
+fn merge_resource_limits(limits: ResourceLimits, base: ResourceLimits) -> ResourceLimits {
+    ResourceLimits {
+        cpu_seconds: limits.cpu_seconds.or(base.cpu_seconds),
+        memory_bytes: limits.memory_bytes.or(base.memory_bytes),
+        file_size_bytes: limits.file_size_bytes.or(base.file_size_bytes),
+        max_processes: limits.max_processes.or(base.max_processes),
+        open_files: limits.open_files.or(base.open_files),
+    }
 }
@@ -4,11 +4,14 @@
 // definitions that do not contain business logic.
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use wildmatch::WildMatchPattern;
 
+use schemars::JsonSchema;
 use serde::Deserialize;
+use serde::Serialize;
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct McpServerConfig {
     pub command: String,
 
@@ -19,7 +22,7 @@ pub struct McpServerConfig {
     pub env: Option<HashMap<String, String>>,
 }
 
-#[derive(Deserialize, Debug, Copy, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, JsonSchema)]
 pub enum UriBasedFileOpener {
     #[serde(rename = "vscode")]
     VsCode,
@@ -51,17 +54,221 @@ impl UriBasedFileOpener {
 }
 
 /// Settings that govern if and what will be written to `~/.seeky/history.jsonl`.
-#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default, JsonSchema)]
 pub struct History {
     /// If true, history entries will not be written to disk.
     pub persistence: HistoryPersistence,
 
-    /// If set, the maximum size of the history file in bytes.
-    /// TODO(mbolin): Not currently honored.
+    /// If set, the maximum size of the history file in bytes. Once the file
+    /// grows past this size, the oldest entries are dropped.
     pub max_bytes: Option<usize>,
+
+    /// If set, entries older than this many days are dropped.
+    pub max_age_days: Option<u64>,
+}
+
+/// `[history]` as written in `config.toml` or a profile. Fields left unset
+/// fall back to the table this one is layered on.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default, JsonSchema)]
+pub struct HistoryToml {
+    /// If true, history entries will not be written to disk.
+    pub persistence: Option<HistoryPersistence>,
+
+    /// If set, the maximum size of the history file in bytes. Once the file
+    /// grows past this size, the oldest entries are dropped.
+    pub max_bytes: Option<usize>,
+
+    /// If set, entries older than this many days are dropped.
+    pub max_age_days: Option<u64>,
+}
+
+impl From<HistoryToml> for History {
+    fn from(toml: HistoryToml) -> Self {
+        Self {
+            persistence: toml.persistence.unwrap_or_default(),
+            max_bytes: toml.max_bytes,
+            max_age_days: toml.max_age_days,
+        }
+    }
+}
+
+/// Settings for scrubbing secrets from tool output sent to the model, the
+/// message history and rollouts.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, JsonSchema)]
+#[serde(default)]
+pub struct Redaction {
+    /// Set to `false` to disable redaction, including the built-in detectors
+    /// for AWS keys, GitHub tokens, JWTs, private keys and `*_KEY=`-style
+    /// assignments.
+    pub enabled: bool,
+
+    /// Additional regular expressions to redact. If a pattern has a capture
+    /// group named `secret`, only that group is replaced.
+    pub patterns: Vec<String>,
+}
+
+impl Default for Redaction {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+/// `[redaction]` as written in `config.toml` or a profile. Fields left unset
+/// fall back to the table this one is layered on.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default, JsonSchema)]
+pub struct RedactionToml {
+    /// Set to `false` to disable redaction, including the built-in detectors
+    /// for AWS keys, GitHub tokens, JWTs, private keys and `*_KEY=`-style
+    /// assignments.
+    pub enabled: Option<bool>,
+
+    /// Additional regular expressions to redact. If a pattern has a capture
+    /// group named `secret`, only that group is replaced.
+    pub patterns: Option<Vec<String>>,
+}
+
+impl From<RedactionToml> for Redaction {
+    fn from(toml: RedactionToml) -> Self {
+        let default = Self::default();
+        Self {
+            enabled: toml.enabled.unwrap_or(default.enabled),
+            patterns: toml.patterns.unwrap_or(default.patterns),
+        }
+    }
+}
+
+/// Settings for the append-only log of every command and patch together with
+/// the safety decision that was made about it.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default, JsonSchema)]
+#[serde(default)]
+pub struct Audit {
+    /// Set to `true` to write `audit.jsonl`.
+    pub enabled: bool,
+
+    /// Write the log to `.seeky/audit.jsonl` in the project (the root of the
+    /// Git repository, or the cwd outside of one) instead of
+    /// `~/.seeky/audit.jsonl`.
+    pub per_project: bool,
+}
+
+/// `[audit]` as written in `config.toml` or a profile. Fields left unset fall
+/// back to the table this one is layered on.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default, JsonSchema)]
+pub struct AuditToml {
+    /// Set to `true` to write `audit.jsonl`.
+    pub enabled: Option<bool>,
+
+    /// Write the log to `.seeky/audit.jsonl` in the project (the root of the
+    /// Git repository, or the cwd outside of one) instead of
+    /// `~/.seeky/audit.jsonl`.
+    pub per_project: Option<bool>,
+}
+
+impl From<AuditToml> for Audit {
+    fn from(toml: AuditToml) -> Self {
+        Self {
+            enabled: toml.enabled.unwrap_or_default(),
+            per_project: toml.per_project.unwrap_or_default(),
+        }
+    }
+}
+
+/// Settings for the sandbox in which commands are executed.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default, JsonSchema)]
+#[serde(default)]
+pub struct Sandbox {
+    /// Mechanism used by `seeky-linux-sandbox` to enforce the sandbox policy.
+    pub linux_backend: LinuxSandboxBackend,
+
+    /// Resource limits applied to every command, sandboxed or not.
+    pub limits: ResourceLimits,
+
+    /// Paths that stay read-only inside the writable folders. Relative
+    /// entries apply inside every writable folder; entries starting with `!`
+    /// keep a path beneath a protected one writable. Defaults to
+    /// `.git/hooks`, `.git/config`, `.git/info` and `.seeky`.
+    pub protected_paths: Option<Vec<String>>,
+}
+
+/// `[sandbox]` as written in `config.toml` or a profile. Fields left unset,
+/// including the individual limits, fall back to the table this one is
+/// layered on.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default, JsonSchema)]
+pub struct SandboxToml {
+    /// Mechanism used by `seeky-linux-sandbox` to enforce the sandbox policy.
+    pub linux_backend: Option<LinuxSandboxBackend>,
+
+    /// Resource limits applied to every command, sandboxed or not.
+    pub limits: Option<ResourceLimits>,
+
+    /// Paths that stay read-only inside the writable folders. Relative
+    /// entries apply inside every writable folder; entries starting with `!`
+    /// keep a path beneath a protected one writable. Defaults to
+    /// `.git/hooks`, `.git/config`, `.git/info` and `.seeky`.
+    pub protected_paths: Option<Vec<String>>,
+}
+
+impl From<SandboxToml> for Sandbox {
+    fn from(toml: SandboxToml) -> Self {
+        Self {
+            linux_backend: toml.linux_backend.unwrap_or_default(),
+            limits: toml.limits.unwrap_or_default(),
+            protected_paths: toml.protected_paths,
+        }
+    }
+}
+
+/// Upper bounds on the resources a command and its children may use. Unset
+/// limits are inherited from Seeky. On Linux, `memory_bytes` and
+/// `max_processes` are enforced by a cgroup v2 leaf when a delegated cgroup is
+/// available, and by `setrlimit` otherwise.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[serde(default)]
+pub struct ResourceLimits {
+    /// CPU time in seconds (`RLIMIT_CPU`).
+    pub cpu_seconds: Option<u64>,
+
+    /// Memory in bytes: `memory.max` of the cgroup, or the address space
+    /// (`RLIMIT_AS`) as a fallback. Note that some runtimes reserve far more
+    /// address space than they use. Not enforced on macOS.
+    pub memory_bytes: Option<u64>,
+
+    /// Size in bytes of the largest file the command may write
+    /// (`RLIMIT_FSIZE`).
+    pub file_size_bytes: Option<u64>,
+
+    /// Number of processes: `pids.max` of the cgroup, or `RLIMIT_NPROC` as a
+    /// fallback, which counts all processes of the current user.
+    pub max_processes: Option<u64>,
+
+    /// Number of open file descriptors per process (`RLIMIT_NOFILE`).
+    pub open_files: Option<u64>,
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinuxSandboxBackend {
+    /// Landlock file-system rules plus a seccomp filter for the network.
+    #[default]
+    Landlock,
+
+    /// Unprivileged user, mount, PID and network namespaces: `/` is mounted
+    /// read-only, writable roots are bind-mounted read-write and `/tmp` is a
+    /// private tmpfs. Falls back to `Landlock` if user namespaces are not
+    /// available.
+    Namespaces,
 }
 
-#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Default, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum HistoryPersistence {
     /// Save all history entries to disk.
@@ -72,7 +279,7 @@ pub enum HistoryPersistence {
 }
 
 /// Collection of settings that are specific to the TUI.
-#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default, JsonSchema)]
 pub struct Tui {
     /// By default, mouse capture is enabled in the TUI so that it is possible
     /// to scroll the conversation history with a mouse. This comes at the cost
@@ -88,7 +295,32 @@ pub struct Tui {
     pub disable_mouse_capture: bool,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+/// Controls how much effort reasoning models spend before responding. See
+/// https://platform.openai.com/docs/guides/reasoning for details.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    Low,
+    Medium,
+    #[default]
+    High,
+    /// Do not send a `reasoning` parameter with the request at all.
+    None,
+}
+
+/// Level of detail of the reasoning summary returned by reasoning models.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningSummary {
+    #[default]
+    Auto,
+    Concise,
+    Detailed,
+    /// Do not request a reasoning summary.
+    None,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default, JsonSchema)]
 
 pub enum ShellEnvironmentPolicyInherit {
     /// "Core" environment variables for the platform. On UNIX, this would
@@ -105,7 +337,7 @@ pub enum ShellEnvironmentPolicyInherit {
 
 /// Policy for building the `env` when spawning a process via either the
 /// `shell` or `local_shell` tool.
-#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[derive(Deserialize, Debug, Clone, PartialEq, Default, JsonSchema)]
 pub struct ShellEnvironmentPolicyToml {
     pub inherit: Option<ShellEnvironmentPolicyInherit>,
 
@@ -118,18 +350,37 @@ pub struct ShellEnvironmentPolicyToml {
 
     /// List of regular expressions.
     pub include_only: Option<Vec<String>>,
+
+    /// Files with `NAME=value` lines (dotenv syntax) to load before `set`.
+    /// Relative paths are resolved against the root of the Git repository,
+    /// or against the cwd outside of one.
+    pub env_files: Option<Vec<String>>,
+
+    /// Variables whose values must never appear in output sent to the model.
+    pub secrets: Option<Vec<String>>,
+
+    /// Start from the environment of the user's login shell instead of the
+    /// environment Seeky was started with.
+    pub snapshot_login_shell: Option<bool>,
+
+    /// Run `bash -lc` commands with the user's shell, if it is bash or zsh,
+    /// with the aliases and functions of its login shell. Implies
+    /// `snapshot_login_shell`.
+    pub use_user_shell: Option<bool>,
 }
 
 pub type EnvironmentVariablePattern = WildMatchPattern<'*', '?'>;
 
 /// Deriving the `env` based on this policy works as follows:
-/// 1. Create an initial map based on the `inherit` policy.
+/// 1. Create an initial map based on the `inherit` policy, from the snapshot
+///    of the login shell if `snapshot_login_shell` is set.
 /// 2. If `ignore_default_excludes` is false, filter the map using the default
 ///    exclude pattern(s), which are: `"*KEY*"` and `"*TOKEN*"`.
 /// 3. If `exclude` is not empty, filter the map using the provided patterns.
-/// 4. Insert any entries from `r#set` into the map.
-/// 5. If non-empty, filter the map using the `include_only` patterns.
-#[derive(Debug, Clone, PartialEq, Default)]
+/// 4. Insert the entries of the `env_files` that exist, in order.
+/// 5. Insert any entries from `r#set` into the map.
+/// 6. If non-empty, filter the map using the `include_only` patterns.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
 pub struct ShellEnvironmentPolicy {
     /// Starting point when building the environment.
     pub inherit: ShellEnvironmentPolicyInherit,
@@ -139,13 +390,42 @@ pub struct ShellEnvironmentPolicy {
     pub ignore_default_excludes: bool,
 
     /// Environment variable names to exclude from the environment.
+    #[serde(serialize_with = "serialize_patterns")]
     pub exclude: Vec<EnvironmentVariablePattern>,
 
+    /// Files with variables to insert in the environment. [`Config`] makes
+    /// relative paths absolute.
+    ///
+    /// [`Config`]: crate::config::Config
+    pub env_files: Vec<PathBuf>,
+
     /// (key, value) pairs to insert in the environment.
     pub r#set: HashMap<String, String>,
 
     /// Environment variable names to retain in the environment.
+    #[serde(serialize_with = "serialize_patterns")]
     pub include_only: Vec<EnvironmentVariablePattern>,
+
+    /// Variables whose values are redacted from the output of tool calls.
+    #[serde(serialize_with = "serialize_patterns")]
+    pub secrets: Vec<EnvironmentVariablePattern>,
+
+    /// Inherit the environment of the user's login shell, captured once per
+    /// session, rather than the environment of this process.
+    pub snapshot_login_shell: bool,
+
+    /// Run `bash -lc` commands with the user's `$SHELL` instead.
+    pub use_user_shell: bool,
+}
+
+fn serialize_patterns<S>(
+    patterns: &[EnvironmentVariablePattern],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.collect_seq(patterns.iter().map(ToString::to_string))
 }
 
 impl From<ShellEnvironmentPolicyToml> for ShellEnvironmentPolicy {
@@ -158,6 +438,12 @@ impl From<ShellEnvironmentPolicyToml> for ShellEnvironmentPolicy {
             .into_iter()
             .map(|s| EnvironmentVariablePattern::new_case_insensitive(&s))
             .collect();
+        let env_files = toml
+            .env_files
+            .unwrap_or_default()
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
         let r#set = toml.r#set.unwrap_or_default();
         let include_only = toml
             .include_only
@@ -165,13 +451,25 @@ impl From<ShellEnvironmentPolicyToml> for ShellEnvironmentPolicy {
             .into_iter()
             .map(|s| EnvironmentVariablePattern::new_case_insensitive(&s))
             .collect();
+        let secrets = toml
+            .secrets
+            .unwrap_or_default()
+            .into_iter()
+            .map(|s| EnvironmentVariablePattern::new_case_insensitive(&s))
+            .collect();
+        let use_user_shell = toml.use_user_shell.unwrap_or(false);
+        let snapshot_login_shell = toml.snapshot_login_shell.unwrap_or(false) || use_user_shell;
 
         Self {
             inherit,
             ignore_default_excludes,
             exclude,
+            env_files,
             r#set,
             include_only,
+            secrets,
+            snapshot_login_shell,
+            use_user_shell,
         }
     }
 }
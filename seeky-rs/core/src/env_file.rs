@@ -0,0 +1,320 @@
+//! Parser for the `env_files` of the
+//! [`ShellEnvironmentPolicy`](crate::config_types::ShellEnvironmentPolicy),
+//! which use the dotenv syntax:
+//!
+//! ```text
+//! # comment
+//! export DATABASE_URL=postgres://localhost/${USER}_test   # trailing comment
+//! GREETING="multi-line\nvalue with $EXPANSION"
+//! LITERAL='no $expansion here'
+//! ```
+//!
+//! `$NAME`, `${NAME}` and `${NAME:-default}` are expanded in unquoted and
+//! double-quoted values, against the variables defined so far.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("line {line}: {message}")]
+pub struct EnvFileError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// Parses `contents` into `(name, value)` pairs in file order. `lookup`
+/// resolves variables that are not defined earlier in the file.
+pub(crate) fn parse_env_file(
+    contents: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<Vec<(String, String)>, EnvFileError> {
+    let mut parser = Parser {
+        chars: contents.chars().peekable(),
+        line: 1,
+        vars: Vec::new(),
+        lookup,
+    };
+    parser.parse()?;
+    Ok(parser.vars)
+}
+
+struct Parser<'a, F> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    vars: Vec<(String, String)>,
+    lookup: F,
+}
+
+impl<F: Fn(&str) -> Option<String>> Parser<'_, F> {
+    fn parse(&mut self) -> Result<(), EnvFileError> {
+        loop {
+            self.skip_blanks();
+            match self.chars.peek() {
+                None => return Ok(()),
+                Some('\n') => {
+                    self.next();
+                }
+                Some('#') => self.skip_line(),
+                Some(_) => self.parse_assignment()?,
+            }
+        }
+    }
+
+    fn parse_assignment(&mut self) -> Result<(), EnvFileError> {
+        let mut name = self.parse_name();
+        if name == "export" && self.chars.peek().is_some_and(|c| *c == ' ' || *c == '\t') {
+            self.skip_blanks();
+            name = self.parse_name();
+        }
+        if name.is_empty() {
+            return Err(self.error("expected a variable name"));
+        }
+        self.skip_blanks();
+        if self.next() != Some('=') {
+            return Err(self.error(format!("expected `=` after `{name}`")));
+        }
+        self.skip_blanks();
+        let value = match self.chars.peek() {
+            Some('\'') => self.parse_single_quoted()?,
+            Some('"') => self.parse_double_quoted()?,
+            _ => self.parse_unquoted()?,
+        };
+        self.finish_line()?;
+        self.vars.retain(|(existing, _)| *existing != name);
+        self.vars.push((name, value));
+        Ok(())
+    }
+
+    fn parse_name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self
+            .chars
+            .next_if(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '.')
+        {
+            name.push(c);
+        }
+        name
+    }
+
+    fn parse_single_quoted(&mut self) -> Result<String, EnvFileError> {
+        let start = self.line;
+        self.next();
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                Some('\'') => return Ok(value),
+                Some(c) => value.push(c),
+                None => return Err(self.error_at(start, "unterminated single-quoted value")),
+            }
+        }
+    }
+
+    fn parse_double_quoted(&mut self) -> Result<String, EnvFileError> {
+        let start = self.line;
+        self.next();
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(value),
+                Some('\\') => match self.next() {
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some(c @ ('"' | '\\' | '$')) => value.push(c),
+                    Some(c) => {
+                        value.push('\\');
+                        value.push(c);
+                    }
+                    None => return Err(self.error_at(start, "unterminated double-quoted value")),
+                },
+                Some('$') => self.expand(&mut value)?,
+                Some(c) => value.push(c),
+                None => return Err(self.error_at(start, "unterminated double-quoted value")),
+            }
+        }
+    }
+
+    fn parse_unquoted(&mut self) -> Result<String, EnvFileError> {
+        let mut value = String::new();
+        while let Some(c) = self.chars.next_if(|c| *c != '\n') {
+            // A `#` only starts a comment after whitespace, so that values
+            // such as URL fragments survive.
+            if c == '#' && (value.is_empty() || value.ends_with([' ', '\t'])) {
+                self.skip_line();
+                break;
+            }
+            if c == '$' {
+                self.expand(&mut value)?;
+                continue;
+            }
+            value.push(c);
+        }
+        Ok(value.trim_end().to_string())
+    }
+
+    /// Expands the variable reference after a `$` into `value`.
+    fn expand(&mut self, value: &mut String) -> Result<(), EnvFileError> {
+        if self.chars.next_if_eq(&'{').is_none() {
+            let name = self.parse_name();
+            if name.is_empty() {
+                value.push('$');
+            } else {
+                value.push_str(&self.resolve(&name).unwrap_or_default());
+            }
+            return Ok(());
+        }
+
+        let name = self.parse_name();
+        let mut default = None;
+        if self.chars.next_if_eq(&':').is_some() {
+            if self.chars.next_if_eq(&'-').is_none() {
+                return Err(self.error(format!("unsupported expansion in `${{{name}:`")));
+            }
+            let mut text = String::new();
+            while let Some(c) = self.chars.next_if(|c| *c != '}' && *c != '\n') {
+                text.push(c);
+            }
+            default = Some(text);
+        }
+        if self.chars.next_if_eq(&'}').is_none() {
+            return Err(self.error(format!("unterminated `${{{name}`")));
+        }
+        let resolved = self.resolve(&name).filter(|resolved| !resolved.is_empty());
+        value.push_str(&resolved.or(default).unwrap_or_default());
+        Ok(())
+    }
+
+    fn resolve(&self, name: &str) -> Option<String> {
+        self.vars
+            .iter()
+            .rev()
+            .find(|(existing, _)| existing == name)
+            .map(|(_, value)| value.clone())
+            .or_else(|| (self.lookup)(name))
+    }
+
+    /// Allows only whitespace and a comment after a value.
+    fn finish_line(&mut self) -> Result<(), EnvFileError> {
+        self.skip_blanks();
+        match self.chars.peek().copied() {
+            None | Some('\n') => Ok(()),
+            Some('#') => {
+                self.skip_line();
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("unexpected `{c}` after the value"))),
+        }
+    }
+
+    fn skip_blanks(&mut self) {
+        while self
+            .chars
+            .next_if(|c| *c == ' ' || *c == '\t' || *c == '\r')
+            .is_some()
+        {}
+    }
+
+    fn skip_line(&mut self) {
+        while self.chars.next_if(|c| *c != '\n').is_some() {}
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    fn error(&self, message: impl Into<String>) -> EnvFileError {
+        self.error_at(self.line, message)
+    }
+
+    fn error_at(&self, line: usize, message: impl Into<String>) -> EnvFileError {
+        EnvFileError {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn parse(contents: &str) -> Result<Vec<(String, String)>, EnvFileError> {
+        parse_env_file(contents, |name| {
+            (name == "USER").then(|| "alice".to_string())
+        })
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_dotenv_syntax() {
+        let contents = r#"
+# database for the integration tests
+export DATABASE_URL=postgres://localhost/${USER}_test   # per user
+PAGE=https://example.com/docs#intro
+GREETING="hello\n$USER"
+LITERAL='no $USER here'
+MULTI="first
+second"
+EMPTY=
+"#;
+
+        assert_eq!(
+            pairs(&[
+                ("DATABASE_URL", "postgres://localhost/alice_test"),
+                ("PAGE", "https://example.com/docs#intro"),
+                ("GREETING", "hello\nalice"),
+                ("LITERAL", "no $USER here"),
+                ("MULTI", "first\nsecond"),
+                ("EMPTY", ""),
+            ]),
+            parse(contents).unwrap()
+        );
+    }
+
+    #[test]
+    fn expands_earlier_entries_and_defaults() {
+        let contents = "\
+HOST=db.internal
+URL=postgres://$HOST:${PORT:-5432}/app
+HOST=other
+";
+
+        assert_eq!(
+            pairs(&[
+                ("URL", "postgres://db.internal:5432/app"),
+                ("HOST", "other"),
+            ]),
+            parse(contents).unwrap()
+        );
+    }
+
+    #[test]
+    fn reports_the_line_of_errors() {
+        let contents = "A=1\nB=\"unterminated\n\nC=3\n";
+        assert_eq!(
+            EnvFileError {
+                line: 2,
+                message: "unterminated double-quoted value".to_string(),
+            },
+            parse(contents).unwrap_err()
+        );
+
+        assert_eq!(3, parse("A=1\n\nB='x' y\n").unwrap_err().line,);
+        assert_eq!(1, parse("not an assignment\n").unwrap_err().line);
+    }
+}
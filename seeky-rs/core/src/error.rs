@@ -4,13 +4,17 @@ use std::io;
 use thiserror::Error;
 use tokio::task::JoinError;
 
+use crate::exec::ExecToolCallOutput;
+use crate::protocol::SandboxDenial;
+
 pub type Result<T> = std::result::Result<T, SeekyErr>;
 
 #[derive(Error, Debug)]
 pub enum SandboxErr {
-    /// Error from sandbox execution
+    /// Error from sandbox execution, with the access that was blocked if it
+    /// could be told from the output.
     #[error("sandbox denied exec error, exit code: {0}, stdout: {1}, stderr: {2}")]
-    Denied(i32, String, String),
+    Denied(i32, String, String, Option<SandboxDenial>),
 
     /// Error from linux seccomp filter setup
     #[cfg(target_os = "linux")]
@@ -33,6 +37,51 @@ pub enum SandboxErr {
     /// Error from linux landlock
     #[error("Landlock was not able to fully enforce all sandbox rules")]
     LandlockRestrict,
+
+    /// The command used more CPU time than `sandbox.limits.cpu_seconds`.
+    #[error("command was stopped after using its CPU time limit of {0} seconds")]
+    CpuTimeLimitExceeded(u64, Box<ExecToolCallOutput>),
+
+    /// The command ran out of memory under `sandbox.limits.memory_bytes`.
+    #[error("command ran out of memory under its limit of {0} bytes")]
+    MemoryLimitExceeded(u64, Box<ExecToolCallOutput>),
+
+    /// The command tried to grow a file past `sandbox.limits.file_size_bytes`.
+    #[error("command tried to write a file larger than its limit of {0} bytes")]
+    FileSizeLimitExceeded(u64, Box<ExecToolCallOutput>),
+
+    /// The command could not fork because of `sandbox.limits.max_processes`.
+    #[error("command could not start more processes than its limit of {0}")]
+    ProcessLimitExceeded(u64, Box<ExecToolCallOutput>),
+
+    /// The command could not open more files than
+    /// `sandbox.limits.open_files`.
+    #[error("command could not open more files than its limit of {0}")]
+    OpenFilesLimitExceeded(u64, Box<ExecToolCallOutput>),
+}
+
+impl SandboxErr {
+    /// Output of a command that hit one of its resource limits. Such a
+    /// command was not blocked by the sandbox, so retrying it without the
+    /// sandbox would not help.
+    pub fn resource_limit_output(&self) -> Option<&ExecToolCallOutput> {
+        match self {
+            SandboxErr::CpuTimeLimitExceeded(_, output)
+            | SandboxErr::MemoryLimitExceeded(_, output)
+            | SandboxErr::FileSizeLimitExceeded(_, output)
+            | SandboxErr::ProcessLimitExceeded(_, output)
+            | SandboxErr::OpenFilesLimitExceeded(_, output) => Some(output),
+            _ => None,
+        }
+    }
+
+    /// The access the sandbox blocked, if the command was denied.
+    pub fn sandbox_denial(&self) -> Option<&SandboxDenial> {
+        match self {
+            SandboxErr::Denied(_, _, _, denial) => denial.as_ref(),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Error, Debug)]
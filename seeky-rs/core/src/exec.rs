@@ -11,17 +11,28 @@ use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
 
+use serde::Deserialize;
+use serde::Serialize;
 use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt;
 use tokio::io::BufReader;
 use tokio::process::Child;
 use tokio::process::Command;
 use tokio::sync::Notify;
+use tokio::task::JoinHandle;
 
+use crate::config_types::LinuxSandboxBackend;
+use crate::config_types::ResourceLimits;
 use crate::error::Result;
 use crate::error::SandboxErr;
 use crate::error::SeekyErr;
+use crate::network_proxy::SEEKY_SANDBOX_NETWORK_PROXY_PORT_ENV_VAR;
+use crate::process_group::KILL_GRACE_PERIOD;
+use crate::process_group::ProcessGroup;
 use crate::protocol::SandboxPolicy;
+use crate::resource_limits::check_resource_limit_messages;
+use crate::resource_limits::check_resource_limits;
+use crate::sandbox_denial::diagnose_denial;
 
 // Maximum we send for each stream, which is either:
 // - 10KiB OR
@@ -31,6 +42,10 @@ const MAX_STREAM_OUTPUT_LINES: usize = 256;
 
 const DEFAULT_TIMEOUT_MS: u64 = 10_000;
 
+/// How long background processes that inherited the output pipes have to
+/// close them once the command exited, before they are killed.
+const OUTPUT_GRACE_PERIOD: Duration = Duration::from_millis(500);
+
 // Hardcode these since it does not seem worth including the libc crate just
 // for these.
 const SIGKILL_CODE: i32 = 9;
@@ -38,6 +53,21 @@ const TIMEOUT_CODE: i32 = 64;
 
 const MACOS_SEATBELT_BASE_POLICY: &str = include_str!("seatbelt_base_policy.sbpl");
 
+/// Folders that stay readable under Seatbelt when the policy does not grant
+/// full read access, so that binaries and their libraries can still be loaded.
+const MACOS_SYSTEM_READ_ROOTS: &[&str] = &[
+    "/bin",
+    "/sbin",
+    "/usr",
+    "/System",
+    "/Library",
+    "/Applications/Xcode.app",
+    "/opt/homebrew",
+    "/private/etc",
+    "/private/var/db/timezone",
+    "/dev",
+];
+
 /// When working with `sandbox-exec`, only consider `sandbox-exec` in `/usr/bin`
 /// to defend against an attacker trying to inject a malicious version on the
 /// PATH. If /usr/bin/sandbox-exec has been tampered with, then the attacker
@@ -54,15 +84,24 @@ const MACOS_PATH_TO_SEATBELT_EXECUTABLE: &str = "/usr/bin/sandbox-exec";
 /// attributes, so this may change in the future.
 pub const SEEKY_SANDBOX_NETWORK_DISABLED_ENV_VAR: &str = "SEEKY_SANDBOX_NETWORK_DISABLED";
 
+/// Port of the [`crate::network_proxy::NetworkProxy`] the command may reach,
+/// as set by [`crate::network_proxy::NetworkProxy::apply_to_env`].
+fn network_proxy_port_from_env(env: &HashMap<String, String>) -> Option<u16> {
+    env.get(SEEKY_SANDBOX_NETWORK_PROXY_PORT_ENV_VAR)
+        .and_then(|port| port.parse().ok())
+}
+
 #[derive(Debug, Clone)]
 pub struct ExecParams {
     pub command: Vec<String>,
     pub cwd: PathBuf,
     pub timeout_ms: Option<u64>,
     pub env: HashMap<String, String>,
+    pub limits: ResourceLimits,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SandboxType {
     None,
 
@@ -71,6 +110,10 @@ pub enum SandboxType {
 
     /// Only available on Linux.
     LinuxSeccomp,
+
+    /// Unprivileged namespaces, see [`LinuxSandboxBackend::Namespaces`].
+    /// Only available on Linux.
+    LinuxNamespaces,
 }
 
 pub async fn process_exec_tool_call(
@@ -81,6 +124,8 @@ pub async fn process_exec_tool_call(
     seeky_linux_sandbox_exe: &Option<PathBuf>,
 ) -> Result<ExecToolCallOutput> {
     let start = Instant::now();
+    let limits = params.limits.clone();
+    let cwd = params.cwd.clone();
 
     let raw_output_result = match sandbox_type {
         SandboxType::None => exec(params, sandbox_policy, ctrl_c).await,
@@ -90,10 +135,12 @@ pub async fn process_exec_tool_call(
                 cwd,
                 timeout_ms,
                 env,
+                limits,
             } = params;
             let child = spawn_command_under_seatbelt(
                 command,
                 sandbox_policy,
+                &limits,
                 cwd,
                 StdioPolicy::RedirectForShellTool,
                 env,
@@ -101,13 +148,19 @@ pub async fn process_exec_tool_call(
             .await?;
             consume_truncated_output(child, ctrl_c, timeout_ms).await
         }
-        SandboxType::LinuxSeccomp => {
+        SandboxType::LinuxSeccomp | SandboxType::LinuxNamespaces => {
             let ExecParams {
                 command,
                 cwd,
                 timeout_ms,
                 env,
+                limits,
             } = params;
+            let backend = if sandbox_type == SandboxType::LinuxNamespaces {
+                LinuxSandboxBackend::Namespaces
+            } else {
+                LinuxSandboxBackend::Landlock
+            };
 
             let seeky_linux_sandbox_exe = seeky_linux_sandbox_exe
                 .as_ref()
@@ -116,6 +169,8 @@ pub async fn process_exec_tool_call(
                 seeky_linux_sandbox_exe,
                 command,
                 sandbox_policy,
+                backend,
+                &limits,
                 cwd,
                 StdioPolicy::RedirectForShellTool,
                 env,
@@ -132,7 +187,24 @@ pub async fn process_exec_tool_call(
             let stderr = String::from_utf8_lossy(&raw_output.stderr).to_string();
 
             #[cfg(target_family = "unix")]
-            match raw_output.exit_status.signal() {
+            let signal = raw_output.exit_status.signal();
+            #[cfg(not(target_family = "unix"))]
+            let signal: Option<i32> = None;
+
+            // A command that was killed for exceeding one of its resource
+            // limits is reported as such, whether or not it was sandboxed.
+            let output = check_resource_limits(
+                &limits,
+                signal,
+                ExecToolCallOutput {
+                    exit_code: raw_output.exit_status.code().unwrap_or(-1),
+                    stdout,
+                    stderr,
+                    duration,
+                },
+            )?;
+
+            match signal {
                 Some(TIMEOUT_CODE) => return Err(SeekyErr::Sandbox(SandboxErr::Timeout)),
                 Some(signal) => {
                     return Err(SeekyErr::Sandbox(SandboxErr::Signal(signal)));
@@ -140,24 +212,30 @@ pub async fn process_exec_tool_call(
                 None => {}
             }
 
-            let exit_code = raw_output.exit_status.code().unwrap_or(-1);
-
             // NOTE(ragona): This is much less restrictive than the previous check. If we exec
             // a command, and it returns anything other than success, we assume that it may have
             // been a sandboxing error and allow the user to retry. (The user of course may choose
             // not to retry, or in a non-interactive mode, would automatically reject the approval.)
-            if exit_code != 0 && sandbox_type != SandboxType::None {
+            // Error messages that point at a resource limit are only trusted
+            // if the sandbox visibly blocked nothing, so that real denials are
+            // still offered for a retry.
+            if output.exit_code != 0 && sandbox_type != SandboxType::None {
+                let denial = diagnose_denial(sandbox_policy, &cwd, &output.stderr);
+                let ExecToolCallOutput {
+                    exit_code,
+                    stdout,
+                    stderr,
+                    ..
+                } = match denial {
+                    Some(_) => output,
+                    None => check_resource_limit_messages(&limits, output)?,
+                };
                 return Err(SeekyErr::Sandbox(SandboxErr::Denied(
-                    exit_code, stdout, stderr,
+                    exit_code, stdout, stderr, denial,
                 )));
             }
 
-            Ok(ExecToolCallOutput {
-                exit_code,
-                stdout,
-                stderr,
-                duration,
-            })
+            Ok(check_resource_limit_messages(&limits, output)?)
         }
         Err(err) => {
             tracing::error!("exec error: {err}");
@@ -169,11 +247,13 @@ pub async fn process_exec_tool_call(
 pub async fn spawn_command_under_seatbelt(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
+    limits: &ResourceLimits,
     cwd: PathBuf,
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
 ) -> std::io::Result<Child> {
-    let args = create_seatbelt_command_args(command, sandbox_policy, &cwd);
+    let network_proxy_port = network_proxy_port_from_env(&env);
+    let args = create_seatbelt_command_args(command, sandbox_policy, &cwd, network_proxy_port);
     let arg0 = None;
     spawn_child_async(
         PathBuf::from(MACOS_PATH_TO_SEATBELT_EXECUTABLE),
@@ -181,6 +261,7 @@ pub async fn spawn_command_under_seatbelt(
         arg0,
         cwd,
         sandbox_policy,
+        limits,
         stdio_policy,
         env,
     )
@@ -193,11 +274,15 @@ pub async fn spawn_command_under_seatbelt(
 /// Unlike macOS Seatbelt where we directly embed the policy text, the Linux
 /// helper accepts a list of `--sandbox-permission`/`-s` flags mirroring the
 /// public CLI. We convert the internal [`SandboxPolicy`] representation into
-/// the equivalent CLI options.
+/// the equivalent CLI options. The resource limits are passed along too, so
+/// that the helper can apply them once the sandbox is set up.
+#[allow(clippy::too_many_arguments)]
 pub async fn spawn_command_under_linux_sandbox<P>(
     seeky_linux_sandbox_exe: P,
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
+    backend: LinuxSandboxBackend,
+    limits: &ResourceLimits,
     cwd: PathBuf,
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
@@ -205,7 +290,15 @@ pub async fn spawn_command_under_linux_sandbox<P>(
 where
     P: AsRef<Path>,
 {
-    let args = create_linux_sandbox_command_args(command, sandbox_policy, &cwd);
+    let network_proxy_port = network_proxy_port_from_env(&env);
+    let args = create_linux_sandbox_command_args(
+        command,
+        sandbox_policy,
+        backend,
+        limits,
+        &cwd,
+        network_proxy_port,
+    );
     let arg0 = Some("seeky-linux-sandbox");
     spawn_child_async(
         seeky_linux_sandbox_exe.as_ref().to_path_buf(),
@@ -213,6 +306,7 @@ where
         arg0,
         cwd,
         sandbox_policy,
+        &ResourceLimits::default(),
         stdio_policy,
         env,
     )
@@ -223,15 +317,51 @@ where
 fn create_linux_sandbox_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
+    backend: LinuxSandboxBackend,
+    limits: &ResourceLimits,
     cwd: &Path,
+    network_proxy_port: Option<u16>,
 ) -> Vec<String> {
     let mut linux_cmd: Vec<String> = vec![];
 
+    if backend == LinuxSandboxBackend::Namespaces {
+        linux_cmd.extend(["--backend", "namespaces"].map(String::from));
+    }
+
+    for (flag, limit) in [
+        ("--limit-cpu-seconds", limits.cpu_seconds),
+        ("--limit-memory-bytes", limits.memory_bytes),
+        ("--limit-file-size-bytes", limits.file_size_bytes),
+        ("--limit-processes", limits.max_processes),
+        ("--limit-open-files", limits.open_files),
+    ] {
+        if let Some(limit) = limit {
+            linux_cmd.extend([flag.to_string(), limit.to_string()]);
+        }
+    }
+
     // Translate individual permissions.
     // Use high-level helper methods to infer flags when we cannot see the
     // exact permission list.
+    let writable_roots = if sandbox_policy.has_full_disk_write_access() {
+        Vec::new()
+    } else {
+        sandbox_policy.get_writable_roots_with_cwd(cwd)
+    };
+
     if sandbox_policy.has_full_disk_read_access() {
         linux_cmd.extend(["-s", "disk-full-read-access"].map(String::from));
+    } else {
+        // `cwd` and the writable roots are readable implicitly, so only the
+        // extra read-only folders have to be passed along.
+        for root in sandbox_policy.get_readable_roots_with_cwd(cwd) {
+            if root != cwd && !writable_roots.contains(&root) {
+                linux_cmd.extend([
+                    "-s".to_string(),
+                    format!("disk-read-folder={}", root.to_string_lossy()),
+                ]);
+            }
+        }
     }
 
     if sandbox_policy.has_full_disk_write_access() {
@@ -239,7 +369,7 @@ fn create_linux_sandbox_command_args(
     } else {
         // Derive granular writable paths (includes cwd if `DiskWriteCwd` is
         // present).
-        for root in sandbox_policy.get_writable_roots_with_cwd(cwd) {
+        for root in writable_roots {
             // Check if this path corresponds exactly to cwd to map to
             // `disk-write-cwd`, otherwise use the generic folder rule.
             if root == cwd {
@@ -251,10 +381,26 @@ fn create_linux_sandbox_command_args(
                 ]);
             }
         }
+        for entry in sandbox_policy.protected_paths() {
+            linux_cmd.push(format!("--protected-path={entry}"));
+        }
     }
 
     if sandbox_policy.has_full_network_access() {
         linux_cmd.extend(["-s", "network-full-access"].map(String::from));
+    } else {
+        let allowed_domains = sandbox_policy.get_allowed_network_domains();
+        if !allowed_domains.is_empty() {
+            linux_cmd.extend([
+                "-s".to_string(),
+                format!("network-allow-domains={}", allowed_domains.join(",")),
+            ]);
+            // Without a proxy the helper falls back to blocking all network
+            // access.
+            if let Some(port) = network_proxy_port {
+                linux_cmd.extend(["--network-proxy-port".to_string(), port.to_string()]);
+            }
+        }
     }
 
     // Separator so that command arguments starting with `-` are not parsed as
@@ -271,6 +417,7 @@ fn create_seatbelt_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
+    network_proxy_port: Option<u16>,
 ) -> Vec<String> {
     let (file_write_policy, extra_cli_args) = {
         if sandbox_policy.has_full_disk_write_access() {
@@ -281,16 +428,61 @@ fn create_seatbelt_command_args(
             )
         } else {
             let writable_roots = sandbox_policy.get_writable_roots_with_cwd(cwd);
-            let (writable_folder_policies, cli_args): (Vec<String>, Vec<String>) = writable_roots
-                .iter()
-                .enumerate()
-                .map(|(index, root)| {
-                    let param_name = format!("WRITABLE_ROOT_{index}");
-                    let policy: String = format!("(subpath (param \"{param_name}\"))");
-                    let cli_arg = format!("-D{param_name}={}", root.to_string_lossy());
-                    (policy, cli_arg)
-                })
-                .unzip();
+            let protected_paths = sandbox_policy.get_protected_paths(&writable_roots);
+            let protected_path_ancestors =
+                sandbox_policy.get_protected_path_ancestors(&writable_roots);
+            let mut writable_folder_policies = Vec::new();
+            let mut cli_args = Vec::new();
+            for (index, root) in writable_roots.iter().enumerate() {
+                let param_name = format!("WRITABLE_ROOT_{index}");
+                cli_args.push(format!("-D{param_name}={}", root.to_string_lossy()));
+
+                // Protected paths are carved out of the root they lie in,
+                // their writable subpaths are allowed again on their own.
+                let mut exclusions = Vec::new();
+                for (protected_index, protected) in protected_paths
+                    .iter()
+                    .filter(|protected| protected.path.starts_with(root))
+                    .enumerate()
+                {
+                    let protected_param = format!("{param_name}_PROTECTED_{protected_index}");
+                    exclusions.push(format!(
+                        "(require-not (subpath (param \"{protected_param}\")))"
+                    ));
+                    cli_args.push(format!(
+                        "-D{protected_param}={}",
+                        protected.path.to_string_lossy()
+                    ));
+                    for (subpath_index, subpath) in protected.writable_subpaths.iter().enumerate() {
+                        let subpath_param = format!("{protected_param}_WRITABLE_{subpath_index}");
+                        writable_folder_policies
+                            .push(format!("(subpath (param \"{subpath_param}\"))"));
+                        cli_args.push(format!("-D{subpath_param}={}", subpath.to_string_lossy()));
+                    }
+                }
+                // The folders leading to them can be written to, but not
+                // moved or removed.
+                for (ancestor_index, ancestor) in protected_path_ancestors
+                    .iter()
+                    .filter(|ancestor| ancestor.starts_with(root))
+                    .enumerate()
+                {
+                    let ancestor_param = format!("{param_name}_PINNED_{ancestor_index}");
+                    exclusions.push(format!(
+                        "(require-not (literal (param \"{ancestor_param}\")))"
+                    ));
+                    cli_args.push(format!("-D{ancestor_param}={}", ancestor.to_string_lossy()));
+                }
+                let root_policy = format!("(subpath (param \"{param_name}\"))");
+                if exclusions.is_empty() {
+                    writable_folder_policies.push(root_policy);
+                } else {
+                    writable_folder_policies.push(format!(
+                        "(require-all {root_policy} {})",
+                        exclusions.join(" ")
+                    ));
+                }
+            }
             if writable_folder_policies.is_empty() {
                 ("".to_string(), Vec::<String>::new())
             } else {
@@ -303,17 +495,46 @@ fn create_seatbelt_command_args(
         }
     };
 
-    let file_read_policy = if sandbox_policy.has_full_disk_read_access() {
-        "; allow read-only file operations\n(allow file-read*)"
+    let (file_read_policy, read_cli_args) = if sandbox_policy.has_full_disk_read_access() {
+        (
+            "; allow read-only file operations\n(allow file-read*)".to_string(),
+            Vec::<String>::new(),
+        )
     } else {
-        ""
+        let (readable_folder_policies, cli_args): (Vec<String>, Vec<String>) = sandbox_policy
+            .get_readable_roots_with_cwd(cwd)
+            .iter()
+            .enumerate()
+            .map(|(index, root)| {
+                let param_name = format!("READABLE_ROOT_{index}");
+                let policy: String = format!("(subpath (param \"{param_name}\"))");
+                let cli_arg = format!("-D{param_name}={}", root.to_string_lossy());
+                (policy, cli_arg)
+            })
+            .unzip();
+        let system_folder_policies = MACOS_SYSTEM_READ_ROOTS
+            .iter()
+            .map(|root| format!("(subpath \"{root}\")"))
+            .collect::<Vec<_>>();
+        let file_read_policy = format!(
+            "; allow reads of the system and the readable roots only\n(allow file-read-metadata)\n(allow file-read*\n{}\n{}\n)",
+            system_folder_policies.join(" "),
+            readable_folder_policies.join(" ")
+        );
+        (file_read_policy, cli_args)
     };
 
     // TODO(mbolin): apply_patch calls must also honor the SandboxPolicy.
     let network_policy = if sandbox_policy.has_full_network_access() {
-        "(allow network-outbound)\n(allow network-inbound)\n(allow system-socket)"
+        "(allow network-outbound)\n(allow network-inbound)\n(allow system-socket)".to_string()
     } else {
-        ""
+        match network_proxy_port {
+            // Only the proxy that enforces `NetworkAllowDomains` is reachable.
+            Some(port) if !sandbox_policy.get_allowed_network_domains().is_empty() => {
+                format!("(allow network-outbound (remote ip \"localhost:{port}\"))")
+            }
+            _ => String::new(),
+        }
     };
 
     let full_policy = format!(
@@ -321,6 +542,7 @@ fn create_seatbelt_command_args(
     );
     let mut seatbelt_args: Vec<String> = vec!["-p".to_string(), full_policy];
     seatbelt_args.extend(extra_cli_args);
+    seatbelt_args.extend(read_cli_args);
     seatbelt_args.push("--".to_string());
     seatbelt_args.extend(command);
     seatbelt_args
@@ -347,6 +569,7 @@ async fn exec(
         cwd,
         timeout_ms,
         env,
+        limits,
     }: ExecParams,
     sandbox_policy: &SandboxPolicy,
     ctrl_c: Arc<Notify>,
@@ -364,6 +587,7 @@ async fn exec(
         arg0,
         cwd,
         sandbox_policy,
+        &limits,
         StdioPolicy::RedirectForShellTool,
         env,
     )
@@ -384,12 +608,16 @@ pub enum StdioPolicy {
 /// For now, we take `SandboxPolicy` as a parameter to spawn_child() because
 /// we need to determine whether to set the
 /// `SEEKY_SANDBOX_NETWORK_DISABLED_ENV_VAR` environment variable.
+///
+/// `limits` are applied in the child right before it execs `program`.
+#[allow(clippy::too_many_arguments)]
 async fn spawn_child_async(
     program: PathBuf,
     args: Vec<String>,
     #[cfg_attr(not(unix), allow(unused_variables))] arg0: Option<&str>,
     cwd: PathBuf,
     sandbox_policy: &SandboxPolicy,
+    #[cfg_attr(not(unix), allow(unused_variables))] limits: &ResourceLimits,
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
 ) -> std::io::Result<Child> {
@@ -405,6 +633,26 @@ async fn spawn_child_async(
         cmd.env(SEEKY_SANDBOX_NETWORK_DISABLED_ENV_VAR, "1");
     }
 
+    // Shell tool commands lead a new session, and with it a new process
+    // group, so that everything they start can be killed together (see
+    // `ProcessGroup`). This also detaches them from the terminal.
+    #[cfg(unix)]
+    let new_session = matches!(stdio_policy, StdioPolicy::RedirectForShellTool);
+    #[cfg(unix)]
+    if new_session || !limits.is_empty() {
+        let limits = limits.clone();
+        // SAFETY: `setsid`, `getrlimit` and `setrlimit` are
+        // async-signal-safe.
+        unsafe {
+            cmd.pre_exec(move || {
+                if new_session && libc::setsid() == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                crate::resource_limits::apply_to_current_process(&limits)
+            });
+        }
+    }
+
     match stdio_policy {
         StdioPolicy::RedirectForShellTool => {
             // Do not create a file descriptor for stdin because otherwise some
@@ -448,40 +696,66 @@ pub(crate) async fn consume_truncated_output(
         ))
     })?;
 
-    let stdout_handle = tokio::spawn(read_capped(
+    let mut stdout_handle = tokio::spawn(read_capped(
         BufReader::new(stdout_reader),
         MAX_STREAM_OUTPUT,
         MAX_STREAM_OUTPUT_LINES,
     ));
-    let stderr_handle = tokio::spawn(read_capped(
+    let mut stderr_handle = tokio::spawn(read_capped(
         BufReader::new(stderr_reader),
         MAX_STREAM_OUTPUT,
         MAX_STREAM_OUTPUT_LINES,
     ));
 
+    let mut process_group = ProcessGroup::new(&child);
     let interrupted = ctrl_c.notified();
+    tokio::pin!(interrupted);
     let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
-    let exit_status = tokio::select! {
-        result = tokio::time::timeout(timeout, child.wait()) => {
-            match result {
-                Ok(Ok(exit_status)) => exit_status,
-                Ok(e) => e?,
-                Err(_) => {
-                    // timeout
-                    child.start_kill()?;
-                    // Debatable whether `child.wait().await` should be called here.
-                    synthetic_exit_status(128 + TIMEOUT_CODE)
-                }
-            }
+    let mut deadline = tokio::time::Instant::now() + timeout;
+
+    // Background processes that inherited the pipes may keep the output open
+    // after the command exited. They get `OUTPUT_GRACE_PERIOD` to close it
+    // and are killed afterwards, but the command keeps its exit status: it
+    // only timed out if it was still running itself at the deadline.
+    let mut exit_status = None;
+    let mut stdout = None;
+    let mut stderr = None;
+    let killed_with = loop {
+        if exit_status.is_some() && stdout.is_some() && stderr.is_some() {
+            break None;
         }
-        _ = interrupted => {
-            child.start_kill()?;
-            synthetic_exit_status(128 + SIGKILL_CODE)
+        tokio::select! {
+            status = child.wait(), if exit_status.is_none() => {
+                exit_status = Some(status?);
+                deadline = tokio::time::Instant::now() + OUTPUT_GRACE_PERIOD;
+            }
+            output = &mut stdout_handle, if stdout.is_none() => stdout = Some(output??),
+            output = &mut stderr_handle, if stderr.is_none() => stderr = Some(output??),
+            _ = tokio::time::sleep_until(deadline) => match exit_status {
+                Some(_) => break None,
+                None => break Some(128 + TIMEOUT_CODE),
+            },
+            _ = &mut interrupted => break Some(128 + SIGKILL_CODE),
         }
     };
 
-    let stdout = stdout_handle.await??;
-    let stderr = stderr_handle.await??;
+    if killed_with.is_none() && stdout.is_some() && stderr.is_some() {
+        process_group.disarm();
+    } else {
+        process_group.terminate(&mut child).await?;
+    }
+    let exit_status = match (killed_with, exit_status) {
+        (None, Some(exit_status)) => exit_status,
+        (killed_with, _) => synthetic_exit_status(killed_with.unwrap_or(128 + SIGKILL_CODE)),
+    };
+    let stdout = match stdout {
+        Some(stdout) => stdout,
+        None => finish_read(stdout_handle).await?,
+    };
+    let stderr = match stderr {
+        Some(stderr) => stderr,
+        None => finish_read(stderr_handle).await?,
+    };
 
     Ok(RawExecToolCallOutput {
         exit_status,
@@ -490,6 +764,19 @@ pub(crate) async fn consume_truncated_output(
     })
 }
 
+/// Collects the output of a reader after the process group was killed. A
+/// process that left the group may still hold the pipe open, so give up on
+/// the rest of the output after [`KILL_GRACE_PERIOD`].
+async fn finish_read(mut handle: JoinHandle<io::Result<Vec<u8>>>) -> Result<Vec<u8>> {
+    match tokio::time::timeout(KILL_GRACE_PERIOD, &mut handle).await {
+        Ok(output) => Ok(output??),
+        Err(_) => {
+            handle.abort();
+            Ok(Vec::new())
+        }
+    }
+}
+
 async fn read_capped<R: AsyncRead + Unpin>(
     mut reader: R,
     max_output: usize,
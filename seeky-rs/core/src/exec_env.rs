@@ -1,8 +1,11 @@
 use crate::config_types::EnvironmentVariablePattern;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::ShellEnvironmentPolicyInherit;
+use crate::env_file::parse_env_file;
+use crate::shell_snapshot::ShellSnapshot;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use tracing::warn;
 
 /// Construct an environment map based on the rules in the specified policy. The
 /// resulting map can be passed directly to `Command::envs()` after calling
@@ -15,6 +18,56 @@ pub fn create_env(policy: &ShellEnvironmentPolicy) -> HashMap<String, String> {
     populate_env(std::env::vars(), policy)
 }
 
+/// Like [`create_env`], but starts from the environment of the user's login
+/// shell instead of the environment of this process.
+pub(crate) fn create_env_from_snapshot(
+    policy: &ShellEnvironmentPolicy,
+    snapshot: &ShellSnapshot,
+) -> HashMap<String, String> {
+    populate_env(snapshot.env().clone(), policy)
+}
+
+/// The values of the variables in `env` that `policy` marks as secret.
+pub fn secret_values(
+    policy: &ShellEnvironmentPolicy,
+    env: &HashMap<String, String>,
+) -> Vec<String> {
+    env.iter()
+        .filter(|(name, value)| {
+            !value.is_empty() && policy.secrets.iter().any(|pattern| pattern.matches(name))
+        })
+        .map(|(_, value)| value.clone())
+        .collect()
+}
+
+/// Whether `name` decides which programs run or what the shell, the dynamic
+/// loader or an interpreter execute on startup. Env files are often checked
+/// into the repository, so such variables are only taken from `set`.
+fn is_startup_variable(name: &str) -> bool {
+    matches!(
+        name,
+        "PATH"
+            | "BASH_ENV"
+            | "ENV"
+            | "PROMPT_COMMAND"
+            | "ZDOTDIR"
+            | "SHELLOPTS"
+            | "BASHOPTS"
+            | "PS4"
+            | "IFS"
+            | "PYTHONPATH"
+            | "PYTHONSTARTUP"
+            | "NODE_OPTIONS"
+            | "PERL5OPT"
+            | "PERL5LIB"
+            | "RUBYOPT"
+            | "GIT_SSH_COMMAND"
+            | "GIT_EXEC_PATH"
+    ) || name.starts_with("LD_")
+        || name.starts_with("DYLD_")
+        || name.starts_with("BASH_FUNC_")
+}
+
 fn populate_env<I>(vars: I, policy: &ShellEnvironmentPolicy) -> HashMap<String, String>
 where
     I: IntoIterator<Item = (String, String)>,
@@ -55,12 +108,40 @@ where
         env_map.retain(|k, _| !matches_any(k, &policy.exclude));
     }
 
-    // Step 4 – Apply user-provided overrides.
+    // Step 4 – Load the env files; later files win. Files that do not exist
+    // are skipped, as the same list usually applies to many projects.
+    for path in &policy.env_files {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => {
+                warn!("failed to read env file {}: {e}", path.display());
+                continue;
+            }
+        };
+        match parse_env_file(&contents, |name| env_map.get(name).cloned()) {
+            Ok(vars) => {
+                for (name, value) in vars {
+                    if is_startup_variable(&name) {
+                        warn!(
+                            "ignoring {name} in env file {}; set it in `set` instead",
+                            path.display()
+                        );
+                    } else {
+                        env_map.insert(name, value);
+                    }
+                }
+            }
+            Err(e) => warn!("ignoring env file {}: {e}", path.display()),
+        }
+    }
+
+    // Step 5 – Apply user-provided overrides.
     for (key, val) in &policy.r#set {
         env_map.insert(key.clone(), val.clone());
     }
 
-    // Step 5 – If include_only is non-empty, keep *only* the matching vars.
+    // Step 6 – If include_only is non-empty, keep *only* the matching vars.
     if !policy.include_only.is_empty() {
         env_map.retain(|k, _| matches_any(k, &policy.include_only));
     }
@@ -193,4 +274,127 @@ mod tests {
         };
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_env_files_before_set() {
+        let dir = tempfile::tempdir().unwrap();
+        let seeky_env = dir.path().join("seeky.env");
+        let dotenv = dir.path().join(".env");
+        std::fs::write(&seeky_env, "DB_HOST=db.internal\nCI=0\n").unwrap();
+        std::fs::write(
+            &dotenv,
+            "DATABASE_URL=postgres://$DB_HOST/${USER}_test\nDB_PASSWORD='hunter2'\n",
+        )
+        .unwrap();
+        let vars = make_vars(&[("USER", "alice"), ("API_KEY", "secret")]);
+
+        let mut policy = ShellEnvironmentPolicy {
+            inherit: ShellEnvironmentPolicyInherit::All,
+            env_files: vec![seeky_env, dir.path().join("missing.env"), dotenv],
+            secrets: vec![EnvironmentVariablePattern::new_case_insensitive(
+                "*PASSWORD",
+            )],
+            ..Default::default()
+        };
+        policy.r#set.insert("CI".to_string(), "1".to_string());
+
+        let result = populate_env(vars, &policy);
+        let expected: HashMap<String, String> = hashmap! {
+            "USER".to_string() => "alice".to_string(),
+            "DB_HOST".to_string() => "db.internal".to_string(),
+            "CI".to_string() => "1".to_string(),
+            "DATABASE_URL".to_string() => "postgres://db.internal/alice_test".to_string(),
+            "DB_PASSWORD".to_string() => "hunter2".to_string(),
+        };
+        assert_eq!(result, expected);
+        assert_eq!(vec!["hunter2".to_string()], secret_values(&policy, &result));
+    }
+
+    #[test]
+    fn test_env_files_cannot_set_startup_variables() {
+        let dir = tempfile::tempdir().unwrap();
+        let dotenv = dir.path().join(".env");
+        std::fs::write(
+            &dotenv,
+            "PATH=/tmp/evil\nLD_PRELOAD=/tmp/evil.so\nBASH_ENV=/tmp/evil.sh\nRUST_LOG=debug\n",
+        )
+        .unwrap();
+        let vars = make_vars(&[("PATH", "/usr/bin")]);
+
+        let mut policy = ShellEnvironmentPolicy {
+            env_files: vec![dotenv],
+            ..Default::default()
+        };
+        policy
+            .r#set
+            .insert("LD_LIBRARY_PATH".to_string(), "/opt/lib".to_string());
+
+        let result = populate_env(vars, &policy);
+        let expected: HashMap<String, String> = hashmap! {
+            "PATH".to_string() => "/usr/bin".to_string(),
+            "RUST_LOG".to_string() => "debug".to_string(),
+            "LD_LIBRARY_PATH".to_string() => "/opt/lib".to_string(),
+        };
+        assert_eq!(result, expected);
+    }
+
+    /// The variables an env file with `contents` adds to an empty environment.
+    fn vars_from_env_file(contents: &str) -> HashMap<String, String> {
+        let dir = tempfile::tempdir().unwrap();
+        let dotenv = dir.path().join(".env");
+        std::fs::write(&dotenv, contents).unwrap();
+        let policy = ShellEnvironmentPolicy {
+            env_files: vec![dotenv],
+            ..Default::default()
+        };
+        populate_env(Vec::new(), &policy)
+    }
+
+    #[test]
+    fn test_env_files_cannot_set_shell_options() {
+        let result = vars_from_env_file(
+            "SHELLOPTS=xtrace\nBASHOPTS=extdebug\nPS4='$(touch /tmp/evil)'\nIFS=/\nTERM=dumb\n",
+        );
+        let expected = hashmap! { "TERM".to_string() => "dumb".to_string() };
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_env_files_cannot_set_python_startup_variables() {
+        let result = vars_from_env_file(
+            "PYTHONPATH=/tmp/evil\nPYTHONSTARTUP=/tmp/evil.py\nPYTHONUNBUFFERED=1\n",
+        );
+        let expected = hashmap! { "PYTHONUNBUFFERED".to_string() => "1".to_string() };
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_env_files_cannot_set_node_options() {
+        let result = vars_from_env_file("NODE_OPTIONS=--require=/tmp/evil.js\nNODE_ENV=test\n");
+        let expected = hashmap! { "NODE_ENV".to_string() => "test".to_string() };
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_env_files_cannot_set_perl_startup_variables() {
+        let result = vars_from_env_file("PERL5OPT=-Mevil\nPERL5LIB=/tmp/evil\nPERL_BADLANG=0\n");
+        let expected = hashmap! { "PERL_BADLANG".to_string() => "0".to_string() };
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_env_files_cannot_set_ruby_options() {
+        let result = vars_from_env_file("RUBYOPT=-revil\nRAILS_ENV=test\n");
+        let expected = hashmap! { "RAILS_ENV".to_string() => "test".to_string() };
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_env_files_cannot_set_git_commands() {
+        let result = vars_from_env_file(
+            "GIT_SSH_COMMAND=/tmp/evil\nGIT_EXEC_PATH=/tmp/evil\nGIT_AUTHOR_NAME=Alice\n",
+        );
+        let expected = hashmap! { "GIT_AUTHOR_NAME".to_string() => "Alice".to_string() };
+        assert_eq!(result, expected);
+    }
 }
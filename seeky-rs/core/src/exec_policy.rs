@@ -0,0 +1,401 @@
+//! Command safety from the rules of `seeky-execpolicy`: the built-in
+//! `default.policy` with the user's `.policy` files (see
+//! [`Config::exec_policy_files`](crate::config::Config::exec_policy_files))
+//! layered on top, followed by the forbidding rules of the project's
+//! `.policy` files.
+
+use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
+
+use path_absolutize::Absolutize;
+use seeky_execpolicy::ArgType;
+use seeky_execpolicy::ExecCall;
+use seeky_execpolicy::ExecvChecker;
+use seeky_execpolicy::MatchedExec;
+use seeky_execpolicy::PolicyParser;
+use seeky_execpolicy::ValidExec;
+use seeky_execpolicy::get_default_policy;
+
+use crate::is_safe_command::exec_commands;
+use crate::protocol::ProtectedPath;
+
+/// What the policy says about a command.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum PolicyDecision {
+    /// The command must not run.
+    Forbidden { reason: String },
+    /// The command only reads files the sandbox policy lets it read.
+    ReadOnly,
+    /// The command writes files, all of them inside the writable roots and
+    /// outside of the protected paths.
+    WritesInWritableRoots,
+    /// No rule matched, or the command may write elsewhere.
+    Unverified,
+}
+
+pub(crate) struct ExecPolicy {
+    /// `None` if not even `default.policy` could be loaded.
+    checker: Option<ExecvChecker>,
+}
+
+impl ExecPolicy {
+    /// Loads `default.policy` and layers `files` on top, in order, followed by
+    /// `project_files`. Only the rules that forbid commands are taken from
+    /// `project_files`: a repository must not be able to declare its own
+    /// commands safe. Files that cannot be loaded are skipped, and the errors
+    /// are returned so they can be shown to the user.
+    pub(crate) fn load(files: &[PathBuf], project_files: &[PathBuf]) -> (Self, Vec<String>) {
+        let mut errors = Vec::new();
+        let mut policy = match get_default_policy() {
+            Ok(policy) => Some(policy),
+            Err(e) => {
+                errors.push(format!("failed to load the default exec policy: {e}"));
+                None
+            }
+        };
+        let layers = files
+            .iter()
+            .map(|file| (file, false))
+            .chain(project_files.iter().map(|file| (file, true)));
+        for (file, forbid_only) in layers {
+            let layer = std::fs::read_to_string(file)
+                .map_err(|e| e.to_string())
+                .and_then(|contents| {
+                    PolicyParser::new(&file.to_string_lossy(), &contents)
+                        .parse()
+                        .map_err(|e| e.to_string())
+                })
+                .map(|mut layer| {
+                    if forbid_only {
+                        layer.retain_forbidden();
+                    }
+                    layer
+                })
+                .and_then(|layer| match &mut policy {
+                    Some(policy) => policy.extend(layer).map_err(|e| e.to_string()),
+                    None => {
+                        policy = Some(layer);
+                        Ok(())
+                    }
+                });
+            if let Err(e) = layer {
+                errors.push(format!(
+                    "failed to load exec policy {}: {e}",
+                    file.display()
+                ));
+            }
+        }
+        let exec_policy = Self {
+            checker: policy.map(ExecvChecker::new),
+        };
+        (exec_policy, errors)
+    }
+
+    /// Checks `command`, run in `cwd`. `readable_roots` is `None` if the
+    /// sandbox policy allows reading the whole disk. A script of several
+    /// commands is forbidden if any of them is, and otherwise only as safe as
+    /// its least safe command. Paths are resolved against `cwd`, so a script
+    /// that changes directories is never more than unverified.
+    pub(crate) fn check(
+        &self,
+        command: &[String],
+        cwd: &Path,
+        readable_roots: Option<&[PathBuf]>,
+        writable_roots: &[PathBuf],
+        protected_paths: &[ProtectedPath],
+    ) -> PolicyDecision {
+        let (Some(checker), Some(commands)) = (&self.checker, exec_commands(command)) else {
+            return PolicyDecision::Unverified;
+        };
+        let mut decision = PolicyDecision::ReadOnly;
+        for argv in &commands {
+            if argv
+                .first()
+                .is_some_and(|program| matches!(program.as_str(), "cd" | "pushd" | "popd"))
+            {
+                decision = PolicyDecision::Unverified;
+                continue;
+            }
+            match check_argv(
+                checker,
+                argv,
+                cwd,
+                readable_roots,
+                writable_roots,
+                protected_paths,
+            ) {
+                forbidden @ PolicyDecision::Forbidden { .. } => return forbidden,
+                PolicyDecision::ReadOnly => {}
+                PolicyDecision::WritesInWritableRoots => {
+                    if decision == PolicyDecision::ReadOnly {
+                        decision = PolicyDecision::WritesInWritableRoots;
+                    }
+                }
+                // Keep looking for forbidden commands.
+                PolicyDecision::Unverified => decision = PolicyDecision::Unverified,
+            }
+        }
+        decision
+    }
+}
+
+fn check_argv(
+    checker: &ExecvChecker,
+    argv: &[String],
+    cwd: &Path,
+    readable_roots: Option<&[PathBuf]>,
+    writable_roots: &[PathBuf],
+    protected_paths: &[ProtectedPath],
+) -> PolicyDecision {
+    let Some((program, args)) = argv.split_first() else {
+        return PolicyDecision::Unverified;
+    };
+    let exec_call = ExecCall {
+        program: program.clone(),
+        args: args.to_vec(),
+    };
+    let exec = match checker.r#match(&exec_call) {
+        Ok(MatchedExec::Match { exec }) => exec,
+        Ok(MatchedExec::Forbidden { reason, .. }) => {
+            return PolicyDecision::Forbidden { reason };
+        }
+        Err(_) => return PolicyDecision::Unverified,
+    };
+
+    let readable_roots = match readable_roots {
+        Some(roots) => roots.iter().map(|root| canonicalize(root)).collect(),
+        None => vec![PathBuf::from("/")],
+    };
+    let writable_roots = writable_roots
+        .iter()
+        .map(|root| canonicalize(&cwd.join(root)))
+        .collect::<Vec<_>>();
+    let cwd_os = Some(OsString::from(cwd.as_os_str()));
+
+    if !exec.might_write_files() {
+        return match checker.check(exec, &cwd_os, &readable_roots, &[]) {
+            Ok(_) => PolicyDecision::ReadOnly,
+            Err(_) => PolicyDecision::Unverified,
+        };
+    }
+
+    // Arguments of unknown type might be files that are written.
+    if has_arg_of_type(&exec, &ArgType::Unknown) {
+        return PolicyDecision::Unverified;
+    }
+    let written = written_files(&exec, cwd);
+    if checker
+        .check(exec, &cwd_os, &readable_roots, &writable_roots)
+        .is_err()
+    {
+        return PolicyDecision::Unverified;
+    }
+    // The checker compares paths as written; a symlink inside a writable
+    // root may still point elsewhere.
+    let confined = written.iter().all(|path| {
+        let resolved = resolve_symlinks(path);
+        writable_roots.iter().any(|root| resolved.starts_with(root))
+            && !protected_paths
+                .iter()
+                .any(|protected| protected.protects(path) || protected.protects(&resolved))
+    });
+    if confined {
+        PolicyDecision::WritesInWritableRoots
+    } else {
+        PolicyDecision::Unverified
+    }
+}
+
+fn has_arg_of_type(exec: &ValidExec, arg_type: &ArgType) -> bool {
+    exec.args.iter().any(|arg| arg.r#type == *arg_type)
+        || exec.opts.iter().any(|opt| opt.r#type == *arg_type)
+}
+
+/// The absolute paths of the `WriteableFile` arguments of `exec`.
+fn written_files(exec: &ValidExec, cwd: &Path) -> Vec<PathBuf> {
+    exec.args
+        .iter()
+        .map(|arg| (&arg.r#type, &arg.value))
+        .chain(exec.opts.iter().map(|opt| (&opt.r#type, &opt.value)))
+        .filter(|(arg_type, _)| **arg_type == ArgType::WriteableFile)
+        .map(|(_, value)| {
+            Path::new(value)
+                .absolutize_from(cwd)
+                .map(|path| path.into_owned())
+                .unwrap_or_else(|_| cwd.join(value))
+        })
+        .collect()
+}
+
+fn canonicalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Resolves symlinks in `path`, which need not exist: the file itself if it
+/// does, otherwise its parent directory.
+fn resolve_symlinks(path: &Path) -> PathBuf {
+    if let Ok(resolved) = path.canonicalize() {
+        return resolved;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => canonicalize(parent).join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::protocol::SandboxPolicy;
+    use pretty_assertions::assert_eq;
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn checks_reads_and_writes_against_the_roots() {
+        let (policy, errors) = ExecPolicy::load(&[], &[]);
+        assert!(errors.is_empty(), "{errors:?}");
+        let project = tempfile::tempdir().unwrap();
+        let cwd = project.path().canonicalize().unwrap();
+        let writable_roots = [cwd.clone()];
+        let protected_paths =
+            SandboxPolicy::new_full_auto_policy().get_protected_paths(&writable_roots);
+        let check = |command: &[&str], readable_roots: Option<&[PathBuf]>| {
+            policy.check(
+                &strings(command),
+                &cwd,
+                readable_roots,
+                &writable_roots,
+                &protected_paths,
+            )
+        };
+
+        assert_eq!(PolicyDecision::ReadOnly, check(&["cat", "README.md"], None));
+        assert_eq!(
+            PolicyDecision::ReadOnly,
+            check(&["bash", "-lc", "head -n 5 /etc/hosts"], None)
+        );
+        assert_eq!(
+            PolicyDecision::Unverified,
+            check(&["cat", "/etc/hosts"], Some(&writable_roots))
+        );
+        assert_eq!(
+            PolicyDecision::WritesInWritableRoots,
+            check(&["cp", "/etc/hosts", "hosts.txt"], None)
+        );
+        assert_eq!(
+            PolicyDecision::Unverified,
+            check(&["cp", "README.md", "/tmp/README.md"], None)
+        );
+        assert_eq!(
+            PolicyDecision::Unverified,
+            check(&["cp", "hook.sh", ".git/hooks/pre-commit"], None)
+        );
+        assert_eq!(PolicyDecision::Unverified, check(&["rm", "-rf", "."], None));
+        assert_eq!(
+            PolicyDecision::Unverified,
+            check(&["bash", "-lc", "cat a && rm b"], None)
+        );
+        assert_eq!(
+            PolicyDecision::ReadOnly,
+            check(&["bash", "-lc", "cat a.txt && head -n 5 b.txt"], None)
+        );
+        assert_eq!(
+            PolicyDecision::WritesInWritableRoots,
+            check(&["bash", "-lc", "ls; cp a.txt b.txt"], None)
+        );
+        // Paths after a `cd` would be resolved against the wrong directory.
+        assert_eq!(
+            PolicyDecision::Unverified,
+            check(&["bash", "-lc", "cd /etc && cp a.txt b.txt"], None)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn writes_through_symlinks_are_not_confined() {
+        let (policy, _) = ExecPolicy::load(&[], &[]);
+        let project = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let cwd = project.path().canonicalize().unwrap();
+        std::os::unix::fs::symlink(outside.path(), cwd.join("out")).unwrap();
+
+        assert_eq!(
+            PolicyDecision::Unverified,
+            policy.check(
+                &strings(&["cp", "a.txt", "out/a.txt"]),
+                &cwd,
+                None,
+                &[cwd.clone()],
+                &[],
+            )
+        );
+    }
+
+    #[test]
+    fn user_policies_are_layered_on_the_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let forbid = dir.path().join("forbid.policy");
+        std::fs::write(
+            &forbid,
+            r#"forbid_program_regex("^curl$", "no network access from the agent")"#,
+        )
+        .unwrap();
+        let broken = dir.path().join("broken.policy");
+        std::fs::write(&broken, "define_program(").unwrap();
+
+        let (policy, errors) = ExecPolicy::load(&[forbid, broken.clone()], &[]);
+
+        assert_eq!(1, errors.len(), "{errors:?}");
+        assert!(errors[0].contains(&broken.display().to_string()));
+        let check = |command: &[&str]| policy.check(&strings(command), dir.path(), None, &[], &[]);
+        assert_eq!(
+            PolicyDecision::Forbidden {
+                reason: "no network access from the agent".to_string()
+            },
+            check(&["curl", "https://example.com"])
+        );
+        assert_eq!(PolicyDecision::ReadOnly, check(&["pwd"]));
+        assert_eq!(
+            PolicyDecision::Forbidden {
+                reason: "no network access from the agent".to_string()
+            },
+            check(&["bash", "-lc", "ls && curl https://example.com | head -n 1"])
+        );
+    }
+
+    #[test]
+    fn project_policies_can_only_forbid() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project.policy");
+        std::fs::write(
+            &project,
+            r#"
+define_program(
+    program="rm",
+    args=[ARG_RFILES],
+)
+
+forbid_program_regex("^curl$", "no network access from the agent")
+"#,
+        )
+        .unwrap();
+
+        let (policy, errors) = ExecPolicy::load(&[], &[project]);
+
+        assert_eq!(Vec::<String>::new(), errors);
+        let check = |command: &[&str]| policy.check(&strings(command), dir.path(), None, &[], &[]);
+        assert_eq!(PolicyDecision::Unverified, check(&["rm", "a.txt"]));
+        assert_eq!(
+            PolicyDecision::Forbidden {
+                reason: "no network access from the agent".to_string()
+            },
+            check(&["curl", "https://example.com"])
+        );
+    }
+}
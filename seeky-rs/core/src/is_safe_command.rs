@@ -1,22 +1,24 @@
+use tree_sitter::Node;
 use tree_sitter::Parser;
 use tree_sitter::Tree;
 use tree_sitter_bash::LANGUAGE as BASH;
 
 pub fn is_known_safe_command(command: &[String]) -> bool {
-    if is_safe_to_call_with_exec(command) {
-        return true;
-    }
+    exec_commands(command)
+        .is_some_and(|commands| commands.iter().all(|argv| is_safe_to_call_with_exec(argv)))
+}
 
-    // TODO(mbolin): Also support safe commands that are piped together such
-    // as `cat foo | wc -l`.
-    matches!(
-        command,
-        [bash, flag, script]
-            if bash == "bash"
-            && flag == "-lc"
-            && try_parse_bash(script).and_then(|tree|
-                try_parse_single_word_only_command(&tree, script)).is_some_and(|parsed_bash_command| is_safe_to_call_with_exec(&parsed_bash_command))
-    )
+/// The argvs that `command` runs: `command` itself, or the plain commands of a
+/// `bash -lc` script made of lists (`&&`, `||`), pipelines and sequences (see
+/// [`try_parse_plain_commands`]). `None` for any other script, as what it
+/// runs cannot be told from its words.
+pub(crate) fn exec_commands(command: &[String]) -> Option<Vec<Vec<String>>> {
+    match command {
+        [bash, flag, script] if bash == "bash" && flag == "-lc" => {
+            try_parse_bash(script).and_then(|tree| try_parse_plain_commands(&tree, script))
+        }
+        _ => Some(vec![command.to_vec()]),
+    }
 }
 
 fn is_safe_to_call_with_exec(command: &[String]) -> bool {
@@ -82,28 +84,116 @@ fn try_parse_bash(bash_lc_arg: &str) -> Option<Tree> {
     parser.parse(bash_lc_arg, old_tree)
 }
 
-/// If `tree` represents a single Bash command whose name and every argument is
-/// an ordinary `word`, return those words in order; otherwise, return `None`.
+/// Breaks a script made of commands joined by `&&`, `||`, `|`, `;` and
+/// newlines into the words of each command, in order. Every command must
+/// consist of ordinary words only.
 ///
 /// `src` must be the exact source string that was parsed into `tree`, so we can
 /// extract the text for every node.
-pub fn try_parse_single_word_only_command(tree: &Tree, src: &str) -> Option<Vec<String>> {
-    // Any parse error is an immediate rejection.
-    if tree.root_node().has_error() {
+/// Returns `None` for anything else, including background jobs, subshells,
+/// command substitutions, variable assignments and redirections other than
+/// discarding output to `/dev/null` or merging stderr into stdout, as these
+/// can run or write things the words do not show.
+pub fn try_parse_plain_commands(tree: &Tree, src: &str) -> Option<Vec<Vec<String>>> {
+    let root = tree.root_node();
+    if root.has_error() || root.kind() != "program" {
         return None;
     }
 
-    // (program …) with exactly one statement
-    let root = tree.root_node();
-    if root.kind() != "program" || root.named_child_count() != 1 {
+    let mut commands = Vec::new();
+    collect_plain_commands(root, src, &mut commands)?;
+    if commands.is_empty() {
         return None;
     }
+    Some(commands)
+}
+
+fn collect_plain_commands(node: Node, src: &str, commands: &mut Vec<Vec<String>>) -> Option<()> {
+    // The operators that may join the named children of `node`.
+    let operators: &[&str] = match node.kind() {
+        "program" => &[";"],
+        "list" => &["&&", "||"],
+        "pipeline" => &["|"],
+        "command" => {
+            commands.push(parse_word_only_command(node, src)?);
+            return Some(());
+        }
+        "redirected_statement" => {
+            let body = node.child_by_field_name("body")?;
+            if body.kind() != "command" {
+                return None;
+            }
+            let mut cursor = node.walk();
+            for redirect in node.children_by_field_name("redirect", &mut cursor) {
+                if !is_harmless_redirect(redirect, src) {
+                    return None;
+                }
+            }
+            commands.push(parse_word_only_command(body, src)?);
+            return Some(());
+        }
+        _ => return None,
+    };
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.is_named() {
+            if child.kind() != "comment" {
+                collect_plain_commands(child, src, commands)?;
+            }
+        } else if !operators.contains(&child.kind()) {
+            return None;
+        }
+    }
+    Some(())
+}
+
+/// `>/dev/null`, `2>/dev/null`, `&>/dev/null` and `2>&1` (or `>&2`).
+fn is_harmless_redirect(redirect: Node, src: &str) -> bool {
+    if redirect.kind() != "file_redirect" {
+        return false;
+    }
+    let mut operator = None;
+    let mut destination = None;
+    let mut cursor = redirect.walk();
+    for child in redirect.children(&mut cursor) {
+        match child.kind() {
+            // Only stdout and stderr: other descriptors may be open files.
+            "file_descriptor" => {
+                if !matches!(child.utf8_text(src.as_bytes()), Ok("1" | "2")) {
+                    return false;
+                }
+            }
+            _ if !child.is_named() => operator = Some(child.kind()),
+            _ => destination = Some(child),
+        }
+    }
+    let (Some(operator), Some(destination)) = (operator, destination) else {
+        return false;
+    };
+    let Ok(target) = destination.utf8_text(src.as_bytes()) else {
+        return false;
+    };
+    match operator {
+        ">" | ">>" | "&>" | "&>>" => destination.kind() == "word" && target == "/dev/null",
+        ">&" => destination.kind() == "number" && matches!(target, "1" | "2"),
+        _ => false,
+    }
+}
 
-    let cmd = root.named_child(0)?; // (command …)
-    if cmd.kind() != "command" {
+/// The text of an unquoted `word`, unless the shell would expand it into
+/// something else: globs and `~` are left to the shell.
+fn plain_word(word: Node, src: &str) -> Option<String> {
+    let text = word.utf8_text(src.as_bytes()).ok()?;
+    if text.starts_with('~') || text.contains(['*', '?', '[']) {
         return None;
     }
+    Some(text.to_owned())
+}
 
+/// The words of a `command` node, if its name and every argument is an
+/// ordinary `word`.
+fn parse_word_only_command(cmd: Node, src: &str) -> Option<Vec<String>> {
     let mut words = Vec::new();
     let mut cursor = cmd.walk();
 
@@ -115,10 +205,13 @@ pub fn try_parse_single_word_only_command(tree: &Tree, src: &str) -> Option<Vec<
                 if word_node.kind() != "word" {
                     return None;
                 }
-                words.push(word_node.utf8_text(src.as_bytes()).ok()?.to_owned());
+                words.push(plain_word(word_node, src)?);
             }
             // Positional‑argument word (allowed).
-            "word" | "number" => {
+            "word" => {
+                words.push(plain_word(child, src)?);
+            }
+            "number" => {
                 words.push(child.utf8_text(src.as_bytes()).ok()?.to_owned());
             }
             "string" => {
@@ -296,39 +389,130 @@ mod tests {
     }
 
     #[test]
-    fn test_try_parse_single_word_only_command() {
+    fn bash_lc_compound_scripts() {
+        #[rustfmt::skip]
+        let cases: &[(&str, bool)] = &[
+            // Lists, pipelines and sequences of safe commands.
+            ("ls && cat README.md", true),
+            ("rg foo | head -n 20", true),
+            ("cd src; ls", true),
+            ("cd src && ls -1 && cat main.rs", true),
+            ("git status || git log", true),
+            ("ls\npwd\ncat README.md", true),
+            ("cat Cargo.toml | grep name | head -n 1", true),
+            ("find . -name '*.rs' | wc -l", true),
+            ("git diff | head -n 100; git status", true),
+            ("ls && cat README.md # show the readme", true),
+            ("sed -n 1,20p src/lib.rs && sed -n '40,60p' src/lib.rs", true),
+            ("grep -R \"TODO\" -n src | head -n 5", true),
+            // Harmless redirections.
+            ("ls 2>/dev/null", true),
+            ("cat missing.txt 2>/dev/null || echo missing", true),
+            ("rg foo 2>&1 | head -n 20", true),
+            ("cargo check >/dev/null 2>&1 && echo ok", true),
+            ("ls &>/dev/null", true),
+            // Unsafe parts.
+            ("ls && rm -rf target", false),
+            ("cat README.md | sh", false),
+            ("cd /tmp; curl https://example.com", false),
+            ("git status && git push", false),
+            ("find . -name '*.rs' -delete && ls", false),
+            ("rg foo | xargs rm", false),
+            ("ls || python3 -c 'import os'", false),
+            // Redirections to files.
+            ("ls > files.txt", false),
+            ("echo hi >> ~/.bashrc", false),
+            ("cat README.md > /dev/null/../../etc/passwd", false),
+            ("ls 2> errors.txt", false),
+            ("cat < /etc/passwd", false),
+            ("cat <<EOF\nhello\nEOF", false),
+            ("ls 3>&1", false),
+            ("ls >&3", false),
+            // Substitutions and other ways to run more than the words show.
+            ("echo $(rm -rf target)", false),
+            ("echo `rm -rf target`", false),
+            ("cat <(curl https://example.com)", false),
+            ("echo $HOME", false),
+            ("echo \"$(whoami)\"", false),
+            ("(ls)", false),
+            ("{ ls; }", false),
+            ("ls &", false),
+            ("ls & rm -rf target", false),
+            ("! ls", false),
+            ("FOO=bar ls", false),
+            ("export FOO=bar; ls", false),
+            ("ls |& head", false),
+            ("if true; then ls; fi", false),
+            ("for f in *; do cat $f; done", false),
+            ("while true; do ls; done", false),
+            ("f() { ls; }; f", false),
+            ("ls *.rs", false),
+            ("ls ~", false),
+            // Parse errors and empty scripts.
+            ("ls &&", false),
+            ("cat 'unterminated", false),
+            ("", false),
+            ("# just a comment", false),
+        ];
+
+        for (script, expected) in cases {
+            assert_eq!(
+                *expected,
+                is_known_safe_command(&vec_str(&["bash", "-lc", script])),
+                "{script:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_try_parse_plain_commands() {
+        let script = "cd src && rg -n \"fn main\" | head -n 1; ls 2>/dev/null";
+        let commands = try_parse_bash(script)
+            .and_then(|tree| try_parse_plain_commands(&tree, script))
+            .unwrap();
+        assert_eq!(
+            vec![
+                vec_str(&["cd", "src"]),
+                vec_str(&["rg", "-n", "fn main"]),
+                vec_str(&["head", "-n", "1"]),
+                vec_str(&["ls"]),
+            ],
+            commands
+        );
+    }
+
+    #[test]
+    fn test_try_parse_plain_commands_strips_quotes() {
         let script_with_single_quoted_string = "sed -n '1,5p' file.txt";
         let parsed_words = try_parse_bash(script_with_single_quoted_string)
-            .and_then(|tree| {
-                try_parse_single_word_only_command(&tree, script_with_single_quoted_string)
-            })
+            .and_then(|tree| try_parse_plain_commands(&tree, script_with_single_quoted_string))
             .unwrap();
         assert_eq!(
-            vec![
+            vec![vec![
                 "sed".to_string(),
                 "-n".to_string(),
                 // Ensure the single quotes are properly removed.
                 "1,5p".to_string(),
                 "file.txt".to_string()
-            ],
+            ]],
             parsed_words,
         );
 
         let script_with_number_arg = "ls -1";
         let parsed_words = try_parse_bash(script_with_number_arg)
-            .and_then(|tree| try_parse_single_word_only_command(&tree, script_with_number_arg))
+            .and_then(|tree| try_parse_plain_commands(&tree, script_with_number_arg))
             .unwrap();
-        assert_eq!(vec!["ls", "-1"], parsed_words,);
+        assert_eq!(vec![vec!["ls", "-1"]], parsed_words,);
 
         let script_with_double_quoted_string_with_no_funny_stuff_arg = "grep -R \"Cargo.toml\" -n";
         let parsed_words = try_parse_bash(script_with_double_quoted_string_with_no_funny_stuff_arg)
             .and_then(|tree| {
-                try_parse_single_word_only_command(
+                try_parse_plain_commands(
                     &tree,
                     script_with_double_quoted_string_with_no_funny_stuff_arg,
                 )
             })
             .unwrap();
-        assert_eq!(vec!["grep", "-R", "Cargo.toml", "-n"], parsed_words);
+        assert_eq!(vec![vec!["grep", "-R", "Cargo.toml", "-n"]], parsed_words);
     }
 }
@@ -5,31 +5,44 @@
 // the TUI or the tracing stack).
 #![deny(clippy::print_stdout, clippy::print_stderr)]
 
+pub mod approval_rules;
+mod audit_log;
 mod chat_completions;
 mod client;
 mod client_common;
 pub mod seeky;
 pub use seeky::Seeky;
 pub mod config;
+pub mod config_edit;
 pub mod config_profile;
 pub mod config_types;
 mod conversation_history;
+mod env_file;
 pub mod error;
 pub mod exec;
 pub mod exec_env;
+mod exec_policy;
 mod flags;
 mod is_safe_command;
 mod mcp_connection_manager;
 mod mcp_tool_call;
-mod message_history;
+pub mod message_history;
 mod model_provider_info;
+pub mod network_proxy;
 pub mod seeky_wrapper;
 pub use model_provider_info::ModelProviderInfo;
 pub use model_provider_info::WireApi;
-mod models;
+pub mod models;
+mod process_group;
 mod project_doc;
 pub mod protocol;
-mod rollout;
+pub mod redaction;
+pub mod resource_limits;
+pub mod rollout;
 mod safety;
+mod sandbox_denial;
+pub mod schema;
+pub mod sessions;
+mod shell_snapshot;
 mod user_notification;
 pub mod util;
@@ -13,13 +13,32 @@
 //! trailing `\n`) and write it with a **single `write(2)` system call** while
 //! the file descriptor is opened with the `O_APPEND` flag. POSIX guarantees
 //! that writes up to `PIPE_BUF` bytes are atomic in that case.
+//!
+//! When `history.max_bytes` or `history.max_age_days` is configured, the
+//! writer that appends an entry also compacts the file while it still holds
+//! the exclusive lock: the oldest entries are dropped and the remainder is
+//! written to a temporary file that is then renamed over `history.jsonl`.
+//! Because the rename gives the history a new inode, the `log_id` handed out
+//! by [`history_metadata`] changes as well, so [`lookup`] calls made with
+//! offsets into the old file return `None` rather than the wrong entry.
+//! [`append_entry`] returns the new `log_id` and entry count so that the
+//! session can pass them on to the UI.
 
 use std::fs::File;
 use std::fs::OpenOptions;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
 use std::io::Result;
+use std::io::Seek;
+use std::io::SeekFrom;
 use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::PoisonError;
 
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
 use std::time::Duration;
@@ -28,6 +47,7 @@ use tokio::io::AsyncReadExt;
 use uuid::Uuid;
 
 use crate::config::Config;
+use crate::config_types::History;
 use crate::config_types::HistoryPersistence;
 
 #[cfg(unix)]
@@ -38,16 +58,42 @@ use std::os::unix::fs::PermissionsExt;
 /// Filename that stores the message history inside `~/.seeky`.
 const HISTORY_FILENAME: &str = "history.jsonl";
 
+/// Name of the temporary file that a compacted history is written to before
+/// it replaces [`HISTORY_FILENAME`].
+const COMPACTION_TMP_FILENAME: &str = "history.jsonl.tmp";
+
 const MAX_RETRIES: usize = 10;
 const RETRY_SLEEP: Duration = Duration::from_millis(100);
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
 pub struct HistoryEntry {
     pub session_id: String,
     pub ts: u64,
     pub text: String,
 }
 
+/// How [`search`] matches the query against the text of an entry.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HistorySearchMode {
+    /// The text contains the query.
+    #[default]
+    Substring,
+    /// The text starts with the query.
+    Prefix,
+}
+
+impl HistorySearchMode {
+    fn matches(self, text: &str, query: &str) -> bool {
+        match self {
+            HistorySearchMode::Substring => text.contains(query),
+            HistorySearchMode::Prefix => text.starts_with(query),
+        }
+    }
+}
+
 fn history_filepath(config: &Config) -> PathBuf {
     let mut path = config.seeky_home.clone();
     path.push(HISTORY_FILENAME);
@@ -57,18 +103,26 @@ fn history_filepath(config: &Config) -> PathBuf {
 /// Append a `text` entry associated with `session_id` to the history file. Uses
 /// advisory file locking to ensure that concurrent writes do not interleave,
 /// which entails a small amount of blocking I/O internally.
-pub(crate) async fn append_entry(text: &str, session_id: &Uuid, config: &Config) -> Result<()> {
+///
+/// If appending the entry triggered a compaction, returns the `log_id` and
+/// entry count of the compacted history.
+pub(crate) async fn append_entry(
+    text: &str,
+    session_id: &Uuid,
+    config: &Config,
+) -> Result<Option<(u64, usize)>> {
     match config.history.persistence {
         HistoryPersistence::SaveAll => {
             // Save everything: proceed.
         }
         HistoryPersistence::None => {
             // No history persistence requested.
-            return Ok(());
+            return Ok(None);
         }
     }
 
-    // TODO: check `text` for sensitive patterns
+    // Secrets have already been redacted by the caller (see
+    // `Op::AddToHistory`).
 
     // Resolve `~/.seeky/history.jsonl` and ensure the parent directory exists.
     let path = history_filepath(config);
@@ -92,7 +146,25 @@ pub(crate) async fn append_entry(text: &str, session_id: &Uuid, config: &Config)
         .map_err(|e| std::io::Error::other(format!("failed to serialise history entry: {e}")))?;
     line.push('\n');
 
-    // Open in append-only mode.
+    let mut history_file = open_locked_history_file(&path).await?;
+    let retention = config.history.clone();
+
+    // We use sync I/O with spawn_blocking() because we are using a
+    // [`std::fs::File`] instead of a [`tokio::fs::File`] to leverage an
+    // advisory file locking API that is not available in the async API.
+    tokio::task::spawn_blocking(move || -> Result<Option<(u64, usize)>> {
+        history_file.write_all(line.as_bytes())?;
+        history_file.flush()?;
+        compact_if_needed(&path, &history_file, &retention, ts)
+    })
+    .await?
+}
+
+/// Opens the history file in append-only mode and takes the exclusive lock
+/// on it. If another writer compacted the history while we were waiting for
+/// the lock, the file we locked has since been replaced, so we start over
+/// with the new one.
+async fn open_locked_history_file(path: &Path) -> Result<File> {
     let mut options = OpenOptions::new();
     options.append(true).read(true).create(true);
     #[cfg(unix)]
@@ -100,25 +172,133 @@ pub(crate) async fn append_entry(text: &str, session_id: &Uuid, config: &Config)
         options.mode(0o600);
     }
 
-    let mut history_file = options.open(&path)?;
+    for _ in 0..MAX_RETRIES {
+        let history_file = options.open(path)?;
 
-    // Ensure permissions.
-    ensure_owner_only_permissions(&history_file).await?;
+        // Ensure permissions.
+        ensure_owner_only_permissions(&history_file).await?;
 
-    // Lock file.
-    acquire_exclusive_lock_with_retry(&history_file).await?;
+        // Lock file.
+        acquire_exclusive_lock_with_retry(&history_file).await?;
 
-    // We use sync I/O with spawn_blocking() because we are using a
-    // [`std::fs::File`] instead of a [`tokio::fs::File`] to leverage an
-    // advisory file locking API that is not available in the async API.
-    tokio::task::spawn_blocking(move || -> Result<()> {
-        history_file.write_all(line.as_bytes())?;
-        history_file.flush()?;
-        Ok(())
-    })
-    .await??;
+        if is_current_history_file(&history_file, path)? {
+            return Ok(history_file);
+        }
+    }
 
-    Ok(())
+    Err(std::io::Error::new(
+        std::io::ErrorKind::WouldBlock,
+        "history file kept being replaced while waiting for its lock",
+    ))
+}
+
+/// Returns whether `file` is still the file at `path`, i.e., it has not been
+/// replaced by a compaction.
+#[cfg(unix)]
+fn is_current_history_file(file: &File, path: &Path) -> Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+
+    let locked = file.metadata()?;
+    match std::fs::metadata(path) {
+        Ok(current) => Ok(locked.dev() == current.dev() && locked.ino() == current.ino()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(not(unix))]
+fn is_current_history_file(_file: &File, _path: &Path) -> Result<bool> {
+    Ok(true)
+}
+
+/// Drops the oldest entries from the history at `path` (whose exclusive lock
+/// is held via `file`) if it exceeds the limits configured in `retention`.
+///
+/// Entries older than `max_age_days` are always dropped. When the file is
+/// larger than `max_bytes`, entries are dropped until it is at most three
+/// quarters of that size so that the file is not rewritten on every append.
+///
+/// Returns the `log_id` and entry count of the compacted file, or `None` if
+/// the history was left alone.
+fn compact_if_needed(
+    path: &Path,
+    file: &File,
+    retention: &History,
+    now: u64,
+) -> Result<Option<(u64, usize)>> {
+    let cutoff = retention
+        .max_age_days
+        .map(|days| now.saturating_sub(days.saturating_mul(SECONDS_PER_DAY)));
+    let max_bytes = retention.max_bytes.map(|max| max as u64);
+
+    let len = file.metadata()?.len();
+    let over_size = max_bytes.is_some_and(|max| len > max);
+    let has_expired = match cutoff {
+        Some(cutoff) => first_entry_ts(file)?.is_some_and(|ts| ts < cutoff),
+        None => false,
+    };
+    if !over_size && !has_expired {
+        return Ok(None);
+    }
+
+    let mut contents = String::new();
+    let mut reader = file;
+    reader.seek(SeekFrom::Start(0))?;
+    reader.read_to_string(&mut contents)?;
+    let lines: Vec<&str> = contents.split_inclusive('\n').collect();
+
+    let mut keep_from = 0;
+    if let Some(cutoff) = cutoff {
+        while keep_from < lines.len() && entry_ts(lines[keep_from]).unwrap_or(0) < cutoff {
+            keep_from += 1;
+        }
+    }
+    if let Some(max_bytes) = max_bytes {
+        let target = max_bytes / 4 * 3;
+        let mut size: u64 = lines[keep_from..].iter().map(|l| l.len() as u64).sum();
+        while keep_from < lines.len() && size > target {
+            size -= lines[keep_from].len() as u64;
+            keep_from += 1;
+        }
+    }
+
+    let tmp_path = path.with_file_name(COMPACTION_TMP_FILENAME);
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        options.mode(0o600);
+    }
+    let mut tmp_file = options.open(&tmp_path)?;
+    let kept = lines[keep_from..].concat();
+    tmp_file.write_all(kept.as_bytes())?;
+    tmp_file.sync_all()?;
+    std::fs::rename(&tmp_path, path)?;
+
+    #[cfg(unix)]
+    let log_id = {
+        use std::os::unix::fs::MetadataExt;
+        tmp_file.metadata()?.ino()
+    };
+    #[cfg(not(unix))]
+    let log_id = 0u64;
+    let entry_count = kept.bytes().filter(|&b| b == b'\n').count();
+    Ok(Some((log_id, entry_count)))
+}
+
+/// Timestamp of the first (i.e., oldest) entry in `file`, if any.
+fn first_entry_ts(file: &File) -> Result<Option<u64>> {
+    let mut reader = BufReader::new(file);
+    reader.seek(SeekFrom::Start(0))?;
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    Ok(entry_ts(&line))
+}
+
+fn entry_ts(line: &str) -> Option<u64> {
+    serde_json::from_str::<HistoryEntry>(line)
+        .ok()
+        .map(|entry| entry.ts)
 }
 
 /// Attempt to acquire an exclusive advisory lock on `file`, retrying up to 10
@@ -190,16 +370,153 @@ pub(crate) async fn history_metadata(config: &Config) -> (u64, usize) {
 /// the current history file **and** the requested offset exists. Any I/O or
 /// parsing errors are logged and result in `None`.
 ///
+/// Lookups go through an in-memory index of line offsets (see
+/// [`LineIndex`]), so they do not get slower as the history grows.
+///
 /// Note this function is not async because it uses a sync advisory file
 /// locking API.
 #[cfg(unix)]
-pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<HistoryEntry> {
-    use std::io::BufRead;
-    use std::io::BufReader;
+pub fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<HistoryEntry> {
+    with_line_index(&history_filepath(config), log_id, |file, index| {
+        read_entry(file, index, offset)
+    })
+}
+
+/// Fallback stub for non-Unix systems: currently always returns `None`.
+#[cfg(not(unix))]
+pub fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<HistoryEntry> {
+    let _ = (log_id, offset, config);
+    None
+}
+
+/// Searches the history identified by `log_id` for the newest entry whose
+/// text matches `query` (case-sensitively, according to `mode`) and returns
+/// it along with its offset. Only entries before `before_offset` are
+/// considered, if set, so that a reverse-i-search can step back through
+/// older matches by passing the offset of the previous match.
+#[cfg(unix)]
+pub fn search(
+    log_id: u64,
+    query: &str,
+    mode: HistorySearchMode,
+    before_offset: Option<usize>,
+    config: &Config,
+) -> Option<(usize, HistoryEntry)> {
+    with_line_index(&history_filepath(config), log_id, |file, index| {
+        let end = before_offset.map_or(index.len(), |before| before.min(index.len()));
+        (0..end).rev().find_map(|offset| {
+            let entry = read_entry(file, index, offset)?;
+            mode.matches(&entry.text, query).then_some((offset, entry))
+        })
+    })
+}
+
+/// Fallback stub for non-Unix systems: currently always returns `None`.
+#[cfg(not(unix))]
+pub fn search(
+    log_id: u64,
+    query: &str,
+    mode: HistorySearchMode,
+    before_offset: Option<usize>,
+    config: &Config,
+) -> Option<(usize, HistoryEntry)> {
+    let _ = (log_id, query, mode, before_offset, config);
+    None
+}
+
+/// Byte offsets of the lines of one history file, so that entry `n` can be
+/// read with a single seek. Entries are only ever appended to a given file
+/// (compaction replaces the file and therefore changes `log_id`), so the
+/// index is extended incrementally as the file grows.
+#[derive(Debug, Default)]
+struct LineIndex {
+    log_id: u64,
+    /// Contents of the first line. Inode numbers can be reused once a
+    /// compacted file is deleted, so this is compared on every update to
+    /// detect that `log_id` now refers to a different file.
+    first_line: Vec<u8>,
+    /// Offset of the first byte of each complete line.
+    line_starts: Vec<u64>,
+    /// Number of bytes covered by `line_starts`, i.e., the offset just past
+    /// the last newline that has been indexed.
+    indexed_len: u64,
+}
+
+/// Index of the most recently used history file. There is a single history
+/// file per `SEEKY_HOME`, so caching one index is enough.
+static LINE_INDEX: Mutex<Option<LineIndex>> = Mutex::new(None);
+
+impl LineIndex {
+    fn len(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Byte range of line `offset`, excluding its trailing newline.
+    fn line_range(&self, offset: usize) -> Option<(u64, u64)> {
+        let start = *self.line_starts.get(offset)?;
+        let end = self
+            .line_starts
+            .get(offset + 1)
+            .copied()
+            .unwrap_or(self.indexed_len);
+        Some((start, end.saturating_sub(1)))
+    }
+
+    /// Indexes the lines that were appended to `file` since the last call.
+    fn update(&mut self, file: &File, len: u64) -> Result<()> {
+        let replaced = match self.line_range(0) {
+            Some((start, end)) => {
+                len < self.indexed_len || read_range(file, start, end)? != self.first_line
+            }
+            None => false,
+        };
+        if replaced {
+            self.first_line.clear();
+            self.line_starts.clear();
+            self.indexed_len = 0;
+        }
+        let mut reader = BufReader::new(file);
+        reader.seek(SeekFrom::Start(self.indexed_len))?;
+        let mut line_start = self.indexed_len;
+        let mut pos = self.indexed_len;
+        loop {
+            let buf = reader.fill_buf()?;
+            if buf.is_empty() {
+                break;
+            }
+            for (i, &byte) in buf.iter().enumerate() {
+                if byte == b'\n' {
+                    self.line_starts.push(line_start);
+                    line_start = pos + i as u64 + 1;
+                }
+            }
+            let consumed = buf.len();
+            pos += consumed as u64;
+            reader.consume(consumed);
+        }
+        self.indexed_len = line_start;
+
+        if self.first_line.is_empty() {
+            if let Some((start, end)) = self.line_range(0) {
+                self.first_line = read_range(file, start, end)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Opens the history file at `path` with a shared lock, checks that it is the
+/// file identified by `log_id`, brings the cached [`LineIndex`] up to date
+/// and passes both to `f`. Errors are logged and result in `None`.
+#[cfg(unix)]
+fn with_line_index<T>(
+    path: &Path,
+    log_id: u64,
+    f: impl FnOnce(&File, &LineIndex) -> Option<T>,
+) -> Option<T> {
     use std::os::unix::fs::MetadataExt;
 
-    let path = history_filepath(config);
-    let file: File = match OpenOptions::new().read(true).open(&path) {
+    let file: File = match OpenOptions::new().read(true).open(path) {
         Ok(f) => f,
         Err(e) => {
             tracing::warn!(error = %e, "failed to open history file");
@@ -224,36 +541,57 @@ pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<Hist
         tracing::warn!(error = %e, "failed to acquire shared lock on history file");
         return None;
     }
+    // Re-read the length now that writers are excluded.
+    let len = match file.metadata() {
+        Ok(m) => m.len(),
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to stat history file");
+            return None;
+        }
+    };
 
-    let reader = BufReader::new(&file);
-    for (idx, line_res) in reader.lines().enumerate() {
-        let line = match line_res {
-            Ok(l) => l,
-            Err(e) => {
-                tracing::warn!(error = %e, "failed to read line from history file");
-                return None;
-            }
-        };
+    let mut cache = LINE_INDEX.lock().unwrap_or_else(PoisonError::into_inner);
+    let index = match cache.as_mut() {
+        Some(index) if index.log_id == log_id => index,
+        _ => cache.insert(LineIndex {
+            log_id,
+            ..Default::default()
+        }),
+    };
+    if let Err(e) = index.update(&file, len) {
+        tracing::warn!(error = %e, "failed to index history file");
+        *cache = None;
+        return None;
+    }
+    f(&file, index)
+}
 
-        if idx == offset {
-            match serde_json::from_str::<HistoryEntry>(&line) {
-                Ok(entry) => return Some(entry),
-                Err(e) => {
-                    tracing::warn!(error = %e, "failed to parse history entry");
-                    return None;
-                }
-            }
+/// Reads and parses entry `offset` of `file` using `index`.
+fn read_entry(file: &File, index: &LineIndex, offset: usize) -> Option<HistoryEntry> {
+    let (start, end) = index.line_range(offset)?;
+    let line = match read_range(file, start, end) {
+        Ok(line) => line,
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to read line from history file");
+            return None;
+        }
+    };
+    match serde_json::from_slice::<HistoryEntry>(&line) {
+        Ok(entry) => Some(entry),
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to parse history entry");
+            None
         }
     }
-
-    None
 }
 
-/// Fallback stub for non-Unix systems: currently always returns `None`.
-#[cfg(not(unix))]
-pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<HistoryEntry> {
-    let _ = (log_id, offset, config);
-    None
+fn read_range(file: &File, start: u64, end: u64) -> Result<Vec<u8>> {
+    let len = usize::try_from(end.saturating_sub(start)).map_err(std::io::Error::other)?;
+    let mut buf = vec![0; len];
+    let mut reader = file;
+    reader.seek(SeekFrom::Start(start))?;
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
 }
 
 #[cfg(unix)]
@@ -295,3 +633,221 @@ async fn ensure_owner_only_permissions(_file: &File) -> Result<()> {
     // For now, on non-Unix, simply succeed.
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    const NOW: u64 = 100 * SECONDS_PER_DAY;
+
+    fn entry_line(ts: u64, text: &str) -> String {
+        let entry = HistoryEntry {
+            session_id: "s".to_string(),
+            ts,
+            text: text.to_string(),
+        };
+        format!("{}\n", serde_json::to_string(&entry).unwrap())
+    }
+
+    /// Writes `lines` to a history file, compacts it with `retention` and
+    /// returns the texts of the surviving entries.
+    fn compact(lines: &[String], retention: History) -> Vec<String> {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(HISTORY_FILENAME);
+        std::fs::write(&path, lines.concat()).unwrap();
+        let file = OpenOptions::new()
+            .append(true)
+            .read(true)
+            .open(&path)
+            .unwrap();
+
+        let compacted = compact_if_needed(&path, &file, &retention, NOW).unwrap();
+
+        assert!(!dir.path().join(COMPACTION_TMP_FILENAME).exists());
+        let contents = std::fs::read_to_string(&path).unwrap();
+        if let Some((_, entry_count)) = compacted {
+            assert_eq!(contents.lines().count(), entry_count);
+        }
+        contents
+            .lines()
+            .map(|line| serde_json::from_str::<HistoryEntry>(line).unwrap().text)
+            .collect()
+    }
+
+    #[test]
+    fn leaves_file_alone_within_limits() {
+        let lines = vec![entry_line(NOW, "a"), entry_line(NOW, "b")];
+        let retention = History {
+            max_bytes: Some(lines.concat().len()),
+            max_age_days: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(vec!["a", "b"], compact(&lines, retention));
+    }
+
+    #[test]
+    fn drops_oldest_entries_when_too_large() {
+        let lines: Vec<String> = (0..8).map(|i| entry_line(NOW, &i.to_string())).collect();
+        let line_len = lines[0].len();
+        let retention = History {
+            // Exceeded by one line; compaction goes down to 3/4 of the limit.
+            max_bytes: Some(7 * line_len),
+            ..Default::default()
+        };
+        assert_eq!(vec!["3", "4", "5", "6", "7"], compact(&lines, retention));
+    }
+
+    #[test]
+    fn drops_expired_entries() {
+        let lines = vec![
+            entry_line(NOW - 10 * SECONDS_PER_DAY, "old"),
+            entry_line(NOW - 3 * SECONDS_PER_DAY, "older than limit"),
+            entry_line(NOW - SECONDS_PER_DAY, "recent"),
+            entry_line(NOW, "new"),
+        ];
+        let retention = History {
+            max_age_days: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(vec!["recent", "new"], compact(&lines, retention));
+    }
+
+    #[cfg(unix)]
+    fn test_config(seeky_home: &Path) -> Config {
+        use crate::config::ConfigOverrides;
+        use crate::config::ConfigToml;
+
+        Config::load_from_base_config_with_overrides(
+            ConfigToml::default(),
+            ConfigOverrides::default(),
+            seeky_home.to_path_buf(),
+        )
+        .unwrap()
+    }
+
+    #[cfg(unix)]
+    fn log_id(path: &Path) -> u64 {
+        use std::os::unix::fs::MetadataExt;
+        std::fs::metadata(path).unwrap().ino()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn lookup_uses_index_and_sees_appends() {
+        let seeky_home = TempDir::new().unwrap();
+        let config = test_config(seeky_home.path());
+        let path = history_filepath(&config);
+        let lines: Vec<String> = (0..5).map(|i| entry_line(NOW, &format!("e{i}"))).collect();
+        std::fs::write(&path, lines.concat()).unwrap();
+        let log_id = log_id(&path);
+
+        let text = |offset| lookup(log_id, offset, &config).map(|e| e.text);
+        assert_eq!(Some("e3".to_string()), text(3));
+        assert_eq!(Some("e0".to_string()), text(0));
+        assert_eq!(None, text(5));
+        assert_eq!(None, lookup(log_id + 1, 0, &config).map(|e| e.text));
+
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(entry_line(NOW, "e5").as_bytes()).unwrap();
+        // A partially written line is not visible yet.
+        file.write_all(br#"{"session_id":"s","#).unwrap();
+        assert_eq!(Some("e5".to_string()), text(5));
+        assert_eq!(None, text(6));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn lookup_detects_rewritten_file() {
+        let seeky_home = TempDir::new().unwrap();
+        let config = test_config(seeky_home.path());
+        let path = history_filepath(&config);
+        std::fs::write(&path, entry_line(NOW, "old a") + &entry_line(NOW, "old b")).unwrap();
+        let log_id = log_id(&path);
+        assert_eq!(
+            Some("old b".to_string()),
+            lookup(log_id, 1, &config).map(|e| e.text)
+        );
+
+        // Simulate inode reuse by rewriting the file in place with longer
+        // contents.
+        std::fs::write(
+            &path,
+            entry_line(NOW, "new a") + &entry_line(NOW, "new b") + &entry_line(NOW, "new c"),
+        )
+        .unwrap();
+        assert_eq!(
+            Some("new b".to_string()),
+            lookup(log_id, 1, &config).map(|e| e.text)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn search_steps_back_through_matches() {
+        let seeky_home = TempDir::new().unwrap();
+        let config = test_config(seeky_home.path());
+        let path = history_filepath(&config);
+        let texts = ["cargo build", "git status", "cargo test", "run cargo fmt"];
+        let lines: Vec<String> = texts.iter().map(|t| entry_line(NOW, t)).collect();
+        std::fs::write(&path, lines.concat()).unwrap();
+        let log_id = log_id(&path);
+
+        let find = |query, mode, before| {
+            search(log_id, query, mode, before, &config).map(|(offset, e)| (offset, e.text))
+        };
+        use HistorySearchMode::Prefix;
+        use HistorySearchMode::Substring;
+        assert_eq!(
+            Some((3, "run cargo fmt".to_string())),
+            find("cargo", Substring, None)
+        );
+        assert_eq!(
+            Some((2, "cargo test".to_string())),
+            find("cargo", Prefix, None)
+        );
+        assert_eq!(
+            Some((0, "cargo build".to_string())),
+            find("cargo", Prefix, Some(2))
+        );
+        assert_eq!(None, find("cargo", Prefix, Some(0)));
+        assert_eq!(None, find("Cargo", Substring, None));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn compaction_replaces_the_file() {
+        use std::os::unix::fs::MetadataExt;
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(HISTORY_FILENAME);
+        std::fs::write(&path, entry_line(0, "expired")).unwrap();
+        let file = OpenOptions::new()
+            .append(true)
+            .read(true)
+            .open(&path)
+            .unwrap();
+        let ino = file.metadata().unwrap().ino();
+        assert!(is_current_history_file(&file, &path).unwrap());
+
+        let retention = History {
+            max_age_days: Some(1),
+            ..Default::default()
+        };
+        let compacted = compact_if_needed(&path, &file, &retention, NOW).unwrap();
+
+        // Writers holding the old file must notice that it was replaced, and
+        // the new inode invalidates the `log_id` of the old file. The caller
+        // learns the new one.
+        assert!(!is_current_history_file(&file, &path).unwrap());
+        let new_ino = std::fs::metadata(&path).unwrap().ino();
+        assert_ne!(ino, new_ino);
+        assert_eq!(Some((new_ino, 0)), compacted);
+        assert_eq!(
+            0o600,
+            std::fs::metadata(&path).unwrap().permissions().mode() & 0o777
+        );
+    }
+}
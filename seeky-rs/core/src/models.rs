@@ -159,10 +159,9 @@ pub struct ShellToolCallParams {
     pub timeout_ms: Option<u64>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Debug, Clone)]
 pub struct FunctionCallOutputPayload {
     pub content: String,
-    #[expect(dead_code)]
     pub success: Option<bool>,
 }
 
@@ -187,6 +186,34 @@ impl Serialize for FunctionCallOutputPayload {
     }
 }
 
+// Since `Serialize` only writes `content`, accept the bare string form as
+// well as the object form when reading payloads back, e.g. from a rollout.
+
+impl<'de> Deserialize<'de> for FunctionCallOutputPayload {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Payload {
+            Content(String),
+            Object {
+                content: String,
+                success: Option<bool>,
+            },
+        }
+
+        Ok(match Payload::deserialize(deserializer)? {
+            Payload::Content(content) => Self {
+                content,
+                success: None,
+            },
+            Payload::Object { content, success } => Self { content, success },
+        })
+    }
+}
+
 // Implement Display so callers can treat the payload like a plain string when logging or doing
 // trivial substring checks in tests (existing tests call `.contains()` on the output). Display
 // returns the raw `content` field.
@@ -242,6 +269,19 @@ mod tests {
         assert_eq!(v.get("output").unwrap().as_str().unwrap(), "bad");
     }
 
+    #[test]
+    fn deserializes_plain_string_and_object_output() {
+        let plain: FunctionCallOutputPayload = serde_json::from_str(r#""ok""#).unwrap();
+        assert_eq!(("ok", None), (plain.content.as_str(), plain.success));
+
+        let object: FunctionCallOutputPayload =
+            serde_json::from_str(r#"{"content":"bad","success":false}"#).unwrap();
+        assert_eq!(
+            ("bad", Some(false)),
+            (object.content.as_str(), object.success)
+        );
+    }
+
     #[test]
     fn deserialize_shell_tool_call_params() {
         let json = r#"{
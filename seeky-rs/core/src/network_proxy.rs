@@ -0,0 +1,381 @@
+//! In-process HTTP(S) proxy that enforces the
+//! [`SandboxPermission::NetworkAllowDomains`] allowlist.
+//!
+//! Sandboxed commands are only allowed to open TCP connections to the port of
+//! this proxy, and `HTTP_PROXY`/`HTTPS_PROXY` point them at it. `CONNECT`
+//! requests (used for HTTPS) and plain HTTP requests with an absolute URI are
+//! forwarded if the target host is on the allowlist and answered with
+//! `403 Forbidden` otherwise. The host of every denied request is reported
+//! through the channel passed to [`NetworkProxy::start`].
+//!
+//! [`SandboxPermission::NetworkAllowDomains`]: crate::protocol::SandboxPermission::NetworkAllowDomains
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::task::JoinHandle;
+use tracing::debug;
+use tracing::warn;
+
+/// Set (to the port of the proxy) in the environment of commands whose
+/// network access is restricted to the allowlist, so that the sandbox helpers
+/// know which port the child may connect to.
+pub const SEEKY_SANDBOX_NETWORK_PROXY_PORT_ENV_VAR: &str = "SEEKY_SANDBOX_NETWORK_PROXY_PORT";
+
+/// Upper bound on the size of the request line plus headers.
+const MAX_REQUEST_HEAD_BYTES: usize = 16 * 1024;
+
+/// A running proxy. The listener is shut down when this is dropped.
+#[derive(Debug)]
+pub struct NetworkProxy {
+    port: u16,
+    task: JoinHandle<()>,
+}
+
+impl NetworkProxy {
+    /// Starts listening on an ephemeral port on `127.0.0.1`. The host of each
+    /// denied request is sent on `denied_hosts`.
+    pub async fn start(
+        allowed_domains: Vec<String>,
+        denied_hosts: UnboundedSender<String>,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let port = listener.local_addr()?.port();
+        let allowed_domains: Arc<[String]> = allowed_domains
+            .iter()
+            .map(|domain| normalize_host(domain))
+            .collect();
+
+        let task = tokio::spawn(async move {
+            loop {
+                let (stream, _) = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        warn!("network proxy failed to accept connection: {e}");
+                        continue;
+                    }
+                };
+                let allowed_domains = Arc::clone(&allowed_domains);
+                let denied_hosts = denied_hosts.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream, &allowed_domains, &denied_hosts).await
+                    {
+                        debug!("network proxy connection failed: {e}");
+                    }
+                });
+            }
+        });
+
+        Ok(Self { port, task })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Points the usual proxy environment variables at this proxy.
+    pub fn apply_to_env(&self, env: &mut HashMap<String, String>) {
+        let url = format!("http://127.0.0.1:{}", self.port);
+        for key in [
+            "HTTP_PROXY",
+            "HTTPS_PROXY",
+            "ALL_PROXY",
+            "http_proxy",
+            "https_proxy",
+            "all_proxy",
+        ] {
+            env.insert(key.to_string(), url.clone());
+        }
+        env.remove("NO_PROXY");
+        env.remove("no_proxy");
+        env.insert(
+            SEEKY_SANDBOX_NETWORK_PROXY_PORT_ENV_VAR.to_string(),
+            self.port.to_string(),
+        );
+    }
+}
+
+impl Drop for NetworkProxy {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Returns whether `host` is covered by `allowed_domains`. An entry matches
+/// the host exactly; an entry of the form `*.example.com` matches every
+/// subdomain of `example.com` (but not `example.com` itself).
+pub fn is_host_allowed(host: &str, allowed_domains: &[String]) -> bool {
+    let host = normalize_host(host);
+    allowed_domains.iter().any(|domain| {
+        let domain = normalize_host(domain);
+        match domain.strip_prefix("*.") {
+            Some(suffix) => host
+                .strip_suffix(suffix)
+                .is_some_and(|prefix| prefix.len() > 1 && prefix.ends_with('.')),
+            None => host == domain,
+        }
+    })
+}
+
+fn normalize_host(host: &str) -> String {
+    host.trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim_end_matches('.')
+        .to_ascii_lowercase()
+}
+
+async fn handle_connection(
+    mut client: TcpStream,
+    allowed_domains: &[String],
+    denied_hosts: &UnboundedSender<String>,
+) -> io::Result<()> {
+    let (head, rest) = read_request_head(&mut client).await?;
+    let Some(request) = ProxyRequest::parse(&head) else {
+        client
+            .write_all(b"HTTP/1.1 400 Bad Request\r\nConnection: close\r\n\r\n")
+            .await?;
+        return Ok(());
+    };
+
+    if !is_host_allowed(&request.host, allowed_domains) {
+        denied_hosts.send(request.host.clone()).ok();
+        client
+            .write_all(b"HTTP/1.1 403 Forbidden\r\nConnection: close\r\nContent-Length: 0\r\n\r\n")
+            .await?;
+        return Ok(());
+    }
+
+    let mut upstream = match TcpStream::connect((request.host.as_str(), request.port)).await {
+        Ok(upstream) => upstream,
+        Err(e) => {
+            debug!("network proxy failed to connect to {}: {e}", request.host);
+            client
+                .write_all(b"HTTP/1.1 502 Bad Gateway\r\nConnection: close\r\n\r\n")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    match request.forward_head {
+        None => {
+            client
+                .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+                .await?;
+        }
+        Some(forward_head) => upstream.write_all(&forward_head).await?,
+    }
+    upstream.write_all(&rest).await?;
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+/// Reads until the end of the request head. Returns the head (including the
+/// terminating blank line) and whatever was read past it.
+async fn read_request_head(stream: &mut TcpStream) -> io::Result<(Vec<u8>, Vec<u8>)> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        if let Some(end) = find_head_end(&buf) {
+            let rest = buf.split_off(end);
+            return Ok((buf, rest));
+        }
+        if buf.len() > MAX_REQUEST_HEAD_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request head too large",
+            ));
+        }
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "connection closed before end of request head",
+            ));
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+}
+
+fn find_head_end(buf: &[u8]) -> Option<usize> {
+    buf.windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .map(|pos| pos + 4)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct ProxyRequest {
+    host: String,
+    port: u16,
+    /// Request head to send upstream for plain HTTP requests. `None` for
+    /// `CONNECT`, where the client speaks to the upstream directly.
+    forward_head: Option<Vec<u8>>,
+}
+
+impl ProxyRequest {
+    fn parse(head: &[u8]) -> Option<Self> {
+        let head = std::str::from_utf8(head).ok()?;
+        let mut lines = head.split("\r\n");
+        let mut request_line = lines.next()?.split(' ');
+        let (method, target, version) = (
+            request_line.next()?,
+            request_line.next()?,
+            request_line.next()?,
+        );
+
+        if method.eq_ignore_ascii_case("CONNECT") {
+            let (host, port) = split_host_port(target)?;
+            return Some(Self {
+                host,
+                port: port?,
+                forward_head: None,
+            });
+        }
+
+        // Plain HTTP: the target is an absolute URI which is rewritten to
+        // origin-form for the upstream server.
+        let without_scheme = target.strip_prefix("http://")?;
+        let (authority, path) = match without_scheme.find('/') {
+            Some(idx) => without_scheme.split_at(idx),
+            None => (without_scheme, "/"),
+        };
+        let (host, port) = split_host_port(authority)?;
+        let mut forward_head = format!("{method} {path} {version}\r\n");
+        for line in lines {
+            let name = line.split(':').next().unwrap_or_default();
+            if name.eq_ignore_ascii_case("proxy-connection")
+                || name.eq_ignore_ascii_case("proxy-authorization")
+            {
+                continue;
+            }
+            forward_head.push_str(line);
+            forward_head.push_str("\r\n");
+        }
+        // `lines` ends with the two empty strings of the terminating
+        // `\r\n\r\n`, which leaves one `\r\n` too many.
+        forward_head.truncate(forward_head.len() - 2);
+        Some(Self {
+            host,
+            port: port.unwrap_or(80),
+            forward_head: Some(forward_head.into_bytes()),
+        })
+    }
+}
+
+/// Splits `host[:port]` (with `[...]` around IPv6 addresses). Returns `None`
+/// if the host is empty or the port is not a number.
+fn split_host_port(authority: &str) -> Option<(String, Option<u16>)> {
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        let (host, after) = rest.split_once(']')?;
+        (host, after.strip_prefix(':'))
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        }
+    };
+    if host.is_empty() {
+        return None;
+    }
+    let port = match port {
+        Some(port) => Some(port.parse().ok()?),
+        None => None,
+    };
+    Some((normalize_host(host), port))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn matches_exact_and_wildcard_domains() {
+        let allowed = vec!["crates.io".to_string(), "*.PyPI.org".to_string()];
+        assert!(is_host_allowed("crates.io", &allowed));
+        assert!(is_host_allowed("Crates.IO.", &allowed));
+        assert!(is_host_allowed("files.pypi.org", &allowed));
+        assert!(is_host_allowed("a.b.pypi.org", &allowed));
+
+        assert!(!is_host_allowed("static.crates.io", &allowed));
+        assert!(!is_host_allowed("pypi.org", &allowed));
+        assert!(!is_host_allowed("evilpypi.org", &allowed));
+        assert!(!is_host_allowed("crates.io.evil.com", &allowed));
+    }
+
+    #[test]
+    fn parses_connect_and_absolute_form_requests() {
+        assert_eq!(
+            Some(ProxyRequest {
+                host: "index.crates.io".to_string(),
+                port: 443,
+                forward_head: None,
+            }),
+            ProxyRequest::parse(b"CONNECT index.crates.io:443 HTTP/1.1\r\nHost: x\r\n\r\n")
+        );
+        assert_eq!(
+            Some(ProxyRequest {
+                host: "example.com".to_string(),
+                port: 8080,
+                forward_head: Some(b"GET /a?b=c HTTP/1.1\r\nHost: example.com\r\n\r\n".to_vec()),
+            }),
+            ProxyRequest::parse(
+                b"GET http://example.com:8080/a?b=c HTTP/1.1\r\nHost: example.com\r\nProxy-Connection: keep-alive\r\n\r\n"
+            )
+        );
+        assert_eq!(
+            None,
+            ProxyRequest::parse(b"CONNECT example.com HTTP/1.1\r\n\r\n")
+        );
+        assert_eq!(None, ProxyRequest::parse(b"GET /relative HTTP/1.1\r\n\r\n"));
+    }
+
+    #[tokio::test]
+    async fn tunnels_allowed_hosts_and_reports_denied_ones() {
+        let upstream = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let upstream_port = upstream.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = upstream.accept().await.unwrap();
+            let mut buf = [0u8; 4];
+            stream.read_exact(&mut buf).await.unwrap();
+            stream.write_all(&buf).await.unwrap();
+        });
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let proxy = NetworkProxy::start(vec!["127.0.0.1".to_string()], tx)
+            .await
+            .unwrap();
+
+        let mut client = TcpStream::connect(("127.0.0.1", proxy.port()))
+            .await
+            .unwrap();
+        client
+            .write_all(format!("CONNECT 127.0.0.1:{upstream_port} HTTP/1.1\r\n\r\nping").as_bytes())
+            .await
+            .unwrap();
+        let expected = b"HTTP/1.1 200 Connection Established\r\n\r\nping";
+        let mut response = vec![0u8; expected.len()];
+        client.read_exact(&mut response).await.unwrap();
+        assert_eq!(expected.to_vec(), response);
+
+        let mut client = TcpStream::connect(("127.0.0.1", proxy.port()))
+            .await
+            .unwrap();
+        client
+            .write_all(b"CONNECT example.com:443 HTTP/1.1\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden"), "{response}");
+        assert_eq!(Some("example.com".to_string()), rx.recv().await);
+    }
+}
@@ -0,0 +1,100 @@
+//! Termination of everything a shell tool command started.
+//!
+//! Shell tool commands are spawned as the leader of a new session (see
+//! `spawn_child_async`), so every process they fork, including background
+//! jobs and the processes inside a sandbox, shares their process group.
+//! Signalling the group instead of the direct child keeps timed out or
+//! interrupted commands from leaving grandchildren behind.
+
+use std::time::Duration;
+
+use tokio::process::Child;
+
+/// How long the processes get to exit after `SIGTERM` before they are sent
+/// `SIGKILL`.
+pub(crate) const KILL_GRACE_PERIOD: Duration = Duration::from_secs(2);
+
+/// How often the group is checked for remaining processes during the grace
+/// period.
+#[cfg(unix)]
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// The process group of a running shell tool command. Unless the command
+/// finished on its own (see [`ProcessGroup::disarm`]), the whole group is
+/// sent `SIGKILL` on drop, which covers tool calls that are aborted, e.g.
+/// when their `Session` is dropped.
+pub(crate) struct ProcessGroup {
+    #[cfg(unix)]
+    pgid: Option<libc::pid_t>,
+    #[cfg_attr(not(unix), allow(dead_code))]
+    armed: bool,
+}
+
+impl ProcessGroup {
+    /// `child` must be the leader of its own process group.
+    pub(crate) fn new(#[cfg_attr(not(unix), allow(unused_variables))] child: &Child) -> Self {
+        Self {
+            #[cfg(unix)]
+            pgid: child.id().and_then(|pid| libc::pid_t::try_from(pid).ok()),
+            armed: true,
+        }
+    }
+
+    /// Leaves the processes that are still in the group alone, e.g. servers
+    /// the command started in the background with their output redirected.
+    pub(crate) fn disarm(&mut self) {
+        self.armed = false;
+    }
+
+    /// Sends `SIGTERM` to the group, then `SIGKILL` to whatever is left after
+    /// [`KILL_GRACE_PERIOD`], and reaps `child`.
+    #[cfg(unix)]
+    pub(crate) async fn terminate(&mut self, child: &mut Child) -> std::io::Result<()> {
+        if self.signal(libc::SIGTERM) {
+            let deadline = tokio::time::Instant::now() + KILL_GRACE_PERIOD;
+            loop {
+                // Reap the leader as soon as it exits so that it no longer
+                // counts as a member of the group.
+                child.try_wait()?;
+                if !self.signal(0) || tokio::time::Instant::now() >= deadline {
+                    break;
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+            self.signal(libc::SIGKILL);
+        }
+        // The group may already be gone while the leader is still around,
+        // e.g. because it moved itself into another group.
+        child.start_kill().ok();
+        child.wait().await?;
+        self.armed = false;
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) async fn terminate(&mut self, child: &mut Child) -> std::io::Result<()> {
+        child.start_kill()?;
+        child.wait().await?;
+        self.armed = false;
+        Ok(())
+    }
+
+    /// Sends `signal` to the group and returns whether any process received
+    /// it. Signal `0` only checks whether the group still exists.
+    #[cfg(unix)]
+    fn signal(&self, signal: libc::c_int) -> bool {
+        match self.pgid {
+            Some(pgid) => unsafe { libc::killpg(pgid, signal) == 0 },
+            None => false,
+        }
+    }
+}
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if self.armed {
+            self.signal(libc::SIGKILL);
+        }
+    }
+}
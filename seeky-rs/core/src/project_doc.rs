@@ -1,18 +1,29 @@
 //! Project-level documentation discovery.
 //!
-//! Project-level documentation can be stored in a file named `AGENTS.md`.
-//! Currently, we include only the contents of the first file found as follows:
+//! Project-level documentation can be stored in files named `AGENTS.md`. All
+//! of the following are included, in this order:
 //!
-//! 1.  Look for the doc file in the current working directory (as determined
-//!     by the `Config`).
-//! 2.  If not found, walk *upwards* until the Git repository root is reached
-//!     (detected by the presence of a `.git` directory/file), or failing that,
-//!     the filesystem root.
-//! 3.  If the Git root is encountered, look for the doc file there. If it
-//!     exists, the search stops – we do **not** walk past the Git root.
+//! 1.  `$SEEKY_HOME/AGENTS.md`, for personal instructions that apply to
+//!     every project.
+//! 2.  Every `AGENTS.md` from the Git repository root (detected by the
+//!     presence of a `.git` directory/file) down to the current working
+//!     directory (as determined by the `Config`), so that repo-wide
+//!     conventions come before the notes for a specific package. If `cwd` is
+//!     not inside a Git repository, only `cwd` itself is searched.
+//!
+//! The docs share a single `project_doc_max_bytes` budget: once it is used
+//! up, later docs are truncated or skipped. Each doc is preceded by a header
+//! naming its path so the model knows where an instruction came from.
+//!
+//! Docs in subdirectories that are *not* on the path from the Git root to
+//! `cwd` are picked up lazily by [`SubtreeDocs`] once the agent starts working
+//! in that part of the tree.
 
 use crate::config::Config;
+use std::collections::HashSet;
+use std::io::Read;
 use std::path::Path;
+use std::path::PathBuf;
 use tokio::io::AsyncReadExt;
 use tracing::error;
 
@@ -23,80 +34,249 @@ const CANDIDATE_FILENAMES: &[&str] = &["AGENTS.md"];
 /// be concatenated with the following separator.
 const PROJECT_DOC_SEPARATOR: &str = "\n\n--- project-doc ---\n\n";
 
-/// Combines `Config::instructions` and `AGENTS.md` (if present) into a single
-/// string of instructions.
-pub(crate) async fn create_full_instructions(config: &Config) -> Option<String> {
-    match find_project_doc(config).await {
-        Ok(Some(project_doc)) => match &config.instructions {
-            Some(original_instructions) => Some(format!(
-                "{original_instructions}{PROJECT_DOC_SEPARATOR}{project_doc}"
-            )),
-            None => Some(project_doc),
-        },
-        Ok(None) => config.instructions.clone(),
+/// Instructions for a session along with the docs they were assembled from.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct FullInstructions {
+    pub(crate) instructions: Option<String>,
+    /// Paths of the `AGENTS.md` files included in `instructions`.
+    pub(crate) project_docs: Vec<PathBuf>,
+}
+
+/// A single `AGENTS.md` that was loaded (possibly truncated).
+#[derive(Debug, Clone, PartialEq)]
+struct ProjectDoc {
+    path: PathBuf,
+    contents: String,
+}
+
+/// Combines `Config::instructions` and every applicable `AGENTS.md` into a
+/// single string of instructions.
+pub(crate) async fn create_full_instructions(config: &Config) -> FullInstructions {
+    let docs = match find_project_docs(config).await {
+        Ok(docs) => docs,
         Err(e) => {
             error!("error trying to find project doc: {e:#}");
-            config.instructions.clone()
+            Vec::new()
+        }
+    };
+
+    let project_doc = (!docs.is_empty()).then(|| render_project_docs(&docs));
+    let instructions = match (&config.instructions, project_doc) {
+        (Some(original_instructions), Some(project_doc)) => Some(format!(
+            "{original_instructions}{PROJECT_DOC_SEPARATOR}{project_doc}"
+        )),
+        (None, Some(project_doc)) => Some(project_doc),
+        (original_instructions, None) => original_instructions.clone(),
+    };
+
+    FullInstructions {
+        instructions,
+        project_docs: docs.into_iter().map(|doc| doc.path).collect(),
+    }
+}
+
+fn render_project_docs(docs: &[ProjectDoc]) -> String {
+    docs.iter()
+        .map(|doc| format!("--- {} ---\n\n{}", doc.path.display(), doc.contents))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Tracks the `AGENTS.md` files the model has seen during a session so that
+/// docs living in subdirectories can be injected the first time the agent
+/// works inside them (by applying a patch there or running a command with
+/// that `workdir`). Each doc is injected at most once per session.
+#[derive(Debug, Default)]
+pub(crate) struct SubtreeDocs {
+    /// Only directories under this root (the Git root of the session's `cwd`,
+    /// or `cwd` itself outside of a repository) are searched.
+    root: PathBuf,
+    max_bytes: usize,
+    /// Docs that have already been loaded or queued for injection.
+    seen: HashSet<PathBuf>,
+    /// Docs that were discovered but not yet sent to the model.
+    pending: Vec<ProjectDoc>,
+}
+
+impl SubtreeDocs {
+    /// `loaded_docs` are the docs that were included in the session's
+    /// instructions and therefore must not be injected again.
+    pub(crate) async fn new(cwd: &Path, max_bytes: usize, loaded_docs: &[PathBuf]) -> Self {
+        let root = match project_doc_dirs(cwd).await {
+            Ok(dirs) => dirs.into_iter().next().unwrap_or_else(|| cwd.to_path_buf()),
+            Err(e) => {
+                error!("error trying to find project doc root: {e:#}");
+                cwd.to_path_buf()
+            }
+        };
+        Self {
+            root,
+            max_bytes,
+            seen: loaded_docs.iter().cloned().collect(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Records that the agent touched `path` (a file or directory). Any
+    /// unseen `AGENTS.md` between `path` and the project root is queued for
+    /// injection, outermost first.
+    pub(crate) fn note_path(&mut self, path: &Path) {
+        if self.max_bytes == 0 {
+            return;
+        }
+
+        let start = if path.is_dir() {
+            path
+        } else {
+            match path.parent() {
+                Some(parent) => parent,
+                None => return,
+            }
+        };
+        let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+
+        let mut discovered = Vec::new();
+        let mut dir = Some(start.as_path());
+        while let Some(current) = dir {
+            if !current.starts_with(&self.root) {
+                break;
+            }
+            for name in CANDIDATE_FILENAMES {
+                let candidate = current.join(name);
+                if self.seen.contains(&candidate) {
+                    continue;
+                }
+                match read_doc(&candidate, self.max_bytes) {
+                    Ok(Some(contents)) => {
+                        self.seen.insert(candidate.clone());
+                        discovered.push(ProjectDoc {
+                            path: candidate,
+                            contents,
+                        });
+                    }
+                    Ok(None) => {}
+                    Err(e) => error!("error reading {}: {e:#}", candidate.display()),
+                }
+            }
+            dir = current.parent();
         }
+
+        discovered.reverse();
+        self.pending.extend(discovered);
+    }
+
+    /// Returns the docs queued since the last call, rendered as the text of
+    /// a developer message.
+    pub(crate) fn take_pending(&mut self) -> Vec<String> {
+        self.pending
+            .drain(..)
+            .map(|doc| {
+                let dir = doc.path.parent().unwrap_or(&doc.path);
+                format!(
+                    "The following AGENTS.md applies to files under {}:\n\n{}",
+                    dir.display(),
+                    render_project_docs(std::slice::from_ref(&doc))
+                )
+            })
+            .collect()
     }
 }
 
-/// Attempt to locate and load the project documentation. Currently, the search
-/// starts from `Config::cwd`, but if we may want to consider other directories
-/// in the future, e.g., additional writable directories in the `SandboxPolicy`.
+/// Reads at most `max_bytes` of the doc at `path`. Returns `None` if the file
+/// does not exist or is blank.
+fn read_doc(path: &Path, max_bytes: usize) -> std::io::Result<Option<String>> {
+    let file = match std::fs::File::open(path) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+        Ok(f) => f,
+    };
+    if !file.metadata()?.is_file() {
+        return Ok(None);
+    }
+    let mut data = Vec::new();
+    file.take(max_bytes as u64).read_to_end(&mut data)?;
+    let contents = String::from_utf8_lossy(&data).to_string();
+    if contents.trim().is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(contents))
+}
+
+/// Locate and load the personal and project documentation. Currently, the
+/// search starts from `Config::cwd`, but if we may want to consider other
+/// directories in the future, e.g., additional writable directories in the
+/// `SandboxPolicy`.
 ///
-/// On success returns `Ok(Some(contents))`. If no documentation file is found
-/// the function returns `Ok(None)`. Unexpected I/O failures bubble up as
-/// `Err` so callers can decide how to handle them.
-async fn find_project_doc(config: &Config) -> std::io::Result<Option<String>> {
-    let max_bytes = config.project_doc_max_bytes;
-
-    // Attempt to load from the working directory first.
-    if let Some(doc) = load_first_candidate(&config.cwd, CANDIDATE_FILENAMES, max_bytes).await? {
-        return Ok(Some(doc));
+/// Returns the docs in the order they should appear in the instructions.
+/// Unexpected I/O failures bubble up as `Err` so callers can decide how to
+/// handle them.
+async fn find_project_docs(config: &Config) -> std::io::Result<Vec<ProjectDoc>> {
+    let mut remaining = config.project_doc_max_bytes;
+
+    let mut dirs = vec![config.seeky_home.clone()];
+    for dir in project_doc_dirs(&config.cwd).await? {
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
     }
 
-    // Walk up towards the filesystem root, stopping once we encounter the Git
-    // repository root. The presence of **either** a `.git` *file* or
-    // *directory* counts.
-    let mut dir = config.cwd.clone();
+    let mut docs = Vec::new();
+    for dir in dirs {
+        if remaining == 0 {
+            break;
+        }
+        if let Some((doc, bytes_read)) =
+            load_first_candidate(&dir, CANDIDATE_FILENAMES, remaining).await?
+        {
+            remaining -= bytes_read;
+            docs.push(doc);
+        }
+    }
 
+    Ok(docs)
+}
+
+/// Returns the directories to search for project docs, ordered from the Git
+/// repository root down to `cwd`. If `cwd` is not inside a Git repository,
+/// only `cwd` is returned.
+async fn project_doc_dirs(cwd: &Path) -> std::io::Result<Vec<PathBuf>> {
     // Canonicalize the path so that we do not end up in an infinite loop when
     // `cwd` contains `..` components.
-    if let Ok(canon) = dir.canonicalize() {
-        dir = canon;
-    }
+    let cwd = cwd.canonicalize().unwrap_or_else(|_| cwd.to_path_buf());
 
-    while let Some(parent) = dir.parent() {
-        // `.git` can be a *file* (for worktrees or submodules) or a *dir*.
-        let git_marker = dir.join(".git");
-        let git_exists = match tokio::fs::metadata(&git_marker).await {
-            Ok(_) => true,
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
-            Err(e) => return Err(e),
-        };
+    // Walk up towards the filesystem root, stopping once we encounter the Git
+    // repository root. The presence of **either** a `.git` *file* or
+    // *directory* counts.
+    let mut dirs = Vec::new();
+    let mut dir = Some(cwd.as_path());
+    while let Some(current) = dir {
+        dirs.push(current.to_path_buf());
 
-        if git_exists {
-            // We are at the repo root – attempt one final load.
-            if let Some(doc) = load_first_candidate(&dir, CANDIDATE_FILENAMES, max_bytes).await? {
-                return Ok(Some(doc));
+        // `.git` can be a *file* (for worktrees or submodules) or a *dir*.
+        let git_marker = current.join(".git");
+        match tokio::fs::metadata(&git_marker).await {
+            Ok(_) => {
+                dirs.reverse();
+                return Ok(dirs);
             }
-            break;
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
         }
 
-        dir = parent.to_path_buf();
+        dir = current.parent();
     }
 
-    Ok(None)
+    Ok(vec![cwd])
 }
 
-/// Attempt to load the first candidate file found in `dir`. Returns the file
-/// contents (truncated if it exceeds `max_bytes`) when successful.
+/// Attempt to load the first candidate file found in `dir`. Returns the doc
+/// (truncated if it exceeds `max_bytes`) along with the number of bytes that
+/// were read from disk.
 async fn load_first_candidate(
     dir: &Path,
     names: &[&str],
     max_bytes: usize,
-) -> std::io::Result<Option<String>> {
+) -> std::io::Result<Option<(ProjectDoc, usize)>> {
     for name in names {
         let candidate = dir.join(name);
 
@@ -115,7 +295,7 @@ async fn load_first_candidate(
 
         if size as usize > max_bytes {
             tracing::warn!(
-                "Project doc `{}` exceeds {max_bytes} bytes - truncating.",
+                "Project doc `{}` exceeds the remaining {max_bytes} bytes - truncating.",
                 candidate.display(),
             );
         }
@@ -126,7 +306,11 @@ async fn load_first_candidate(
             continue;
         }
 
-        return Ok(Some(contents));
+        let doc = ProjectDoc {
+            path: candidate,
+            contents,
+        };
+        return Ok(Some((doc, data.len())));
     }
 
     Ok(None)
@@ -163,6 +347,12 @@ mod tests {
         config
     }
 
+    /// Expected rendering of a single doc found in `dir`.
+    fn doc_section(dir: &Path, contents: &str) -> String {
+        let path = dir.canonicalize().unwrap().join("AGENTS.md");
+        format!("--- {} ---\n\n{contents}", path.display())
+    }
+
     /// AGENTS.md missing – should yield `None`.
     #[tokio::test]
     async fn no_doc_file_returns_none() {
@@ -170,26 +360,30 @@ mod tests {
 
         let res = create_full_instructions(&make_config(&tmp, 4096, None)).await;
         assert!(
-            res.is_none(),
+            res.instructions.is_none(),
             "Expected None when AGENTS.md is absent and no system instructions provided"
         );
-        assert!(res.is_none(), "Expected None when AGENTS.md is absent");
+        assert!(res.project_docs.is_empty());
     }
 
-    /// Small file within the byte-limit is returned unmodified.
+    /// Small file within the byte-limit is returned unmodified, preceded by
+    /// a header naming its path.
     #[tokio::test]
     async fn doc_smaller_than_limit_is_returned() {
         let tmp = tempfile::tempdir().expect("tempdir");
         fs::write(tmp.path().join("AGENTS.md"), "hello world").unwrap();
 
-        let res = create_full_instructions(&make_config(&tmp, 4096, None))
-            .await
-            .expect("doc expected");
+        let res = create_full_instructions(&make_config(&tmp, 4096, None)).await;
 
         assert_eq!(
-            res, "hello world",
+            res.instructions,
+            Some(doc_section(tmp.path(), "hello world")),
             "The document should be returned verbatim when it is smaller than the limit and there are no existing instructions"
         );
+        assert_eq!(
+            res.project_docs,
+            vec![tmp.path().canonicalize().unwrap().join("AGENTS.md")]
+        );
     }
 
     /// Oversize file is truncated to `project_doc_max_bytes`.
@@ -203,10 +397,10 @@ mod tests {
 
         let res = create_full_instructions(&make_config(&tmp, LIMIT, None))
             .await
+            .instructions
             .expect("doc expected");
 
-        assert_eq!(res.len(), LIMIT, "doc should be truncated to LIMIT bytes");
-        assert_eq!(res, huge[..LIMIT]);
+        assert_eq!(res, doc_section(tmp.path(), &huge[..LIMIT]));
     }
 
     /// When `cwd` is nested inside a repo, the search should locate AGENTS.md
@@ -233,8 +427,147 @@ mod tests {
         let mut cfg = make_config(&repo, 4096, None);
         cfg.cwd = nested;
 
-        let res = create_full_instructions(&cfg).await.expect("doc expected");
-        assert_eq!(res, "root level doc");
+        let res = create_full_instructions(&cfg)
+            .await
+            .instructions
+            .expect("doc expected");
+        assert_eq!(res, doc_section(repo.path(), "root level doc"));
+    }
+
+    /// Every AGENTS.md between the repo root and `cwd` is included, outermost
+    /// first.
+    #[tokio::test]
+    async fn merges_docs_from_repo_root_down_to_cwd() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        fs::create_dir(repo.path().join(".git")).unwrap();
+        fs::write(repo.path().join("AGENTS.md"), "root doc").unwrap();
+
+        let package = repo.path().join("packages/app");
+        fs::create_dir_all(package.join("src")).unwrap();
+        fs::write(package.join("AGENTS.md"), "package doc").unwrap();
+
+        let mut cfg = make_config(&repo, 4096, None);
+        cfg.cwd = package.join("src");
+
+        let res = create_full_instructions(&cfg).await;
+        assert_eq!(
+            res.instructions,
+            Some(format!(
+                "{}\n\n{}",
+                doc_section(repo.path(), "root doc"),
+                doc_section(&package, "package doc")
+            ))
+        );
+        assert_eq!(res.project_docs.len(), 2);
+    }
+
+    /// The byte budget is shared, so docs closer to `cwd` are truncated (or
+    /// dropped) once the outer docs have used it up.
+    #[tokio::test]
+    async fn docs_share_the_byte_budget() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        fs::create_dir(repo.path().join(".git")).unwrap();
+        fs::write(repo.path().join("AGENTS.md"), "0123456789").unwrap();
+
+        let package = repo.path().join("package");
+        fs::create_dir(&package).unwrap();
+        fs::write(package.join("AGENTS.md"), "abcdefghij").unwrap();
+
+        let mut cfg = make_config(&repo, 15, None);
+        cfg.cwd = package.clone();
+        let res = create_full_instructions(&cfg).await;
+        assert_eq!(
+            res.instructions,
+            Some(format!(
+                "{}\n\n{}",
+                doc_section(repo.path(), "0123456789"),
+                doc_section(&package, "abcde")
+            ))
+        );
+
+        cfg.project_doc_max_bytes = 10;
+        let res = create_full_instructions(&cfg).await;
+        assert_eq!(
+            res.instructions,
+            Some(doc_section(repo.path(), "0123456789"))
+        );
+    }
+
+    /// `$SEEKY_HOME/AGENTS.md` is included before any project docs.
+    #[tokio::test]
+    async fn personal_doc_comes_first() {
+        let seeky_home = tempfile::tempdir().expect("tempdir");
+        fs::write(seeky_home.path().join("AGENTS.md"), "personal doc").unwrap();
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        fs::write(tmp.path().join("AGENTS.md"), "proj doc").unwrap();
+
+        let mut cfg = make_config(&tmp, 4096, None);
+        cfg.seeky_home = seeky_home.path().canonicalize().unwrap();
+
+        let res = create_full_instructions(&cfg).await;
+        assert_eq!(
+            res.instructions,
+            Some(format!(
+                "{}\n\n{}",
+                doc_section(seeky_home.path(), "personal doc"),
+                doc_section(tmp.path(), "proj doc")
+            ))
+        );
+    }
+
+    /// Docs in subtrees are queued once, the first time a path inside them is
+    /// touched, and never for docs that were loaded at session start.
+    #[tokio::test]
+    async fn subtree_docs_are_injected_once() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        fs::create_dir(repo.path().join(".git")).unwrap();
+        fs::write(repo.path().join("AGENTS.md"), "root doc").unwrap();
+
+        let billing = repo.path().join("services/billing");
+        fs::create_dir_all(billing.join("src")).unwrap();
+        fs::write(repo.path().join("services/AGENTS.md"), "services doc").unwrap();
+        fs::write(billing.join("AGENTS.md"), "billing doc").unwrap();
+
+        let cfg = make_config(&repo, 4096, None);
+        let loaded = create_full_instructions(&cfg).await.project_docs;
+        let mut docs = SubtreeDocs::new(&cfg.cwd, 4096, &loaded).await;
+
+        docs.note_path(&billing.join("src/lib.rs"));
+        let billing_dir = billing.canonicalize().unwrap();
+        let services_dir = billing_dir.parent().unwrap();
+        assert_eq!(
+            docs.take_pending(),
+            vec![
+                format!(
+                    "The following AGENTS.md applies to files under {}:\n\n{}",
+                    services_dir.display(),
+                    doc_section(services_dir, "services doc")
+                ),
+                format!(
+                    "The following AGENTS.md applies to files under {}:\n\n{}",
+                    billing_dir.display(),
+                    doc_section(&billing_dir, "billing doc")
+                ),
+            ]
+        );
+
+        docs.note_path(&billing);
+        docs.note_path(repo.path());
+        assert!(docs.take_pending().is_empty());
+    }
+
+    /// Paths outside of the project root never trigger injection.
+    #[tokio::test]
+    async fn subtree_docs_ignore_paths_outside_root() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        fs::create_dir(repo.path().join(".git")).unwrap();
+        let elsewhere = tempfile::tempdir().expect("tempdir");
+        fs::write(elsewhere.path().join("AGENTS.md"), "other doc").unwrap();
+
+        let mut docs = SubtreeDocs::new(repo.path(), 4096, &[]).await;
+        docs.note_path(&elsewhere.path().join("file.txt"));
+        assert!(docs.take_pending().is_empty());
     }
 
     /// Explicitly setting the byte-limit to zero disables project docs.
@@ -245,7 +578,7 @@ mod tests {
 
         let res = create_full_instructions(&make_config(&tmp, 0, None)).await;
         assert!(
-            res.is_none(),
+            res.instructions.is_none(),
             "With limit 0 the function should return None"
         );
     }
@@ -261,9 +594,13 @@ mod tests {
 
         let res = create_full_instructions(&make_config(&tmp, 4096, Some(INSTRUCTIONS)))
             .await
+            .instructions
             .expect("should produce a combined instruction string");
 
-        let expected = format!("{INSTRUCTIONS}{PROJECT_DOC_SEPARATOR}{}", "proj doc");
+        let expected = format!(
+            "{INSTRUCTIONS}{PROJECT_DOC_SEPARATOR}{}",
+            doc_section(tmp.path(), "proj doc")
+        );
 
         assert_eq!(res, expected);
     }
@@ -278,6 +615,6 @@ mod tests {
 
         let res = create_full_instructions(&make_config(&tmp, 4096, Some(INSTRUCTIONS))).await;
 
-        assert_eq!(res, Some(INSTRUCTIONS.to_string()));
+        assert_eq!(res.instructions, Some(INSTRUCTIONS.to_string()));
     }
 }
@@ -4,19 +4,23 @@
 //! between user and agent.
 
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
 use std::path::Path;
 use std::path::PathBuf;
 
 use mcp_types::CallToolResult;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
 use uuid::Uuid;
 
 use crate::message_history::HistoryEntry;
+use crate::message_history::HistorySearchMode;
 use crate::model_provider_info::ModelProviderInfo;
 
 /// Submission Queue Entry - requests from user
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct Submission {
     /// Unique id for this Submission to correlate with Events
     pub id: String,
@@ -25,7 +29,7 @@ pub struct Submission {
 }
 
 /// Submission operation
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[allow(clippy::large_enum_variant)]
 #[non_exhaustive]
@@ -39,6 +43,10 @@ pub enum Op {
         model: String,
         /// Model instructions
         instructions: Option<String>,
+        /// Paths of the `AGENTS.md` files whose contents are included in
+        /// `instructions`.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        project_docs: Vec<PathBuf>,
         /// When to escalate for approval for execution
         approval_policy: AskForApproval,
         /// How to sandbox commands executed in the system
@@ -93,7 +101,8 @@ pub enum Op {
     /// Append an entry to the persistent cross-session message history.
     ///
     /// Note the entry is not guaranteed to be logged if the user has
-    /// history disabled, it matches the list of "sensitive" patterns, etc.
+    /// history disabled, and secrets matched by the `redaction` config are
+    /// replaced before it is saved.
     AddToHistory {
         /// The message text to be stored.
         text: String,
@@ -101,10 +110,23 @@ pub enum Op {
 
     /// Request a single history entry identified by `log_id` + `offset`.
     GetHistoryEntryRequest { offset: usize, log_id: u64 },
+
+    /// Search the history identified by `log_id` for the newest entry that
+    /// matches `query`, e.g. for a reverse-i-search. If `before_offset` is
+    /// set, only older entries are considered, which allows stepping back
+    /// through successive matches.
+    SearchHistoryRequest {
+        log_id: u64,
+        query: String,
+        #[serde(default)]
+        mode: HistorySearchMode,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        before_offset: Option<usize>,
+    },
 }
 
 /// Determines how liberally commands are auto‑approved by the system.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum AskForApproval {
     /// Under this policy, only “known safe” commands—as determined by
@@ -131,23 +153,61 @@ pub enum AskForApproval {
 }
 
 /// Determines execution restrictions for model shell commands
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct SandboxPolicy {
     permissions: Vec<SandboxPermission>,
+
+    /// Paths that stay read-only even inside the writable roots, see
+    /// [`SandboxPolicy::get_protected_paths`].
+    #[serde(default = "default_protected_paths")]
+    protected_paths: Vec<String>,
+}
+
+/// Git hooks, config and `info/` as well as Seeky's own settings would let a
+/// command run code outside the sandbox later on. The rest of `.git` stays
+/// writable, so that `git add` and `git commit` work in the sandbox.
+pub const DEFAULT_PROTECTED_PATHS: &[&str] = &[".git/hooks", ".git/config", ".git/info", ".seeky"];
+
+fn default_protected_paths() -> Vec<String> {
+    DEFAULT_PROTECTED_PATHS
+        .iter()
+        .map(|path| path.to_string())
+        .collect()
 }
 
 impl From<Vec<SandboxPermission>> for SandboxPolicy {
     fn from(permissions: Vec<SandboxPermission>) -> Self {
-        Self { permissions }
+        Self {
+            permissions,
+            protected_paths: default_protected_paths(),
+        }
+    }
+}
+
+/// A path inside a writable root that the sandbox keeps read-only.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtectedPath {
+    pub path: PathBuf,
+
+    /// Paths beneath `path` that stay writable.
+    pub writable_subpaths: Vec<PathBuf>,
+}
+
+impl ProtectedPath {
+    /// Whether writing to `path` is denied by this protection.
+    pub fn protects(&self, path: &Path) -> bool {
+        path.starts_with(&self.path)
+            && !self
+                .writable_subpaths
+                .iter()
+                .any(|subpath| path.starts_with(subpath))
     }
 }
 
 impl SandboxPolicy {
     pub fn new_read_only_policy() -> Self {
-        Self {
-            permissions: vec![SandboxPermission::DiskFullReadAccess],
-        }
+        Self::from(vec![SandboxPermission::DiskFullReadAccess])
     }
 
     pub fn new_read_only_policy_with_writable_roots(writable_roots: &[PathBuf]) -> Self {
@@ -157,17 +217,103 @@ impl SandboxPolicy {
                 folder: folder.clone(),
             }
         }));
-        Self { permissions }
+        Self::from(permissions)
     }
 
     pub fn new_full_auto_policy() -> Self {
-        Self {
-            permissions: vec![
-                SandboxPermission::DiskFullReadAccess,
-                SandboxPermission::DiskWritePlatformUserTempFolder,
-                SandboxPermission::DiskWriteCwd,
-            ],
+        Self::from(vec![
+            SandboxPermission::DiskFullReadAccess,
+            SandboxPermission::DiskWritePlatformUserTempFolder,
+            SandboxPermission::DiskWriteCwd,
+        ])
+    }
+
+    /// Replaces the protected paths, [`DEFAULT_PROTECTED_PATHS`] by default.
+    /// Relative entries apply inside every writable root, and entries
+    /// starting with `!` keep a path beneath a protected one writable.
+    pub fn with_protected_paths(mut self, protected_paths: Vec<String>) -> Self {
+        self.protected_paths = protected_paths;
+        self
+    }
+
+    pub fn protected_paths(&self) -> &[String] {
+        &self.protected_paths
+    }
+
+    /// Resolves the protected paths against `writable_roots`. Only paths
+    /// inside a writable root are returned, as everything else is read-only
+    /// anyway; they do not have to exist.
+    pub fn get_protected_paths(&self, writable_roots: &[PathBuf]) -> Vec<ProtectedPath> {
+        let resolve = |entry: &str| -> Vec<PathBuf> {
+            let entry = Path::new(entry);
+            if entry.is_absolute() {
+                vec![entry.to_path_buf()]
+            } else {
+                writable_roots.iter().map(|root| root.join(entry)).collect()
+            }
+        };
+        let (exceptions, entries): (Vec<&String>, Vec<&String>) = self
+            .protected_paths
+            .iter()
+            .filter(|entry| !entry.trim_start_matches('!').is_empty())
+            .partition(|entry| entry.starts_with('!'));
+        let exceptions = exceptions
+            .into_iter()
+            .flat_map(|entry| resolve(&entry[1..]))
+            .collect::<Vec<_>>();
+
+        let mut protected_paths: Vec<ProtectedPath> = Vec::new();
+        for path in entries.into_iter().flat_map(|entry| resolve(entry)) {
+            let inside_writable_root = writable_roots.iter().any(|root| path.starts_with(root));
+            if !inside_writable_root || protected_paths.iter().any(|p| p.path == path) {
+                continue;
+            }
+            let writable_subpaths = exceptions
+                .iter()
+                .filter(|exception| exception.starts_with(&path) && **exception != path)
+                .cloned()
+                .collect();
+            protected_paths.push(ProtectedPath {
+                path,
+                writable_subpaths,
+            });
         }
+        protected_paths
+    }
+
+    /// The folders between `writable_roots` and the protected paths inside
+    /// them, e.g. `.git` for `.git/hooks`. Moving one of them away would take
+    /// the protection along, so the sandbox must keep them in place.
+    pub fn get_protected_path_ancestors(&self, writable_roots: &[PathBuf]) -> Vec<PathBuf> {
+        let mut ancestors: Vec<PathBuf> = Vec::new();
+        for protected in self.get_protected_paths(writable_roots) {
+            // The outermost root, as renaming inside a nested root only
+            // needs write access to the root around it.
+            let Some(root) = writable_roots
+                .iter()
+                .filter(|root| protected.path.starts_with(root))
+                .min_by_key(|root| root.components().count())
+            else {
+                continue;
+            };
+            for ancestor in protected.path.ancestors().skip(1) {
+                if ancestor == root {
+                    break;
+                }
+                if !ancestors.iter().any(|a| a == ancestor) {
+                    ancestors.push(ancestor.to_path_buf());
+                }
+            }
+        }
+        ancestors
+    }
+
+    /// The protected paths inside the writable roots of this policy.
+    pub fn get_protected_paths_with_cwd(&self, cwd: &Path) -> Vec<ProtectedPath> {
+        if self.has_full_disk_write_access() {
+            return Vec::new();
+        }
+        self.get_protected_paths(&self.get_writable_roots_with_cwd(cwd))
     }
 
     pub fn has_full_disk_read_access(&self) -> bool {
@@ -188,6 +334,22 @@ impl SandboxPolicy {
             .any(|perm| matches!(perm, SandboxPermission::NetworkFullAccess))
     }
 
+    /// Domains that may be reached through the sandbox's network proxy when
+    /// the policy does not grant [`SandboxPermission::NetworkFullAccess`].
+    pub fn get_allowed_network_domains(&self) -> Vec<String> {
+        let mut domains = Vec::new();
+        for perm in &self.permissions {
+            if let SandboxPermission::NetworkAllowDomains { domains: allowed } = perm {
+                for domain in allowed {
+                    if !domains.contains(domain) {
+                        domains.push(domain.clone());
+                    }
+                }
+            }
+        }
+        domains
+    }
+
     pub fn get_writable_roots_with_cwd(&self, cwd: &Path) -> Vec<PathBuf> {
         let mut writable_roots = Vec::<PathBuf>::new();
         for perm in &self.permissions {
@@ -230,7 +392,10 @@ impl SandboxPolicy {
                 DiskWriteFolder { folder } => {
                     writable_roots.push(folder.clone());
                 }
-                DiskFullReadAccess | NetworkFullAccess => {}
+                DiskFullReadAccess
+                | DiskReadFolder { .. }
+                | NetworkFullAccess
+                | NetworkAllowDomains { .. } => {}
                 DiskFullWriteAccess => {
                     // Currently, we expect callers to only invoke this method
                     // after verifying has_full_disk_write_access() is false.
@@ -240,6 +405,25 @@ impl SandboxPolicy {
         writable_roots
     }
 
+    /// Folders that may be read when the policy does not grant
+    /// [`SandboxPermission::DiskFullReadAccess`]: `cwd`, every writable root,
+    /// and each [`SandboxPermission::DiskReadFolder`]. Sandboxes additionally
+    /// expose the system directories needed to execute binaries.
+    pub fn get_readable_roots_with_cwd(&self, cwd: &Path) -> Vec<PathBuf> {
+        let mut readable_roots = vec![cwd.to_path_buf()];
+        if !self.has_full_disk_write_access() {
+            readable_roots.extend(self.get_writable_roots_with_cwd(cwd));
+        }
+        for perm in &self.permissions {
+            if let SandboxPermission::DiskReadFolder { folder } = perm {
+                readable_roots.push(folder.clone());
+            }
+        }
+        let mut seen = HashSet::new();
+        readable_roots.retain(|root| seen.insert(root.clone()));
+        readable_roots
+    }
+
     pub fn is_unrestricted(&self) -> bool {
         self.has_full_disk_read_access()
             && self.has_full_disk_write_access()
@@ -249,12 +433,17 @@ impl SandboxPolicy {
 
 /// Permissions that should be granted to the sandbox in which the agent
 /// operates.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum SandboxPermission {
     /// Is allowed to read all files on disk.
     DiskFullReadAccess,
 
+    /// Is allowed to read the specified folder. Only meaningful when
+    /// `DiskFullReadAccess` is not granted. `PathBuf` must be an absolute
+    /// path.
+    DiskReadFolder { folder: PathBuf },
+
     /// Is allowed to write to the operating system's temp dir that
     /// is restricted to the user the agent is running as. For
     /// example, on macOS, this is generally something under
@@ -279,11 +468,86 @@ pub enum SandboxPermission {
 
     /// Can make arbitrary network requests.
     NetworkFullAccess,
+
+    /// Can make HTTP(S) requests to the listed domains through a proxy that
+    /// Seeky runs for the sandbox. An entry of the form `*.example.com`
+    /// matches every subdomain of `example.com`.
+    NetworkAllowDomains { domains: Vec<String> },
+}
+
+/// An access that the sandbox blocked, as far as it can be told from the
+/// output of the failed command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SandboxDenial {
+    pub kind: SandboxDenialKind,
+    /// The file or folder the command tried to access, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxDenialKind {
+    Read,
+    Write,
+    /// A write to a protected path inside a writable root, which no
+    /// permission grants.
+    ProtectedWrite,
+    Network,
+}
+
+impl SandboxDenial {
+    /// The permission that grants just the denied access, in the syntax of
+    /// `--sandbox-permission` and `sandbox_permissions`.
+    pub fn suggested_permission(&self) -> Option<String> {
+        // Grant the folder rather than a single file, which may not exist yet.
+        let folder = |path: &Path| {
+            if path.is_dir() {
+                path.to_path_buf()
+            } else {
+                path.parent().unwrap_or(path).to_path_buf()
+            }
+        };
+        match (self.kind, &self.path) {
+            (SandboxDenialKind::Read, Some(path)) => {
+                Some(format!("disk-read-folder={}", folder(path).display()))
+            }
+            (SandboxDenialKind::Write, Some(path)) => {
+                Some(format!("disk-write-folder={}", folder(path).display()))
+            }
+            (SandboxDenialKind::Network, _) => Some("network-full-access".to_string()),
+            (SandboxDenialKind::ProtectedWrite, _) | (_, None) => None,
+        }
+    }
+}
+
+impl fmt::Display for SandboxDenial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.kind, &self.path) {
+            (SandboxDenialKind::Read, Some(path)) => {
+                write!(f, "the sandbox blocked reading `{}`", path.display())
+            }
+            (SandboxDenialKind::Read, None) => write!(f, "the sandbox blocked reading a file"),
+            (SandboxDenialKind::Write, Some(path)) => {
+                write!(f, "the sandbox blocked writing to `{}`", path.display())
+            }
+            (SandboxDenialKind::Write, None) => write!(f, "the sandbox blocked writing a file"),
+            (SandboxDenialKind::ProtectedWrite, Some(path)) => write!(
+                f,
+                "the sandbox blocked writing to the protected path `{}`",
+                path.display()
+            ),
+            (SandboxDenialKind::ProtectedWrite, None) => {
+                write!(f, "the sandbox blocked writing to a protected path")
+            }
+            (SandboxDenialKind::Network, _) => write!(f, "the sandbox blocked network access"),
+        }
+    }
 }
 
 /// User input
 #[non_exhaustive]
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum InputItem {
     Text {
@@ -302,7 +566,7 @@ pub enum InputItem {
 }
 
 /// Event Queue Entry - events from agent
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct Event {
     /// Submission `id` that this event is correlated with.
     pub id: String,
@@ -311,7 +575,7 @@ pub struct Event {
 }
 
 /// Response event from the agent
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum EventMsg {
     /// Error while executing a submission
@@ -356,31 +620,38 @@ pub enum EventMsg {
 
     /// Response to GetHistoryEntryRequest.
     GetHistoryEntryResponse(GetHistoryEntryResponseEvent),
+
+    /// Response to SearchHistoryRequest.
+    SearchHistoryResponse(SearchHistoryResponseEvent),
+
+    /// Notification that appending to the history compacted the history log,
+    /// which invalidates the `log_id` and offsets handed out so far.
+    HistoryCompacted(HistoryCompactedEvent),
 }
 
 // Individual event payload types matching each `EventMsg` variant.
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct ErrorEvent {
     pub message: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct TaskCompleteEvent {
     pub last_agent_message: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct AgentMessageEvent {
     pub message: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct AgentReasoningEvent {
     pub text: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct McpToolCallBeginEvent {
     /// Identifier so this can be paired with the McpToolCallEnd event.
     pub call_id: String,
@@ -392,17 +663,18 @@ pub struct McpToolCallBeginEvent {
     pub arguments: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct McpToolCallEndEvent {
     /// Identifier for the corresponding McpToolCallBegin that finished.
     pub call_id: String,
     /// Whether the tool call was successful. If `false`, `result` might not be present.
     pub success: bool,
     /// Result of the tool call. Note this could be an error.
+    #[schemars(with = "Option<serde_json::Value>")]
     pub result: Option<CallToolResult>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct ExecCommandBeginEvent {
     /// Identifier so this can be paired with the ExecCommandEnd event.
     pub call_id: String,
@@ -412,7 +684,7 @@ pub struct ExecCommandBeginEvent {
     pub cwd: PathBuf,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct ExecCommandEndEvent {
     /// Identifier for the ExecCommandBegin that finished.
     pub call_id: String,
@@ -424,7 +696,7 @@ pub struct ExecCommandEndEvent {
     pub exit_code: i32,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct ExecApprovalRequestEvent {
     /// The command to be executed.
     pub command: Vec<String>,
@@ -433,9 +705,19 @@ pub struct ExecApprovalRequestEvent {
     /// Optional human-readable reason for the approval (e.g. retry without sandbox).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<String>,
+    /// What the sandbox blocked when this is a request to retry a failed
+    /// command without the sandbox.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub denial: Option<SandboxDenial>,
+    /// Whether this is a request to retry a failed command without the
+    /// sandbox. Approval rules do not lift the sandbox, so
+    /// [`ReviewDecision::ApprovedAlways`] only approves such a command for the
+    /// session.
+    #[serde(default)]
+    pub retry_without_sandbox: bool,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct ApplyPatchApprovalRequestEvent {
     pub changes: HashMap<PathBuf, FileChange>,
     /// Optional explanatory reason (e.g. request for extra write access).
@@ -446,12 +728,12 @@ pub struct ApplyPatchApprovalRequestEvent {
     pub grant_root: Option<PathBuf>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct BackgroundEventEvent {
     pub message: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct PatchApplyBeginEvent {
     /// Identifier so this can be paired with the PatchApplyEnd event.
     pub call_id: String,
@@ -461,7 +743,7 @@ pub struct PatchApplyBeginEvent {
     pub changes: HashMap<PathBuf, FileChange>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct PatchApplyEndEvent {
     /// Identifier for the PatchApplyBegin that finished.
     pub call_id: String,
@@ -473,7 +755,7 @@ pub struct PatchApplyEndEvent {
     pub success: bool,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct GetHistoryEntryResponseEvent {
     pub offset: usize,
     pub log_id: u64,
@@ -482,7 +764,28 @@ pub struct GetHistoryEntryResponseEvent {
     pub entry: Option<HistoryEntry>,
 }
 
-#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SearchHistoryResponseEvent {
+    pub log_id: u64,
+    pub query: String,
+    /// Offset of the matching entry, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    /// The matching entry, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry: Option<HistoryEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct HistoryCompactedEvent {
+    /// Identifier of the compacted history log.
+    pub history_log_id: u64,
+
+    /// Number of entries in the compacted history log.
+    pub history_entry_count: usize,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct SessionConfiguredEvent {
     /// Unique id for this session.
     pub session_id: Uuid,
@@ -495,10 +798,15 @@ pub struct SessionConfiguredEvent {
 
     /// Current number of entries in the history log.
     pub history_entry_count: usize,
+
+    /// Paths of the `AGENTS.md` files that were loaded into the instructions
+    /// for this session, in the order they were included.
+    #[serde(default)]
+    pub project_docs: Vec<PathBuf>,
 }
 
 /// User's decision in response to an ExecApprovalRequest.
-#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ReviewDecision {
     /// User has approved this command and the agent should execute it.
@@ -509,6 +817,11 @@ pub enum ReviewDecision {
     /// remainder of the session.
     ApprovedForSession,
 
+    /// User has approved this command and wants to save an approval rule so
+    /// that commands starting with it are approved in this project from now
+    /// on, in this and future sessions.
+    ApprovedAlways,
+
     /// User has denied this command and the agent should not execute it, but
     /// it should continue the session and try something else.
     #[default]
@@ -519,7 +832,7 @@ pub enum ReviewDecision {
     Abort,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum FileChange {
     Add {
@@ -532,7 +845,7 @@ pub enum FileChange {
     },
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct Chunk {
     /// 1-based line index of the first line in the original file
     pub orig_index: u32,
@@ -557,12 +870,69 @@ mod tests {
                 model: "o4-mini".to_string(),
                 history_log_id: 0,
                 history_entry_count: 0,
+                project_docs: vec![PathBuf::from("/repo/AGENTS.md")],
             }),
         };
         let serialized = serde_json::to_string(&event).unwrap();
         assert_eq!(
             serialized,
-            r#"{"id":"1234","msg":{"type":"session_configured","session_id":"67e55044-10b1-426f-9247-bb680e5fe0c8","model":"o4-mini","history_log_id":0,"history_entry_count":0}}"#
+            r#"{"id":"1234","msg":{"type":"session_configured","session_id":"67e55044-10b1-426f-9247-bb680e5fe0c8","model":"o4-mini","history_log_id":0,"history_entry_count":0,"project_docs":["/repo/AGENTS.md"]}}"#
+        );
+    }
+
+    #[test]
+    fn protected_paths_resolve_inside_each_writable_root() {
+        let policy = SandboxPolicy::new_full_auto_policy().with_protected_paths(vec![
+            ".git".to_string(),
+            "!.git/objects".to_string(),
+            "/elsewhere/.env".to_string(),
+            "/work/b/.env".to_string(),
+        ]);
+        let roots = [PathBuf::from("/work/a"), PathBuf::from("/work/b")];
+        let protected_paths = policy.get_protected_paths(&roots);
+        assert_eq!(
+            vec![
+                ProtectedPath {
+                    path: PathBuf::from("/work/a/.git"),
+                    writable_subpaths: vec![PathBuf::from("/work/a/.git/objects")],
+                },
+                ProtectedPath {
+                    path: PathBuf::from("/work/b/.git"),
+                    writable_subpaths: vec![PathBuf::from("/work/b/.git/objects")],
+                },
+                ProtectedPath {
+                    path: PathBuf::from("/work/b/.env"),
+                    writable_subpaths: vec![],
+                },
+            ],
+            protected_paths
         );
+
+        let git = &protected_paths[0];
+        assert!(git.protects(Path::new("/work/a/.git/hooks/pre-commit")));
+        assert!(git.protects(Path::new("/work/a/.git")));
+        assert!(!git.protects(Path::new("/work/a/.git/objects/ab/cdef")));
+        assert!(!git.protects(Path::new("/work/a/.gitignore")));
+    }
+
+    #[test]
+    fn protected_path_ancestors_stop_at_the_outermost_root() {
+        let policy = SandboxPolicy::new_full_auto_policy();
+        let roots = [PathBuf::from("/work"), PathBuf::from("/work/nested")];
+        assert_eq!(
+            vec![
+                PathBuf::from("/work/.git"),
+                PathBuf::from("/work/nested/.git"),
+                PathBuf::from("/work/nested"),
+            ],
+            policy.get_protected_path_ancestors(&roots)
+        );
+    }
+
+    #[test]
+    fn protected_paths_default_when_missing() {
+        let policy: SandboxPolicy =
+            serde_json::from_str(r#"{"permissions":["disk-write-cwd"]}"#).unwrap();
+        assert_eq!(DEFAULT_PROTECTED_PATHS, policy.protected_paths());
     }
 }
@@ -0,0 +1,270 @@
+//! Enforcement of [`ResourceLimits`] and recognition of commands that ran
+//! into one of them.
+//!
+//! The limits are applied with `setrlimit` right before the command is
+//! exec'd. `seeky-linux-sandbox` may instead enforce the memory and process
+//! limits with a cgroup v2 leaf; the kernel reports either the same way
+//! (failed allocations and forks), so [`check_resource_limit_messages`] does
+//! not need to know which mechanism was used.
+//!
+//! The CPU time and file size limits are recognized by the signal that
+//! killed the command, or its child as reported by the shell. The other
+//! limits are only visible in the error messages the command printed, which
+//! is a guess, so [`check_resource_limit_messages`] is only used for failures
+//! that are not known to come from the sandbox.
+
+#[cfg(unix)]
+use std::io;
+
+use crate::config_types::ResourceLimits;
+use crate::error::SandboxErr;
+use crate::exec::ExecToolCallOutput;
+
+// Hardcode these since the libc crate is not available on every platform.
+const SIGXCPU_CODE: i32 = 24;
+const SIGXFSZ_CODE: i32 = 25;
+
+/// Messages printed by common runtimes when an allocation fails.
+const OUT_OF_MEMORY_MESSAGES: &[&str] = &[
+    "cannot allocate memory",
+    "out of memory",
+    "memoryerror",
+    "bad_alloc",
+    "memory allocation of",
+];
+
+/// `EAGAIN` from `fork`, as reported by shells and common runtimes.
+const FORK_FAILED_MESSAGES: &[&str] = &[
+    "fork: resource temporarily unavailable",
+    "fork: retry",
+    "cannot fork",
+    "can't fork",
+];
+
+/// `EMFILE`.
+const TOO_MANY_OPEN_FILES_MESSAGE: &str = "too many open files";
+
+/// `EFBIG`, reported by commands that ignore `SIGXFSZ`.
+const FILE_TOO_LARGE_MESSAGE: &str = "file too large";
+
+/// How shells describe a child killed by `SIGXCPU` and `SIGXFSZ`.
+const SIGXCPU_DESCRIPTION: &str = "cpu time limit exceeded";
+const SIGXFSZ_DESCRIPTION: &str = "file size limit exceeded";
+
+/// Applies `limits` to the current process with `setrlimit`, so that they are
+/// inherited by the command it is about to exec. Only calls async-signal-safe
+/// functions, so it may run between `fork` and `exec`.
+#[cfg(unix)]
+pub fn apply_to_current_process(limits: &ResourceLimits) -> io::Result<()> {
+    if let Some(seconds) = limits.cpu_seconds {
+        // The soft limit sends SIGXCPU, which is what we look for when
+        // reporting the limit; the hard limit SIGKILLs commands that ignore it.
+        set_limit(libc::RLIMIT_CPU, seconds, seconds.saturating_add(1))?;
+    }
+    // macOS does not support limiting the address space.
+    #[cfg(not(target_os = "macos"))]
+    if let Some(bytes) = limits.memory_bytes {
+        set_limit(libc::RLIMIT_AS, bytes, bytes)?;
+    }
+    if let Some(bytes) = limits.file_size_bytes {
+        set_limit(libc::RLIMIT_FSIZE, bytes, bytes)?;
+    }
+    if let Some(processes) = limits.max_processes {
+        set_limit(libc::RLIMIT_NPROC, processes, processes)?;
+    }
+    if let Some(files) = limits.open_files {
+        set_limit(libc::RLIMIT_NOFILE, files, files)?;
+    }
+    Ok(())
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type Resource = libc::__rlimit_resource_t;
+#[cfg(all(unix, not(all(target_os = "linux", target_env = "gnu"))))]
+type Resource = libc::c_int;
+
+/// Lowers the limits of `resource`. A limit above the current hard limit
+/// cannot be set without privileges and is clamped to it.
+#[cfg(unix)]
+fn set_limit(resource: Resource, soft: u64, hard: u64) -> io::Result<()> {
+    let mut current = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    if unsafe { libc::getrlimit(resource, &mut current) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let hard = hard.min(current.rlim_max);
+    let limit = libc::rlimit {
+        rlim_cur: soft.min(hard),
+        rlim_max: hard,
+    };
+    if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Turns the output of a command that was killed for exceeding its CPU time
+/// or file size limit into the matching [`SandboxErr`], and passes any other
+/// output through.
+///
+/// `signal` is the signal that terminated the command, if any. A shell whose
+/// child was killed exits with code 128 + the signal number, but the command
+/// may just have exited with that code, so it only counts together with the
+/// shell's description of the signal.
+pub(crate) fn check_resource_limits(
+    limits: &ResourceLimits,
+    signal: Option<i32>,
+    output: ExecToolCallOutput,
+) -> Result<ExecToolCallOutput, SandboxErr> {
+    let reported_by_shell = |signal: i32, description: &str| {
+        output.exit_code == 128 + signal && output.stderr.to_lowercase().contains(description)
+    };
+    let signal = signal.or_else(|| {
+        [
+            (SIGXCPU_CODE, SIGXCPU_DESCRIPTION),
+            (SIGXFSZ_CODE, SIGXFSZ_DESCRIPTION),
+        ]
+        .into_iter()
+        .find(|(signal, description)| reported_by_shell(*signal, description))
+        .map(|(signal, _)| signal)
+    });
+    match (signal, limits.cpu_seconds, limits.file_size_bytes) {
+        (Some(SIGXCPU_CODE), Some(limit), _) => {
+            Err(SandboxErr::CpuTimeLimitExceeded(limit, Box::new(output)))
+        }
+        (Some(SIGXFSZ_CODE), _, Some(limit)) => {
+            Err(SandboxErr::FileSizeLimitExceeded(limit, Box::new(output)))
+        }
+        _ => Ok(output),
+    }
+}
+
+/// Turns the output of a failed command whose error messages point at one of
+/// `limits` into the matching [`SandboxErr`], and passes any other output
+/// through.
+pub(crate) fn check_resource_limit_messages(
+    limits: &ResourceLimits,
+    output: ExecToolCallOutput,
+) -> Result<ExecToolCallOutput, SandboxErr> {
+    if output.exit_code == 0 {
+        return Ok(output);
+    }
+    let stderr = output.stderr.to_lowercase();
+    let stderr_mentions = |messages: &[&str]| messages.iter().any(|m| stderr.contains(m));
+
+    if let Some(limit) = limits.file_size_bytes {
+        if stderr_mentions(&[FILE_TOO_LARGE_MESSAGE]) {
+            return Err(SandboxErr::FileSizeLimitExceeded(limit, Box::new(output)));
+        }
+    }
+    if let Some(limit) = limits.memory_bytes {
+        if stderr_mentions(OUT_OF_MEMORY_MESSAGES) {
+            return Err(SandboxErr::MemoryLimitExceeded(limit, Box::new(output)));
+        }
+    }
+    if let Some(limit) = limits.max_processes {
+        if stderr_mentions(FORK_FAILED_MESSAGES) {
+            return Err(SandboxErr::ProcessLimitExceeded(limit, Box::new(output)));
+        }
+    }
+    if let Some(limit) = limits.open_files {
+        if stderr_mentions(&[TOO_MANY_OPEN_FILES_MESSAGE]) {
+            return Err(SandboxErr::OpenFilesLimitExceeded(limit, Box::new(output)));
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use std::time::Duration;
+
+    fn output(exit_code: i32, stderr: &str) -> ExecToolCallOutput {
+        ExecToolCallOutput {
+            exit_code,
+            stdout: String::new(),
+            stderr: stderr.to_string(),
+            duration: Duration::ZERO,
+        }
+    }
+
+    fn all_limits() -> ResourceLimits {
+        ResourceLimits {
+            cpu_seconds: Some(1),
+            memory_bytes: Some(2),
+            file_size_bytes: Some(3),
+            max_processes: Some(4),
+            open_files: Some(5),
+        }
+    }
+
+    #[test]
+    fn recognizes_limit_signals_only() {
+        let limits = all_limits();
+        assert!(matches!(
+            check_resource_limits(&limits, Some(SIGXCPU_CODE), output(-1, "")),
+            Err(SandboxErr::CpuTimeLimitExceeded(1, _))
+        ));
+        assert!(matches!(
+            check_resource_limits(&limits, Some(SIGXFSZ_CODE), output(-1, "")),
+            Err(SandboxErr::FileSizeLimitExceeded(3, _))
+        ));
+        assert!(matches!(
+            check_resource_limits(
+                &limits,
+                None,
+                output(
+                    128 + SIGXFSZ_CODE,
+                    "bash: line 1:  42 File size limit exceeded head -c 4096 /dev/zero > big"
+                )
+            ),
+            Err(SandboxErr::FileSizeLimitExceeded(3, _))
+        ));
+
+        // A command may exit with these codes on its own.
+        let passed = check_resource_limits(&limits, None, output(128 + SIGXCPU_CODE, "")).unwrap();
+        assert_eq!(128 + SIGXCPU_CODE, passed.exit_code);
+        check_resource_limits(&limits, None, output(128 + SIGXFSZ_CODE, "")).unwrap();
+        check_resource_limits(&limits, None, output(1, "File size limit exceeded")).unwrap();
+    }
+
+    #[test]
+    fn recognizes_limit_errors_in_stderr() {
+        let limits = all_limits();
+        assert!(matches!(
+            check_resource_limit_messages(
+                &limits,
+                output(1, "memory allocation of 1048576 bytes failed")
+            ),
+            Err(SandboxErr::MemoryLimitExceeded(2, _))
+        ));
+        assert!(matches!(
+            check_resource_limit_messages(
+                &limits,
+                output(254, "bash: fork: Resource temporarily unavailable")
+            ),
+            Err(SandboxErr::ProcessLimitExceeded(4, _))
+        ));
+        assert!(matches!(
+            check_resource_limit_messages(&limits, output(1, "cat: x: Too many open files")),
+            Err(SandboxErr::OpenFilesLimitExceeded(5, _))
+        ));
+    }
+
+    #[test]
+    fn ignores_failures_without_a_matching_limit() {
+        let limits = ResourceLimits::default();
+        check_resource_limits(&limits, Some(SIGXCPU_CODE), output(-1, "")).unwrap();
+        let passed =
+            check_resource_limit_messages(&limits, output(1, "Too many open files")).unwrap();
+        assert_eq!(1, passed.exit_code);
+
+        // Successful commands are never reported, whatever they print.
+        check_resource_limit_messages(&all_limits(), output(0, "out of memory")).unwrap();
+    }
+}
@@ -1,14 +1,30 @@
-//! Functionality to persist a Seeky conversation *rollout* – a linear list of
-//! [`ResponseItem`] objects exchanged during a session – to disk so that
-//! sessions can be replayed or inspected later (mirrors the behaviour of the
-//! upstream TypeScript implementation).
+//! Functionality to persist a Seeky conversation *rollout* – a linear log of
+//! everything that happened during a session – to disk so that sessions can
+//! be replayed or inspected later (mirrors the behaviour of the upstream
+//! TypeScript implementation).
+//!
+//! A rollout is a JSONL file. The first line is a [`SessionMeta`] header that
+//! records the format version and the full session configuration. Every
+//! subsequent line is a [`RolloutLine`]: a timestamped [`RolloutItem`] that is
+//! either a [`ResponseItem`] exchanged with the model, an [`Event`] emitted by
+//! the agent, or a [`Submission`] from the user (such as an approval).
+//!
+//! Use [`SessionLog`] to parse a rollout back into typed values. Files written
+//! before the format was versioned (a bare `{id, timestamp, instructions}`
+//! header followed by bare `ResponseItem`s) are still accepted and reported
+//! as version 0.
 
 use std::fs::File;
 use std::fs::{self};
 use std::io::Error as IoError;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
 
+use serde::Deserialize;
 use serde::Serialize;
 use time::OffsetDateTime;
+use time::UtcOffset;
 use time::format_description::FormatItem;
 use time::macros::format_description;
 use tokio::io::AsyncWriteExt;
@@ -17,20 +33,129 @@ use tokio::sync::mpsc::{self};
 use uuid::Uuid;
 
 use crate::config::Config;
+use crate::model_provider_info::ModelProviderInfo;
 use crate::models::ResponseItem;
+use crate::protocol::AskForApproval;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::Op;
+use crate::protocol::SandboxPolicy;
+use crate::protocol::Submission;
+use crate::redaction::Redactor;
 
 /// Folder inside `~/.seeky` that holds saved rollouts.
-const SESSIONS_SUBDIR: &str = "sessions";
+pub const SESSIONS_SUBDIR: &str = "sessions";
 
-#[derive(Serialize)]
-struct SessionMeta {
-    id: String,
-    timestamp: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    instructions: Option<String>,
+/// Version of the rollout format written by this build.
+pub const ROLLOUT_FORMAT_VERSION: u32 = 1;
+
+/// Upper bound on how long we wait for `git` when collecting [`GitInfo`].
+const GIT_COMMAND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// First line of a rollout file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionMeta {
+    /// Version of the rollout format; see [`ROLLOUT_FORMAT_VERSION`].
+    pub version: u32,
+    pub id: Uuid,
+    /// Start of the session, in UTC.
+    pub timestamp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    /// Configuration the session was started with. Missing for rollouts
+    /// written before the format was versioned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<SessionConfig>,
+    /// State of the Git repository containing `cwd`, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitInfo>,
+}
+
+/// Settings that determine how a session behaves, as sent in
+/// `Op::ConfigureSession`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionConfig {
+    pub model: String,
+    pub provider: ModelProviderInfo,
+    pub cwd: PathBuf,
+    pub approval_policy: AskForApproval,
+    pub sandbox_policy: SandboxPolicy,
+    pub disable_response_storage: bool,
+    #[serde(default)]
+    pub project_docs: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitInfo {
+    /// Output of `git rev-parse HEAD`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_hash: Option<String>,
+    /// Current branch, if `HEAD` is not detached.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+}
+
+/// A single entry after the [`SessionMeta`] header.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "payload", rename_all = "snake_case")]
+pub enum RolloutItem {
+    /// An item sent to or received from the model.
+    ResponseItem(ResponseItem),
+    /// An event emitted by the agent, see [`should_record_event`].
+    Event(Event),
+    /// A submission from the user that is not otherwise visible in the
+    /// conversation, such as an approval decision or an interrupt.
+    Submission(Submission),
 }
 
-/// Records all [`ResponseItem`]s for a session and flushes them to disk after
+/// A timestamped [`RolloutItem`], i.e., one line of a rollout file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolloutLine {
+    /// Time the item was recorded, in UTC. Empty for items read from
+    /// rollouts written before the format was versioned.
+    #[serde(default)]
+    pub timestamp: String,
+    #[serde(flatten)]
+    pub item: RolloutItem,
+}
+
+/// Returns whether `msg` is worth recording in the rollout. Streaming
+/// output that duplicates a recorded [`ResponseItem`] is skipped, as are
+/// events that only make sense for a live front-end.
+pub(crate) fn should_record_event(msg: &EventMsg) -> bool {
+    match msg {
+        EventMsg::Error(_)
+        | EventMsg::TaskStarted
+        | EventMsg::TaskComplete(_)
+        | EventMsg::McpToolCallBegin(_)
+        | EventMsg::McpToolCallEnd(_)
+        | EventMsg::ExecCommandBegin(_)
+        | EventMsg::ExecCommandEnd(_)
+        | EventMsg::ExecApprovalRequest(_)
+        | EventMsg::ApplyPatchApprovalRequest(_)
+        | EventMsg::BackgroundEvent(_)
+        | EventMsg::PatchApplyBegin(_)
+        | EventMsg::PatchApplyEnd(_) => true,
+        EventMsg::AgentMessage(_)
+        | EventMsg::AgentReasoning(_)
+        | EventMsg::SessionConfigured(_)
+        | EventMsg::GetHistoryEntryResponse(_)
+        | EventMsg::SearchHistoryResponse(_)
+        | EventMsg::HistoryCompacted(_) => false,
+    }
+}
+
+/// Returns whether `op` is worth recording in the rollout. User input is
+/// already recorded as a [`ResponseItem`] and `ConfigureSession` is captured
+/// by the [`SessionMeta`] header.
+pub(crate) fn should_record_op(op: &Op) -> bool {
+    matches!(
+        op,
+        Op::Interrupt | Op::ExecApproval { .. } | Op::PatchApproval { .. }
+    )
+}
+
+/// Records everything that happens in a session and flushes it to disk after
 /// every update.
 ///
 /// Rollouts are recorded as JSONL and can be inspected with tools such as:
@@ -42,6 +167,8 @@ struct SessionMeta {
 #[derive(Clone)]
 pub(crate) struct RolloutRecorder {
     tx: Sender<String>,
+    /// Applied to every string in an item before it is written.
+    redactor: Redactor,
 }
 
 impl RolloutRecorder {
@@ -52,6 +179,8 @@ impl RolloutRecorder {
         config: &Config,
         uuid: Uuid,
         instructions: Option<String>,
+        session_config: SessionConfig,
+        redactor: Redactor,
     ) -> std::io::Result<Self> {
         let LogFileInfo {
             file,
@@ -60,17 +189,16 @@ impl RolloutRecorder {
         } = create_log_file(config, uuid)?;
 
         // Build the static session metadata JSON first.
-        let timestamp_format: &[FormatItem] = format_description!(
-            "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z"
-        );
-        let timestamp = timestamp
-            .format(timestamp_format)
-            .map_err(|e| IoError::other(format!("failed to format timestamp: {e}")))?;
+        let timestamp = format_timestamp(timestamp)?;
+        let git = collect_git_info(&session_config.cwd).await;
 
         let meta = SessionMeta {
+            version: ROLLOUT_FORMAT_VERSION,
             timestamp,
-            id: session_id.to_string(),
+            id: session_id,
             instructions,
+            config: Some(session_config),
+            git,
         };
 
         // A reasonably-sized bounded channel. If the buffer fills up the send
@@ -101,9 +229,9 @@ impl RolloutRecorder {
             }
         });
 
-        let recorder = Self { tx };
+        let recorder = Self { tx, redactor };
         // Ensure SessionMeta is the first item in the file.
-        recorder.record_item(&meta).await?;
+        recorder.write_json(&meta).await?;
         Ok(recorder)
     }
 
@@ -123,16 +251,47 @@ impl RolloutRecorder {
                     continue;
                 }
             }
-            self.record_item(item).await?;
+            self.record(RolloutItem::ResponseItem(item.clone())).await?;
         }
         Ok(())
     }
 
-    async fn record_item(&self, item: &impl Serialize) -> std::io::Result<()> {
+    /// Append `event` to the rollout file if [`should_record_event`] says so.
+    pub(crate) async fn record_event(&self, event: &Event) -> std::io::Result<()> {
+        if !should_record_event(&event.msg) {
+            return Ok(());
+        }
+        self.record(RolloutItem::Event(event.clone())).await
+    }
+
+    /// Append `submission` to the rollout file if [`should_record_op`] says
+    /// so.
+    pub(crate) async fn record_submission(&self, submission: &Submission) -> std::io::Result<()> {
+        if !should_record_op(&submission.op) {
+            return Ok(());
+        }
+        self.record(RolloutItem::Submission(submission.clone()))
+            .await
+    }
+
+    async fn record(&self, item: RolloutItem) -> std::io::Result<()> {
+        let line = RolloutLine {
+            timestamp: format_timestamp(OffsetDateTime::now_utc())?,
+            item,
+        };
+        self.write_json(&line).await
+    }
+
+    async fn write_json(&self, item: &impl Serialize) -> std::io::Result<()> {
         // Serialize the item to JSON first so that the writer thread only has
-        // to perform the actual write.
-        let json = serde_json::to_string(item)
-            .map_err(|e| IoError::other(format!("failed to serialize response items: {e}")))?;
+        // to perform the actual write. Secrets are redacted from the string
+        // values rather than the serialized text so that JSON escapes are
+        // never split.
+        let mut value = serde_json::to_value(item)
+            .map_err(|e| IoError::other(format!("failed to serialize rollout item: {e}")))?;
+        self.redactor.redact_json(&mut value);
+        let json = serde_json::to_string(&value)
+            .map_err(|e| IoError::other(format!("failed to serialize rollout item: {e}")))?;
 
         self.tx
             .send(json)
@@ -141,6 +300,109 @@ impl RolloutRecorder {
     }
 }
 
+/// A rollout parsed back into typed values.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionLog {
+    pub meta: SessionMeta,
+    pub lines: Vec<RolloutLine>,
+}
+
+impl SessionLog {
+    /// Reads and parses the rollout at `path`.
+    pub fn read(path: &Path) -> std::io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        Self::parse(&contents).map_err(|e| {
+            IoError::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{}: {e}", path.display()),
+            )
+        })
+    }
+
+    /// Parses the contents of a rollout file. Errors name the offending
+    /// (1-based) line.
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut lines = contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+
+        let Some((_, header)) = lines.next() else {
+            return Err("rollout is empty".to_string());
+        };
+        let header: serde_json::Value =
+            serde_json::from_str(header).map_err(|e| format!("line 1: {e}"))?;
+        // Legacy rollouts have no `version` in their header.
+        let legacy = header.get("version").is_none();
+        let meta = if legacy {
+            let LegacySessionMeta {
+                id,
+                timestamp,
+                instructions,
+            } = serde_json::from_value(header).map_err(|e| format!("line 1: {e}"))?;
+            SessionMeta {
+                version: 0,
+                id,
+                timestamp,
+                instructions,
+                config: None,
+                git: None,
+            }
+        } else {
+            let meta: SessionMeta =
+                serde_json::from_value(header).map_err(|e| format!("line 1: {e}"))?;
+            if meta.version > ROLLOUT_FORMAT_VERSION {
+                return Err(format!(
+                    "unsupported rollout version {} (expected at most {ROLLOUT_FORMAT_VERSION})",
+                    meta.version
+                ));
+            }
+            meta
+        };
+
+        let lines = lines
+            .map(|(index, line)| {
+                let parsed = if legacy {
+                    serde_json::from_str::<ResponseItem>(line).map(|item| RolloutLine {
+                        timestamp: String::new(),
+                        item: RolloutItem::ResponseItem(item),
+                    })
+                } else {
+                    serde_json::from_str::<RolloutLine>(line)
+                };
+                parsed.map_err(|e| format!("line {}: {e}", index + 1))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { meta, lines })
+    }
+
+    /// The items exchanged with the model, in order.
+    pub fn response_items(&self) -> impl Iterator<Item = &ResponseItem> {
+        self.lines.iter().filter_map(|line| match &line.item {
+            RolloutItem::ResponseItem(item) => Some(item),
+            _ => None,
+        })
+    }
+
+    /// The recorded events, in order, with their timestamps.
+    pub fn events(&self) -> impl Iterator<Item = (&str, &Event)> {
+        self.lines.iter().filter_map(|line| match &line.item {
+            RolloutItem::Event(event) => Some((line.timestamp.as_str(), event)),
+            _ => None,
+        })
+    }
+}
+
+/// Header written by versions of Seeky that predate [`SessionMeta::version`].
+#[derive(Deserialize)]
+struct LegacySessionMeta {
+    id: Uuid,
+    timestamp: String,
+    #[serde(default)]
+    instructions: Option<String>,
+}
+
 struct LogFileInfo {
     /// Opened file handle to the rollout file.
     file: File,
@@ -183,3 +445,163 @@ fn create_log_file(config: &Config, session_id: Uuid) -> std::io::Result<LogFile
         timestamp,
     })
 }
+
+/// Formats `timestamp` as an RFC 3339 UTC timestamp with millisecond
+/// precision.
+pub(crate) fn format_timestamp(timestamp: OffsetDateTime) -> std::io::Result<String> {
+    let timestamp_format: &[FormatItem] =
+        format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z");
+    timestamp
+        .to_offset(UtcOffset::UTC)
+        .format(timestamp_format)
+        .map_err(|e| IoError::other(format!("failed to format timestamp: {e}")))
+}
+
+/// Returns the current commit and branch of the repository containing `cwd`,
+/// or `None` if `cwd` is not in a Git repository or `git` is unavailable.
+async fn collect_git_info(cwd: &Path) -> Option<GitInfo> {
+    let commit_hash = run_git(cwd, &["rev-parse", "HEAD"]).await;
+    let branch = run_git(cwd, &["rev-parse", "--abbrev-ref", "HEAD"])
+        .await
+        .filter(|branch| branch != "HEAD");
+    if commit_hash.is_none() && branch.is_none() {
+        return None;
+    }
+    Some(GitInfo {
+        commit_hash,
+        branch,
+    })
+}
+
+async fn run_git(cwd: &Path, args: &[&str]) -> Option<String> {
+    let output = tokio::time::timeout(
+        GIT_COMMAND_TIMEOUT,
+        tokio::process::Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .ok()?
+    .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    let stdout = stdout.trim();
+    (!stdout.is_empty()).then(|| stdout.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::models::ContentItem;
+    use crate::protocol::ExecCommandEndEvent;
+    use crate::protocol::ReviewDecision;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_versioned_rollout() {
+        let contents = r#"{"version":1,"id":"67e55044-10b1-426f-9247-bb680e5fe0c8","timestamp":"2025-05-07T17:24:21.123Z","config":{"model":"o3","provider":{"name":"OpenAI","base_url":"https://api.openai.com/v1","env_key":"OPENAI_API_KEY","env_key_instructions":null,"wire_api":"responses"},"cwd":"/repo","approval_policy":"on-failure","sandbox_policy":{"permissions":["disk-full-read-access"]},"disable_response_storage":false},"git":{"commit_hash":"abc123","branch":"main"}}
+{"timestamp":"2025-05-07T17:24:22.000Z","type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"hi"}]}}
+{"timestamp":"2025-05-07T17:24:23.000Z","type":"event","payload":{"id":"1","msg":{"type":"exec_command_end","call_id":"c1","stdout":"","stderr":"","exit_code":0}}}
+{"timestamp":"2025-05-07T17:24:24.000Z","type":"submission","payload":{"id":"2","op":{"type":"exec_approval","id":"1","decision":"approved"}}}
+"#;
+        let log = SessionLog::parse(contents).unwrap();
+        assert_eq!(1, log.meta.version);
+        assert_eq!(
+            Some(GitInfo {
+                commit_hash: Some("abc123".to_string()),
+                branch: Some("main".to_string()),
+            }),
+            log.meta.git
+        );
+        assert_eq!("o3", log.meta.config.as_ref().unwrap().model);
+        assert_eq!(3, log.lines.len());
+        assert_eq!(1, log.response_items().count());
+
+        let events: Vec<_> = log.events().collect();
+        assert_eq!(1, events.len());
+        assert_eq!("2025-05-07T17:24:23.000Z", events[0].0);
+        assert!(matches!(
+            events[0].1.msg,
+            EventMsg::ExecCommandEnd(ExecCommandEndEvent { exit_code: 0, .. })
+        ));
+        assert!(matches!(
+            &log.lines[2].item,
+            RolloutItem::Submission(Submission {
+                op: Op::ExecApproval {
+                    decision: ReviewDecision::Approved,
+                    ..
+                },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_legacy_rollout() {
+        let contents = r#"{"id":"67e55044-10b1-426f-9247-bb680e5fe0c8","timestamp":"2025-05-07T17:24:21.123Z","instructions":"be nice"}
+{"type":"message","role":"assistant","content":[{"type":"output_text","text":"hello"}]}
+"#;
+        let log = SessionLog::parse(contents).unwrap();
+        assert_eq!(0, log.meta.version);
+        assert_eq!(Some("be nice".to_string()), log.meta.instructions);
+        assert_eq!(None, log.meta.config);
+        let items: Vec<_> = log.response_items().collect();
+        assert!(matches!(
+            items.as_slice(),
+            [ResponseItem::Message { role, content }]
+                if role == "assistant"
+                    && matches!(content.as_slice(), [ContentItem::OutputText { text }] if text == "hello")
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_versions_and_reports_line_numbers() {
+        let contents =
+            r#"{"version":99,"id":"67e55044-10b1-426f-9247-bb680e5fe0c8","timestamp":""}"#;
+        assert!(
+            SessionLog::parse(contents)
+                .unwrap_err()
+                .contains("unsupported rollout version 99")
+        );
+
+        let contents = r#"{"version":1,"id":"67e55044-10b1-426f-9247-bb680e5fe0c8","timestamp":""}
+{"timestamp":"","type":"event","payload":{"id":"1","msg":{"type":"task_started"}}}
+not json
+"#;
+        assert!(
+            SessionLog::parse(contents)
+                .unwrap_err()
+                .starts_with("line 3:")
+        );
+    }
+
+    #[test]
+    fn line_round_trips() {
+        let line = RolloutLine {
+            timestamp: "2025-05-07T17:24:23.000Z".to_string(),
+            item: RolloutItem::Event(Event {
+                id: "1".to_string(),
+                msg: EventMsg::TaskStarted,
+            }),
+        };
+        let json = serde_json::to_string(&line).unwrap();
+        assert_eq!(
+            r#"{"timestamp":"2025-05-07T17:24:23.000Z","type":"event","payload":{"id":"1","msg":{"type":"task_started"}}}"#,
+            json
+        );
+        let parsed: RolloutLine = serde_json::from_str(&json).unwrap();
+        assert!(matches!(
+            parsed.item,
+            RolloutItem::Event(Event {
+                msg: EventMsg::TaskStarted,
+                ..
+            })
+        ));
+    }
+}
@@ -6,22 +6,48 @@ use std::path::PathBuf;
 use seeky_apply_patch::ApplyPatchAction;
 use seeky_apply_patch::ApplyPatchFileChange;
 
+use crate::approval_rules::RuleDecision;
+use crate::approval_rules::SavedRule;
+use crate::approval_rules::decide;
 use crate::exec::SandboxType;
+use crate::exec_policy::ExecPolicy;
+use crate::exec_policy::PolicyDecision;
 use crate::is_safe_command::is_known_safe_command;
 use crate::protocol::AskForApproval;
+use crate::protocol::ProtectedPath;
 use crate::protocol::SandboxPolicy;
 
+/// Whether a command or patch may run, and why. The reason ends up in the
+/// audit log.
 #[derive(Debug)]
 pub enum SafetyCheck {
-    AutoApprove { sandbox_type: SandboxType },
-    AskUser,
-    Reject { reason: String },
+    AutoApprove {
+        sandbox_type: SandboxType,
+        reason: String,
+    },
+    AskUser {
+        reason: String,
+    },
+    Reject {
+        reason: String,
+    },
+}
+
+impl SafetyCheck {
+    pub fn reason(&self) -> &str {
+        match self {
+            SafetyCheck::AutoApprove { reason, .. }
+            | SafetyCheck::AskUser { reason }
+            | SafetyCheck::Reject { reason } => reason,
+        }
+    }
 }
 
 pub fn assess_patch_safety(
     action: &ApplyPatchAction,
     policy: AskForApproval,
     writable_roots: &[PathBuf],
+    protected_paths: &[ProtectedPath],
     cwd: &Path,
 ) -> SafetyCheck {
     if action.is_empty() {
@@ -37,21 +63,42 @@ pub fn assess_patch_safety(
         // TODO(ragona): I'm not sure this is actually correct? I believe in this case
         // we want to continue to the writable paths check before asking the user.
         AskForApproval::UnlessAllowListed => {
-            return SafetyCheck::AskUser;
+            return SafetyCheck::AskUser {
+                reason: "approval policy asks for every patch".to_string(),
+            };
         }
     }
 
-    if is_write_patch_constrained_to_writable_paths(action, writable_roots, cwd) {
+    if is_write_patch_constrained_to_writable_paths(action, writable_roots, protected_paths, cwd) {
         SafetyCheck::AutoApprove {
             sandbox_type: SandboxType::None,
+            reason: "all paths are inside the writable roots".to_string(),
+        }
+    } else if touches_protected_path(action, protected_paths, cwd) {
+        // Patches are applied outside of the sandbox, so writes to protected
+        // paths always need the user's approval.
+        if policy == AskForApproval::Never {
+            SafetyCheck::Reject {
+                reason: "writing to a protected path; rejected by user approval settings"
+                    .to_string(),
+            }
+        } else {
+            SafetyCheck::AskUser {
+                reason: "writing to a protected path".to_string(),
+            }
         }
     } else if policy == AskForApproval::OnFailure {
         // Only auto‑approve when we can actually enforce a sandbox. Otherwise
         // fall back to asking the user because the patch may touch arbitrary
         // paths outside the project.
         match get_platform_sandbox() {
-            Some(sandbox_type) => SafetyCheck::AutoApprove { sandbox_type },
-            None => SafetyCheck::AskUser,
+            Some(sandbox_type) => SafetyCheck::AutoApprove {
+                sandbox_type,
+                reason: "writing outside of the project with a sandbox available".to_string(),
+            },
+            None => SafetyCheck::AskUser {
+                reason: "writing outside of the project without a sandbox".to_string(),
+            },
         }
     } else if policy == AskForApproval::Never {
         SafetyCheck::Reject {
@@ -59,35 +106,101 @@ pub fn assess_patch_safety(
                 .to_string(),
         }
     } else {
-        SafetyCheck::AskUser
+        SafetyCheck::AskUser {
+            reason: "writing outside of the project".to_string(),
+        }
     }
 }
 
-pub fn assess_command_safety(
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn assess_command_safety(
     command: &[String],
+    cwd: &Path,
     approval_policy: AskForApproval,
     sandbox_policy: &SandboxPolicy,
+    writable_roots: &[PathBuf],
+    exec_policy: &ExecPolicy,
+    approval_rules: &[SavedRule],
     approved: &HashSet<Vec<String>>,
 ) -> SafetyCheck {
-    let approve_without_sandbox = || SafetyCheck::AutoApprove {
+    let approve_without_sandbox = |reason: &str| SafetyCheck::AutoApprove {
         sandbox_type: SandboxType::None,
+        reason: reason.to_string(),
+    };
+
+    // Rules of the exec policy come first, so that forbidden commands are
+    // rejected even if they were approved before.
+    let readable_roots = if sandbox_policy.has_full_disk_read_access() {
+        None
+    } else {
+        Some(sandbox_policy.get_readable_roots_with_cwd(cwd))
     };
+    let protected_paths = sandbox_policy.get_protected_paths(writable_roots);
+    let policy_decision = exec_policy.check(
+        command,
+        cwd,
+        readable_roots.as_deref(),
+        writable_roots,
+        &protected_paths,
+    );
+    if let PolicyDecision::Forbidden { reason } = policy_decision {
+        return SafetyCheck::Reject {
+            reason: format!("forbidden by the exec policy: {reason}"),
+        };
+    }
+    // Then the user's deny rules, for commands the exec policy considers safe.
+    let rule_decision = decide(approval_rules, command, cwd);
+    if rule_decision == Some(RuleDecision::Deny) {
+        return SafetyCheck::Reject {
+            reason: "denied by an approval rule".to_string(),
+        };
+    }
+    match policy_decision {
+        PolicyDecision::ReadOnly => {
+            return approve_without_sandbox("read-only command allowed by the exec policy");
+        }
+        PolicyDecision::WritesInWritableRoots if approval_policy == AskForApproval::AutoEdit => {
+            return approve_without_sandbox("writes only inside the writable roots");
+        }
+        PolicyDecision::Forbidden { .. }
+        | PolicyDecision::WritesInWritableRoots
+        | PolicyDecision::Unverified => {}
+    }
 
     // Previously approved or allow-listed commands
     // All approval modes allow these commands to continue without sandboxing
-    if is_known_safe_command(command) || approved.contains(command) {
-        // TODO(ragona): I think we should consider running even these inside the sandbox, but it's
-        // a change in behavior so I'm keeping it at parity with upstream for now.
-        return approve_without_sandbox();
+    // TODO(ragona): I think we should consider running even these inside the sandbox, but it's
+    // a change in behavior so I'm keeping it at parity with upstream for now.
+    if is_known_safe_command(command) {
+        return approve_without_sandbox("known safe command");
+    }
+    if approved.contains(command) {
+        return approve_without_sandbox("approved for this session");
+    }
+    if rule_decision == Some(RuleDecision::Allow) {
+        // A saved rule only spares the user the question: the command still
+        // runs in the sandbox, if there is one.
+        let sandbox_type = if sandbox_policy.is_unrestricted() {
+            SandboxType::None
+        } else {
+            get_platform_sandbox().unwrap_or(SandboxType::None)
+        };
+        return SafetyCheck::AutoApprove {
+            sandbox_type,
+            reason: "allowed by an approval rule".to_string(),
+        };
     }
 
     // Command was not known-safe or allow-listed
     if sandbox_policy.is_unrestricted() {
-        approve_without_sandbox()
+        approve_without_sandbox("sandbox policy allows everything")
     } else {
         match get_platform_sandbox() {
             // We have a sandbox, so we can approve the command in all modes
-            Some(sandbox_type) => SafetyCheck::AutoApprove { sandbox_type },
+            Some(sandbox_type) => SafetyCheck::AutoApprove {
+                sandbox_type,
+                reason: "runs in the sandbox".to_string(),
+            },
             None => {
                 // We do not have a sandbox, so we need to consider the approval policy
                 match approval_policy {
@@ -96,7 +209,9 @@ pub fn assess_command_safety(
                         reason: "auto-rejected by user approval settings".to_string(),
                     },
                     // Otherwise, we ask the user for approval
-                    _ => SafetyCheck::AskUser,
+                    _ => SafetyCheck::AskUser {
+                        reason: "no sandbox is available on this platform".to_string(),
+                    },
                 }
             }
         }
@@ -113,9 +228,55 @@ pub fn get_platform_sandbox() -> Option<SandboxType> {
     }
 }
 
+/// Normalize a path by removing `.` and resolving `..` without touching the
+/// filesystem (works even if the file does not exist).
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut out = PathBuf::new();
+    for comp in path.components() {
+        match comp {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => { /* skip */ }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    Some(out)
+}
+
+/// Every path written by `action`, including the destinations of moves.
+fn written_paths(action: &ApplyPatchAction) -> Vec<&PathBuf> {
+    action
+        .changes()
+        .iter()
+        .flat_map(|(path, change)| match change {
+            ApplyPatchFileChange::Update {
+                move_path: Some(dest),
+                ..
+            } => vec![path, dest],
+            _ => vec![path],
+        })
+        .collect()
+}
+
+fn touches_protected_path(
+    action: &ApplyPatchAction,
+    protected_paths: &[ProtectedPath],
+    cwd: &Path,
+) -> bool {
+    written_paths(action).into_iter().any(|path| {
+        normalize(&cwd.join(path)).is_some_and(|abs| {
+            protected_paths
+                .iter()
+                .any(|protected| protected.protects(&abs))
+        })
+    })
+}
+
 fn is_write_patch_constrained_to_writable_paths(
     action: &ApplyPatchAction,
     writable_roots: &[PathBuf],
+    protected_paths: &[ProtectedPath],
     cwd: &Path,
 ) -> bool {
     // Early‑exit if there are no declared writable roots.
@@ -123,20 +284,8 @@ fn is_write_patch_constrained_to_writable_paths(
         return false;
     }
 
-    // Normalize a path by removing `.` and resolving `..` without touching the
-    // filesystem (works even if the file does not exist).
-    fn normalize(path: &Path) -> Option<PathBuf> {
-        let mut out = PathBuf::new();
-        for comp in path.components() {
-            match comp {
-                Component::ParentDir => {
-                    out.pop();
-                }
-                Component::CurDir => { /* skip */ }
-                other => out.push(other.as_os_str()),
-            }
-        }
-        Some(out)
+    if touches_protected_path(action, protected_paths, cwd) {
+        return false;
     }
 
     // Determine whether `path` is inside **any** writable root. Both `path`
@@ -164,27 +313,7 @@ fn is_write_patch_constrained_to_writable_paths(
         })
     };
 
-    for (path, change) in action.changes() {
-        match change {
-            ApplyPatchFileChange::Add { .. } | ApplyPatchFileChange::Delete => {
-                if !is_path_writable(path) {
-                    return false;
-                }
-            }
-            ApplyPatchFileChange::Update { move_path, .. } => {
-                if !is_path_writable(path) {
-                    return false;
-                }
-                if let Some(dest) = move_path {
-                    if !is_path_writable(dest) {
-                        return false;
-                    }
-                }
-            }
-        }
-    }
-
-    true
+    written_paths(action).into_iter().all(is_path_writable)
 }
 
 #[cfg(test)]
@@ -207,6 +336,7 @@ mod tests {
         assert!(is_write_patch_constrained_to_writable_paths(
             &add_inside,
             &[PathBuf::from(".")],
+            &[],
             &cwd,
         ));
 
@@ -214,6 +344,7 @@ mod tests {
         assert!(!is_write_patch_constrained_to_writable_paths(
             &add_outside_2,
             &[PathBuf::from(".")],
+            &[],
             &cwd,
         ));
 
@@ -221,7 +352,143 @@ mod tests {
         assert!(is_write_patch_constrained_to_writable_paths(
             &add_outside,
             &[PathBuf::from("..")],
+            &[],
             &cwd,
         ))
     }
+
+    #[test]
+    fn patches_to_protected_paths_need_approval() {
+        let cwd = std::env::current_dir().unwrap();
+        let writable_roots = [cwd.clone()];
+        let protected_paths =
+            SandboxPolicy::new_full_auto_policy().get_protected_paths(&writable_roots);
+
+        let hook =
+            ApplyPatchAction::new_add_for_test(&cwd.join(".git/hooks/pre-commit"), "".to_string());
+        assert!(!is_write_patch_constrained_to_writable_paths(
+            &hook,
+            &writable_roots,
+            &protected_paths,
+            &cwd,
+        ));
+        for (policy, expect_reject) in [
+            (AskForApproval::OnFailure, false),
+            (AskForApproval::AutoEdit, false),
+            (AskForApproval::Never, true),
+        ] {
+            let check = assess_patch_safety(&hook, policy, &writable_roots, &protected_paths, &cwd);
+            match check {
+                SafetyCheck::AskUser { .. } => assert!(!expect_reject, "{policy:?}"),
+                SafetyCheck::Reject { .. } => assert!(expect_reject, "{policy:?}"),
+                SafetyCheck::AutoApprove { .. } => panic!("{policy:?} auto-approved a hook"),
+            }
+        }
+
+        let object =
+            ApplyPatchAction::new_add_for_test(&cwd.join(".git/objects/ab/cdef"), "".to_string());
+        assert!(is_write_patch_constrained_to_writable_paths(
+            &object,
+            &writable_roots,
+            &protected_paths,
+            &cwd,
+        ));
+    }
+
+    #[test]
+    fn approval_rules_deny_safe_commands_and_allow_others_in_the_sandbox() {
+        use crate::approval_rules::ApprovalRule;
+        use crate::approval_rules::RulePattern;
+        use crate::approval_rules::RuleSource;
+
+        let cwd = std::env::current_dir().unwrap();
+        let (exec_policy, _) = ExecPolicy::load(&[], &[]);
+        let rule = |prefix: &[&str], decision| SavedRule {
+            source: RuleSource::User,
+            rule: ApprovalRule {
+                pattern: RulePattern::Prefix(prefix.iter().map(|arg| arg.to_string()).collect()),
+                cwd: None,
+                decision,
+            },
+        };
+        let rules = [
+            rule(&["cat"], RuleDecision::Deny),
+            rule(&["make", "test"], RuleDecision::Allow),
+        ];
+        let check = |command: &[&str]| {
+            let command = command
+                .iter()
+                .map(|arg| arg.to_string())
+                .collect::<Vec<_>>();
+            assess_command_safety(
+                &command,
+                &cwd,
+                AskForApproval::UnlessAllowListed,
+                &SandboxPolicy::new_read_only_policy(),
+                &[],
+                &exec_policy,
+                &rules,
+                &HashSet::new(),
+            )
+        };
+
+        assert!(matches!(
+            check(&["cat", "Cargo.toml"]),
+            SafetyCheck::Reject { .. }
+        ));
+        assert!(matches!(
+            check(&["make", "test"]),
+            SafetyCheck::AutoApprove { sandbox_type, .. }
+                if Some(sandbox_type) == get_platform_sandbox()
+        ));
+        assert!(matches!(
+            check(&["bash", "-lc", "ls && cat Cargo.toml"]),
+            SafetyCheck::Reject { .. }
+        ));
+    }
+
+    #[test]
+    fn reads_in_readable_folders_are_allowed_by_the_exec_policy() {
+        use crate::protocol::SandboxPermission;
+
+        let project = tempfile::tempdir().unwrap();
+        let docs = tempfile::tempdir().unwrap();
+        let elsewhere = tempfile::tempdir().unwrap();
+        let cwd = project.path().canonicalize().unwrap();
+        let docs = docs.path().canonicalize().unwrap();
+        let elsewhere = elsewhere.path().canonicalize().unwrap();
+        std::fs::write(docs.join("notes.txt"), "notes\n").unwrap();
+        std::fs::write(elsewhere.join("notes.txt"), "notes\n").unwrap();
+        let (exec_policy, _) = ExecPolicy::load(&[], &[]);
+        let sandbox_policy = SandboxPolicy::from(vec![
+            SandboxPermission::DiskWriteCwd,
+            SandboxPermission::DiskReadFolder {
+                folder: docs.clone(),
+            },
+        ]);
+        let check = |folder: &Path| {
+            let command = vec![
+                "cat".to_string(),
+                folder.join("notes.txt").to_string_lossy().to_string(),
+            ];
+            assess_command_safety(
+                &command,
+                &cwd,
+                AskForApproval::UnlessAllowListed,
+                &sandbox_policy,
+                &[cwd.clone()],
+                &exec_policy,
+                &[],
+                &HashSet::new(),
+            )
+        };
+
+        // `cat` is a known safe command either way, but only the read in a
+        // readable folder is vouched for by the exec policy.
+        assert_eq!(
+            "read-only command allowed by the exec policy",
+            check(&docs).reason()
+        );
+        assert_eq!("known safe command", check(&elsewhere).reason());
+    }
 }
@@ -0,0 +1,324 @@
+//! Recognition of the accesses the sandbox blocked in the output of a failed
+//! command.
+//!
+//! Landlock and the read-only mounts of the namespace backend fail file
+//! operations with `EACCES` and `EROFS`, Seatbelt and the seccomp filters with
+//! `EPERM`. Commands report these as the usual "Permission denied",
+//! "Read-only file system" and "Operation not permitted" messages, usually
+//! together with the path they tried to access. A path only counts as denied
+//! by the sandbox if it lies outside the roots the policy grants; otherwise
+//! the failure is an ordinary permission problem.
+
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::protocol::SandboxDenial;
+use crate::protocol::SandboxDenialKind;
+use crate::protocol::SandboxPolicy;
+
+/// `EACCES`, `EPERM` and `EROFS`.
+const DENIED_MESSAGES: &[&str] = &[
+    "Permission denied",
+    "Operation not permitted",
+    "Read-only file system",
+];
+
+const READ_ONLY_FILE_SYSTEM_MESSAGE: &str = "Read-only file system";
+
+/// Phrases of common tools that show that a denied operation was a write.
+const WRITE_HINTS: &[&str] = &[
+    "cannot create",
+    "cannot touch",
+    "cannot remove",
+    "cannot move",
+    "cannot make",
+    "cannot overwrite",
+    "cannot open file for writing",
+    "failed to create",
+    "could not create",
+    "unable to create",
+    "unable to write",
+    "couldn't create",
+];
+
+/// `EPERM` from creating or connecting a socket, which only the sandbox
+/// causes.
+const NETWORK_DENIED_MESSAGES: &[&str] = &[
+    "socket: operation not permitted",
+    "connect: operation not permitted",
+];
+
+/// Network errors of common tools and runtimes, as seen when the network
+/// namespace has no route. A typo in a host name or a host that is down look
+/// the same, so these only count if the sandbox blocks all network access.
+const NETWORK_FAILURE_MESSAGES: &[&str] = &[
+    "network is unreachable",
+    "could not resolve host",
+    "temporary failure in name resolution",
+    "name or service not known",
+    "nodename nor servname provided",
+    "getaddrinfo",
+    "failed to connect",
+    "couldn't connect",
+    "failed to establish a new connection",
+];
+
+/// Quote pairs used around paths in error messages.
+const QUOTES: &[(char, char)] = &[('\'', '\''), ('‘', '’'), ('"', '"'), ('`', '\'')];
+
+/// Returns the first access in `stderr` that `sandbox_policy` explains. Only
+/// meaningful for commands that ran in a sandbox.
+pub(crate) fn diagnose_denial(
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+    stderr: &str,
+) -> Option<SandboxDenial> {
+    stderr
+        .lines()
+        .find_map(|line| diagnose_line(sandbox_policy, cwd, line))
+}
+
+fn diagnose_line(sandbox_policy: &SandboxPolicy, cwd: &Path, line: &str) -> Option<SandboxDenial> {
+    let lower = line.to_lowercase();
+    let contains_any = |messages: &[&str]| messages.iter().any(|message| lower.contains(message));
+    let network_blocked = sandbox_policy.get_allowed_network_domains().is_empty();
+    if !sandbox_policy.has_full_network_access()
+        && (contains_any(NETWORK_DENIED_MESSAGES)
+            || (network_blocked && contains_any(NETWORK_FAILURE_MESSAGES)))
+    {
+        return Some(SandboxDenial {
+            kind: SandboxDenialKind::Network,
+            path: None,
+        });
+    }
+
+    let message = DENIED_MESSAGES
+        .iter()
+        .find(|message| line.contains(*message))?;
+    let path = absolutize(cwd, &extract_path(line, message)?);
+
+    let protected = sandbox_policy
+        .get_protected_paths_with_cwd(cwd)
+        .iter()
+        .any(|protected| protected.protects(&path));
+    let writable = sandbox_policy.has_full_disk_write_access()
+        || sandbox_policy
+            .get_writable_roots_with_cwd(cwd)
+            .iter()
+            .any(|root| path.starts_with(root));
+    let readable = writable
+        || sandbox_policy.has_full_disk_read_access()
+        || sandbox_policy
+            .get_readable_roots_with_cwd(cwd)
+            .iter()
+            .any(|root| path.starts_with(root));
+    let is_write = *message == READ_ONLY_FILE_SYSTEM_MESSAGE
+        || WRITE_HINTS.iter().any(|hint| lower.contains(hint));
+
+    let kind = if protected {
+        SandboxDenialKind::ProtectedWrite
+    } else if !writable && (is_write || readable) {
+        SandboxDenialKind::Write
+    } else if !readable {
+        SandboxDenialKind::Read
+    } else {
+        return None;
+    };
+    Some(SandboxDenial {
+        kind,
+        path: Some(path),
+    })
+}
+
+/// Extracts the path from messages such as `touch: cannot touch '/a/b':
+/// Permission denied`, `bash: line 1: /a/b: Read-only file system` and
+/// `PermissionError: [Errno 13] Permission denied: '/a/b'`.
+fn extract_path(line: &str, message: &str) -> Option<String> {
+    for (open, close) in QUOTES {
+        if let Some(start) = line.find(*open) {
+            let rest = &line[start + open.len_utf8()..];
+            if let Some(end) = rest.find(*close) {
+                let quoted = &rest[..end];
+                if !quoted.trim().is_empty() {
+                    return Some(quoted.to_string());
+                }
+            }
+        }
+    }
+
+    // Otherwise the path is the `: `-separated field right before the
+    // message. The first field is the name of the program.
+    let before = line.split(message).next()?.trim_end().strip_suffix(':')?;
+    let (_, path) = before.rsplit_once(": ")?;
+    let path = path.trim();
+    if path.is_empty() || path.starts_with("line ") {
+        return None;
+    }
+    Some(path.to_string())
+}
+
+/// Resolves `path` against `cwd` and removes `.` and `..` components without
+/// touching the file system, as the path may not exist.
+fn absolutize(cwd: &Path, path: &str) -> PathBuf {
+    let mut absolute = PathBuf::new();
+    for component in cwd.join(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                absolute.pop();
+            }
+            component => absolute.push(component),
+        }
+    }
+    absolute
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::SandboxPermission;
+    use pretty_assertions::assert_eq;
+
+    fn full_auto_policy() -> SandboxPolicy {
+        SandboxPolicy::from(vec![
+            SandboxPermission::DiskFullReadAccess,
+            SandboxPermission::DiskWriteCwd,
+        ])
+    }
+
+    fn denial(kind: SandboxDenialKind, path: &str) -> Option<SandboxDenial> {
+        Some(SandboxDenial {
+            kind,
+            path: Some(PathBuf::from(path)),
+        })
+    }
+
+    #[test]
+    fn writes_outside_the_writable_roots_are_denials() {
+        let policy = full_auto_policy();
+        let cwd = Path::new("/work/project");
+        let cases = [
+            "touch: cannot touch '/etc/x': Permission denied",
+            "mkdir: cannot create directory ‘/etc/x’: Read-only file system",
+            "bash: line 1: /etc/x: Read-only file system",
+            "bash: /etc/x: Permission denied",
+            "PermissionError: [Errno 13] Permission denied: '/etc/x'",
+            "cp: cannot create regular file '../../etc/x': Operation not permitted",
+        ];
+        for stderr in cases {
+            assert_eq!(
+                denial(SandboxDenialKind::Write, "/etc/x"),
+                diagnose_denial(&policy, cwd, stderr),
+                "{stderr}"
+            );
+        }
+    }
+
+    #[test]
+    fn reads_outside_the_readable_roots_are_denials() {
+        let policy = SandboxPolicy::from(vec![SandboxPermission::DiskWriteCwd]);
+        let cwd = Path::new("/work/project");
+        assert_eq!(
+            denial(SandboxDenialKind::Read, "/work/other/secret.txt"),
+            diagnose_denial(&policy, cwd, "cat: ../other/secret.txt: Permission denied")
+        );
+        assert_eq!(
+            None,
+            diagnose_denial(&policy, cwd, "cat: notes.txt: Permission denied")
+        );
+    }
+
+    #[test]
+    fn failures_inside_the_granted_roots_are_not_denials() {
+        let policy = full_auto_policy();
+        let cwd = Path::new("/work/project");
+        assert_eq!(
+            None,
+            diagnose_denial(&policy, cwd, "rm: cannot remove 'x': Permission denied")
+        );
+        assert_eq!(
+            None,
+            diagnose_denial(&policy, cwd, "error: could not compile `foo`")
+        );
+    }
+
+    #[test]
+    fn writes_to_protected_paths_are_denials() {
+        let cwd = Path::new("/work/project");
+        assert_eq!(
+            denial(
+                SandboxDenialKind::ProtectedWrite,
+                "/work/project/.git/hooks/pre-commit"
+            ),
+            diagnose_denial(
+                &full_auto_policy(),
+                cwd,
+                "bash: .git/hooks/pre-commit: Read-only file system"
+            )
+        );
+        assert_eq!(
+            None,
+            diagnose_denial(
+                &full_auto_policy(),
+                cwd,
+                "touch: cannot touch '.git/objects/ab/cdef': Permission denied"
+            )
+        );
+    }
+
+    #[test]
+    fn network_errors_are_denials_without_network_access() {
+        let cwd = Path::new("/work/project");
+        let stderr = "curl: (6) Could not resolve host: example.com";
+        assert_eq!(
+            Some(SandboxDenial {
+                kind: SandboxDenialKind::Network,
+                path: None,
+            }),
+            diagnose_denial(&full_auto_policy(), cwd, stderr)
+        );
+
+        let policy = SandboxPolicy::from(vec![
+            SandboxPermission::DiskFullReadAccess,
+            SandboxPermission::NetworkFullAccess,
+        ]);
+        assert_eq!(None, diagnose_denial(&policy, cwd, stderr));
+    }
+
+    #[test]
+    fn only_socket_errors_are_denials_with_allowed_domains() {
+        let cwd = Path::new("/work/project");
+        let policy = SandboxPolicy::from(vec![
+            SandboxPermission::DiskFullReadAccess,
+            SandboxPermission::NetworkAllowDomains {
+                domains: vec!["example.com".to_string()],
+            },
+        ]);
+        for stderr in [
+            "curl: (6) Could not resolve host: exmaple.com",
+            "curl: (7) Failed to connect to example.com port 443",
+        ] {
+            assert_eq!(None, diagnose_denial(&policy, cwd, stderr), "{stderr}");
+        }
+        assert_eq!(
+            Some(SandboxDenial {
+                kind: SandboxDenialKind::Network,
+                path: None,
+            }),
+            diagnose_denial(&policy, cwd, "ping: socket: Operation not permitted")
+        );
+    }
+
+    #[test]
+    fn suggested_permission_grants_the_folder() {
+        let denial = SandboxDenial {
+            kind: SandboxDenialKind::Write,
+            path: Some(PathBuf::from("/nonexistent/dir/file.txt")),
+        };
+        assert_eq!(
+            Some("disk-write-folder=/nonexistent/dir".to_string()),
+            denial.suggested_permission()
+        );
+    }
+}
@@ -0,0 +1,384 @@
+//! JSON Schema and TypeScript definitions for `config.toml` and the
+//! submission/event protocol, so that editors can validate config files and
+//! third-party front-ends do not have to hand-copy the protocol types.
+//!
+//! The generated files are checked in under `seeky-rs/schema/` and can be
+//! regenerated with `seeky schema config|protocol --out schema`.
+
+use schemars::r#gen::SchemaGenerator;
+use schemars::r#gen::SchemaSettings;
+use schemars::schema::InstanceType;
+use schemars::schema::Metadata;
+use schemars::schema::RootSchema;
+use schemars::schema::Schema;
+use schemars::schema::SchemaObject;
+use schemars::schema::SingleOrVec;
+use schemars::schema::SubschemaValidation;
+
+use crate::config::ConfigToml;
+use crate::protocol::Event;
+use crate::protocol::Submission;
+
+/// The set of types a schema can be generated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaKind {
+    /// The contents of `config.toml`.
+    Config,
+    /// [`Submission`]s sent to and [`Event`]s received from a Seeky session.
+    Protocol,
+}
+
+impl SchemaKind {
+    /// Base name of the generated files, e.g. `config` for
+    /// `config.schema.json` and `config.ts`.
+    pub fn file_stem(self) -> &'static str {
+        match self {
+            SchemaKind::Config => "config",
+            SchemaKind::Protocol => "protocol",
+        }
+    }
+}
+
+/// Returns the JSON Schema for `kind`.
+pub fn json_schema(kind: SchemaKind) -> RootSchema {
+    let generator = SchemaSettings::draft07().into_generator();
+    match kind {
+        SchemaKind::Config => generator.into_root_schema_for::<ConfigToml>(),
+        SchemaKind::Protocol => protocol_root_schema(generator),
+    }
+}
+
+/// Returns the `(file name, contents)` pairs that make up the generated
+/// schema for `kind`: a JSON Schema and the equivalent TypeScript
+/// definitions.
+pub fn schema_files(kind: SchemaKind) -> Result<Vec<(String, String)>, serde_json::Error> {
+    let schema = json_schema(kind);
+    let stem = kind.file_stem();
+    let mut json = serde_json::to_string_pretty(&schema)?;
+    json.push('\n');
+    Ok(vec![
+        (format!("{stem}.schema.json"), json),
+        (format!("{stem}.ts"), typescript_definitions(&schema)),
+    ])
+}
+
+/// The protocol has two entry points, so the root schema is a union of
+/// [`Submission`] and [`Event`] with both types available as definitions.
+fn protocol_root_schema(mut generator: SchemaGenerator) -> RootSchema {
+    let submission = generator.subschema_for::<Submission>();
+    let event = generator.subschema_for::<Event>();
+    RootSchema {
+        meta_schema: generator.settings().meta_schema.clone(),
+        schema: SchemaObject {
+            metadata: Some(Box::new(Metadata {
+                title: Some("SeekyProtocol".to_string()),
+                description: Some(
+                    "A message on the Seeky protocol: a `Submission` sent by the client or an \
+                     `Event` sent by the agent."
+                        .to_string(),
+                ),
+                ..Default::default()
+            })),
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![submission, event]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        },
+        definitions: generator.take_definitions(),
+    }
+}
+
+/// Renders `schema` as TypeScript type declarations: one exported type per
+/// definition plus one for the root schema, named after its title.
+pub fn typescript_definitions(schema: &RootSchema) -> String {
+    let mut out = String::from("// GENERATED CODE! DO NOT MODIFY BY HAND!\n");
+
+    let root_name = schema
+        .schema
+        .metadata
+        .as_ref()
+        .and_then(|m| m.title.clone())
+        .unwrap_or_else(|| "Root".to_string());
+    write_declaration(&mut out, &root_name, &schema.schema);
+
+    for (name, definition) in &schema.definitions {
+        match definition {
+            Schema::Object(object) => write_declaration(&mut out, name, object),
+            Schema::Bool(_) => {
+                out.push('\n');
+                out.push_str(&format!(
+                    "export type {name} = {};\n",
+                    ts_type(definition, 0)
+                ));
+            }
+        }
+    }
+    out
+}
+
+fn write_declaration(out: &mut String, name: &str, object: &SchemaObject) {
+    out.push('\n');
+    write_doc_comment(out, object, 0);
+    out.push_str(&format!(
+        "export type {name} = {};\n",
+        ts_object_type(object, 0)
+    ));
+}
+
+fn write_doc_comment(out: &mut String, object: &SchemaObject, indent: usize) {
+    let Some(description) = object
+        .metadata
+        .as_ref()
+        .and_then(|m| m.description.as_deref())
+    else {
+        return;
+    };
+    let pad = "  ".repeat(indent);
+    out.push_str(&format!("{pad}/**\n"));
+    for line in description.lines() {
+        if line.is_empty() {
+            out.push_str(&format!("{pad} *\n"));
+        } else {
+            out.push_str(&format!("{pad} * {line}\n"));
+        }
+    }
+    out.push_str(&format!("{pad} */\n"));
+}
+
+fn ts_type(schema: &Schema, indent: usize) -> String {
+    match schema {
+        Schema::Bool(true) => "unknown".to_string(),
+        Schema::Bool(false) => "never".to_string(),
+        Schema::Object(object) => ts_object_type(object, indent),
+    }
+}
+
+/// Converts a single schema object. The assertions present on the object
+/// (`$ref`, `enum`, `type`, `allOf`, `anyOf`/`oneOf`) all have to hold, so
+/// each is rendered separately and the results are intersected.
+fn ts_object_type(object: &SchemaObject, indent: usize) -> String {
+    let mut parts = Vec::new();
+
+    if let Some(reference) = &object.reference {
+        let name = reference.rsplit('/').next().unwrap_or(reference);
+        parts.push(name.to_string());
+    }
+
+    if let Some(value) = &object.const_value {
+        parts.push(value.to_string());
+    } else if let Some(values) = &object.enum_values {
+        parts.push(union(values.iter().map(ToString::to_string).collect()));
+    } else if let Some(instance_type) = &object.instance_type {
+        let types = match instance_type {
+            SingleOrVec::Single(t) => vec![**t],
+            SingleOrVec::Vec(types) => types.clone(),
+        };
+        parts.push(union(
+            types
+                .into_iter()
+                .map(|t| ts_instance_type(t, object, indent))
+                .collect(),
+        ));
+    }
+
+    if let Some(subschemas) = &object.subschemas {
+        if let Some(all_of) = &subschemas.all_of {
+            parts.extend(all_of.iter().map(|s| ts_type(s, indent)));
+        }
+        for alternatives in [&subschemas.any_of, &subschemas.one_of]
+            .into_iter()
+            .flatten()
+        {
+            parts.push(union(
+                alternatives.iter().map(|s| ts_type(s, indent)).collect(),
+            ));
+        }
+    }
+
+    match parts.len() {
+        0 => "unknown".to_string(),
+        1 => parts.remove(0),
+        _ => parts
+            .into_iter()
+            .map(|part| parenthesize_union(&part))
+            .collect::<Vec<_>>()
+            .join(" & "),
+    }
+}
+
+fn ts_instance_type(instance_type: InstanceType, object: &SchemaObject, indent: usize) -> String {
+    match instance_type {
+        InstanceType::Null => "null".to_string(),
+        InstanceType::Boolean => "boolean".to_string(),
+        InstanceType::Integer | InstanceType::Number => "number".to_string(),
+        InstanceType::String => "string".to_string(),
+        InstanceType::Array => {
+            let items = object.array.as_ref().and_then(|a| a.items.as_ref());
+            match items {
+                Some(SingleOrVec::Single(item)) => {
+                    format!("Array<{}>", ts_type(item, indent))
+                }
+                Some(SingleOrVec::Vec(items)) => format!(
+                    "[{}]",
+                    items
+                        .iter()
+                        .map(|item| ts_type(item, indent))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                None => "Array<unknown>".to_string(),
+            }
+        }
+        InstanceType::Object => ts_object_literal(object, indent),
+    }
+}
+
+fn ts_object_literal(object: &SchemaObject, indent: usize) -> String {
+    let Some(validation) = object.object.as_ref() else {
+        return "{ [key: string]: unknown }".to_string();
+    };
+
+    let pad = "  ".repeat(indent + 1);
+    let mut out = String::from("{\n");
+    for (name, property) in &validation.properties {
+        if let Schema::Object(property_object) = property {
+            write_doc_comment(&mut out, property_object, indent + 1);
+        }
+        let optional = if validation.required.contains(name) {
+            ""
+        } else {
+            "?"
+        };
+        out.push_str(&format!(
+            "{pad}{}{optional}: {};\n",
+            property_name(name),
+            ts_type(property, indent + 1)
+        ));
+    }
+    if let Some(additional) = &validation.additional_properties {
+        if !matches!(additional.as_ref(), Schema::Bool(false)) {
+            out.push_str(&format!(
+                "{pad}[key: string]: {};\n",
+                ts_type(additional, indent + 1)
+            ));
+        }
+    }
+    out.push_str(&"  ".repeat(indent));
+    out.push('}');
+
+    if out == "{\n}" {
+        "Record<string, never>".to_string()
+    } else {
+        out
+    }
+}
+
+/// Quotes property names that are not valid identifiers.
+fn property_name(name: &str) -> String {
+    let is_identifier = name.chars().enumerate().all(|(i, c)| {
+        c == '_' || c == '$' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit())
+    });
+    if is_identifier && !name.is_empty() {
+        name.to_string()
+    } else {
+        format!("{name:?}")
+    }
+}
+
+fn union(mut members: Vec<String>) -> String {
+    members.dedup();
+    match members.len() {
+        0 => "never".to_string(),
+        1 => members.remove(0),
+        _ => members
+            .iter()
+            .map(|member| parenthesize_intersection(member))
+            .collect::<Vec<_>>()
+            .join(" | "),
+    }
+}
+
+fn parenthesize_union(ts: &str) -> String {
+    if is_top_level(ts, '|') {
+        format!("({ts})")
+    } else {
+        ts.to_string()
+    }
+}
+
+fn parenthesize_intersection(ts: &str) -> String {
+    if is_top_level(ts, '&') {
+        format!("({ts})")
+    } else {
+        ts.to_string()
+    }
+}
+
+/// Returns true if `operator` appears in `ts` outside of any brackets or
+/// string literals.
+fn is_top_level(ts: &str, operator: char) -> bool {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in ts.chars() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '(' | '[' | '<' => depth += 1,
+            '}' | ')' | ']' | '>' => depth = depth.saturating_sub(1),
+            c if c == operator && depth == 0 => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use schemars::JsonSchema;
+    use serde::Deserialize;
+
+    /// Example doc.
+    #[allow(dead_code)]
+    #[derive(Deserialize, JsonSchema)]
+    #[serde(tag = "type", rename_all = "snake_case")]
+    enum Example {
+        Unit,
+        WithFields { name: String, count: Option<u32> },
+    }
+
+    #[test]
+    fn typescript_for_tagged_enum() {
+        let schema = SchemaSettings::draft07()
+            .into_generator()
+            .into_root_schema_for::<Example>();
+        assert_eq!(
+            r#"// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+/**
+ * Example doc.
+ */
+export type Example = {
+  type: "unit";
+} | {
+  count?: number | null;
+  name: string;
+  type: "with_fields";
+};
+"#,
+            typescript_definitions(&schema)
+        );
+    }
+}
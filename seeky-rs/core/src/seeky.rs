@@ -9,6 +9,7 @@ use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::atomic::AtomicU64;
 use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::Context;
 use async_channel::Receiver;
@@ -33,10 +34,16 @@ use tracing::warn;
 use uuid::Uuid;
 
 use crate::WireApi;
+use crate::approval_rules::ApprovalRule;
+use crate::approval_rules::ApprovalRules;
+use crate::audit_log::AuditEvent;
+use crate::audit_log::AuditLog;
 use crate::client::ModelClient;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::config::Config;
+use crate::config_types::LinuxSandboxBackend;
+use crate::config_types::ResourceLimits;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::conversation_history::ConversationHistory;
 use crate::error::Result as SeekyResult;
@@ -47,6 +54,9 @@ use crate::exec::ExecToolCallOutput;
 use crate::exec::SandboxType;
 use crate::exec::process_exec_tool_call;
 use crate::exec_env::create_env;
+use crate::exec_env::create_env_from_snapshot;
+use crate::exec_env::secret_values;
+use crate::exec_policy::ExecPolicy;
 use crate::flags::OPENAI_STREAM_MAX_RETRIES;
 use crate::mcp_connection_manager::McpConnectionManager;
 use crate::mcp_connection_manager::try_parse_fully_qualified_tool_name;
@@ -58,6 +68,9 @@ use crate::models::ReasoningItemReasoningSummary;
 use crate::models::ResponseInputItem;
 use crate::models::ResponseItem;
 use crate::models::ShellToolCallParams;
+use crate::network_proxy::NetworkProxy;
+use crate::project_doc::FullInstructions;
+use crate::project_doc::SubtreeDocs;
 use crate::project_doc::create_full_instructions;
 use crate::protocol::AgentMessageEvent;
 use crate::protocol::AgentReasoningEvent;
@@ -76,14 +89,18 @@ use crate::protocol::Op;
 use crate::protocol::PatchApplyBeginEvent;
 use crate::protocol::PatchApplyEndEvent;
 use crate::protocol::ReviewDecision;
+use crate::protocol::SandboxDenial;
 use crate::protocol::SandboxPolicy;
 use crate::protocol::SessionConfiguredEvent;
 use crate::protocol::Submission;
 use crate::protocol::TaskCompleteEvent;
+use crate::redaction::Redactor;
 use crate::rollout::RolloutRecorder;
+use crate::rollout::SessionConfig;
 use crate::safety::SafetyCheck;
 use crate::safety::assess_command_safety;
 use crate::safety::assess_patch_safety;
+use crate::shell_snapshot::ShellSnapshot;
 use crate::user_notification::UserNotification;
 use crate::util::backoff;
 
@@ -103,11 +120,15 @@ impl Seeky {
         let (tx_sub, rx_sub) = async_channel::bounded(64);
         let (tx_event, rx_event) = async_channel::bounded(64);
 
-        let instructions = create_full_instructions(&config).await;
+        let FullInstructions {
+            instructions,
+            project_docs,
+        } = create_full_instructions(&config).await;
         let configure_session = Op::ConfigureSession {
             provider: config.model_provider.clone(),
             model: config.model.clone(),
             instructions,
+            project_docs,
             approval_policy: config.approval_policy,
             sandbox_policy: config.sandbox_policy.clone(),
             disable_response_storage: config.disable_response_storage,
@@ -174,6 +195,8 @@ pub(crate) struct Session {
     approval_policy: AskForApproval,
     sandbox_policy: SandboxPolicy,
     shell_environment_policy: ShellEnvironmentPolicy,
+    /// Set if `shell_environment_policy` asks for the login shell.
+    shell_snapshot: Option<ShellSnapshot>,
     writable_roots: Mutex<Vec<PathBuf>>,
 
     /// Manager for external MCP servers/tools.
@@ -186,8 +209,23 @@ pub(crate) struct Session {
     /// Optional rollout recorder for persisting the conversation transcript so
     /// sessions can be replayed or inspected later.
     rollout: Mutex<Option<crate::rollout::RolloutRecorder>>,
+    /// `AGENTS.md` files in subdirectories that are injected as the agent
+    /// starts working in them.
+    subtree_docs: Mutex<SubtreeDocs>,
+    /// Scrubs secrets from tool output before it is sent to the model.
+    redactor: Redactor,
+    /// Set if `audit.enabled` is.
+    audit_log: Option<AuditLog>,
+    /// Rules for which commands are safe to run without asking.
+    exec_policy: ExecPolicy,
+    /// Commands the user always allows or denies.
+    approval_rules: ApprovalRules,
+    /// Proxy that enforces `NetworkAllowDomains` for sandboxed commands.
+    network_proxy: Option<NetworkProxy>,
     state: Mutex<State>,
     seeky_linux_sandbox_exe: Option<PathBuf>,
+    linux_sandbox_backend: LinuxSandboxBackend,
+    resource_limits: ResourceLimits,
 }
 
 impl Session {
@@ -196,6 +234,33 @@ impl Session {
             .map(PathBuf::from)
             .map_or_else(|| self.cwd.clone(), |p| self.cwd.join(p))
     }
+
+    /// The environment for shell tool calls.
+    fn shell_env(&self) -> HashMap<String, String> {
+        match &self.shell_snapshot {
+            Some(snapshot) => create_env_from_snapshot(&self.shell_environment_policy, snapshot),
+            None => create_env(&self.shell_environment_policy),
+        }
+    }
+
+    /// The command that actually runs for `command`: with `use_user_shell`,
+    /// `bash -lc` commands run with the user's shell and its aliases and
+    /// functions instead, whether they run in the sandbox or not.
+    fn shell_command(&self, command: Vec<String>) -> Vec<String> {
+        match &self.shell_snapshot {
+            Some(snapshot) if self.shell_environment_policy.use_user_shell => {
+                snapshot.wrap_command(command)
+            }
+            _ => command,
+        }
+    }
+
+    /// Appends `event` to the audit log, if it is enabled.
+    async fn audit(&self, event: AuditEvent) {
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.append(event).await;
+        }
+    }
 }
 
 /// Mutable state of the agent
@@ -207,6 +272,9 @@ struct State {
     pending_approvals: HashMap<String, oneshot::Sender<ReviewDecision>>,
     pending_input: Vec<ResponseInputItem>,
     zdr_transcript: Option<ConversationHistory>,
+    /// Values of the secrets in the environment of the most recent command,
+    /// redacted from tool output.
+    exec_secrets: Vec<String>,
 }
 
 impl Session {
@@ -230,6 +298,7 @@ impl Session {
     /// Sends the given event to the client and swallows the send event, if
     /// any, logging it as an error.
     pub(crate) async fn send_event(&self, event: Event) {
+        self.record_rollout_event(&event).await;
         if let Err(e) = self.tx_event.send(event).await {
             error!("failed to send tool call event: {e}");
         }
@@ -241,6 +310,8 @@ impl Session {
         command: Vec<String>,
         cwd: PathBuf,
         reason: Option<String>,
+        denial: Option<SandboxDenial>,
+        retry_without_sandbox: bool,
     ) -> oneshot::Receiver<ReviewDecision> {
         let (tx_approve, rx_approve) = oneshot::channel();
         let event = Event {
@@ -249,9 +320,11 @@ impl Session {
                 command,
                 cwd,
                 reason,
+                denial,
+                retry_without_sandbox,
             }),
         };
-        let _ = self.tx_event.send(event).await;
+        self.send_event(event).await;
         {
             let mut state = self.state.lock().unwrap();
             state.pending_approvals.insert(sub_id, tx_approve);
@@ -275,7 +348,7 @@ impl Session {
                 grant_root,
             }),
         };
-        let _ = self.tx_event.send(event).await;
+        self.send_event(event).await;
         {
             let mut state = self.state.lock().unwrap();
             state.pending_approvals.insert(sub_id, tx_approve);
@@ -295,6 +368,24 @@ impl Session {
         state.approved_commands.insert(cmd);
     }
 
+    /// Approves `cmd` for this session and saves an approval rule for it.
+    async fn add_always_approved_command(&self, sub_id: &str, cmd: Vec<String>) {
+        let rule = ApprovalRule::allow_command(&cmd, self.approval_rules.project_root());
+        let message = match self.approval_rules.add(&rule) {
+            Ok(()) => format!(
+                "always approving `{}` in {}",
+                rule.pattern,
+                self.approval_rules.project_root().display()
+            ),
+            Err(e) => {
+                error!("failed to save approval rule: {e}");
+                format!("failed to save approval rule: {e}")
+            }
+        };
+        self.notify_background_event(sub_id, message).await;
+        self.add_approved_command(cmd);
+    }
+
     /// Append the given items to the session's rollout transcript (if enabled)
     /// and persist them to disk.
     async fn record_rollout_items(&self, items: &[ResponseItem]) {
@@ -312,6 +403,56 @@ impl Session {
         }
     }
 
+    /// Replaces secrets in `content`, the output of a tool call that is about
+    /// to be sent to the model, and tells the user if anything was redacted.
+    async fn redact_tool_output(&self, sub_id: &str, content: &mut String) {
+        let secrets = self.state.lock().unwrap().exec_secrets.clone();
+        let redacted = self.redactor.redact_with_values(content, &secrets);
+        if redacted.kinds.is_empty() {
+            return;
+        }
+        let message = format!(
+            "Redacted {} from tool output before sending it to the model.",
+            redacted.kinds.join(", ")
+        );
+        *content = redacted.text.into_owned();
+        self.send_event(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::BackgroundEvent(BackgroundEventEvent { message }),
+        })
+        .await;
+    }
+
+    /// Append `event` to the session's rollout (if enabled and the event is
+    /// worth recording).
+    async fn record_rollout_event(&self, event: &Event) {
+        let recorder = {
+            let guard = self.rollout.lock().unwrap();
+            guard.as_ref().cloned()
+        };
+
+        if let Some(rec) = recorder {
+            if let Err(e) = rec.record_event(event).await {
+                error!("failed to record rollout event: {e:#}");
+            }
+        }
+    }
+
+    /// Append `submission` to the session's rollout (if enabled and the
+    /// submission is worth recording).
+    async fn record_rollout_submission(&self, submission: &Submission) {
+        let recorder = {
+            let guard = self.rollout.lock().unwrap();
+            guard.as_ref().cloned()
+        };
+
+        if let Some(rec) = recorder {
+            if let Err(e) = rec.record_submission(submission).await {
+                error!("failed to record rollout submission: {e:#}");
+            }
+        }
+    }
+
     async fn notify_exec_command_begin(&self, sub_id: &str, call_id: &str, params: &ExecParams) {
         let event = Event {
             id: sub_id.to_string(),
@@ -321,7 +462,7 @@ impl Session {
                 cwd: params.cwd.clone(),
             }),
         };
-        let _ = self.tx_event.send(event).await;
+        self.send_event(event).await;
     }
 
     async fn notify_exec_command_end(
@@ -344,7 +485,7 @@ impl Session {
                 exit_code,
             }),
         };
-        let _ = self.tx_event.send(event).await;
+        self.send_event(event).await;
     }
 
     /// Helper that emits a BackgroundEvent with the given message. This keeps
@@ -357,7 +498,32 @@ impl Session {
                 message: message.into(),
             }),
         };
-        let _ = self.tx_event.send(event).await;
+        self.send_event(event).await;
+    }
+
+    /// Records that the agent is working at `paths` so that any `AGENTS.md`
+    /// in the corresponding subtrees is sent to the model in the next turn.
+    fn note_touched_paths<'a>(&self, paths: impl IntoIterator<Item = &'a Path>) {
+        let mut subtree_docs = self.subtree_docs.lock().unwrap();
+        for path in paths {
+            subtree_docs.note_path(path);
+        }
+    }
+
+    /// Returns developer messages for the subtree docs discovered since the
+    /// last call. They become part of the turn input and are therefore also
+    /// recorded in the rollout.
+    fn take_pending_project_docs(&self) -> Vec<ResponseItem> {
+        self.subtree_docs
+            .lock()
+            .unwrap()
+            .take_pending()
+            .into_iter()
+            .map(|text| ResponseItem::Message {
+                role: "developer".to_string(),
+                content: vec![ContentItem::InputText { text }],
+            })
+            .collect()
     }
 
     /// Returns the input if there was no task running to inject into
@@ -482,9 +648,9 @@ impl AgentTask {
                     message: "Turn interrupted".to_string(),
                 }),
             };
-            let tx_event = self.sess.tx_event.clone();
+            let sess = Arc::clone(&self.sess);
             tokio::spawn(async move {
-                tx_event.send(event).await.ok();
+                sess.send_event(event).await;
             });
         }
     }
@@ -498,6 +664,12 @@ async fn submission_loop(
 ) {
     // Generate a unique ID for the lifetime of this Seeky session.
     let session_id = Uuid::new_v4();
+    // `Config` validates the patterns when it is loaded, so this only falls
+    // back to the built-in detectors for hand-constructed configs.
+    let redactor = Redactor::new(&config.redaction).unwrap_or_else(|e| {
+        error!("invalid redaction pattern: {e}");
+        Redactor::default()
+    });
 
     let mut sess: Option<Arc<Session>> = None;
     // shorthand - send an event when there is no active session
@@ -528,6 +700,9 @@ async fn submission_loop(
         };
 
         debug!(?sub, "Submission");
+        if let Some(sess) = sess.as_ref() {
+            sess.record_rollout_submission(&sub).await;
+        }
         match sub.op {
             Op::Interrupt => {
                 let sess = match sess.as_ref() {
@@ -543,6 +718,7 @@ async fn submission_loop(
                 provider,
                 model,
                 instructions,
+                project_docs,
                 approval_policy,
                 sandbox_policy,
                 disable_response_storage,
@@ -563,7 +739,12 @@ async fn submission_loop(
                     return;
                 }
 
-                let client = ModelClient::new(model.clone(), provider.clone());
+                let client = ModelClient::new(
+                    model.clone(),
+                    provider.clone(),
+                    config.model_reasoning_effort,
+                    config.model_reasoning_summary,
+                );
 
                 // abort any current running session and clone its state
                 let retain_zdr_transcript =
@@ -587,6 +768,9 @@ async fn submission_loop(
                 };
 
                 let writable_roots = Mutex::new(get_writable_roots(&cwd));
+                let subtree_docs = Mutex::new(
+                    SubtreeDocs::new(&cwd, config.project_doc_max_bytes, &project_docs).await,
+                );
 
                 // Error messages to dispatch after SessionConfigured is sent.
                 let mut mcp_connection_errors = Vec::<Event>::new();
@@ -617,19 +801,89 @@ async fn submission_loop(
                     }
                 }
 
+                let shell_snapshot = if config.shell_environment_policy.snapshot_login_shell {
+                    ShellSnapshot::capture(
+                        &cwd,
+                        &config.seeky_home.join("shell_snapshots"),
+                        &session_id.to_string(),
+                    )
+                    .await
+                } else {
+                    None
+                };
+
+                let network_proxy = if sandbox_policy.has_full_network_access() {
+                    None
+                } else {
+                    start_network_proxy(&sandbox_policy, &sub.id, &tx_event)
+                        .await
+                        .unwrap_or_else(|message| {
+                            error!("{message}");
+                            mcp_connection_errors.push(Event {
+                                id: sub.id.clone(),
+                                msg: EventMsg::Error(ErrorEvent { message }),
+                            });
+                            None
+                        })
+                };
+
                 // Attempt to create a RolloutRecorder *before* moving the
                 // `instructions` value into the Session struct.
                 // TODO: if ConfigureSession is sent twice, we will create an
                 // overlapping rollout file. Consider passing RolloutRecorder
                 // from above.
-                let rollout_recorder =
-                    match RolloutRecorder::new(&config, session_id, instructions.clone()).await {
-                        Ok(r) => Some(r),
-                        Err(e) => {
-                            tracing::warn!("failed to initialise rollout recorder: {e}");
-                            None
-                        }
-                    };
+                let session_config = SessionConfig {
+                    model: model.clone(),
+                    provider: provider.clone(),
+                    cwd: cwd.clone(),
+                    approval_policy,
+                    sandbox_policy: sandbox_policy.clone(),
+                    disable_response_storage,
+                    project_docs: project_docs.clone(),
+                };
+                let rollout_recorder = match RolloutRecorder::new(
+                    &config,
+                    session_id,
+                    instructions.clone(),
+                    session_config,
+                    redactor.clone(),
+                )
+                .await
+                {
+                    Ok(r) => Some(r),
+                    Err(e) => {
+                        tracing::warn!("failed to initialise rollout recorder: {e}");
+                        None
+                    }
+                };
+
+                let (exec_policy, exec_policy_errors) =
+                    ExecPolicy::load(&config.exec_policy_files, &config.project_exec_policy_files);
+                for message in exec_policy_errors {
+                    error!("{message}");
+                    mcp_connection_errors.push(Event {
+                        id: sub.id.clone(),
+                        msg: EventMsg::Error(ErrorEvent { message }),
+                    });
+                }
+
+                let approval_rules = ApprovalRules::new(&config.seeky_home, &cwd);
+                if let Err(e) = approval_rules.load() {
+                    let message = format!("failed to load approval rules: {e}");
+                    error!("{message}");
+                    mcp_connection_errors.push(Event {
+                        id: sub.id.clone(),
+                        msg: EventMsg::Error(ErrorEvent { message }),
+                    });
+                }
+
+                let audit_log = AuditLog::new(
+                    &config.audit,
+                    &config.seeky_home,
+                    &cwd,
+                    session_id,
+                    redactor.clone(),
+                );
 
                 sess = Some(Arc::new(Session {
                     client,
@@ -639,13 +893,22 @@ async fn submission_loop(
                     approval_policy,
                     sandbox_policy,
                     shell_environment_policy: config.shell_environment_policy.clone(),
+                    shell_snapshot,
                     cwd,
                     writable_roots,
                     mcp_connection_manager,
                     notify,
                     state: Mutex::new(state),
                     rollout: Mutex::new(rollout_recorder),
+                    subtree_docs,
+                    redactor: redactor.clone(),
+                    audit_log,
+                    exec_policy,
+                    approval_rules,
+                    network_proxy,
                     seeky_linux_sandbox_exe: config.seeky_linux_sandbox_exe.clone(),
+                    linux_sandbox_backend: config.sandbox.linux_backend,
+                    resource_limits: config.sandbox.limits.clone(),
                 }));
 
                 // Gather history metadata for SessionConfiguredEvent.
@@ -660,6 +923,7 @@ async fn submission_loop(
                         model,
                         history_log_id,
                         history_entry_count,
+                        project_docs,
                     }),
                 })
                 .chain(mcp_connection_errors.into_iter());
@@ -718,10 +982,40 @@ async fn submission_loop(
             Op::AddToHistory { text } => {
                 let id = session_id;
                 let config = config.clone();
+                let redacted = redactor.redact(&text);
+                if !redacted.kinds.is_empty() {
+                    let event = Event {
+                        id: sub.id.clone(),
+                        msg: EventMsg::BackgroundEvent(BackgroundEventEvent {
+                            message: format!(
+                                "Redacted {} from the message before saving it to history.",
+                                redacted.kinds.join(", ")
+                            ),
+                        }),
+                    };
+                    tx_event.send(event).await.ok();
+                }
+                let text = redacted.text.into_owned();
+                let tx_event = tx_event.clone();
+                let sub_id = sub.id.clone();
                 tokio::spawn(async move {
-                    if let Err(e) = crate::message_history::append_entry(&text, &id, &config).await
-                    {
-                        tracing::warn!("failed to append to message history: {e}");
+                    match crate::message_history::append_entry(&text, &id, &config).await {
+                        Ok(Some((history_log_id, history_entry_count))) => {
+                            let event = Event {
+                                id: sub_id,
+                                msg: EventMsg::HistoryCompacted(
+                                    crate::protocol::HistoryCompactedEvent {
+                                        history_log_id,
+                                        history_entry_count,
+                                    },
+                                ),
+                            };
+                            if let Err(e) = tx_event.send(event).await {
+                                tracing::warn!("failed to send HistoryCompacted event: {e}");
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => tracing::warn!("failed to append to message history: {e}"),
                     }
                 });
             }
@@ -755,6 +1049,50 @@ async fn submission_loop(
                     }
                 });
             }
+
+            Op::SearchHistoryRequest {
+                log_id,
+                query,
+                mode,
+                before_offset,
+            } => {
+                let config = config.clone();
+                let tx_event = tx_event.clone();
+                let sub_id = sub.id.clone();
+
+                tokio::spawn(async move {
+                    // Run search in blocking thread because it does file IO + locking.
+                    let search_query = query.clone();
+                    let found = tokio::task::spawn_blocking(move || {
+                        crate::message_history::search(
+                            log_id,
+                            &search_query,
+                            mode,
+                            before_offset,
+                            &config,
+                        )
+                    })
+                    .await
+                    .unwrap_or(None);
+                    let (offset, entry) = found.unzip();
+
+                    let event = Event {
+                        id: sub_id,
+                        msg: EventMsg::SearchHistoryResponse(
+                            crate::protocol::SearchHistoryResponseEvent {
+                                log_id,
+                                query,
+                                offset,
+                                entry,
+                            },
+                        ),
+                    };
+
+                    if let Err(e) = tx_event.send(event).await {
+                        tracing::warn!("failed to send SearchHistoryResponse event: {e}");
+                    }
+                });
+            }
         }
     }
     debug!("Agent loop exited");
@@ -768,6 +1106,7 @@ async fn run_task(sess: Arc<Session>, sub_id: String, input: Vec<InputItem>) {
         id: sub_id.clone(),
         msg: EventMsg::TaskStarted,
     };
+    sess.record_rollout_event(&event).await;
     if sess.tx_event.send(event).await.is_err() {
         return;
     }
@@ -786,6 +1125,10 @@ async fn run_task(sess: Arc<Session>, sub_id: String, input: Vec<InputItem>) {
         let pending_input = sess.get_pending_input().into_iter().map(ResponseItem::from);
         net_new_turn_input.extend(pending_input);
 
+        // AGENTS.md files for subtrees the agent entered during the previous
+        // turn (or task).
+        net_new_turn_input.extend(sess.take_pending_project_docs());
+
         // Persist only the net-new items of this turn to the rollout.
         sess.record_rollout_items(&net_new_turn_input).await;
 
@@ -871,7 +1214,7 @@ async fn run_task(sess: Arc<Session>, sub_id: String, input: Vec<InputItem>) {
                         message: e.to_string(),
                     }),
                 };
-                sess.tx_event.send(event).await.ok();
+                sess.send_event(event).await;
                 return;
             }
         }
@@ -881,7 +1224,7 @@ async fn run_task(sess: Arc<Session>, sub_id: String, input: Vec<InputItem>) {
         id: sub_id,
         msg: EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }),
     };
-    sess.tx_event.send(event).await.ok();
+    sess.send_event(event).await;
 }
 
 async fn run_turn(
@@ -982,7 +1325,10 @@ async fn try_run_turn(
     for event in input {
         match event {
             ResponseEvent::OutputItemDone(item) => {
-                let response = handle_response_item(sess, sub_id, item.clone()).await?;
+                let mut response = handle_response_item(sess, sub_id, item.clone()).await?;
+                if let Some(ResponseInputItem::FunctionCallOutput { output, .. }) = &mut response {
+                    sess.redact_tool_output(sub_id, &mut output.content).await;
+                }
                 output.push(ProcessedResponseItem { item, response });
             }
             ResponseEvent::Completed { response_id } => {
@@ -1009,7 +1355,7 @@ async fn handle_response_item(
                         id: sub_id.to_string(),
                         msg: EventMsg::AgentMessage(AgentMessageEvent { message: text }),
                     };
-                    sess.tx_event.send(event).await.ok();
+                    sess.send_event(event).await;
                 }
             }
             None
@@ -1023,7 +1369,7 @@ async fn handle_response_item(
                     id: sub_id.to_string(),
                     msg: EventMsg::AgentReasoning(AgentReasoningEvent { text }),
                 };
-                sess.tx_event.send(event).await.ok();
+                sess.send_event(event).await;
             }
             None
         }
@@ -1125,12 +1471,52 @@ async fn handle_function_call(
     }
 }
 
+/// Starts the proxy that enforces the `NetworkAllowDomains` permissions of
+/// `sandbox_policy`, if there are any. Denied hosts are reported to the client
+/// as background events correlated with `sub_id`.
+async fn start_network_proxy(
+    sandbox_policy: &SandboxPolicy,
+    sub_id: &str,
+    tx_event: &Sender<Event>,
+) -> Result<Option<NetworkProxy>, String> {
+    let allowed_domains = sandbox_policy.get_allowed_network_domains();
+    if allowed_domains.is_empty() {
+        return Ok(None);
+    }
+
+    let (tx_denied, mut rx_denied) = tokio::sync::mpsc::unbounded_channel::<String>();
+    let proxy = NetworkProxy::start(allowed_domains, tx_denied)
+        .await
+        .map_err(|e| format!("Failed to start network proxy: {e}"))?;
+    info!("network proxy listening on 127.0.0.1:{}", proxy.port());
+
+    let sub_id = sub_id.to_string();
+    let tx_event = tx_event.clone();
+    tokio::spawn(async move {
+        while let Some(host) = rx_denied.recv().await {
+            let event = Event {
+                id: sub_id.clone(),
+                msg: EventMsg::BackgroundEvent(BackgroundEventEvent {
+                    message: format!(
+                        "sandbox blocked network access to `{host}` (not in network-allow-domains)"
+                    ),
+                }),
+            };
+            if tx_event.send(event).await.is_err() {
+                break;
+            }
+        }
+    });
+    Ok(Some(proxy))
+}
+
 fn to_exec_params(params: ShellToolCallParams, sess: &Session) -> ExecParams {
     ExecParams {
         command: params.command,
         cwd: sess.resolve_path(params.workdir.clone()),
         timeout_ms: params.timeout_ms,
-        env: create_env(&sess.shell_environment_policy),
+        env: sess.shell_env(),
+        limits: sess.resource_limits.clone(),
     }
 }
 
@@ -1186,32 +1572,53 @@ async fn handle_container_exec_with_params(
     }
 
     // safety checks
+    let approval_rules = sess.approval_rules.load().unwrap_or_else(|e| {
+        warn!("failed to load approval rules: {e}");
+        Vec::new()
+    });
     let safety = {
+        let writable_roots = sess.writable_roots.lock().unwrap().clone();
         let state = sess.state.lock().unwrap();
         assess_command_safety(
             &params.command,
+            &params.cwd,
             sess.approval_policy,
             &sess.sandbox_policy,
+            &writable_roots,
+            &sess.exec_policy,
+            &approval_rules,
             &state.approved_commands,
         )
     };
+    let mut audit = AuditEvent::command(&params.command, &params.cwd, &safety);
+    sess.state.lock().unwrap().exec_secrets =
+        secret_values(&sess.shell_environment_policy, &params.env);
     let sandbox_type = match safety {
-        SafetyCheck::AutoApprove { sandbox_type } => sandbox_type,
-        SafetyCheck::AskUser => {
+        SafetyCheck::AutoApprove { sandbox_type, .. } => sandbox_type,
+        SafetyCheck::AskUser { .. } => {
             let rx_approve = sess
                 .request_command_approval(
                     sub_id.clone(),
                     params.command.clone(),
                     params.cwd.clone(),
                     None,
+                    None,
+                    false,
                 )
                 .await;
-            match rx_approve.await.unwrap_or_default() {
+            let decision = rx_approve.await.unwrap_or_default();
+            audit.user_decision = Some(decision);
+            match decision {
                 ReviewDecision::Approved => (),
                 ReviewDecision::ApprovedForSession => {
                     sess.add_approved_command(params.command.clone());
                 }
+                ReviewDecision::ApprovedAlways => {
+                    sess.add_always_approved_command(&sub_id, params.command.clone())
+                        .await;
+                }
                 ReviewDecision::Denied | ReviewDecision::Abort => {
+                    sess.audit(audit).await;
                     return ResponseInputItem::FunctionCallOutput {
                         call_id,
                         output: crate::models::FunctionCallOutputPayload {
@@ -1228,6 +1635,7 @@ async fn handle_container_exec_with_params(
             SandboxType::None
         }
         SafetyCheck::Reject { reason } => {
+            sess.audit(audit).await;
             return ResponseInputItem::FunctionCallOutput {
                 call_id,
                 output: crate::models::FunctionCallOutputPayload {
@@ -1238,11 +1646,31 @@ async fn handle_container_exec_with_params(
         }
     };
 
+    // `assess_command_safety` only knows the default sandbox of the platform.
+    let sandbox_type = match (sandbox_type, sess.linux_sandbox_backend) {
+        (SandboxType::LinuxSeccomp, LinuxSandboxBackend::Namespaces) => {
+            SandboxType::LinuxNamespaces
+        }
+        (sandbox_type, _) => sandbox_type,
+    };
+    audit.sandbox = Some(sandbox_type);
+
+    sess.note_touched_paths([params.cwd.as_path()]);
     sess.notify_exec_command_begin(&sub_id, &call_id, &params)
         .await;
 
+    // Only sandboxed commands are routed through the proxy; a command the user
+    // approved to run without a sandbox gets the original environment.
+    let mut sandboxed_params = params.clone();
+    sandboxed_params.command = sess.shell_command(sandboxed_params.command);
+    if sandbox_type != SandboxType::None {
+        if let Some(proxy) = &sess.network_proxy {
+            proxy.apply_to_env(&mut sandboxed_params.env);
+        }
+    }
+
     let output_result = process_exec_tool_call(
-        params.clone(),
+        sandboxed_params,
         sandbox_type,
         sess.ctrl_c.clone(),
         &sess.sandbox_policy,
@@ -1259,6 +1687,9 @@ async fn handle_container_exec_with_params(
                 duration,
             } = output;
 
+            audit.exit_code = Some(exit_code);
+            audit.set_duration(duration);
+            sess.audit(audit).await;
             sess.notify_exec_command_end(&sub_id, &call_id, &stdout, &stderr, exit_code)
                 .await;
 
@@ -1278,9 +1709,22 @@ async fn handle_container_exec_with_params(
             }
         }
         Err(SeekyErr::Sandbox(error)) => {
+            audit.error = Some(error.to_string());
+            match &error {
+                SandboxErr::Denied(exit_code, ..) => audit.exit_code = Some(*exit_code),
+                error => {
+                    if let Some(output) = error.resource_limit_output() {
+                        audit.exit_code = Some(output.exit_code);
+                        audit.set_duration(output.duration);
+                    }
+                }
+            }
+            sess.audit(audit).await;
             handle_sanbox_error(error, sandbox_type, params, sess, sub_id, call_id).await
         }
         Err(e) => {
+            audit.error = Some(e.to_string());
+            sess.audit(audit).await;
             // Handle non-sandbox errors
             ResponseInputItem::FunctionCallOutput {
                 call_id,
@@ -1301,14 +1745,42 @@ async fn handle_sanbox_error(
     sub_id: String,
     call_id: String,
 ) -> ResponseInputItem {
+    // The sandbox did not block a command that ran into a resource limit, and
+    // the limits also apply without it, so there is nothing to retry.
+    if let Some(output) = error.resource_limit_output() {
+        sess.notify_exec_command_end(
+            &sub_id,
+            &call_id,
+            &output.stdout,
+            &output.stderr,
+            output.exit_code,
+        )
+        .await;
+        let content = format_exec_output(
+            &format!("{error}\n{}", output.stderr),
+            output.exit_code,
+            output.duration,
+        );
+        return ResponseInputItem::FunctionCallOutput {
+            call_id,
+            output: FunctionCallOutputPayload {
+                content,
+                success: Some(false),
+            },
+        };
+    }
+
+    let denial = error.sandbox_denial().cloned();
+
     // Early out if the user never wants to be asked for approval; just return to the model immediately
     if sess.approval_policy == AskForApproval::Never {
         return ResponseInputItem::FunctionCallOutput {
             call_id,
             output: FunctionCallOutputPayload {
                 content: format!(
-                    "failed in sandbox {:?} with execution error: {error}",
-                    sandbox_type
+                    "failed in sandbox {:?} with execution error: {error}{}",
+                    sandbox_type,
+                    format_sandbox_denial(denial.as_ref())
                 ),
                 success: Some(false),
             },
@@ -1319,17 +1791,40 @@ async fn handle_sanbox_error(
     sess.notify_background_event(&sub_id, format!("Execution failed: {error}"))
         .await;
 
+    let reason = match &denial {
+        Some(denial) => format!("command failed: {denial}; retry without sandbox?"),
+        None => "command failed; retry without sandbox?".to_string(),
+    };
     let rx_approve = sess
         .request_command_approval(
             sub_id.clone(),
             params.command.clone(),
             params.cwd.clone(),
-            Some("command failed; retry without sandbox?".to_string()),
+            Some(reason),
+            denial.clone(),
+            true,
         )
         .await;
 
-    match rx_approve.await.unwrap_or_default() {
-        ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {
+    // An approval rule would still run the command in the sandbox, see
+    // `assess_command_safety`, so approving it always only lasts for the
+    // session.
+    let decision = match rx_approve.await.unwrap_or_default() {
+        ReviewDecision::ApprovedAlways => ReviewDecision::ApprovedForSession,
+        decision => decision,
+    };
+    let mut audit = AuditEvent::command(
+        &params.command,
+        &params.cwd,
+        &SafetyCheck::AskUser {
+            reason: format!("failed in sandbox {sandbox_type:?}; retry without sandbox"),
+        },
+    );
+    audit.user_decision = Some(decision);
+    match decision {
+        ReviewDecision::Approved
+        | ReviewDecision::ApprovedForSession
+        | ReviewDecision::ApprovedAlways => {
             // Persist this command as pre‑approved for the
             // remainder of the session so future
             // executions skip the sandbox directly.
@@ -1347,7 +1842,10 @@ async fn handle_sanbox_error(
             // This is an escalated retry; the policy will not be
             // examined and the sandbox has been set to `None`.
             let retry_output_result = process_exec_tool_call(
-                params,
+                ExecParams {
+                    command: sess.shell_command(params.command),
+                    ..params
+                },
                 SandboxType::None,
                 sess.ctrl_c.clone(),
                 &sess.sandbox_policy,
@@ -1364,6 +1862,10 @@ async fn handle_sanbox_error(
                         duration,
                     } = retry_output;
 
+                    audit.sandbox = Some(SandboxType::None);
+                    audit.exit_code = Some(exit_code);
+                    audit.set_duration(duration);
+                    sess.audit(audit).await;
                     sess.notify_exec_command_end(
                         &sub_id,
                         &retry_call_id,
@@ -1389,6 +1891,9 @@ async fn handle_sanbox_error(
                     }
                 }
                 Err(e) => {
+                    audit.sandbox = Some(SandboxType::None);
+                    audit.error = Some(e.to_string());
+                    sess.audit(audit).await;
                     // Handle retry failure
                     ResponseInputItem::FunctionCallOutput {
                         call_id,
@@ -1401,11 +1906,15 @@ async fn handle_sanbox_error(
             }
         }
         ReviewDecision::Denied | ReviewDecision::Abort => {
+            sess.audit(audit).await;
             // Fall through to original failure handling.
             ResponseInputItem::FunctionCallOutput {
                 call_id,
                 output: FunctionCallOutputPayload {
-                    content: "exec command rejected by user".to_string(),
+                    content: format!(
+                        "exec command rejected by user{}",
+                        format_sandbox_denial(denial.as_ref())
+                    ),
                     success: None,
                 },
             }
@@ -1424,22 +1933,34 @@ async fn apply_patch(
         guard.clone()
     };
 
-    let auto_approved = match assess_patch_safety(
+    let protected_paths = sess
+        .sandbox_policy
+        .get_protected_paths(&writable_roots_snapshot);
+
+    let safety = assess_patch_safety(
         &action,
         sess.approval_policy,
         &writable_roots_snapshot,
+        &protected_paths,
         &sess.cwd,
-    ) {
+    );
+    let mut audit = AuditEvent::patch(&action, &sess.cwd, &safety);
+    let auto_approved = match safety {
         SafetyCheck::AutoApprove { .. } => true,
-        SafetyCheck::AskUser => {
+        SafetyCheck::AskUser { .. } => {
             // Compute a readable summary of path changes to include in the
             // approval request so the user can make an informed decision.
             let rx_approve = sess
                 .request_patch_approval(sub_id.clone(), &action, None, None)
                 .await;
-            match rx_approve.await.unwrap_or_default() {
-                ReviewDecision::Approved | ReviewDecision::ApprovedForSession => false,
+            let decision = rx_approve.await.unwrap_or_default();
+            audit.user_decision = Some(decision);
+            match decision {
+                ReviewDecision::Approved
+                | ReviewDecision::ApprovedForSession
+                | ReviewDecision::ApprovedAlways => false,
                 ReviewDecision::Denied | ReviewDecision::Abort => {
+                    sess.audit(audit).await;
                     return ResponseInputItem::FunctionCallOutput {
                         call_id,
                         output: FunctionCallOutputPayload {
@@ -1451,6 +1972,7 @@ async fn apply_patch(
             }
         }
         SafetyCheck::Reject { reason } => {
+            sess.audit(audit).await;
             return ResponseInputItem::FunctionCallOutput {
                 call_id,
                 output: FunctionCallOutputPayload {
@@ -1476,10 +1998,15 @@ async fn apply_patch(
             .request_patch_approval(sub_id.clone(), &action, reason.clone(), Some(root.clone()))
             .await;
 
+        let decision = rx.await.unwrap_or_default();
+        audit.user_decision = Some(decision);
         if !matches!(
-            rx.await.unwrap_or_default(),
-            ReviewDecision::Approved | ReviewDecision::ApprovedForSession
+            decision,
+            ReviewDecision::Approved
+                | ReviewDecision::ApprovedForSession
+                | ReviewDecision::ApprovedAlways
         ) {
+            sess.audit(audit).await;
             return ResponseInputItem::FunctionCallOutput {
                 call_id,
                 output: FunctionCallOutputPayload {
@@ -1493,18 +2020,17 @@ async fn apply_patch(
         sess.writable_roots.lock().unwrap().push(root);
     }
 
-    let _ = sess
-        .tx_event
-        .send(Event {
-            id: sub_id.clone(),
-            msg: EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
-                call_id: call_id.clone(),
-                auto_approved,
-                changes: convert_apply_patch_to_protocol(&action),
-            }),
-        })
-        .await;
+    sess.send_event(Event {
+        id: sub_id.clone(),
+        msg: EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
+            call_id: call_id.clone(),
+            auto_approved,
+            changes: convert_apply_patch_to_protocol(&action),
+        }),
+    })
+    .await;
 
+    let start = Instant::now();
     let mut stdout = Vec::new();
     let mut stderr = Vec::new();
     // Enforce writable roots. If a write is blocked, collect offending root
@@ -1562,9 +2088,13 @@ async fn apply_patch(
                         Some(root.clone()),
                     )
                     .await;
+                let decision = rx.await.unwrap_or_default();
+                audit.user_decision = Some(decision);
                 if matches!(
-                    rx.await.unwrap_or_default(),
-                    ReviewDecision::Approved | ReviewDecision::ApprovedForSession
+                    decision,
+                    ReviewDecision::Approved
+                        | ReviewDecision::ApprovedForSession
+                        | ReviewDecision::ApprovedAlways
                 ) {
                     // Extend writable roots.
                     sess.writable_roots.lock().unwrap().push(root);
@@ -1580,20 +2110,32 @@ async fn apply_patch(
         }
     }
 
+    sess.note_touched_paths(action.changes().iter().flat_map(|(path, change)| {
+        let move_path = match change {
+            ApplyPatchFileChange::Update { move_path, .. } => move_path.as_deref(),
+            _ => None,
+        };
+        std::iter::once(path.as_path()).chain(move_path)
+    }));
+
+    audit.set_duration(start.elapsed());
+    if let Err(e) = &result {
+        audit.error = Some(e.to_string());
+    }
+    sess.audit(audit).await;
+
     // Emit PatchApplyEnd event.
     let success_flag = result.is_ok();
-    let _ = sess
-        .tx_event
-        .send(Event {
-            id: sub_id.clone(),
-            msg: EventMsg::PatchApplyEnd(PatchApplyEndEvent {
-                call_id: call_id.clone(),
-                stdout: String::from_utf8_lossy(&stdout).to_string(),
-                stderr: String::from_utf8_lossy(&stderr).to_string(),
-                success: success_flag,
-            }),
-        })
-        .await;
+    sess.send_event(Event {
+        id: sub_id.clone(),
+        msg: EventMsg::PatchApplyEnd(PatchApplyEndEvent {
+            call_id: call_id.clone(),
+            stdout: String::from_utf8_lossy(&stdout).to_string(),
+            stderr: String::from_utf8_lossy(&stderr).to_string(),
+            success: success_flag,
+        }),
+    })
+    .await;
 
     match result {
         Ok(_) => ResponseInputItem::FunctionCallOutput {
@@ -1813,6 +2355,20 @@ fn format_exec_output(output: &str, exit_code: i32, duration: std::time::Duratio
     serde_json::to_string(&payload).expect("serialize ExecOutput")
 }
 
+/// Explains to the model what the sandbox blocked, so that it can work around
+/// it or ask for just the missing permission.
+fn format_sandbox_denial(denial: Option<&SandboxDenial>) -> String {
+    let Some(denial) = denial else {
+        return String::new();
+    };
+    match denial.suggested_permission() {
+        Some(permission) => {
+            format!("\n{denial}; it can be allowed with the sandbox permission `{permission}`")
+        }
+        None => format!("\n{denial}"),
+    }
+}
+
 fn get_last_assistant_message_from_turn(responses: &[ResponseItem]) -> Option<String> {
     responses.iter().rev().find_map(|item| {
         if let ResponseItem::Message { role, content } = item {
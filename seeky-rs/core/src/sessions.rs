@@ -0,0 +1,804 @@
+//! Listing, searching, rendering and pruning the rollouts saved under
+//! `$SEEKY_HOME/sessions`. See [`crate::rollout`] for the file format.
+
+use std::collections::HashMap;
+use std::io::Error as IoError;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use serde::Serialize;
+use shlex::try_join;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use uuid::Uuid;
+
+use crate::models::ContentItem;
+use crate::models::ResponseItem;
+use crate::protocol::EventMsg;
+use crate::protocol::FileChange;
+use crate::protocol::Op;
+use crate::rollout::RolloutItem;
+use crate::rollout::SESSIONS_SUBDIR;
+use crate::rollout::SessionLog;
+
+/// One line worth of information about a saved session, as shown by
+/// `seeky sessions list`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionSummary {
+    pub id: Uuid,
+    pub path: PathBuf,
+    /// Start of the session as recorded in the rollout header.
+    pub started_at: String,
+    pub cwd: Option<PathBuf>,
+    pub model: Option<String>,
+    /// Text of the first user message.
+    pub first_prompt: Option<String>,
+    /// Time between the start of the session and the last recorded line.
+    /// `None` for rollouts that predate per-line timestamps.
+    pub duration_secs: Option<u64>,
+}
+
+impl SessionSummary {
+    pub fn new(path: PathBuf, log: &SessionLog) -> Self {
+        let config = log.meta.config.as_ref();
+        let first_prompt = log.response_items().find_map(|item| match item {
+            ResponseItem::Message { role, content } if role == "user" => {
+                Some(message_text(content))
+            }
+            _ => None,
+        });
+        let duration_secs = log
+            .lines
+            .iter()
+            .rev()
+            .find(|line| !line.timestamp.is_empty())
+            .and_then(|last| {
+                let start = OffsetDateTime::parse(&log.meta.timestamp, &Rfc3339).ok()?;
+                let end = OffsetDateTime::parse(&last.timestamp, &Rfc3339).ok()?;
+                u64::try_from((end - start).whole_seconds()).ok()
+            });
+
+        Self {
+            id: log.meta.id,
+            path,
+            started_at: log.meta.timestamp.clone(),
+            cwd: config.map(|c| c.cwd.clone()),
+            model: config.map(|c| c.model.clone()),
+            first_prompt,
+            duration_secs,
+        }
+    }
+}
+
+/// Directory that holds the rollouts for `seeky_home`.
+pub fn sessions_dir(seeky_home: &Path) -> PathBuf {
+    seeky_home.join(SESSIONS_SUBDIR)
+}
+
+/// Summaries of all saved sessions, newest first. When `query` is set, only
+/// sessions whose transcript contains it (case-insensitively) are returned.
+///
+/// Rollouts that cannot be parsed are skipped with a warning so that one
+/// corrupt file does not hide the rest.
+pub fn list_sessions(
+    seeky_home: &Path,
+    query: Option<&str>,
+) -> std::io::Result<Vec<SessionSummary>> {
+    let query = query.map(str::to_lowercase);
+    let mut summaries = Vec::new();
+    for path in rollout_paths(seeky_home)? {
+        let log = match SessionLog::read(&path) {
+            Ok(log) => log,
+            Err(e) => {
+                tracing::warn!("skipping unreadable rollout: {e}");
+                continue;
+            }
+        };
+        if let Some(query) = &query {
+            if !transcript_contains(&log, query) {
+                continue;
+            }
+        }
+        summaries.push(SessionSummary::new(path, &log));
+    }
+    summaries.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    Ok(summaries)
+}
+
+/// Resolves `id` to a rollout file. `id` may be a path to a rollout, a full
+/// session id, or an unambiguous prefix of one.
+pub fn find_session(seeky_home: &Path, id: &str) -> std::io::Result<PathBuf> {
+    let as_path = Path::new(id);
+    if as_path.is_file() {
+        return Ok(as_path.to_path_buf());
+    }
+
+    let prefix = id.to_lowercase();
+    let mut matches: Vec<PathBuf> = rollout_paths(seeky_home)?
+        .into_iter()
+        .filter(|path| {
+            session_id_from_path(path).is_some_and(|session_id| session_id.starts_with(&prefix))
+        })
+        .collect();
+    match matches.len() {
+        0 => Err(IoError::new(
+            ErrorKind::NotFound,
+            format!("no session matches `{id}`"),
+        )),
+        1 => Ok(matches.remove(0)),
+        n => Err(IoError::new(
+            ErrorKind::InvalidInput,
+            format!("`{id}` matches {n} sessions; use a longer prefix"),
+        )),
+    }
+}
+
+/// Deletes rollouts that have not been written to for longer than
+/// `older_than` and returns their paths. With `dry_run`, nothing is deleted.
+pub fn prune_sessions(
+    seeky_home: &Path,
+    older_than: Duration,
+    dry_run: bool,
+) -> std::io::Result<Vec<PathBuf>> {
+    let now = SystemTime::now();
+    let mut pruned = Vec::new();
+    for path in rollout_paths(seeky_home)? {
+        let modified = std::fs::metadata(&path)?.modified()?;
+        let age = now.duration_since(modified).unwrap_or_default();
+        if age <= older_than {
+            continue;
+        }
+        if !dry_run {
+            std::fs::remove_file(&path)?;
+        }
+        pruned.push(path);
+    }
+    Ok(pruned)
+}
+
+/// Output formats supported by [`export_session`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+    /// The parsed rollout, pretty-printed.
+    Json,
+}
+
+/// Renders `log` as a human-readable transcript (or as JSON).
+pub fn export_session(log: &SessionLog, format: ExportFormat) -> Result<String, serde_json::Error> {
+    Ok(match format {
+        ExportFormat::Markdown => render_markdown(log),
+        ExportFormat::Html => render_html(log),
+        ExportFormat::Json => {
+            let mut json = serde_json::to_string_pretty(log)?;
+            json.push('\n');
+            json
+        }
+    })
+}
+
+/// Rollout files directly under the sessions directory, sorted by name.
+fn rollout_paths(seeky_home: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let dir = sessions_dir(seeky_home);
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let is_rollout = path.extension().is_some_and(|ext| ext == "jsonl")
+            && path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("rollout-"));
+        if is_rollout && path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Extracts the session id from `rollout-<timestamp>-<uuid>.jsonl`.
+fn session_id_from_path(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    let start = stem.len().checked_sub(36)?;
+    let id = stem.get(start..)?;
+    Uuid::parse_str(id).ok().map(|uuid| uuid.to_string())
+}
+
+fn message_text(content: &[ContentItem]) -> String {
+    content
+        .iter()
+        .map(|item| match item {
+            ContentItem::InputText { text } | ContentItem::OutputText { text } => text.as_str(),
+            ContentItem::InputImage { .. } => "[image]",
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `query` must already be lowercase.
+fn transcript_contains(log: &SessionLog, query: &str) -> bool {
+    transcript(log).iter().any(|block| {
+        block.heading.to_lowercase().contains(query)
+            || block.parts.iter().any(|part| match part {
+                Part::Text(text) | Part::Code { text, .. } => text.to_lowercase().contains(query),
+            })
+    })
+}
+
+/// A section of a rendered transcript: a heading followed by paragraphs and
+/// code blocks. Both the Markdown and the HTML renderer work from these.
+struct Block {
+    heading: String,
+    parts: Vec<Part>,
+}
+
+enum Part {
+    Text(String),
+    Code {
+        language: &'static str,
+        text: String,
+    },
+}
+
+impl Block {
+    fn new(heading: impl Into<String>) -> Self {
+        Self {
+            heading: heading.into(),
+            parts: Vec::new(),
+        }
+    }
+
+    fn text(mut self, text: impl Into<String>) -> Self {
+        self.push_text(text);
+        self
+    }
+
+    fn code(mut self, language: &'static str, text: impl Into<String>) -> Self {
+        self.push_code(language, text);
+        self
+    }
+
+    fn push_text(&mut self, text: impl Into<String>) {
+        let text = text.into();
+        if !text.trim().is_empty() {
+            self.parts.push(Part::Text(text));
+        }
+    }
+
+    fn push_code(&mut self, language: &'static str, text: impl Into<String>) {
+        let text = text.into();
+        if !text.trim().is_empty() {
+            self.parts.push(Part::Code { language, text });
+        }
+    }
+}
+
+/// Turns the rollout into transcript blocks.
+///
+/// Commands and patches are taken from the recorded events, which carry exit
+/// codes and diffs. Rollouts written before events were recorded only have
+/// the function calls exchanged with the model, so those are used instead.
+fn transcript(log: &SessionLog) -> Vec<Block> {
+    let has_events = log.meta.version > 0;
+    let mut blocks: Vec<Block> = Vec::new();
+    // Index of the block opened by a `*Begin` event, keyed by call id, so
+    // that the matching `*End` event can add its result to it.
+    let mut open_calls: HashMap<String, usize> = HashMap::new();
+
+    for line in &log.lines {
+        match &line.item {
+            RolloutItem::ResponseItem(item) => match item {
+                ResponseItem::Message { role, content } => {
+                    blocks.push(Block::new(capitalize(role)).text(message_text(content)));
+                }
+                ResponseItem::FunctionCall {
+                    name, arguments, ..
+                } if !has_events => {
+                    let command = serde_json::from_str::<serde_json::Value>(arguments)
+                        .ok()
+                        .and_then(|args| {
+                            let command = args.get("command")?.as_array()?;
+                            command
+                                .iter()
+                                .map(|arg| arg.as_str().map(str::to_string))
+                                .collect::<Option<Vec<_>>>()
+                        });
+                    blocks.push(match command {
+                        Some(command) => Block::new("Command").code("sh", escape_command(&command)),
+                        None => Block::new(format!("Tool call: {name}")).code("json", arguments),
+                    });
+                }
+                ResponseItem::FunctionCallOutput { output, .. } if !has_events => {
+                    blocks.push(Block::new("Output").code("", output.content.clone()));
+                }
+                ResponseItem::FunctionCall { .. }
+                | ResponseItem::FunctionCallOutput { .. }
+                | ResponseItem::LocalShellCall { .. }
+                | ResponseItem::Reasoning { .. }
+                | ResponseItem::Other => {}
+            },
+            RolloutItem::Event(event) => match &event.msg {
+                EventMsg::ExecCommandBegin(begin) => {
+                    open_calls.insert(begin.call_id.clone(), blocks.len());
+                    blocks.push(
+                        Block::new("Command")
+                            .code("sh", escape_command(&begin.command))
+                            .text(format!("in `{}`", begin.cwd.display())),
+                    );
+                }
+                EventMsg::ExecCommandEnd(end) => {
+                    let output = [end.stdout.as_str(), end.stderr.as_str()]
+                        .into_iter()
+                        .filter(|s| !s.is_empty())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    let block = attach(&mut blocks, &open_calls, &end.call_id, "Command");
+                    block.push_code("", output);
+                    block.push_text(format!("exit code {}", end.exit_code));
+                }
+                EventMsg::McpToolCallBegin(begin) => {
+                    open_calls.insert(begin.call_id.clone(), blocks.len());
+                    let arguments = begin
+                        .arguments
+                        .as_ref()
+                        .map(|args| serde_json::to_string_pretty(args).unwrap_or_default())
+                        .unwrap_or_default();
+                    blocks.push(
+                        Block::new(format!("Tool call: {}.{}", begin.server, begin.tool))
+                            .code("json", arguments),
+                    );
+                }
+                EventMsg::McpToolCallEnd(end) => {
+                    let result = end
+                        .result
+                        .as_ref()
+                        .map(|result| serde_json::to_string_pretty(result).unwrap_or_default())
+                        .unwrap_or_default();
+                    let block = attach(&mut blocks, &open_calls, &end.call_id, "Tool call");
+                    block.push_code("json", result);
+                    if !end.success {
+                        block.push_text("failed");
+                    }
+                }
+                EventMsg::ExecApprovalRequest(request) => {
+                    let mut block = Block::new("Approval requested")
+                        .code("sh", escape_command(&request.command))
+                        .text(format!("in `{}`", request.cwd.display()));
+                    if let Some(reason) = &request.reason {
+                        block.push_text(reason.clone());
+                    }
+                    if let Some(permission) = request
+                        .denial
+                        .as_ref()
+                        .and_then(|denial| denial.suggested_permission())
+                    {
+                        block.push_text(format!("suggested permission: `{permission}`"));
+                    }
+                    blocks.push(block);
+                }
+                EventMsg::ApplyPatchApprovalRequest(request) => {
+                    let mut block = Block::new("Patch approval requested");
+                    if let Some(reason) = &request.reason {
+                        block.push_text(reason.clone());
+                    }
+                    push_changes(&mut block, &request.changes);
+                    blocks.push(block);
+                }
+                EventMsg::PatchApplyBegin(begin) => {
+                    open_calls.insert(begin.call_id.clone(), blocks.len());
+                    let mut block = Block::new("Patch");
+                    push_changes(&mut block, &begin.changes);
+                    blocks.push(block);
+                }
+                EventMsg::PatchApplyEnd(end) => {
+                    let block = attach(&mut blocks, &open_calls, &end.call_id, "Patch");
+                    block.push_text(if end.success {
+                        "applied"
+                    } else {
+                        "failed to apply"
+                    });
+                    block.push_code("", end.stderr.clone());
+                }
+                EventMsg::Error(error) => {
+                    blocks.push(Block::new("Error").text(error.message.clone()));
+                }
+                EventMsg::BackgroundEvent(event) => {
+                    blocks.push(Block::new("Note").text(event.message.clone()));
+                }
+                EventMsg::TaskStarted
+                | EventMsg::TaskComplete(_)
+                | EventMsg::AgentMessage(_)
+                | EventMsg::AgentReasoning(_)
+                | EventMsg::SessionConfigured(_)
+                | EventMsg::GetHistoryEntryResponse(_)
+                | EventMsg::SearchHistoryResponse(_)
+                | EventMsg::HistoryCompacted(_) => {}
+            },
+            RolloutItem::Submission(submission) => match &submission.op {
+                Op::ExecApproval { decision, .. } | Op::PatchApproval { decision, .. } => {
+                    blocks.push(Block::new("Decision").text(serde_name(decision)));
+                }
+                Op::Interrupt => blocks.push(Block::new("Interrupted")),
+                _ => {}
+            },
+        }
+    }
+    blocks
+}
+
+/// Returns the block opened for `call_id`, or a new one if the matching
+/// begin event is missing.
+fn attach<'a>(
+    blocks: &'a mut Vec<Block>,
+    open_calls: &HashMap<String, usize>,
+    call_id: &str,
+    heading: &str,
+) -> &'a mut Block {
+    let index = match open_calls.get(call_id) {
+        Some(&index) if index < blocks.len() => index,
+        _ => {
+            blocks.push(Block::new(heading));
+            blocks.len() - 1
+        }
+    };
+    &mut blocks[index]
+}
+
+fn push_changes(block: &mut Block, changes: &HashMap<PathBuf, FileChange>) {
+    let mut changes: Vec<_> = changes.iter().collect();
+    changes.sort_by(|a, b| a.0.cmp(b.0));
+    for (path, change) in changes {
+        let path = path.display();
+        match change {
+            FileChange::Add { content } => {
+                let added: String = content.lines().map(|line| format!("+{line}\n")).collect();
+                block.push_code("diff", format!("--- /dev/null\n+++ {path}\n{added}"));
+            }
+            FileChange::Delete => block.push_text(format!("Deleted `{path}`")),
+            FileChange::Update {
+                unified_diff,
+                move_path,
+            } => {
+                let new_path = move_path
+                    .as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|| path.to_string());
+                block.push_code(
+                    "diff",
+                    format!("--- {path}\n+++ {new_path}\n{unified_diff}"),
+                );
+            }
+        }
+    }
+}
+
+/// Header lines describing the session, as `(label, value)` pairs.
+fn metadata(log: &SessionLog) -> Vec<(&'static str, String)> {
+    let meta = &log.meta;
+    let mut rows = vec![("Started", meta.timestamp.clone())];
+    if let Some(config) = &meta.config {
+        rows.push(("Model", config.model.clone()));
+        rows.push(("Provider", config.provider.name.clone()));
+        rows.push(("Working directory", config.cwd.display().to_string()));
+        rows.push(("Approval policy", serde_name(&config.approval_policy)));
+        if !config.project_docs.is_empty() {
+            let docs = config
+                .project_docs
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            rows.push(("Project docs", docs));
+        }
+    }
+    if let Some(git) = &meta.git {
+        let git = match (&git.branch, &git.commit_hash) {
+            (Some(branch), Some(commit)) => format!("{branch} @ {commit}"),
+            (Some(branch), None) => branch.clone(),
+            (None, Some(commit)) => commit.clone(),
+            (None, None) => String::new(),
+        };
+        rows.push(("Git", git));
+    }
+    rows
+}
+
+fn render_markdown(log: &SessionLog) -> String {
+    let mut out = format!("# Session {}\n\n", log.meta.id);
+    for (label, value) in metadata(log) {
+        out.push_str(&format!("- **{label}:** {value}\n"));
+    }
+    for block in transcript(log) {
+        out.push_str(&format!("\n## {}\n", block.heading));
+        for part in block.parts {
+            match part {
+                Part::Text(text) => out.push_str(&format!("\n{}\n", text.trim_end())),
+                Part::Code { language, text } => {
+                    let fence = code_fence(&text);
+                    out.push_str(&format!(
+                        "\n{fence}{language}\n{}\n{fence}\n",
+                        text.trim_end()
+                    ));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// A backtick fence longer than any run of backticks inside `text`.
+fn code_fence(text: &str) -> String {
+    let mut longest = 0;
+    let mut current = 0;
+    for c in text.chars() {
+        if c == '`' {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn render_html(log: &SessionLog) -> String {
+    let title = format!("Session {}", log.meta.id);
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n{HTML_STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n<ul>\n"
+    );
+    for (label, value) in metadata(log) {
+        out.push_str(&format!(
+            "<li><strong>{label}:</strong> {}</li>\n",
+            escape_html(&value)
+        ));
+    }
+    out.push_str("</ul>\n");
+    for block in transcript(log) {
+        out.push_str(&format!(
+            "<section>\n<h2>{}</h2>\n",
+            escape_html(&block.heading)
+        ));
+        for part in block.parts {
+            match part {
+                Part::Text(text) => {
+                    out.push_str(&format!("<p>{}</p>\n", escape_html(text.trim_end())));
+                }
+                Part::Code { language, text } => {
+                    let class = if language.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" class=\"language-{language}\"")
+                    };
+                    out.push_str(&format!(
+                        "<pre><code{class}>{}</code></pre>\n",
+                        escape_html(text.trim_end())
+                    ));
+                }
+            }
+        }
+        out.push_str("</section>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+const HTML_STYLE: &str = "body { font-family: sans-serif; max-width: 60rem; margin: 2rem auto; }
+p { white-space: pre-wrap; }
+pre { background: #f5f5f5; padding: 0.5rem; overflow-x: auto; }
+";
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn escape_command(command: &[String]) -> String {
+    try_join(command.iter().map(|s| s.as_str())).unwrap_or_else(|_| command.join(" "))
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// The serialized name of a unit enum variant, e.g. `on-failure`.
+fn serde_name(value: &impl Serialize) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    const SESSION_ID: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+
+    fn rollout() -> String {
+        format!(
+            r#"{{"version":1,"id":"{SESSION_ID}","timestamp":"2025-05-07T17:24:21.000Z","config":{{"model":"o3","provider":{{"name":"OpenAI","base_url":"https://api.openai.com/v1","env_key":"OPENAI_API_KEY","env_key_instructions":null,"wire_api":"responses"}},"cwd":"/repo","approval_policy":"on-failure","sandbox_policy":{{"permissions":["disk-full-read-access"]}},"disable_response_storage":false}},"git":{{"commit_hash":"abc123","branch":"main"}}}}
+{{"timestamp":"2025-05-07T17:24:22.000Z","type":"response_item","payload":{{"type":"message","role":"user","content":[{{"type":"input_text","text":"list the files"}}]}}}}
+{{"timestamp":"2025-05-07T17:24:23.000Z","type":"response_item","payload":{{"type":"function_call","name":"shell","arguments":"{{\"command\":[\"ls\"]}}","call_id":"c1"}}}}
+{{"timestamp":"2025-05-07T17:24:24.000Z","type":"event","payload":{{"id":"1","msg":{{"type":"exec_command_begin","call_id":"c1","command":["ls","-l"],"cwd":"/repo"}}}}}}
+{{"timestamp":"2025-05-07T17:24:25.000Z","type":"event","payload":{{"id":"1","msg":{{"type":"exec_command_end","call_id":"c1","stdout":"README.md\n","stderr":"","exit_code":0}}}}}}
+{{"timestamp":"2025-05-07T17:26:21.000Z","type":"response_item","payload":{{"type":"message","role":"assistant","content":[{{"type":"output_text","text":"There is a <README>."}}]}}}}
+"#
+        )
+    }
+
+    fn write_rollout(seeky_home: &Path, contents: &str) -> PathBuf {
+        let dir = sessions_dir(seeky_home);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(format!("rollout-2025-05-07T17-24-21-{SESSION_ID}.jsonl"));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn lists_and_finds_sessions() {
+        let seeky_home = TempDir::new().unwrap();
+        let path = write_rollout(seeky_home.path(), &rollout());
+        // Unrelated and corrupt files are ignored.
+        std::fs::write(sessions_dir(seeky_home.path()).join("notes.txt"), "hi").unwrap();
+        std::fs::write(
+            sessions_dir(seeky_home.path()).join("rollout-corrupt.jsonl"),
+            "not json",
+        )
+        .unwrap();
+
+        let summaries = list_sessions(seeky_home.path(), None).unwrap();
+        assert_eq!(
+            vec![SessionSummary {
+                id: Uuid::parse_str(SESSION_ID).unwrap(),
+                path: path.clone(),
+                started_at: "2025-05-07T17:24:21.000Z".to_string(),
+                cwd: Some(PathBuf::from("/repo")),
+                model: Some("o3".to_string()),
+                first_prompt: Some("list the files".to_string()),
+                duration_secs: Some(120),
+            }],
+            summaries
+        );
+
+        assert_eq!(
+            1,
+            list_sessions(seeky_home.path(), Some("readme"))
+                .unwrap()
+                .len()
+        );
+        assert!(
+            list_sessions(seeky_home.path(), Some("nope"))
+                .unwrap()
+                .is_empty()
+        );
+
+        assert_eq!(path, find_session(seeky_home.path(), "67E55044").unwrap());
+        assert_eq!(
+            ErrorKind::NotFound,
+            find_session(seeky_home.path(), "deadbeef")
+                .unwrap_err()
+                .kind()
+        );
+    }
+
+    #[test]
+    fn prunes_only_old_sessions() {
+        let seeky_home = TempDir::new().unwrap();
+        let path = write_rollout(seeky_home.path(), &rollout());
+
+        let day = Duration::from_secs(24 * 60 * 60);
+        assert!(
+            prune_sessions(seeky_home.path(), day, false)
+                .unwrap()
+                .is_empty()
+        );
+
+        let old = SystemTime::now() - 2 * day;
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        assert_eq!(
+            vec![path.clone()],
+            prune_sessions(seeky_home.path(), day, true).unwrap()
+        );
+        assert!(path.exists());
+        assert_eq!(
+            vec![path.clone()],
+            prune_sessions(seeky_home.path(), day, false).unwrap()
+        );
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn renders_markdown_transcript() {
+        let log = SessionLog::parse(&rollout()).unwrap();
+        let expected = format!(
+            r#"# Session {SESSION_ID}
+
+- **Started:** 2025-05-07T17:24:21.000Z
+- **Model:** o3
+- **Provider:** OpenAI
+- **Working directory:** /repo
+- **Approval policy:** on-failure
+- **Git:** main @ abc123
+
+## User
+
+list the files
+
+## Command
+
+```sh
+ls -l
+```
+
+in `/repo`
+
+```
+README.md
+```
+
+exit code 0
+
+## Assistant
+
+There is a <README>.
+"#
+        );
+        assert_eq!(
+            expected,
+            export_session(&log, ExportFormat::Markdown).unwrap()
+        );
+
+        let html = export_session(&log, ExportFormat::Html).unwrap();
+        assert!(html.contains("<p>There is a &lt;README&gt;.</p>"));
+        assert!(html.contains("<pre><code class=\"language-sh\">ls -l</code></pre>"));
+    }
+
+    #[test]
+    fn renders_function_calls_for_legacy_rollouts() {
+        let contents = format!(
+            r#"{{"id":"{SESSION_ID}","timestamp":"2025-05-07T17:24:21.000Z"}}
+{{"type":"function_call","name":"shell","arguments":"{{\"command\":[\"cat\",\"a b\"]}}","call_id":"c1"}}
+{{"type":"function_call_output","call_id":"c1","output":"hello"}}
+"#
+        );
+        let log = SessionLog::parse(&contents).unwrap();
+        let markdown = export_session(&log, ExportFormat::Markdown).unwrap();
+        assert!(markdown.contains("## Command\n\n```sh\ncat 'a b'\n```\n"));
+        assert!(markdown.contains("## Output\n\n```\nhello\n```\n"));
+        assert_eq!(
+            None,
+            SessionSummary::new(PathBuf::new(), &log).duration_secs
+        );
+    }
+}
@@ -0,0 +1,350 @@
+//! Snapshot of the user's login shell, taken once when a session starts.
+//!
+//! Shell tool commands run as `bash -lc`, which misses whatever the user's
+//! own shell sets up in its rc files (`nvm`, `pyenv`, `asdf`, ...). The
+//! snapshot runs `$SHELL` as an interactive login shell and records its
+//! exported environment, which then goes through the regular
+//! [`ShellEnvironmentPolicy`](crate::config_types::ShellEnvironmentPolicy)
+//! rules, and, for bash and zsh, a script that recreates its aliases and
+//! functions for commands run with the user's shell.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+use tokio::process::Command;
+use tracing::warn;
+
+use crate::process_group::ProcessGroup;
+
+/// Slow rc files must not hold up the session forever.
+const SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where the shell writes its environment, as NUL-separated `env -0` output.
+const SNAPSHOT_ENV_VAR: &str = "SEEKY_SNAPSHOT_ENV";
+
+/// Where the shell writes the script with its aliases and functions.
+const SNAPSHOT_FUNCTIONS_VAR: &str = "SEEKY_SNAPSHOT_FUNCTIONS";
+
+/// Variables that describe the snapshot's shell process rather than the
+/// user's setup.
+const PROCESS_VARS: &[&str] = &[
+    "_",
+    "OLDPWD",
+    "PWD",
+    "SHLVL",
+    SNAPSHOT_ENV_VAR,
+    SNAPSHOT_FUNCTIONS_VAR,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShellKind {
+    Bash,
+    Zsh,
+    /// Only the environment is captured.
+    Other,
+}
+
+impl ShellKind {
+    fn of(shell: &Path) -> Self {
+        match shell.file_name().and_then(|name| name.to_str()) {
+            Some("bash") => Self::Bash,
+            Some("zsh") => Self::Zsh,
+            _ => Self::Other,
+        }
+    }
+
+    /// Script run by the shell to write the snapshot.
+    fn snapshot_script(self) -> String {
+        let env = format!(r#"env -0 > "${SNAPSHOT_ENV_VAR}""#);
+        let functions = match self {
+            // Aliases are not expanded in non-interactive bash by default.
+            Self::Bash => "echo 'shopt -s expand_aliases'; alias -p; declare -f",
+            Self::Zsh => "alias -L; typeset -f",
+            Self::Other => return env,
+        };
+        format!(r#"{env}; {{ {functions}; }} > "${SNAPSHOT_FUNCTIONS_VAR}""#)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ShellSnapshot {
+    shell: PathBuf,
+    kind: ShellKind,
+    /// The exported environment of the login shell.
+    env: HashMap<String, String>,
+    /// Script that recreates the aliases and functions; removed on drop.
+    functions: Option<PathBuf>,
+}
+
+impl ShellSnapshot {
+    /// Snapshots the user's `$SHELL`, started in `cwd` so that per-directory
+    /// tool versions are picked up. Files are written to `dir`. Failures are
+    /// logged, as commands still work with the environment of this process.
+    pub(crate) async fn capture(cwd: &Path, dir: &Path, name: &str) -> Option<Self> {
+        let Some(shell) = std::env::var_os("SHELL") else {
+            warn!("cannot snapshot the login shell: $SHELL is not set");
+            return None;
+        };
+        let vars = std::env::vars().collect::<Vec<_>>();
+        match Self::capture_shell(Path::new(&shell), cwd, dir, name, vars).await {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) => {
+                warn!("failed to snapshot the login shell {shell:?}: {e}");
+                None
+            }
+        }
+    }
+
+    async fn capture_shell(
+        shell: &Path,
+        cwd: &Path,
+        dir: &Path,
+        name: &str,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> std::io::Result<Self> {
+        let kind = ShellKind::of(shell);
+        std::fs::create_dir_all(dir)?;
+        let env_path = dir.join(format!("{name}.env"));
+        let functions_path = dir.join(format!("{name}.{}", shell_extension(kind)));
+
+        let mut cmd = Command::new(shell);
+        cmd.args(["-l", "-i", "-c", &kind.snapshot_script()])
+            .current_dir(cwd)
+            .env_clear()
+            .envs(vars)
+            .env(SNAPSHOT_ENV_VAR, &env_path)
+            .env(SNAPSHOT_FUNCTIONS_VAR, &functions_path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
+        // Without a controlling terminal, the interactive shell cannot take
+        // over the terminal of the TUI.
+        #[cfg(unix)]
+        unsafe {
+            cmd.pre_exec(|| {
+                if libc::setsid() == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let mut child = cmd.spawn()?;
+        let mut process_group = ProcessGroup::new(&child);
+        let status = match tokio::time::timeout(SNAPSHOT_TIMEOUT, child.wait()).await {
+            Ok(status) => status?,
+            Err(_) => {
+                process_group.terminate(&mut child).await?;
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("timed out after {}s", SNAPSHOT_TIMEOUT.as_secs()),
+                ));
+            }
+        };
+        // Agents started by the rc files keep running, as in a terminal.
+        process_group.disarm();
+
+        let env = std::fs::read(&env_path);
+        let _ = std::fs::remove_file(&env_path);
+        let env = match env {
+            Ok(env) => parse_env(&env),
+            Err(e) => {
+                let _ = std::fs::remove_file(&functions_path);
+                return Err(std::io::Error::new(
+                    e.kind(),
+                    format!("the shell exited with {status} without writing its environment"),
+                ));
+            }
+        };
+        Ok(Self {
+            shell: shell.to_path_buf(),
+            kind,
+            env,
+            functions: functions_path.exists().then_some(functions_path),
+        })
+    }
+
+    /// The exported environment of the login shell.
+    pub(crate) fn env(&self) -> &HashMap<String, String> {
+        &self.env
+    }
+
+    /// Rewrites a `bash -lc <script>` command to run `script` with the user's
+    /// shell after recreating its aliases and functions. Other commands, and
+    /// all commands if the user's shell is neither bash nor zsh (which would
+    /// not understand the script), are returned unchanged.
+    pub(crate) fn wrap_command(&self, command: Vec<String>) -> Vec<String> {
+        if self.kind == ShellKind::Other {
+            return command;
+        }
+        let [bash, flag, script] = command.as_slice() else {
+            return command;
+        };
+        let is_bash = Path::new(bash).file_name().and_then(|name| name.to_str()) == Some("bash");
+        if !is_bash || !matches!(flag.as_str(), "-lc" | "-c") {
+            return command;
+        }
+        let script = match &self.functions {
+            // The newline makes the aliases available to the script, which
+            // would not be the case on the line that defines them.
+            Some(functions) => {
+                let functions = functions.to_string_lossy();
+                let functions = shlex::try_quote(&functions)
+                    .map(|quoted| quoted.to_string())
+                    .unwrap_or_else(|_| functions.to_string());
+                format!(". {functions}\n{script}")
+            }
+            None => script.clone(),
+        };
+        vec![
+            self.shell.to_string_lossy().to_string(),
+            "-c".to_string(),
+            script,
+        ]
+    }
+}
+
+impl Drop for ShellSnapshot {
+    fn drop(&mut self) {
+        if let Some(functions) = &self.functions {
+            let _ = std::fs::remove_file(functions);
+        }
+    }
+}
+
+fn shell_extension(kind: ShellKind) -> &'static str {
+    match kind {
+        ShellKind::Bash | ShellKind::Other => "sh",
+        ShellKind::Zsh => "zsh",
+    }
+}
+
+/// Parses the output of `env -0`.
+fn parse_env(bytes: &[u8]) -> HashMap<String, String> {
+    bytes
+        .split(|b| *b == 0)
+        .filter_map(|entry| {
+            let entry = String::from_utf8_lossy(entry);
+            let (key, value) = entry.split_once('=')?;
+            (!key.is_empty() && !PROCESS_VARS.contains(&key))
+                .then(|| (key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    fn snapshot(shell: &str, functions: Option<&str>) -> ShellSnapshot {
+        ShellSnapshot {
+            shell: PathBuf::from(shell),
+            kind: ShellKind::of(Path::new(shell)),
+            env: HashMap::new(),
+            functions: functions.map(PathBuf::from),
+        }
+    }
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_env_output() {
+        let env = parse_env(b"PATH=/a:/b\0MULTI=x\ny=z\0SHLVL=2\0EMPTY=\0");
+
+        assert_eq!(
+            HashMap::from([
+                ("PATH".to_string(), "/a:/b".to_string()),
+                ("MULTI".to_string(), "x\ny=z".to_string()),
+                ("EMPTY".to_string(), String::new()),
+            ]),
+            env
+        );
+    }
+
+    #[test]
+    fn wraps_bash_commands_only() {
+        let snapshot = snapshot("/bin/zsh", Some("/home/user/.seeky/shell_snapshots/s.zsh"));
+
+        assert_eq!(
+            strings(&[
+                "/bin/zsh",
+                "-c",
+                ". /home/user/.seeky/shell_snapshots/s.zsh\nll src"
+            ]),
+            snapshot.wrap_command(strings(&["bash", "-lc", "ll src"]))
+        );
+        assert_eq!(
+            strings(&["ls", "-l"]),
+            snapshot.wrap_command(strings(&["ls", "-l"]))
+        );
+        assert_eq!(
+            strings(&["bash", "script.sh", "arg"]),
+            snapshot.wrap_command(strings(&["bash", "script.sh", "arg"]))
+        );
+    }
+
+    #[test]
+    fn wraps_without_functions() {
+        let snapshot = snapshot("/bin/bash", None);
+
+        assert_eq!(
+            strings(&["/bin/bash", "-c", "echo hi"]),
+            snapshot.wrap_command(strings(&["/usr/bin/bash", "-c", "echo hi"]))
+        );
+    }
+
+    #[test]
+    fn does_not_wrap_for_other_shells() {
+        let snapshot = snapshot("/usr/bin/fish", None);
+
+        assert_eq!(
+            strings(&["bash", "-lc", "echo $HOME"]),
+            snapshot.wrap_command(strings(&["bash", "-lc", "echo $HOME"]))
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn captures_env_aliases_and_functions_of_login_bash() {
+        let home = tempfile::tempdir().unwrap();
+        std::fs::write(
+            home.path().join(".bash_profile"),
+            "export PATH=\"$HOME/tools/bin:$PATH\"\nalias greet='echo hello'\nshout() { echo \"$1!\"; }\n",
+        )
+        .unwrap();
+        let dir = home.path().join("shell_snapshots");
+        let vars = [
+            ("HOME", home.path().to_string_lossy().to_string()),
+            ("PATH", "/usr/bin:/bin".to_string()),
+        ]
+        .map(|(key, value)| (key.to_string(), value));
+
+        let snapshot =
+            ShellSnapshot::capture_shell(Path::new("/bin/bash"), home.path(), &dir, "s", vars)
+                .await
+                .unwrap();
+
+        let path = snapshot.env().get("PATH").unwrap();
+        assert!(path.starts_with(&format!("{}/tools/bin:", home.path().display())));
+        assert!(!snapshot.env().contains_key(SNAPSHOT_ENV_VAR));
+        assert!(!dir.join("s.env").exists());
+
+        let command = snapshot.wrap_command(strings(&["bash", "-lc", "greet; shout hey"]));
+        let output = std::process::Command::new(&command[0])
+            .args(&command[1..])
+            .env_clear()
+            .output()
+            .unwrap();
+        assert_eq!("hello\nhey!\n", String::from_utf8_lossy(&output.stdout));
+
+        drop(snapshot);
+        assert!(!dir.join("s.sh").exists());
+    }
+}
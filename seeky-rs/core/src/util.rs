@@ -1,3 +1,5 @@
+use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -48,19 +50,23 @@ pub(crate) fn backoff(attempt: u64) -> Duration {
 /// directory. If you need Seeky to work from such a checkout simply pass the
 /// `--allow-no-git-exec` CLI flag that disables the repo requirement.
 pub fn is_inside_git_repo(config: &Config) -> bool {
-    let mut dir = config.cwd.to_path_buf();
+    find_git_root(&config.cwd).is_some()
+}
+
+/// The closest ancestor of `dir`, or `dir` itself, that contains a `.git`
+/// file or directory. The same caveats as for [`is_inside_git_repo`] apply.
+pub fn find_git_root(dir: &Path) -> Option<PathBuf> {
+    let mut dir = dir.to_path_buf();
 
     loop {
         if dir.join(".git").exists() {
-            return true;
+            return Some(dir);
         }
 
         // Pop one component (go up one directory).  `pop` returns false when
         // we have reached the filesystem root.
         if !dir.pop() {
-            break;
+            return None;
         }
     }
-
-    false
 }
@@ -0,0 +1,125 @@
+#![cfg(unix)]
+#![expect(clippy::unwrap_used)]
+
+//! Resource limits also apply to commands that run without a sandbox.
+
+use std::sync::Arc;
+
+use seeky_core::config_types::ResourceLimits;
+use seeky_core::error::SandboxErr;
+use seeky_core::error::SeekyErr;
+use tokio::sync::Notify;
+
+mod exec_support;
+
+use exec_support::run_unsandboxed;
+
+/// Generous, as the limits under test end the commands long before.
+const TIMEOUT_MS: u64 = 5_000;
+
+#[tokio::test]
+async fn file_size_limit_is_reported() {
+    let tmp = tempfile::tempdir().unwrap();
+    let limits = ResourceLimits {
+        file_size_bytes: Some(1024),
+        ..Default::default()
+    };
+
+    let result = run_unsandboxed(
+        "head -c 4096 /dev/zero > big",
+        tmp.path(),
+        TIMEOUT_MS,
+        limits,
+        Arc::new(Notify::new()),
+    )
+    .await;
+
+    assert!(
+        matches!(
+            result,
+            Err(SeekyErr::Sandbox(SandboxErr::FileSizeLimitExceeded(
+                1024,
+                _
+            )))
+        ),
+        "{result:?}"
+    );
+    assert_eq!(
+        1024,
+        std::fs::metadata(tmp.path().join("big")).unwrap().len()
+    );
+}
+
+#[tokio::test]
+async fn cpu_time_limit_is_reported() {
+    let tmp = tempfile::tempdir().unwrap();
+    let limits = ResourceLimits {
+        cpu_seconds: Some(1),
+        ..Default::default()
+    };
+
+    let result = run_unsandboxed(
+        "while :; do :; done",
+        tmp.path(),
+        TIMEOUT_MS,
+        limits,
+        Arc::new(Notify::new()),
+    )
+    .await;
+
+    assert!(
+        matches!(
+            result,
+            Err(SeekyErr::Sandbox(SandboxErr::CpuTimeLimitExceeded(1, _)))
+        ),
+        "{result:?}"
+    );
+}
+
+#[tokio::test]
+async fn exit_codes_are_not_mistaken_for_limit_signals() {
+    let tmp = tempfile::tempdir().unwrap();
+    let limits = ResourceLimits {
+        cpu_seconds: Some(10),
+        file_size_bytes: Some(1024),
+        ..Default::default()
+    };
+
+    // 128 + SIGXCPU and 128 + SIGXFSZ.
+    for code in [152, 153] {
+        let output = run_unsandboxed(
+            &format!("exit {code}"),
+            tmp.path(),
+            TIMEOUT_MS,
+            limits.clone(),
+            Arc::new(Notify::new()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(code, output.exit_code);
+    }
+}
+
+#[tokio::test]
+async fn commands_within_their_limits_are_unaffected() {
+    let tmp = tempfile::tempdir().unwrap();
+    let limits = ResourceLimits {
+        cpu_seconds: Some(10),
+        file_size_bytes: Some(1024),
+        open_files: Some(64),
+        ..Default::default()
+    };
+
+    let output = run_unsandboxed(
+        "echo ok > small && ulimit -n",
+        tmp.path(),
+        TIMEOUT_MS,
+        limits,
+        Arc::new(Notify::new()),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(0, output.exit_code);
+    assert_eq!("64\n", output.stdout);
+}
@@ -0,0 +1,214 @@
+#![cfg(unix)]
+#![expect(clippy::unwrap_used)]
+
+//! Timed out, interrupted and aborted commands take the processes they
+//! started in the background with them.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use seeky_core::error::SandboxErr;
+use seeky_core::error::SeekyErr;
+use tokio::sync::Notify;
+
+mod exec_support;
+
+use exec_support::run_unsandboxed;
+
+/// Waits for the script to write the pid of its background process to `pid`.
+async fn background_pid(cwd: &Path) -> u32 {
+    let path = cwd.join("pid");
+    for _ in 0..500 {
+        if let Ok(pid) = std::fs::read_to_string(&path) {
+            if let Ok(pid) = pid.trim().parse() {
+                return pid;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("the command did not start its background process");
+}
+
+/// Whether `pid` is alive. Killed processes may linger as zombies if nothing
+/// reaps them, e.g. in containers without an init process.
+fn is_running(pid: u32) -> bool {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "stat=", "-p", &pid.to_string()])
+        .output()
+        .unwrap();
+    let stat = String::from_utf8_lossy(&output.stdout);
+    let stat = stat.trim();
+    !stat.is_empty() && !stat.starts_with('Z')
+}
+
+/// Signals are delivered asynchronously, so give the process a moment.
+async fn assert_killed(pid: u32) {
+    for _ in 0..100 {
+        if !is_running(pid) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("background process {pid} is still running");
+}
+
+#[tokio::test]
+async fn timeout_kills_background_grandchildren() {
+    let tmp = tempfile::tempdir().unwrap();
+    let start = Instant::now();
+
+    let result = run_unsandboxed(
+        "sleep 30 & echo $! > pid; wait",
+        tmp.path(),
+        500,
+        Default::default(),
+        Arc::new(Notify::new()),
+    )
+    .await;
+
+    assert!(
+        matches!(result, Err(SeekyErr::Sandbox(SandboxErr::Timeout))),
+        "{result:?}"
+    );
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert_killed(background_pid(tmp.path()).await).await;
+}
+
+#[tokio::test]
+async fn background_process_holding_the_output_is_killed_once_the_command_exits() {
+    let tmp = tempfile::tempdir().unwrap();
+    let start = Instant::now();
+
+    // The shell exits right away, but `sleep` keeps stdout and stderr open.
+    let output = run_unsandboxed(
+        "sleep 30 & echo $! > pid; echo started; exit 0",
+        tmp.path(),
+        30_000,
+        Default::default(),
+        Arc::new(Notify::new()),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(0, output.exit_code);
+    assert_eq!("started\n", output.stdout);
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert_killed(background_pid(tmp.path()).await).await;
+}
+
+#[tokio::test]
+async fn background_process_holding_the_output_keeps_the_exit_status() {
+    let tmp = tempfile::tempdir().unwrap();
+
+    let output = run_unsandboxed(
+        "sleep 30 & echo $! > pid; exit 3",
+        tmp.path(),
+        30_000,
+        Default::default(),
+        Arc::new(Notify::new()),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(3, output.exit_code);
+    assert_killed(background_pid(tmp.path()).await).await;
+}
+
+#[tokio::test]
+async fn interrupt_kills_background_grandchildren() {
+    let tmp = tempfile::tempdir().unwrap();
+    let ctrl_c = Arc::new(Notify::new());
+
+    let call = tokio::spawn({
+        let cwd = tmp.path().to_path_buf();
+        let ctrl_c = ctrl_c.clone();
+        async move {
+            run_unsandboxed(
+                "sleep 30 & echo $! > pid; wait",
+                &cwd,
+                30_000,
+                Default::default(),
+                ctrl_c,
+            )
+            .await
+        }
+    });
+    let pid = background_pid(tmp.path()).await;
+    ctrl_c.notify_waiters();
+    let result = call.await.unwrap();
+
+    assert!(
+        matches!(result, Err(SeekyErr::Sandbox(SandboxErr::Signal(9)))),
+        "{result:?}"
+    );
+    assert_killed(pid).await;
+}
+
+#[tokio::test]
+async fn grandchildren_ignoring_sigterm_are_killed() {
+    let tmp = tempfile::tempdir().unwrap();
+
+    let result = run_unsandboxed(
+        r#"sh -c 'trap "" TERM; sleep 30' & echo $! > pid; wait"#,
+        tmp.path(),
+        500,
+        Default::default(),
+        Arc::new(Notify::new()),
+    )
+    .await;
+
+    assert!(
+        matches!(result, Err(SeekyErr::Sandbox(SandboxErr::Timeout))),
+        "{result:?}"
+    );
+    assert_killed(background_pid(tmp.path()).await).await;
+}
+
+#[tokio::test]
+async fn aborted_calls_kill_background_grandchildren() {
+    let tmp = tempfile::tempdir().unwrap();
+
+    let call = tokio::spawn({
+        let cwd = tmp.path().to_path_buf();
+        async move {
+            run_unsandboxed(
+                "sleep 30 & echo $! > pid; wait",
+                &cwd,
+                30_000,
+                Default::default(),
+                Arc::new(Notify::new()),
+            )
+            .await
+        }
+    });
+    let pid = background_pid(tmp.path()).await;
+    call.abort();
+    assert!(call.await.unwrap_err().is_cancelled());
+
+    assert_killed(pid).await;
+}
+
+#[tokio::test]
+async fn background_process_with_redirected_output_is_left_running() {
+    let tmp = tempfile::tempdir().unwrap();
+
+    let output = run_unsandboxed(
+        "sleep 30 > /dev/null 2>&1 & echo $! > pid",
+        tmp.path(),
+        5_000,
+        Default::default(),
+        Arc::new(Notify::new()),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(0, output.exit_code);
+    let pid = background_pid(tmp.path()).await;
+    assert!(is_running(pid));
+    std::process::Command::new("kill")
+        .arg(pid.to_string())
+        .status()
+        .unwrap();
+}
@@ -0,0 +1,41 @@
+// Helpers shared by the integration tests that run commands through
+// `process_exec_tool_call`.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use seeky_core::config_types::ResourceLimits;
+use seeky_core::config_types::ShellEnvironmentPolicy;
+use seeky_core::error::Result;
+use seeky_core::exec::ExecParams;
+use seeky_core::exec::ExecToolCallOutput;
+use seeky_core::exec::SandboxType;
+use seeky_core::exec::process_exec_tool_call;
+use seeky_core::exec_env::create_env;
+use seeky_core::protocol::SandboxPolicy;
+use tokio::sync::Notify;
+
+/// Runs `bash -c script` in `cwd` without a sandbox.
+pub async fn run_unsandboxed(
+    script: &str,
+    cwd: &Path,
+    timeout_ms: u64,
+    limits: ResourceLimits,
+    ctrl_c: Arc<Notify>,
+) -> Result<ExecToolCallOutput> {
+    let params = ExecParams {
+        command: vec!["bash".to_string(), "-c".to_string(), script.to_string()],
+        cwd: cwd.to_path_buf(),
+        timeout_ms: Some(timeout_ms),
+        env: create_env(&ShellEnvironmentPolicy::default()),
+        limits,
+    };
+    process_exec_tool_call(
+        params,
+        SandboxType::None,
+        ctrl_c,
+        &SandboxPolicy::new_full_auto_policy(),
+        &None,
+    )
+    .await
+}
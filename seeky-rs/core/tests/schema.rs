@@ -0,0 +1,33 @@
+//! Fails when the checked-in schemas under `seeky-rs/schema/` no longer match
+//! the Rust types they are generated from.
+
+#![allow(clippy::expect_used)]
+
+use std::path::PathBuf;
+
+use seeky_core::schema::SchemaKind;
+use seeky_core::schema::schema_files;
+
+fn assert_schema_up_to_date(kind: SchemaKind) {
+    let schema_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../schema");
+    for (file_name, expected) in schema_files(kind).expect("schema should serialize") {
+        let path = schema_dir.join(&file_name);
+        let actual = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            actual == expected,
+            "{} is out of date; regenerate it with `cargo run --bin seeky -- schema {} --out schema` from seeky-rs/",
+            path.display(),
+            kind.file_stem(),
+        );
+    }
+}
+
+#[test]
+fn config_schema_is_up_to_date() {
+    assert_schema_up_to_date(SchemaKind::Config);
+}
+
+#[test]
+fn protocol_schema_is_up_to_date() {
+    assert_schema_up_to_date(SchemaKind::Protocol);
+}
@@ -1,5 +1,6 @@
 use clap::Parser;
 use clap::ValueEnum;
+use seeky_common::CliConfigOverrides;
 use seeky_common::SandboxPermissionOption;
 use std::path::PathBuf;
 
@@ -45,6 +46,9 @@ pub struct Cli {
     #[arg(long = "output-last-message")]
     pub last_message_file: Option<PathBuf>,
 
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
     /// Initial instructions for the agent.
     pub prompt: String,
 }
@@ -404,6 +404,7 @@ impl EventProcessor {
                     model,
                     history_log_id: _,
                     history_entry_count: _,
+                    project_docs,
                 } = session_configured_event;
 
                 ts_println!(
@@ -413,9 +414,14 @@ impl EventProcessor {
                 );
 
                 ts_println!("model: {}", model);
+                for doc in project_docs {
+                    ts_println!("AGENTS.md: {}", doc.display());
+                }
                 println!();
             }
-            EventMsg::GetHistoryEntryResponse(_) => {
+            EventMsg::GetHistoryEntryResponse(_)
+            | EventMsg::SearchHistoryResponse(_)
+            | EventMsg::HistoryCompacted(_) => {
                 // Currently ignored in exec output.
             }
         }
@@ -38,6 +38,7 @@ pub async fn run_main(cli: Cli, seeky_linux_sandbox_exe: Option<PathBuf>) -> any
         color,
         last_message_file,
         prompt,
+        config_overrides,
     } = cli;
 
     let (stdout_with_ansi, stderr_with_ansi) = match color {
@@ -72,7 +73,10 @@ pub async fn run_main(cli: Cli, seeky_linux_sandbox_exe: Option<PathBuf>) -> any
         model_provider: None,
         seeky_linux_sandbox_exe,
     };
-    let config = Config::load_with_overrides(overrides)?;
+    let cli_overrides = config_overrides
+        .parse_overrides()
+        .map_err(|e| anyhow::anyhow!("Error parsing -c overrides: {e}"))?;
+    let config = Config::load_with_cli_overrides(cli_overrides, overrides)?;
     // Print the effective configuration so users can see what Seeky is using.
     print_config_summary(&config, stdout_with_ansi);
 
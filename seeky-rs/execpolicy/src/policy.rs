@@ -15,6 +15,7 @@ use crate::program::PositiveExampleFailedCheck;
 pub struct Policy {
     programs: MultiMap<String, ProgramSpec>,
     forbidden_program_regexes: Vec<ForbiddenProgramRegex>,
+    forbidden_substrings: Vec<String>,
     forbidden_substrings_pattern: Option<Regex>,
 }
 
@@ -24,23 +25,47 @@ impl Policy {
         forbidden_program_regexes: Vec<ForbiddenProgramRegex>,
         forbidden_substrings: Vec<String>,
     ) -> std::result::Result<Self, RegexError> {
-        let forbidden_substrings_pattern = if forbidden_substrings.is_empty() {
-            None
-        } else {
-            let escaped_substrings = forbidden_substrings
-                .iter()
-                .map(|s| regex::escape(s))
-                .collect::<Vec<_>>()
-                .join("|");
-            Some(Regex::new(&format!("({escaped_substrings})"))?)
-        };
+        let forbidden_substrings_pattern = forbidden_substrings_pattern(&forbidden_substrings)?;
         Ok(Self {
             programs,
             forbidden_program_regexes,
+            forbidden_substrings,
             forbidden_substrings_pattern,
         })
     }
 
+    /// Layers `other` on top of this policy, e.g. a user's `.policy` file on
+    /// top of `default.policy`. The program specs of `other` are tried first,
+    /// so it can forbid invocations this policy allows, and the forbidden
+    /// programs and substrings of both policies apply.
+    pub fn extend(&mut self, other: Policy) -> std::result::Result<(), RegexError> {
+        let Policy {
+            programs,
+            forbidden_program_regexes,
+            forbidden_substrings,
+            forbidden_substrings_pattern: _,
+        } = other;
+        for (program, mut specs) in programs {
+            if let Some(existing) = self.programs.remove(&program) {
+                specs.extend(existing);
+            }
+            self.programs.insert_many(program, specs);
+        }
+        self.forbidden_program_regexes
+            .extend(forbidden_program_regexes);
+        self.forbidden_substrings.extend(forbidden_substrings);
+        self.forbidden_substrings_pattern =
+            forbidden_substrings_pattern(&self.forbidden_substrings)?;
+        Ok(())
+    }
+
+    /// Drops the program specs that allow invocations, so that this policy can
+    /// only forbid commands, e.g. before layering a policy file that is not
+    /// trusted to mark commands as safe.
+    pub fn retain_forbidden(&mut self) {
+        self.programs.retain(|_, spec| spec.is_forbidden());
+    }
+
     pub fn check(&self, exec_call: &ExecCall) -> Result<MatchedExec> {
         let ExecCall { program, args } = &exec_call;
         for ForbiddenProgramRegex { regex, reason } in &self.forbidden_program_regexes {
@@ -101,3 +126,17 @@ impl Policy {
         violations
     }
 }
+
+fn forbidden_substrings_pattern(
+    forbidden_substrings: &[String],
+) -> std::result::Result<Option<Regex>, RegexError> {
+    if forbidden_substrings.is_empty() {
+        return Ok(None);
+    }
+    let escaped_substrings = forbidden_substrings
+        .iter()
+        .map(|s| regex::escape(s))
+        .collect::<Vec<_>>()
+        .join("|");
+    Ok(Some(Regex::new(&format!("({escaped_substrings})"))?))
+}
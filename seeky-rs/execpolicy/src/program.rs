@@ -64,6 +64,12 @@ impl ProgramSpec {
             should_not_match,
         }
     }
+
+    /// Whether invocations matched by this spec are forbidden rather than
+    /// allowed.
+    pub fn is_forbidden(&self) -> bool {
+        self.forbidden.is_some()
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize)]
@@ -0,0 +1,129 @@
+#![expect(clippy::expect_used)]
+extern crate seeky_execpolicy;
+
+use seeky_execpolicy::Error;
+use seeky_execpolicy::ExecCall;
+use seeky_execpolicy::Forbidden;
+use seeky_execpolicy::MatchedExec;
+use seeky_execpolicy::Policy;
+use seeky_execpolicy::PolicyParser;
+use seeky_execpolicy::get_default_policy;
+
+fn setup(user_policy: &str) -> Policy {
+    let mut policy = get_default_policy().expect("failed to load default policy");
+    let user_policy = PolicyParser::new("#user", user_policy)
+        .parse()
+        .expect("failed to parse user policy");
+    policy
+        .extend(user_policy)
+        .expect("failed to extend the default policy");
+    policy
+}
+
+#[test]
+fn test_extend_adds_programs() {
+    let policy = setup(
+        r#"
+define_program(
+    program="wc",
+    options=[flag("-l")],
+    args=[ARG_RFILES],
+)
+"#,
+    );
+
+    let wc = ExecCall::new("wc", &["-l", "Cargo.toml"]);
+    assert!(matches!(policy.check(&wc), Ok(MatchedExec::Match { .. })));
+
+    // The programs of the default policy are still there.
+    let pwd = ExecCall::new("pwd", &[]);
+    assert!(matches!(policy.check(&pwd), Ok(MatchedExec::Match { .. })));
+}
+
+#[test]
+fn test_extend_takes_precedence_over_the_default_policy() {
+    let policy = setup(
+        r#"
+define_program(
+    program="cp",
+    args=[ARG_RFILES, ARG_WFILE],
+    forbidden="use `git mv` instead",
+)
+
+forbid_program_regex("^curl$", "no network access")
+forbid_substrings(["id_rsa"])
+"#,
+    );
+
+    let cp = ExecCall::new("cp", &["foo", "bar"]);
+    assert!(matches!(
+        policy.check(&cp),
+        Ok(MatchedExec::Forbidden { reason, .. }) if reason == "use `git mv` instead"
+    ));
+
+    let curl = ExecCall::new("curl", &["https://example.com"]);
+    assert!(matches!(
+        policy.check(&curl),
+        Ok(MatchedExec::Forbidden {
+            cause: Forbidden::Program { .. },
+            ..
+        })
+    ));
+
+    let cat = ExecCall::new("cat", &["/home/user/.ssh/id_rsa"]);
+    assert!(matches!(
+        policy.check(&cat),
+        Ok(MatchedExec::Forbidden {
+            cause: Forbidden::Arg { .. },
+            ..
+        })
+    ));
+
+    let unknown = ExecCall::new("make", &[]);
+    assert_eq!(
+        Err(Error::NoSpecForProgram {
+            program: "make".into()
+        }),
+        policy.check(&unknown)
+    );
+}
+
+#[test]
+fn test_retain_forbidden_keeps_only_forbidding_rules() {
+    let mut project_policy = PolicyParser::new(
+        "#project",
+        r#"
+define_program(
+    program="rm",
+    args=[ARG_RFILES],
+)
+
+define_program(
+    program="cp",
+    args=[ARG_RFILES, ARG_WFILE],
+    forbidden="use `git mv` instead",
+)
+"#,
+    )
+    .parse()
+    .expect("failed to parse project policy");
+    project_policy.retain_forbidden();
+    let mut policy = get_default_policy().expect("failed to load default policy");
+    policy
+        .extend(project_policy)
+        .expect("failed to extend the default policy");
+
+    let rm = ExecCall::new("rm", &["-rf", "/"]);
+    assert_eq!(
+        Err(Error::NoSpecForProgram {
+            program: "rm".into()
+        }),
+        policy.check(&rm)
+    );
+
+    let cp = ExecCall::new("cp", &["foo", "bar"]);
+    assert!(matches!(
+        policy.check(&cp),
+        Ok(MatchedExec::Forbidden { reason, .. }) if reason == "use `git mv` instead"
+    ));
+}
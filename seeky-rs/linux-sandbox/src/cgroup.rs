@@ -0,0 +1,115 @@
+//! Memory and process limits enforced by a cgroup v2 leaf.
+//!
+//! Unlike `RLIMIT_AS`, `memory.max` counts the memory the command actually
+//! uses, and unlike `RLIMIT_NPROC`, `pids.max` only counts the processes of
+//! the command rather than every process of the user. A leaf can only be
+//! created where the cgroup tree has been delegated to the current user (as
+//! systemd does for user sessions) or when running as root, so the helper
+//! falls back to `setrlimit` otherwise.
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use seeky_core::config_types::ResourceLimits;
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// Prefix of the leaves created by the helper. Leaves left behind by earlier
+/// commands are removed once they are empty.
+const LEAF_PREFIX: &str = "seeky-sandbox-";
+
+/// Moves the current process into a new cgroup leaf enforcing the memory and
+/// process limits of `limits`, when the controllers for them are available.
+/// Returns the limits that still have to be applied with `setrlimit`.
+pub(crate) fn enter_cgroup_leaf(limits: &ResourceLimits) -> ResourceLimits {
+    let mut remaining = limits.clone();
+    let wanted = [
+        ("memory", limits.memory_bytes),
+        ("pids", limits.max_processes),
+    ];
+    if wanted.iter().all(|(_, limit)| limit.is_none()) {
+        return remaining;
+    }
+
+    let Some(parent) = delegated_parent() else {
+        return remaining;
+    };
+    let Ok(subtree_control) = fs::read_to_string(parent.join("cgroup.subtree_control")) else {
+        return remaining;
+    };
+    let enabled = subtree_control.split_whitespace().collect::<Vec<_>>();
+    let controllers = wanted
+        .into_iter()
+        .filter_map(|(controller, limit)| Some((controller, limit?)))
+        .filter(|(controller, _)| enabled.contains(controller))
+        .collect::<Vec<_>>();
+    if controllers.is_empty() {
+        return remaining;
+    }
+
+    remove_stale_leaves(&parent);
+    let leaf = parent.join(format!("{LEAF_PREFIX}{}", std::process::id()));
+    if fs::create_dir(&leaf).is_err() {
+        return remaining;
+    }
+    let configured = controllers.iter().all(|(controller, limit)| {
+        let file = match *controller {
+            "memory" => "memory.max",
+            _ => "pids.max",
+        };
+        fs::write(leaf.join(file), limit.to_string()).is_ok()
+    });
+    if configured {
+        // Swapping would let the command exceed `memory.max` indefinitely.
+        let _ = fs::write(leaf.join("memory.swap.max"), "0");
+    }
+    // Writing 0 moves the writing process itself.
+    if !configured || fs::write(leaf.join("cgroup.procs"), "0").is_err() {
+        let _ = fs::remove_dir(&leaf);
+        return remaining;
+    }
+
+    for (controller, _) in controllers {
+        match controller {
+            "memory" => remaining.memory_bytes = None,
+            _ => remaining.max_processes = None,
+        }
+    }
+    remaining
+}
+
+/// The cgroup in which to create the leaf: the current one if it is the root
+/// of the hierarchy, otherwise its parent, since the "no internal processes"
+/// rule prevents controllers from being enabled below a cgroup that has
+/// processes of its own.
+fn delegated_parent() -> Option<PathBuf> {
+    let root = Path::new(CGROUP_ROOT);
+    if !root.join("cgroup.controllers").exists() {
+        // Not a cgroup v2 (unified) hierarchy.
+        return None;
+    }
+    let cgroups = fs::read_to_string("/proc/self/cgroup").ok()?;
+    let current = cgroups
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))?
+        .trim_start_matches('/');
+    if current.is_empty() {
+        return Some(root.to_path_buf());
+    }
+    Some(root.join(current).parent()?.to_path_buf())
+}
+
+/// Leaves are created by processes that exec the command and can therefore
+/// not remove them, so they are cleaned up the next time a leaf is created.
+fn remove_stale_leaves(parent: &Path) {
+    let Ok(entries) = fs::read_dir(parent) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if entry.file_name().to_string_lossy().starts_with(LEAF_PREFIX) {
+            // Fails with EBUSY while the leaf still has processes.
+            let _ = fs::remove_dir(entry.path());
+        }
+    }
+}
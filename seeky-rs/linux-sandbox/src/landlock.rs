@@ -5,6 +5,7 @@ use std::path::PathBuf;
 use seeky_core::error::Result;
 use seeky_core::error::SandboxErr;
 use seeky_core::error::SeekyErr;
+use seeky_core::protocol::ProtectedPath;
 use seeky_core::protocol::SandboxPolicy;
 
 use landlock::ABI;
@@ -25,43 +26,198 @@ use seccompiler::SeccompRule;
 use seccompiler::TargetArch;
 use seccompiler::apply_filter;
 
+/// Folders that stay readable when the policy does not grant full read
+/// access, so that binaries, their shared libraries and the usual
+/// configuration files can still be loaded. Entries that do not exist on the
+/// host are skipped.
+const SYSTEM_READ_ROOTS: &[&str] = &[
+    "/bin",
+    "/sbin",
+    "/usr",
+    "/lib",
+    "/lib32",
+    "/lib64",
+    "/libx32",
+    "/etc",
+    "/opt",
+    "/nix/store",
+    "/dev",
+    "/proc",
+    "/sys",
+    "/run",
+];
+
 /// Apply sandbox policies inside this thread so only the child inherits
 /// them, not the entire CLI process.
+///
+/// When the policy only allows the domains in `NetworkAllowDomains`, the
+/// current process must already be in a network namespace in which only the
+/// proxy can be reached (`in_proxy_network_namespace`); otherwise all network
+/// access is blocked.
+///
+/// `unmounted_protected_paths` are the protected paths that could not be made
+/// read-only with mounts, including those that do not exist, see
+/// [`writable_rules_around_protected_paths`].
 pub(crate) fn apply_sandbox_policy_to_current_thread(
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
+    in_proxy_network_namespace: bool,
+    unmounted_protected_paths: &[ProtectedPath],
 ) -> Result<()> {
     if !sandbox_policy.has_full_network_access() {
-        install_network_seccomp_filter_on_current_thread()?;
+        if in_proxy_network_namespace {
+            apply_proxy_only_network_policy_to_current_thread()?;
+        } else {
+            install_network_seccomp_filter_on_current_thread()?;
+        }
     }
 
+    // Write access anywhere implies read access anywhere, so there is nothing
+    // left to restrict in that case.
     if !sandbox_policy.has_full_disk_write_access() {
-        let writable_roots = sandbox_policy.get_writable_roots_with_cwd(cwd);
-        install_filesystem_landlock_rules_on_current_thread(writable_roots)?;
+        let mut writable_roots = sandbox_policy.get_writable_roots_with_cwd(cwd);
+        let readable_roots = if sandbox_policy.has_full_disk_read_access() {
+            None
+        } else {
+            Some(readable_roots(sandbox_policy, cwd))
+        };
+        if !unmounted_protected_paths.is_empty() {
+            writable_roots =
+                writable_rules_around_protected_paths(&writable_roots, unmounted_protected_paths);
+        }
+        install_filesystem_landlock_rules_on_current_thread(readable_roots, writable_roots)?;
     }
 
-    // TODO(ragona): Add appropriate restrictions if
-    // `sandbox_policy.has_full_disk_read_access()` is `false`.
+    Ok(())
+}
+
+/// Returns the port of the network proxy if network access is limited to the
+/// domains in `NetworkAllowDomains` and a proxy is available.
+pub(crate) fn proxy_only_port(
+    sandbox_policy: &SandboxPolicy,
+    network_proxy_port: Option<u16>,
+) -> Option<u16> {
+    network_proxy_port.filter(|_| !sandbox_policy.get_allowed_network_domains().is_empty())
+}
 
+/// Limits the network to the TCP connections needed to reach the proxy, for a
+/// process in a network namespace in which only the proxy can be reached.
+pub(crate) fn apply_proxy_only_network_policy_to_current_thread() -> Result<()> {
+    install_proxy_only_seccomp_filter_on_current_thread()?;
     Ok(())
 }
 
-/// Installs Landlock file-system rules on the current thread allowing read
-/// access to the entire file-system while restricting write access to
-/// `/dev/null` and the provided list of `writable_roots`.
+/// Restricts writes to `writable_roots`, keeping `missing_protected_paths`
+/// from being created, and reads to the system directories and the readable
+/// roots of the policy unless it grants full read access. Used by the
+/// namespace backend, which can neither hide files nor keep missing paths
+/// from being created with mounts alone.
+pub(crate) fn apply_filesystem_restrictions_to_current_thread(
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+    mut writable_roots: Vec<PathBuf>,
+    missing_protected_paths: &[ProtectedPath],
+) -> Result<()> {
+    let readable_roots = if sandbox_policy.has_full_disk_read_access() {
+        None
+    } else {
+        Some(readable_roots(sandbox_policy, cwd))
+    };
+    if !missing_protected_paths.is_empty() {
+        writable_roots =
+            writable_rules_around_protected_paths(&writable_roots, missing_protected_paths);
+    }
+    install_filesystem_landlock_rules_on_current_thread(readable_roots, writable_roots)
+}
+
+/// Landlock cannot deny access beneath a path it allows, so without mounts
+/// the protected paths are kept read-only by granting write access to the
+/// entries next to them rather than to the writable root that contains them.
+/// Folders on the way to a protected path are descended into, which means
+/// that no entries can be created or removed directly inside them. Symlinks
+/// are skipped, as a rule on a symlink would apply to its target.
+fn writable_rules_around_protected_paths(
+    writable_roots: &[PathBuf],
+    protected_paths: &[ProtectedPath],
+) -> Vec<PathBuf> {
+    fn collect(path: &Path, protected_paths: &[ProtectedPath], rules: &mut Vec<PathBuf>) {
+        if let Some(protected) = protected_paths.iter().find(|p| p.path == path) {
+            for subpath in &protected.writable_subpaths {
+                collect(subpath, protected_paths, rules);
+            }
+            return;
+        }
+        let contains_protected_path = protected_paths
+            .iter()
+            .any(|protected| protected.path.starts_with(path));
+        if !contains_protected_path {
+            if path.exists() {
+                rules.push(path.to_path_buf());
+            }
+            return;
+        }
+        let Ok(entries) = std::fs::read_dir(path) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            if entry
+                .file_type()
+                .is_ok_and(|file_type| !file_type.is_symlink())
+            {
+                collect(&entry.path(), protected_paths, rules);
+            }
+        }
+    }
+
+    let mut rules = Vec::new();
+    for root in writable_roots {
+        collect(root, protected_paths, &mut rules);
+    }
+    rules
+}
+
+fn readable_roots(sandbox_policy: &SandboxPolicy, cwd: &Path) -> Vec<PathBuf> {
+    let mut readable_roots = system_read_roots();
+    readable_roots.extend(sandbox_policy.get_readable_roots_with_cwd(cwd));
+    readable_roots
+}
+
+fn system_read_roots() -> Vec<PathBuf> {
+    SYSTEM_READ_ROOTS
+        .iter()
+        .map(PathBuf::from)
+        .filter(|path| path.exists())
+        .collect()
+}
+
+/// Installs Landlock file-system rules on the current thread restricting
+/// write access to `/dev/null` and the provided list of `writable_roots`.
+/// Read access is granted to the entire file-system when `readable_roots` is
+/// `None`, and only to `readable_roots` (plus the writable roots) otherwise.
 ///
 /// # Errors
 /// Returns [`SeekyErr::Sandbox`] variants when the ruleset fails to apply.
-fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathBuf>) -> Result<()> {
+fn install_filesystem_landlock_rules_on_current_thread(
+    readable_roots: Option<Vec<PathBuf>>,
+    writable_roots: Vec<PathBuf>,
+) -> Result<()> {
     let abi = ABI::V5;
     let access_rw = AccessFs::from_all(abi);
     let access_ro = AccessFs::from_read(abi);
 
+    let readable_roots = readable_roots.unwrap_or_else(|| vec![PathBuf::from("/")]);
+    // `path_beneath_rules` fails on paths that cannot be opened, e.g. a
+    // `disk-read-folder` that has since been deleted.
+    let readable_roots: Vec<PathBuf> = readable_roots
+        .into_iter()
+        .filter(|path| path.exists())
+        .collect();
+
     let mut ruleset = Ruleset::default()
         .set_compatibility(CompatLevel::BestEffort)
         .handle_access(access_rw)?
         .create()?
-        .add_rules(landlock::path_beneath_rules(&["/"], access_ro))?
+        .add_rules(landlock::path_beneath_rules(&readable_roots, access_ro))?
         .add_rules(landlock::path_beneath_rules(&["/dev/null"], access_rw))?
         .set_no_new_privs(true);
 
@@ -78,6 +234,60 @@ fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathB
     Ok(())
 }
 
+/// Installs a seccomp filter for commands that may only talk to the network
+/// proxy: TCP sockets over IPv4/IPv6 and AF_UNIX sockets can be created and
+/// connected (the network namespace decides what is reachable), everything
+/// else (UDP, raw and netlink sockets, listening for connections) is denied.
+fn install_proxy_only_seccomp_filter_on_current_thread() -> std::result::Result<(), SandboxErr> {
+    let mut rules: BTreeMap<i64, Vec<SeccompRule>> = BTreeMap::new();
+
+    for nr in [
+        libc::SYS_accept,
+        libc::SYS_accept4,
+        libc::SYS_bind,
+        libc::SYS_listen,
+        libc::SYS_ptrace,
+        libc::SYS_socketpair,
+    ] {
+        rules.insert(nr, vec![]); // empty rule vec = unconditional match
+    }
+
+    // Deny any domain other than AF_UNIX, AF_INET and AF_INET6 ...
+    let mut socket_rules = vec![SeccompRule::new(
+        [libc::AF_UNIX, libc::AF_INET, libc::AF_INET6]
+            .into_iter()
+            .map(|domain| {
+                SeccompCondition::new(0, SeccompCmpArgLen::Dword, SeccompCmpOp::Ne, domain as u64)
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?,
+    )?];
+    // ... and any IPv4/IPv6 socket that is not a stream socket. The low bits
+    // of the type argument hold the type, the high bits hold flags such as
+    // SOCK_CLOEXEC.
+    for domain in [libc::AF_INET, libc::AF_INET6] {
+        for socket_type in [
+            libc::SOCK_DGRAM,
+            libc::SOCK_RAW,
+            libc::SOCK_RDM,
+            libc::SOCK_SEQPACKET,
+            libc::SOCK_DCCP,
+        ] {
+            socket_rules.push(SeccompRule::new(vec![
+                SeccompCondition::new(0, SeccompCmpArgLen::Dword, SeccompCmpOp::Eq, domain as u64)?,
+                SeccompCondition::new(
+                    1,
+                    SeccompCmpArgLen::Dword,
+                    SeccompCmpOp::MaskedEq(0xf),
+                    socket_type as u64,
+                )?,
+            ])?);
+        }
+    }
+    rules.insert(libc::SYS_socket, socket_rules);
+
+    apply_seccomp_rules(rules)
+}
+
 /// Installs a seccomp filter that blocks outbound network access except for
 /// AF_UNIX domain sockets.
 fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(), SandboxErr> {
@@ -118,6 +328,14 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
     rules.insert(libc::SYS_socket, vec![unix_only_rule]);
     rules.insert(libc::SYS_socketpair, vec![]); // always deny (Unix can use socketpair but fine, keep open?)
 
+    apply_seccomp_rules(rules)
+}
+
+/// Compiles `rules` into a filter that returns EPERM for matching syscalls and
+/// allows everything else, and applies it to the current thread.
+fn apply_seccomp_rules(
+    rules: BTreeMap<i64, Vec<SeccompRule>>,
+) -> std::result::Result<(), SandboxErr> {
     let filter = SeccompFilter::new(
         rules,
         SeccompAction::Allow,                     // default – allow
@@ -133,7 +351,36 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
 
     let prog: BpfProgram = filter.try_into()?;
 
-    apply_filter(&prog)?;
+    // Kernels older than 4.14 do not support logging the filter's actions.
+    if install_logged_seccomp_filter(&prog).is_err() {
+        apply_filter(&prog)?;
+    }
 
     Ok(())
 }
+
+/// Installs `prog` with `SECCOMP_FILTER_FLAG_LOG`, so that every denied
+/// syscall leaves a `type=SECCOMP` record in the kernel audit log, even when
+/// the command does not report the `EPERM` it got.
+fn install_logged_seccomp_filter(prog: &BpfProgram) -> std::io::Result<()> {
+    if unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let fprog = libc::sock_fprog {
+        len: u16::try_from(prog.len()).map_err(std::io::Error::other)?,
+        // `seccompiler::sock_filter` has the layout of the kernel's struct.
+        filter: prog.as_ptr().cast::<libc::sock_filter>().cast_mut(),
+    };
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_seccomp,
+            libc::SECCOMP_SET_MODE_FILTER,
+            libc::SECCOMP_FILTER_FLAG_LOG,
+            &fprog as *const libc::sock_fprog,
+        )
+    };
+    if ret != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
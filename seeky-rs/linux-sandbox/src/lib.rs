@@ -1,10 +1,24 @@
 #[cfg(target_os = "linux")]
+mod cgroup;
+#[cfg(target_os = "linux")]
 mod landlock;
 #[cfg(target_os = "linux")]
 mod linux_run_main;
+#[cfg(target_os = "linux")]
+mod namespaces;
+#[cfg(target_os = "linux")]
+mod probe;
+#[cfg(target_os = "linux")]
+mod proxy_relay;
 
 #[cfg(target_os = "linux")]
 pub use linux_run_main::run_main;
+#[cfg(target_os = "linux")]
+pub use probe::landlock_abi_version;
+#[cfg(target_os = "linux")]
+pub use probe::seccomp_filter_available;
+#[cfg(target_os = "linux")]
+pub use probe::user_namespaces_available;
 
 use std::future::Future;
 use std::path::PathBuf;
@@ -1,26 +1,107 @@
+use clap::Args;
 use clap::Parser;
+use clap::ValueEnum;
 use seeky_common::SandboxPermissionOption;
+use seeky_core::config_types::ResourceLimits;
+use seeky_core::resource_limits::apply_to_current_process;
 use std::ffi::CString;
 
+use crate::cgroup::enter_cgroup_leaf;
 use crate::landlock::apply_sandbox_policy_to_current_thread;
+use crate::landlock::proxy_only_port;
+use crate::namespaces::NamespaceSandbox;
+use crate::namespaces::enter_namespace_sandbox;
+use crate::namespaces::enter_proxy_network_namespace;
+use crate::namespaces::protect_paths_in_mount_namespace;
 
+/// Runs a command with the given sandbox permissions and resource limits.
 #[derive(Debug, Parser)]
 pub struct LandlockCommand {
     #[clap(flatten)]
     pub sandbox: SandboxPermissionOption,
 
+    /// Mechanism used to enforce the sandbox policy.
+    #[arg(long, value_enum, default_value_t = Backend::Landlock)]
+    pub backend: Backend,
+
+    /// Port of the proxy that enforces `network-allow-domains`. The command
+    /// runs in a network namespace in which only this proxy can be reached.
+    #[arg(long)]
+    pub network_proxy_port: Option<u16>,
+
+    /// Path that stays read-only inside the writable roots, in the syntax of
+    /// `protected_paths` in config.toml. Can be repeated; none by default.
+    #[arg(long = "protected-path")]
+    pub protected_paths: Vec<String>,
+
+    #[clap(flatten)]
+    pub limits: LimitArgs,
+
     /// Full command args to run under landlock.
     #[arg(trailing_var_arg = true)]
     pub command: Vec<String>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// Landlock file-system rules plus a seccomp filter for the network.
+    Landlock,
+    /// Unprivileged user, mount, PID and network namespaces. Falls back to
+    /// `landlock` if user namespaces are not available.
+    Namespaces,
+}
+
+// Resource limits for the command, see `ResourceLimits`.
+#[derive(Debug, Args)]
+pub struct LimitArgs {
+    /// CPU time in seconds.
+    #[arg(long)]
+    pub limit_cpu_seconds: Option<u64>,
+
+    /// Memory in bytes.
+    #[arg(long)]
+    pub limit_memory_bytes: Option<u64>,
+
+    /// Size in bytes of the largest file the command may write.
+    #[arg(long)]
+    pub limit_file_size_bytes: Option<u64>,
+
+    /// Number of processes.
+    #[arg(long)]
+    pub limit_processes: Option<u64>,
+
+    /// Number of open file descriptors per process.
+    #[arg(long)]
+    pub limit_open_files: Option<u64>,
+}
+
+impl From<LimitArgs> for ResourceLimits {
+    fn from(args: LimitArgs) -> Self {
+        Self {
+            cpu_seconds: args.limit_cpu_seconds,
+            memory_bytes: args.limit_memory_bytes,
+            file_size_bytes: args.limit_file_size_bytes,
+            max_processes: args.limit_processes,
+            open_files: args.limit_open_files,
+        }
+    }
+}
+
 pub fn run_main() -> ! {
-    let LandlockCommand { sandbox, command } = LandlockCommand::parse();
+    let LandlockCommand {
+        sandbox,
+        backend,
+        network_proxy_port,
+        protected_paths,
+        limits,
+        command,
+    } = LandlockCommand::parse();
 
     let sandbox_policy = match sandbox.permissions.map(Into::into) {
         Some(sandbox_policy) => sandbox_policy,
         None => seeky_core::protocol::SandboxPolicy::new_read_only_policy(),
     };
+    let sandbox_policy = sandbox_policy.with_protected_paths(protected_paths);
 
     let cwd = match std::env::current_dir() {
         Ok(cwd) => cwd,
@@ -29,14 +110,58 @@ pub fn run_main() -> ! {
         }
     };
 
-    if let Err(e) = apply_sandbox_policy_to_current_thread(&sandbox_policy, &cwd) {
-        panic!("error running landlock: {e:?}");
+    // Join the cgroup first so that the processes forked for the namespace
+    // backend are accounted to it as well.
+    let rlimits = enter_cgroup_leaf(&limits.into());
+
+    let use_landlock = match backend {
+        Backend::Landlock => true,
+        Backend::Namespaces => {
+            match enter_namespace_sandbox(&sandbox_policy, &cwd, network_proxy_port) {
+                Ok(NamespaceSandbox::Entered) => false,
+                Ok(NamespaceSandbox::Unavailable) => true,
+                Err(e) => panic!("error setting up namespaces: {e:?}"),
+            }
+        }
+    };
+
+    if use_landlock {
+        let in_proxy_network_namespace = !sandbox_policy.has_full_network_access()
+            && proxy_only_port(&sandbox_policy, network_proxy_port)
+                .is_some_and(enter_proxy_network_namespace);
+        // Only existing paths can be mounted over; Landlock keeps the others
+        // from being created.
+        let (mut unmounted_protected_paths, missing_protected_paths): (Vec<_>, Vec<_>) =
+            sandbox_policy
+                .get_protected_paths_with_cwd(&cwd)
+                .into_iter()
+                .partition(|protected| protected.path.exists());
+        if !unmounted_protected_paths.is_empty()
+            && protect_paths_in_mount_namespace(&sandbox_policy, &unmounted_protected_paths, &cwd)
+        {
+            unmounted_protected_paths.clear();
+        }
+        unmounted_protected_paths.extend(missing_protected_paths);
+        if let Err(e) = apply_sandbox_policy_to_current_thread(
+            &sandbox_policy,
+            &cwd,
+            in_proxy_network_namespace,
+            &unmounted_protected_paths,
+        ) {
+            panic!("error running landlock: {e:?}");
+        }
     }
 
     if command.is_empty() {
         panic!("No command specified to execute.");
     }
 
+    // Applied last, as `RLIMIT_NPROC` could otherwise prevent the forks of
+    // the namespace backend.
+    if let Err(e) = apply_to_current_process(&rlimits) {
+        panic!("error applying resource limits: {e:?}");
+    }
+
     #[expect(clippy::expect_used)]
     let c_command =
         CString::new(command[0].as_str()).expect("Failed to convert command to CString");
@@ -0,0 +1,496 @@
+//! Sandbox backend built on unprivileged Linux namespaces.
+//!
+//! The helper moves itself into new user, mount and PID namespaces (plus a
+//! network namespace unless full network access is allowed), remounts every
+//! existing mount read-only, bind-mounts the writable roots read-write (and
+//! the protected paths inside them read-only again) and mounts a private tmpfs
+//! on `/tmp`. Protected paths that do not exist are left to Landlock. It then forks so that the command runs in the new PID namespace
+//! with a fresh `/proc`, while the original process waits for it and mirrors
+//! its exit status.
+
+use std::ffi::CString;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::os::fd::AsRawFd;
+use std::path::Path;
+use std::path::PathBuf;
+
+use seeky_core::error::Result;
+use seeky_core::protocol::ProtectedPath;
+use seeky_core::protocol::SandboxPolicy;
+
+use crate::landlock::apply_filesystem_restrictions_to_current_thread;
+use crate::landlock::apply_proxy_only_network_policy_to_current_thread;
+use crate::landlock::proxy_only_port;
+use crate::proxy_relay::ProxyRelay;
+
+/// Mount points whose contents are managed by the kernel. They are left as
+/// they are: `/proc` is replaced for the new PID namespace, and writes to
+/// `/sys` require privileges the sandboxed process does not have.
+const SKIPPED_MOUNT_PREFIXES: &[&str] = &["/proc", "/sys"];
+
+const PRIVATE_TMP: &str = "/tmp";
+
+pub(crate) enum NamespaceSandbox {
+    /// The current process is the sandboxed child and may exec the command.
+    Entered,
+    /// User namespaces are not available, e.g. because they are disabled by
+    /// `kernel.unprivileged_userns_clone` or the process is already
+    /// multi-threaded. Nothing has been changed.
+    Unavailable,
+}
+
+/// Sets up the namespaces for `sandbox_policy`. Only returns in the child
+/// that should exec the command: the parent waits for that child and exits
+/// with its status.
+pub(crate) fn enter_namespace_sandbox(
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+    network_proxy_port: Option<u16>,
+) -> Result<NamespaceSandbox> {
+    let uid = unsafe { libc::geteuid() };
+    let gid = unsafe { libc::getegid() };
+
+    // With an allowlist, the proxy on the host's loopback interface is
+    // forwarded into the network namespace.
+    let relay = if sandbox_policy.has_full_network_access() {
+        None
+    } else {
+        proxy_only_port(sandbox_policy, network_proxy_port)
+            .map(ProxyRelay::start)
+            .transpose()?
+    };
+    let mut flags = libc::CLONE_NEWUSER | libc::CLONE_NEWNS | libc::CLONE_NEWPID;
+    if !sandbox_policy.has_full_network_access() {
+        flags |= libc::CLONE_NEWNET;
+    }
+    if unsafe { libc::unshare(flags) } != 0 {
+        return Ok(NamespaceSandbox::Unavailable);
+    }
+
+    write_id_maps(uid, gid)?;
+    let proxy_only = relay.is_some();
+    if let Some(relay) = relay {
+        relay.listen()?;
+    }
+
+    let writable_roots = if sandbox_policy.has_full_disk_write_access() {
+        None
+    } else {
+        Some(sandbox_policy.get_writable_roots_with_cwd(cwd))
+    };
+    let mut missing_protected_paths = Vec::new();
+    if let Some(writable_roots) = &writable_roots {
+        setup_mounts(writable_roots)?;
+        // The private `/tmp` is discarded along with the command, so there is
+        // nothing to protect in it.
+        let persistent_roots = writable_roots
+            .iter()
+            .filter(|root| root.as_path() != Path::new(PRIVATE_TMP))
+            .cloned()
+            .collect::<Vec<_>>();
+        let (existing, missing): (Vec<_>, Vec<_>) = sandbox_policy
+            .get_protected_paths(&persistent_roots)
+            .into_iter()
+            .partition(|protected| protected.path.exists());
+        mount_protected_paths(
+            &existing,
+            &sandbox_policy.get_protected_path_ancestors(&persistent_roots),
+        )?;
+        missing_protected_paths = missing;
+        // The working directory still refers to the directory as it was
+        // before the mounts; re-resolve it so writes to it go through the new
+        // bind mount.
+        std::env::set_current_dir(cwd)?;
+    }
+    if proxy_only {
+        apply_proxy_only_network_policy_to_current_thread()?;
+    }
+
+    fork_into_pid_namespace()?;
+    mount_proc()?;
+
+    if let Some(mut writable_roots) = writable_roots {
+        if !sandbox_policy.has_full_disk_read_access() || !missing_protected_paths.is_empty() {
+            writable_roots.push(PathBuf::from(PRIVATE_TMP));
+            apply_filesystem_restrictions_to_current_thread(
+                sandbox_policy,
+                cwd,
+                writable_roots,
+                &missing_protected_paths,
+            )?;
+        }
+    }
+
+    drop_capabilities()?;
+    Ok(NamespaceSandbox::Entered)
+}
+
+/// Used by the Landlock backend, which cannot deny writes beneath a writable
+/// root: moves the current process into new user and mount namespaces in
+/// which the existing `protected_paths` are read-only bind mounts, see
+/// [`mount_protected_paths`]. Returns `false` if the namespaces or mounts
+/// cannot be set up; some of the paths may have been protected nonetheless.
+pub(crate) fn protect_paths_in_mount_namespace(
+    sandbox_policy: &SandboxPolicy,
+    protected_paths: &[ProtectedPath],
+    cwd: &Path,
+) -> bool {
+    let ancestors = sandbox_policy
+        .get_protected_path_ancestors(&sandbox_policy.get_writable_roots_with_cwd(cwd));
+    let uid = unsafe { libc::geteuid() };
+    let gid = unsafe { libc::getegid() };
+    if unsafe { libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNS) } != 0 {
+        return false;
+    }
+    let setup = || -> io::Result<()> {
+        write_id_maps(uid, gid)?;
+        mount(None, Path::new("/"), None, libc::MS_REC | libc::MS_PRIVATE)?;
+        mount_protected_paths(protected_paths, &ancestors)?;
+        std::env::set_current_dir(cwd)?;
+        drop_capabilities()
+    };
+    setup().is_ok()
+}
+
+/// Used by the Landlock backend, which cannot restrict connections by address:
+/// moves the current process into new user and network namespaces in which
+/// only the proxy on `port` can be reached, see [`ProxyRelay`]. Returns
+/// `false` if the namespaces cannot be set up, in which case the network must
+/// be blocked altogether.
+pub(crate) fn enter_proxy_network_namespace(port: u16) -> bool {
+    let uid = unsafe { libc::geteuid() };
+    let gid = unsafe { libc::getegid() };
+    let Ok(relay) = ProxyRelay::start(port) else {
+        return false;
+    };
+    if unsafe { libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) } != 0 {
+        return false;
+    }
+    let setup = || -> io::Result<()> {
+        write_id_maps(uid, gid)?;
+        relay.listen()?;
+        drop_capabilities()
+    };
+    setup().is_ok()
+}
+
+/// Bind-mounts each protected path onto itself read-only, and its writable
+/// subpaths read-write on top. The folders leading to them (`ancestors`) are
+/// bind-mounted onto themselves read-write, as a mount point cannot be moved
+/// or removed. The protected paths must exist: missing ones are kept from
+/// being created with Landlock instead, as mounting over them would mean
+/// creating them on the host.
+fn mount_protected_paths(
+    protected_paths: &[ProtectedPath],
+    ancestors: &[PathBuf],
+) -> io::Result<()> {
+    for ancestor in ancestors {
+        if fs::symlink_metadata(ancestor).is_ok_and(|metadata| metadata.is_dir()) {
+            bind_onto_itself(ancestor)?;
+        }
+    }
+    for protected in protected_paths {
+        bind_onto_itself(&protected.path)?;
+        remount(&protected.path, libc::MS_RDONLY)?;
+        for subpath in &protected.writable_subpaths {
+            if subpath.exists() {
+                bind_onto_itself(subpath)?;
+                remount(subpath, 0)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn bind_onto_itself(path: &Path) -> io::Result<()> {
+    let file = File::open(path)?;
+    let source = format!("/proc/self/fd/{}", file.as_raw_fd());
+    mount(Some(&source), path, None, libc::MS_BIND | libc::MS_REC)
+}
+
+/// Maps the current user and group to themselves inside the user namespace.
+fn write_id_maps(uid: libc::uid_t, gid: libc::gid_t) -> io::Result<()> {
+    fs::write("/proc/self/setgroups", "deny")?;
+    fs::write("/proc/self/uid_map", format!("{uid} {uid} 1"))?;
+    fs::write("/proc/self/gid_map", format!("{gid} {gid} 1"))?;
+    Ok(())
+}
+
+fn setup_mounts(writable_roots: &[PathBuf]) -> io::Result<()> {
+    // Keep our changes out of the parent mount namespace.
+    mount(None, Path::new("/"), None, libc::MS_REC | libc::MS_PRIVATE)?;
+
+    // Hold on to the writable roots before `/tmp` is replaced, as some of
+    // them (temp dirs in particular) may live underneath it.
+    let writable_roots = writable_roots
+        .iter()
+        .filter(|root| root.as_path() != Path::new(PRIVATE_TMP))
+        .filter_map(|root| File::open(root).ok().map(|file| (root, file)))
+        .collect::<Vec<_>>();
+
+    for mount_point in mount_points()? {
+        if SKIPPED_MOUNT_PREFIXES
+            .iter()
+            .any(|prefix| mount_point.starts_with(prefix))
+        {
+            continue;
+        }
+        remount(&mount_point, libc::MS_RDONLY)?;
+    }
+
+    if Path::new(PRIVATE_TMP).is_dir() {
+        mount(
+            Some("tmpfs"),
+            Path::new(PRIVATE_TMP),
+            Some("tmpfs"),
+            libc::MS_NOSUID | libc::MS_NODEV,
+        )?;
+    }
+
+    for (root, file) in writable_roots {
+        if !root.exists() {
+            fs::create_dir_all(root)?;
+        }
+        let source = format!("/proc/self/fd/{}", file.as_raw_fd());
+        mount(Some(&source), root, None, libc::MS_BIND | libc::MS_REC)?;
+        // A bind mount inherits the read-only flag of its source.
+        remount(root, 0)?;
+    }
+
+    Ok(())
+}
+
+/// Remounts the bind mount at `path` with `extra_flags`, preserving the flags
+/// that an unprivileged user namespace is not allowed to clear.
+fn remount(path: &Path, extra_flags: libc::c_ulong) -> io::Result<()> {
+    let path_c = path_to_cstring(path)?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path_c.as_ptr(), &mut stat) } != 0 {
+        return Err(with_path(io::Error::last_os_error(), "statvfs", path));
+    }
+    let preserved = [
+        (libc::ST_NOSUID, libc::MS_NOSUID),
+        (libc::ST_NODEV, libc::MS_NODEV),
+        (libc::ST_NOEXEC, libc::MS_NOEXEC),
+        (libc::ST_NOATIME, libc::MS_NOATIME),
+        (libc::ST_NODIRATIME, libc::MS_NODIRATIME),
+        (libc::ST_RELATIME, libc::MS_RELATIME),
+    ]
+    .into_iter()
+    .filter(|(st_flag, _)| stat.f_flag & st_flag != 0)
+    .fold(0, |flags, (_, ms_flag)| flags | ms_flag);
+    mount(
+        None,
+        path,
+        None,
+        libc::MS_REMOUNT | libc::MS_BIND | preserved | extra_flags,
+    )
+}
+
+fn mount_proc() -> io::Result<()> {
+    mount(
+        Some("proc"),
+        Path::new("/proc"),
+        Some("proc"),
+        libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC,
+    )
+}
+
+/// Mount points of the current mount namespace, parents before children.
+fn mount_points() -> io::Result<Vec<PathBuf>> {
+    let mountinfo = fs::read_to_string("/proc/self/mountinfo")?;
+    Ok(mountinfo
+        .lines()
+        .filter_map(|line| line.split(' ').nth(4))
+        .map(|mount_point| PathBuf::from(unescape_mountinfo(mount_point)))
+        .collect())
+}
+
+/// Decodes the octal escapes (`\040` for a space, ...) used in mountinfo.
+fn unescape_mountinfo(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' {
+            if let Some(Ok(value)) = field
+                .get(i + 1..i + 4)
+                .map(|oct| u8::from_str_radix(oct, 8))
+            {
+                out.push(value);
+                i += 4;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Forks twice: the first child becomes PID 1 of the new PID namespace and
+/// only waits for the second one, which returns to exec the command. The
+/// command therefore does not get the special signal handling of an init
+/// process. Each parent exits with the status of its child, so this only
+/// returns in the second child.
+///
+/// PID 1 cannot be killed by a signal it sends itself, so it passes the
+/// signal that killed the command through a pipe to the original process,
+/// which then dies of it. Callers can thus tell a command killed by, e.g.,
+/// `SIGXCPU` from one that exited with code 152.
+fn fork_into_pid_namespace() -> io::Result<()> {
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let [read_end, write_end] = fds;
+
+    if let Some(init) = fork_child()? {
+        unsafe { libc::close(write_end) };
+        let status = wait_for(init);
+        let mut signal: libc::c_int = 0;
+        let size = std::mem::size_of::<libc::c_int>();
+        let read = unsafe { libc::read(read_end, (&raw mut signal).cast(), size) };
+        if read == size as isize {
+            die_of_signal(signal);
+        }
+        exit_with_status(status);
+    }
+
+    unsafe { libc::close(read_end) };
+    if let Some(command) = fork_child()? {
+        let status = wait_for(command);
+        if libc::WIFSIGNALED(status) {
+            let signal = libc::WTERMSIG(status);
+            let size = std::mem::size_of::<libc::c_int>();
+            unsafe { libc::write(write_end, (&raw const signal).cast(), size) };
+        }
+        exit_with_status(status);
+    }
+    // The pipe is closed when the command is exec'd.
+    Ok(())
+}
+
+/// Forks and returns the pid of the child in the parent, or `None` in the
+/// child.
+pub(crate) fn fork_child() -> io::Result<Option<libc::pid_t>> {
+    match unsafe { libc::fork() } {
+        -1 => Err(io::Error::last_os_error()),
+        0 => {
+            // Do not outlive the parent, e.g. when the helper is killed on
+            // timeout.
+            if unsafe { libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(None)
+        }
+        child => Ok(Some(child)),
+    }
+}
+
+fn wait_for(child: libc::pid_t) -> libc::c_int {
+    let mut status = 0;
+    loop {
+        if unsafe { libc::waitpid(child, &mut status, 0) } == child {
+            return status;
+        }
+        if io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+            std::process::exit(1);
+        }
+    }
+}
+
+fn exit_with_status(status: libc::c_int) -> ! {
+    if libc::WIFSIGNALED(status) {
+        die_of_signal(libc::WTERMSIG(status));
+    }
+    std::process::exit(libc::WEXITSTATUS(status));
+}
+
+/// Terminates the current process with `signal`, without dumping core. Falls
+/// back to exiting like a shell would if the signal does not kill it, as for
+/// PID 1 of a PID namespace.
+fn die_of_signal(signal: libc::c_int) -> ! {
+    let no_core = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    unsafe {
+        libc::setrlimit(libc::RLIMIT_CORE, &no_core);
+        libc::signal(signal, libc::SIG_DFL);
+        libc::kill(libc::getpid(), signal);
+    }
+    std::process::exit(128 + signal);
+}
+
+/// Drops every capability from the bounding set so that the command cannot
+/// regain the capabilities this process holds in the user namespace (e.g. to
+/// undo the read-only remounts), even if it runs as uid 0.
+fn drop_capabilities() -> io::Result<()> {
+    let last_cap = fs::read_to_string("/proc/sys/kernel/cap_last_cap")
+        .ok()
+        .and_then(|cap| cap.trim().parse::<libc::c_ulong>().ok())
+        .unwrap_or(63);
+    for cap in 0..=last_cap {
+        if unsafe { libc::prctl(libc::PR_CAPBSET_DROP, cap, 0, 0, 0) } != 0 {
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() != Some(libc::EINVAL) {
+                return Err(err);
+            }
+        }
+    }
+    if unsafe {
+        libc::prctl(
+            libc::PR_CAP_AMBIENT,
+            libc::PR_CAP_AMBIENT_CLEAR_ALL,
+            0,
+            0,
+            0,
+        )
+    } != 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+    if unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn mount(
+    source: Option<&str>,
+    target: &Path,
+    fstype: Option<&str>,
+    flags: libc::c_ulong,
+) -> io::Result<()> {
+    let source = source.map(CString::new).transpose()?;
+    let target_c = path_to_cstring(target)?;
+    let fstype = fstype.map(CString::new).transpose()?;
+    let ret = unsafe {
+        libc::mount(
+            source.as_ref().map_or(std::ptr::null(), |s| s.as_ptr()),
+            target_c.as_ptr(),
+            fstype.as_ref().map_or(std::ptr::null(), |s| s.as_ptr()),
+            flags,
+            std::ptr::null(),
+        )
+    };
+    if ret != 0 {
+        return Err(with_path(io::Error::last_os_error(), "mount", target));
+    }
+    Ok(())
+}
+
+fn path_to_cstring(path: &Path) -> io::Result<CString> {
+    use std::os::unix::ffi::OsStrExt;
+    Ok(CString::new(path.as_os_str().as_bytes())?)
+}
+
+fn with_path(err: io::Error, operation: &str, path: &Path) -> io::Error {
+    io::Error::new(
+        err.kind(),
+        format!("{operation} {} failed: {err}", path.display()),
+    )
+}
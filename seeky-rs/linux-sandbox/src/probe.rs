@@ -0,0 +1,70 @@
+//! Checks for the kernel features that `seeky-linux-sandbox` relies on. None
+//! of them change the state of the calling process.
+
+/// `LANDLOCK_CREATE_RULESET_VERSION`: makes `landlock_create_ruleset` return
+/// the highest supported ABI version instead of creating a ruleset.
+const LANDLOCK_CREATE_RULESET_VERSION: libc::c_uint = 1 << 0;
+
+/// `SECCOMP_GET_ACTION_AVAIL`
+const SECCOMP_GET_ACTION_AVAIL: libc::c_uint = 2;
+
+/// `SECCOMP_RET_ERRNO`, the action of the sandbox's seccomp filters.
+const SECCOMP_RET_ERRNO: u32 = 0x0005_0000;
+
+/// The Landlock ABI version supported by the kernel, or `None` if Landlock is
+/// not built in or disabled (e.g. missing from the `lsm=` boot parameter).
+pub fn landlock_abi_version() -> Option<i32> {
+    let version = unsafe {
+        libc::syscall(
+            libc::SYS_landlock_create_ruleset,
+            std::ptr::null::<libc::c_void>(),
+            0usize,
+            LANDLOCK_CREATE_RULESET_VERSION,
+        )
+    };
+    i32::try_from(version).ok().filter(|version| *version > 0)
+}
+
+/// Whether seccomp filters that return an errno can be installed.
+pub fn seccomp_filter_available() -> bool {
+    let action = SECCOMP_RET_ERRNO;
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_seccomp,
+            SECCOMP_GET_ACTION_AVAIL,
+            0,
+            &action as *const u32,
+        )
+    };
+    ret == 0
+}
+
+/// Whether the namespaces used by the `namespaces` backend can be created
+/// without privileges. The check runs in a forked child, as `unshare` cannot
+/// be undone and fails in multi-threaded processes.
+pub fn user_namespaces_available() -> bool {
+    match unsafe { libc::fork() } {
+        -1 => false,
+        0 => {
+            let flags = libc::CLONE_NEWUSER | libc::CLONE_NEWNS | libc::CLONE_NEWPID;
+            let status = if unsafe { libc::unshare(flags) } == 0 {
+                0
+            } else {
+                1
+            };
+            unsafe { libc::_exit(status) }
+        }
+        child => {
+            let mut status = 0;
+            loop {
+                if unsafe { libc::waitpid(child, &mut status, 0) } == child {
+                    break;
+                }
+                if std::io::Error::last_os_error().kind() != std::io::ErrorKind::Interrupted {
+                    return false;
+                }
+            }
+            libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0
+        }
+    }
+}
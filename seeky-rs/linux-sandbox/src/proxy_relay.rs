@@ -0,0 +1,172 @@
+//! Makes the network proxy reachable from inside a network namespace.
+//!
+//! A new network namespace only has a loopback interface of its own, so the
+//! proxy listening on the host's `127.0.0.1` cannot be reached from it, and
+//! neither can anything else. Before the namespace is created, the helper
+//! forks a relay process that stays in the host's network namespace. Once
+//! inside the new namespace, the helper listens on the proxy's port of the
+//! namespace's loopback interface and passes that socket to the relay, which
+//! forwards every connection accepted on it to the proxy. The proxy URL in the
+//! command's environment therefore works unchanged.
+
+use std::io;
+use std::mem::size_of;
+use std::net::Ipv4Addr;
+use std::net::Shutdown;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::os::fd::AsRawFd;
+use std::os::fd::FromRawFd;
+use std::os::fd::OwnedFd;
+use std::os::fd::RawFd;
+use std::os::unix::net::UnixStream;
+
+use crate::namespaces::fork_child;
+
+pub(crate) struct ProxyRelay {
+    port: u16,
+    /// Connected to the relay process, which exits when this is closed
+    /// without a listener having been sent.
+    channel: UnixStream,
+}
+
+impl ProxyRelay {
+    /// Forks the relay process for the proxy on `port` of the host's loopback
+    /// interface. Must be called before entering the network namespace.
+    pub(crate) fn start(port: u16) -> io::Result<Self> {
+        let (channel, relay_channel) = UnixStream::pair()?;
+        match fork_child()? {
+            Some(_) => Ok(Self { port, channel }),
+            None => {
+                drop(channel);
+                run_relay(relay_channel, port)
+            }
+        }
+    }
+
+    /// Brings up the loopback interface of the current network namespace and
+    /// hands a listener on its `127.0.0.1` to the relay. Requires
+    /// `CAP_NET_ADMIN` in the namespace.
+    pub(crate) fn listen(self) -> io::Result<()> {
+        bring_up_loopback()?;
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, self.port))?;
+        send_fd(&self.channel, listener.as_raw_fd())
+    }
+}
+
+fn run_relay(channel: UnixStream, port: u16) -> ! {
+    // The relay must not hold on to the command's output pipes.
+    if let Ok(dev_null) = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/null")
+    {
+        for fd in 0..=2 {
+            unsafe { libc::dup2(dev_null.as_raw_fd(), fd) };
+        }
+    }
+    let listener = match recv_fd(&channel) {
+        Ok(Some(fd)) => TcpListener::from(fd),
+        _ => std::process::exit(0),
+    };
+    drop(channel);
+    for client in listener.incoming().flatten() {
+        std::thread::spawn(move || forward(client, port));
+    }
+    std::process::exit(0);
+}
+
+/// Copies data between `client` and a new connection to the proxy until both
+/// sides are done.
+fn forward(client: TcpStream, port: u16) {
+    let Ok(proxy) = TcpStream::connect((Ipv4Addr::LOCALHOST, port)) else {
+        return;
+    };
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            let _ = io::copy(&mut &client, &mut &proxy);
+            let _ = proxy.shutdown(Shutdown::Write);
+        });
+        let _ = io::copy(&mut &proxy, &mut &client);
+        let _ = client.shutdown(Shutdown::Write);
+    });
+}
+
+/// A namespace's loopback interface starts out down.
+fn bring_up_loopback() -> io::Result<()> {
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, 0) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+    let mut request: libc::ifreq = unsafe { std::mem::zeroed() };
+    for (dst, src) in request.ifr_name.iter_mut().zip(b"lo") {
+        *dst = *src as libc::c_char;
+    }
+    if unsafe { libc::ioctl(socket.as_raw_fd(), libc::SIOCGIFFLAGS as _, &mut request) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    unsafe { request.ifr_ifru.ifru_flags |= libc::IFF_UP as libc::c_short };
+    if unsafe { libc::ioctl(socket.as_raw_fd(), libc::SIOCSIFFLAGS as _, &mut request) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Large enough and suitably aligned for a control message with one fd.
+type FdControlBuffer = [u64; 4];
+
+fn send_fd(channel: &UnixStream, fd: RawFd) -> io::Result<()> {
+    let mut byte = [0u8];
+    let mut iov = libc::iovec {
+        iov_base: byte.as_mut_ptr().cast(),
+        iov_len: byte.len(),
+    };
+    let mut control: FdControlBuffer = [0; 4];
+    let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.as_mut_ptr().cast();
+    msg.msg_controllen = unsafe { libc::CMSG_SPACE(size_of::<RawFd>() as u32) } as _;
+    unsafe {
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(size_of::<RawFd>() as u32) as _;
+        std::ptr::write_unaligned(libc::CMSG_DATA(cmsg).cast::<RawFd>(), fd);
+    }
+    if unsafe { libc::sendmsg(channel.as_raw_fd(), &msg, 0) } != 1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Returns `None` if the other end was closed without sending an fd.
+fn recv_fd(channel: &UnixStream) -> io::Result<Option<OwnedFd>> {
+    let mut byte = [0u8];
+    let mut iov = libc::iovec {
+        iov_base: byte.as_mut_ptr().cast(),
+        iov_len: byte.len(),
+    };
+    let mut control: FdControlBuffer = [0; 4];
+    let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.as_mut_ptr().cast();
+    msg.msg_controllen = size_of::<FdControlBuffer>() as _;
+    let received = unsafe { libc::recvmsg(channel.as_raw_fd(), &mut msg, libc::MSG_CMSG_CLOEXEC) };
+    if received < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let cmsg = unsafe { libc::CMSG_FIRSTHDR(&msg) };
+    if received == 0 || cmsg.is_null() {
+        return Ok(None);
+    }
+    unsafe {
+        if (*cmsg).cmsg_level != libc::SOL_SOCKET || (*cmsg).cmsg_type != libc::SCM_RIGHTS {
+            return Ok(None);
+        }
+        let fd = std::ptr::read_unaligned(libc::CMSG_DATA(cmsg).cast::<RawFd>());
+        Ok(Some(OwnedFd::from_raw_fd(fd)))
+    }
+}
@@ -1,6 +1,7 @@
 #![cfg(target_os = "linux")]
 #![expect(clippy::unwrap_used, clippy::expect_used)]
 
+use seeky_core::config_types::ResourceLimits;
 use seeky_core::config_types::ShellEnvironmentPolicy;
 use seeky_core::error::SandboxErr;
 use seeky_core::error::SeekyErr;
@@ -8,11 +9,20 @@ use seeky_core::exec::ExecParams;
 use seeky_core::exec::SandboxType;
 use seeky_core::exec::process_exec_tool_call;
 use seeky_core::exec_env::create_env;
+use seeky_core::network_proxy::NetworkProxy;
+use seeky_core::network_proxy::SEEKY_SANDBOX_NETWORK_PROXY_PORT_ENV_VAR;
+use seeky_core::protocol::SandboxDenial;
+use seeky_core::protocol::SandboxDenialKind;
+use seeky_core::protocol::SandboxPermission;
 use seeky_core::protocol::SandboxPolicy;
 use std::collections::HashMap;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tempfile::NamedTempFile;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
 use tokio::sync::Notify;
 
 fn create_env_from_core_vars() -> HashMap<String, String> {
@@ -27,6 +37,7 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         cwd: std::env::current_dir().expect("cwd should exist"),
         timeout_ms: Some(timeout_ms),
         env: create_env_from_core_vars(),
+        limits: ResourceLimits::default(),
     };
 
     let sandbox_policy = SandboxPolicy::new_read_only_policy_with_writable_roots(writable_roots);
@@ -104,6 +115,239 @@ async fn test_timeout() {
     run_cmd(&["sleep", "2"], &[], 50).await;
 }
 
+/// Runs `cmd` in `cwd` under `sandbox_policy` and returns the exit code and
+/// stdout, treating a sandbox denial like any other non-zero exit.
+async fn run_cmd_with_policy(
+    cmd: &[&str],
+    cwd: &Path,
+    sandbox_policy: &SandboxPolicy,
+) -> (i32, String) {
+    run_cmd_with_policy_and_env(cmd, cwd, sandbox_policy, create_env_from_core_vars()).await
+}
+
+async fn run_cmd_with_policy_and_env(
+    cmd: &[&str],
+    cwd: &Path,
+    sandbox_policy: &SandboxPolicy,
+    env: HashMap<String, String>,
+) -> (i32, String) {
+    run_cmd_in_sandbox(SandboxType::LinuxSeccomp, cmd, cwd, sandbox_policy, env).await
+}
+
+async fn run_cmd_in_sandbox(
+    sandbox_type: SandboxType,
+    cmd: &[&str],
+    cwd: &Path,
+    sandbox_policy: &SandboxPolicy,
+    env: HashMap<String, String>,
+) -> (i32, String) {
+    let params = ExecParams {
+        command: cmd.iter().map(|s| s.to_string()).collect(),
+        cwd: cwd.to_path_buf(),
+        timeout_ms: Some(2_000),
+        env,
+        limits: ResourceLimits::default(),
+    };
+    let sandbox_program = env!("CARGO_BIN_EXE_seeky-linux-sandbox");
+    let seeky_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
+    let result = process_exec_tool_call(
+        params,
+        sandbox_type,
+        Arc::new(Notify::new()),
+        sandbox_policy,
+        &seeky_linux_sandbox_exe,
+    )
+    .await;
+    match result {
+        Ok(output) => (output.exit_code, output.stdout),
+        Err(SeekyErr::Sandbox(SandboxErr::Denied(exit_code, stdout, _, _))) => (exit_code, stdout),
+        Err(e) => panic!("unexpected error: {e:?}"),
+    }
+}
+
+/// Creates `<tmp>/project/file.txt` and `<tmp>/other/secret.txt`.
+fn create_project_and_sibling() -> (tempfile::TempDir, PathBuf, PathBuf) {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = tmp.path().join("project");
+    let other = tmp.path().join("other");
+    std::fs::create_dir(&project).unwrap();
+    std::fs::create_dir(&other).unwrap();
+    std::fs::write(project.join("file.txt"), "project\n").unwrap();
+    std::fs::write(other.join("secret.txt"), "secret\n").unwrap();
+    (tmp, project, other)
+}
+
+#[tokio::test]
+async fn project_only_policy_hides_files_outside_cwd() {
+    let (_tmp, project, other) = create_project_and_sibling();
+    let policy = SandboxPolicy::from(vec![SandboxPermission::DiskWriteCwd]);
+
+    // System binaries still run and the project itself is readable.
+    let (exit_code, stdout) = run_cmd_with_policy(&["cat", "file.txt"], &project, &policy).await;
+    assert_eq!((0, "project\n".to_string()), (exit_code, stdout));
+
+    let secret = other.join("secret.txt");
+    let (exit_code, stdout) =
+        run_cmd_with_policy(&["cat", &secret.to_string_lossy()], &project, &policy).await;
+    assert_ne!(0, exit_code);
+    assert_eq!("", stdout);
+
+    let (exit_code, _) =
+        run_cmd_with_policy(&["ls", &other.to_string_lossy()], &project, &policy).await;
+    assert_ne!(0, exit_code);
+}
+
+#[tokio::test]
+async fn disk_read_folder_grants_read_only_access() {
+    let (_tmp, project, other) = create_project_and_sibling();
+    let policy = SandboxPolicy::from(vec![
+        SandboxPermission::DiskWriteCwd,
+        SandboxPermission::DiskReadFolder {
+            folder: other.clone(),
+        },
+    ]);
+
+    let secret = other.join("secret.txt");
+    let (exit_code, stdout) =
+        run_cmd_with_policy(&["cat", &secret.to_string_lossy()], &project, &policy).await;
+    assert_eq!((0, "secret\n".to_string()), (exit_code, stdout));
+
+    let new_file = other.join("new.txt");
+    let (exit_code, _) =
+        run_cmd_with_policy(&["touch", &new_file.to_string_lossy()], &project, &policy).await;
+    assert_ne!(0, exit_code);
+    assert!(!new_file.exists());
+}
+
+/// Starts an HTTP server on `host` that answers every request with `ok`.
+async fn start_http_server(host: &str) -> u16 {
+    let listener = TcpListener::bind((host, 0)).await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf).await;
+                let _ = stream
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                    )
+                    .await;
+            });
+        }
+    });
+    port
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn network_allow_domains_only_reaches_allowed_hosts_via_proxy() {
+    if which_curl().is_none() {
+        return;
+    }
+    let server_port = start_http_server("127.0.0.1").await;
+    let policy = SandboxPolicy::from(vec![
+        SandboxPermission::DiskFullReadAccess,
+        SandboxPermission::NetworkAllowDomains {
+            domains: vec!["127.0.0.1".to_string()],
+        },
+    ]);
+    let (tx_denied, mut rx_denied) = tokio::sync::mpsc::unbounded_channel();
+    let proxy = NetworkProxy::start(policy.get_allowed_network_domains(), tx_denied)
+        .await
+        .unwrap();
+    let mut env = create_env_from_core_vars();
+    proxy.apply_to_env(&mut env);
+    let cwd = std::env::current_dir().unwrap();
+    let url = format!("http://127.0.0.1:{server_port}/");
+
+    // Allowed host, through the proxy.
+    let (exit_code, stdout) =
+        run_cmd_with_policy_and_env(&["curl", "-sS", &url], &cwd, &policy, env.clone()).await;
+    assert_eq!((0, "ok".to_string()), (exit_code, stdout));
+
+    // Host that is not on the allowlist.
+    let denied_url = format!("http://localhost:{server_port}/");
+    let (exit_code, _) =
+        run_cmd_with_policy_and_env(&["curl", "-sSf", &denied_url], &cwd, &policy, env.clone())
+            .await;
+    assert_ne!(0, exit_code);
+    assert_eq!(Some("localhost".to_string()), rx_denied.recv().await);
+
+    // Bypassing the proxy is blocked by the sandbox.
+    let (exit_code, _) =
+        run_cmd_with_policy_and_env(&["curl", "-sS", "--noproxy", "*", &url], &cwd, &policy, env)
+            .await;
+    assert_ne!(0, exit_code);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn proxy_port_is_only_reachable_on_loopback() {
+    if which_curl().is_none() {
+        return;
+    }
+    // Any address of this host other than loopback.
+    let Some(host_ip) = std::net::UdpSocket::bind(("0.0.0.0", 0))
+        .and_then(|socket| socket.connect(("192.0.2.1", 9)).map(|()| socket))
+        .and_then(|socket| socket.local_addr())
+        .ok()
+        .map(|addr| addr.ip())
+        .filter(|ip| !ip.is_loopback())
+    else {
+        return;
+    };
+    // Stands in for the proxy, and listens on every interface.
+    let proxy_port = start_http_server("0.0.0.0").await;
+    let policy = SandboxPolicy::from(vec![
+        SandboxPermission::DiskFullReadAccess,
+        SandboxPermission::NetworkAllowDomains {
+            domains: vec!["example.com".to_string()],
+        },
+    ]);
+    let mut env = create_env_from_core_vars();
+    env.insert(
+        SEEKY_SANDBOX_NETWORK_PROXY_PORT_ENV_VAR.to_string(),
+        proxy_port.to_string(),
+    );
+    let cwd = std::env::current_dir().unwrap();
+    let loopback_url = format!("http://127.0.0.1:{proxy_port}/");
+    let host_url = format!("http://{host_ip}:{proxy_port}/");
+
+    for sandbox_type in [SandboxType::LinuxSeccomp, SandboxType::LinuxNamespaces] {
+        let (exit_code, stdout) = run_cmd_in_sandbox(
+            sandbox_type,
+            &["curl", "-sS", "--noproxy", "*", &loopback_url],
+            &cwd,
+            &policy,
+            env.clone(),
+        )
+        .await;
+        assert_eq!(
+            (0, "ok".to_string()),
+            (exit_code, stdout),
+            "{sandbox_type:?}"
+        );
+
+        let (exit_code, stdout) = run_cmd_in_sandbox(
+            sandbox_type,
+            &["curl", "-sS", "--noproxy", "*", &host_url],
+            &cwd,
+            &policy,
+            env.clone(),
+        )
+        .await;
+        assert_ne!(0, exit_code, "{sandbox_type:?}");
+        assert_eq!("", stdout, "{sandbox_type:?}");
+    }
+}
+
+fn which_curl() -> Option<PathBuf> {
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join("curl"))
+            .find(|path| path.is_file())
+    })
+}
+
 /// Helper that runs `cmd` under the Linux sandbox and asserts that the command
 /// does NOT succeed (i.e. returns a non‑zero exit code) **unless** the binary
 /// is missing in which case we silently treat it as an accepted skip so the
@@ -117,6 +361,7 @@ async fn assert_network_blocked(cmd: &[&str]) {
         // do not stall the suite.
         timeout_ms: Some(2_000),
         env: create_env_from_core_vars(),
+        limits: ResourceLimits::default(),
     };
 
     let sandbox_policy = SandboxPolicy::new_read_only_policy();
@@ -134,7 +379,7 @@ async fn assert_network_blocked(cmd: &[&str]) {
 
     let (exit_code, stdout, stderr) = match result {
         Ok(output) => (output.exit_code, output.stdout, output.stderr),
-        Err(SeekyErr::Sandbox(SandboxErr::Denied(exit_code, stdout, stderr))) => {
+        Err(SeekyErr::Sandbox(SandboxErr::Denied(exit_code, stdout, stderr, _))) => {
             (exit_code, stdout, stderr)
         }
         _ => {
@@ -207,3 +452,188 @@ async fn sandbox_blocks_dev_tcp_redirection() {
     // all images ship bash, so we guard against 127 as well.
     assert_network_blocked(&["bash", "-c", "echo hi > /dev/tcp/127.0.0.1/80"]).await;
 }
+
+async fn run_script_with_limits(
+    script: &str,
+    cwd: &Path,
+    limits: ResourceLimits,
+) -> seeky_core::error::Result<i32> {
+    let params = ExecParams {
+        command: vec!["bash".to_string(), "-c".to_string(), script.to_string()],
+        cwd: cwd.to_path_buf(),
+        timeout_ms: Some(5_000),
+        env: create_env_from_core_vars(),
+        limits,
+    };
+    let sandbox_program = env!("CARGO_BIN_EXE_seeky-linux-sandbox");
+    // The default protected paths are missing from `cwd`, which would keep
+    // the script from creating files in it.
+    let policy = SandboxPolicy::from(vec![
+        SandboxPermission::DiskFullReadAccess,
+        SandboxPermission::DiskWriteCwd,
+    ])
+    .with_protected_paths(Vec::new());
+    process_exec_tool_call(
+        params,
+        SandboxType::LinuxSeccomp,
+        Arc::new(Notify::new()),
+        &policy,
+        &Some(PathBuf::from(sandbox_program)),
+    )
+    .await
+    .map(|output| output.exit_code)
+}
+
+#[tokio::test]
+async fn resource_limits_are_applied_inside_the_sandbox() {
+    let tmp = tempfile::tempdir().unwrap();
+
+    let limits = ResourceLimits {
+        file_size_bytes: Some(1024),
+        ..Default::default()
+    };
+    let result = run_script_with_limits("head -c 4096 /dev/zero > big", tmp.path(), limits).await;
+    assert!(
+        matches!(
+            result,
+            Err(SeekyErr::Sandbox(SandboxErr::FileSizeLimitExceeded(
+                1024,
+                _
+            )))
+        ),
+        "{result:?}"
+    );
+
+    let limits = ResourceLimits {
+        cpu_seconds: Some(1),
+        ..Default::default()
+    };
+    let result = run_script_with_limits("while :; do :; done", tmp.path(), limits).await;
+    assert!(
+        matches!(
+            result,
+            Err(SeekyErr::Sandbox(SandboxErr::CpuTimeLimitExceeded(1, _)))
+        ),
+        "{result:?}"
+    );
+
+    let limits = ResourceLimits {
+        open_files: Some(16),
+        ..Default::default()
+    };
+    assert_eq!(
+        0,
+        run_script_with_limits("test \"$(ulimit -n)\" = 16", tmp.path(), limits)
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn denied_writes_report_the_offending_path() {
+    let (_tmp, project, other) = create_project_and_sibling();
+    let policy = SandboxPolicy::from(vec![
+        SandboxPermission::DiskFullReadAccess,
+        SandboxPermission::DiskWriteCwd,
+    ]);
+    let target = other.join("new.txt");
+
+    let params = ExecParams {
+        command: vec!["touch".to_string(), target.to_string_lossy().to_string()],
+        cwd: project.clone(),
+        timeout_ms: Some(2_000),
+        env: create_env_from_core_vars(),
+        limits: ResourceLimits::default(),
+    };
+    let sandbox_program = env!("CARGO_BIN_EXE_seeky-linux-sandbox");
+    let result = process_exec_tool_call(
+        params,
+        SandboxType::LinuxSeccomp,
+        Arc::new(Notify::new()),
+        &policy,
+        &Some(PathBuf::from(sandbox_program)),
+    )
+    .await;
+
+    let Err(SeekyErr::Sandbox(error)) = result else {
+        panic!("expected a sandbox denial, got {result:?}");
+    };
+    assert_eq!(
+        Some(&SandboxDenial {
+            kind: SandboxDenialKind::Write,
+            path: Some(target),
+        }),
+        error.sandbox_denial()
+    );
+}
+
+#[tokio::test]
+async fn protected_paths_stay_read_only_inside_writable_roots() {
+    let (_tmp, project, _other) = create_project_and_sibling();
+    let git_init = std::process::Command::new("git")
+        .args(["init", "-q"])
+        .current_dir(&project)
+        .status()
+        .unwrap();
+    assert!(git_init.success());
+    std::fs::create_dir(project.join(".seeky")).unwrap();
+    let policy = SandboxPolicy::new_full_auto_policy();
+    let script = r#"
+        git="git -c user.name=seeky -c user.email=seeky@example.com -c maintenance.auto=false"
+        echo change >> file.txt && $git add file.txt && $git commit -q -m change || exit 1
+        if mv .git .git-moved 2>/dev/null; then echo "moved .git"; exit 1; fi
+        for path in .git/hooks/pre-commit .git/config .git/info/exclude .seeky/config.toml; do
+            if touch "$path" 2>/dev/null; then echo "wrote $path"; exit 1; fi
+        done
+        echo ok
+    "#;
+
+    for sandbox_type in [SandboxType::LinuxSeccomp, SandboxType::LinuxNamespaces] {
+        let output = run_cmd_in_sandbox(
+            sandbox_type,
+            &["sh", "-c", script],
+            &project,
+            &policy,
+            create_env_from_core_vars(),
+        )
+        .await;
+        assert_eq!((0, "ok\n".to_string()), output, "{sandbox_type:?}");
+        assert!(!project.join(".git/hooks/pre-commit").exists());
+    }
+
+    let commits = std::process::Command::new("git")
+        .args(["rev-list", "--count", "HEAD"])
+        .current_dir(&project)
+        .output()
+        .unwrap();
+    assert_eq!("2\n", String::from_utf8_lossy(&commits.stdout));
+}
+
+#[tokio::test]
+async fn missing_protected_paths_cannot_be_created() {
+    let policy = SandboxPolicy::new_full_auto_policy();
+    let script = r#"
+        mkdir src/nested && touch src/new.txt && echo change >> file.txt || exit 1
+        for path in .seeky .git; do
+            if mkdir "$path" 2>/dev/null; then echo "created $path"; exit 1; fi
+        done
+        echo ok
+    "#;
+
+    for sandbox_type in [SandboxType::LinuxSeccomp, SandboxType::LinuxNamespaces] {
+        let (_tmp, project, _other) = create_project_and_sibling();
+        std::fs::create_dir(project.join("src")).unwrap();
+        let output = run_cmd_in_sandbox(
+            sandbox_type,
+            &["sh", "-c", script],
+            &project,
+            &policy,
+            create_env_from_core_vars(),
+        )
+        .await;
+        assert_eq!((0, "ok\n".to_string()), output, "{sandbox_type:?}");
+        assert!(project.join("src/nested").is_dir());
+        assert!(!project.join(".seeky").exists());
+        assert!(!project.join(".git").exists());
+    }
+}
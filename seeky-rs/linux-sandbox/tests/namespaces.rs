@@ -0,0 +1,129 @@
+#![cfg(target_os = "linux")]
+#![expect(clippy::unwrap_used)]
+
+use seeky_core::config_types::ResourceLimits;
+use seeky_core::config_types::ShellEnvironmentPolicy;
+use seeky_core::error::SandboxErr;
+use seeky_core::error::SeekyErr;
+use seeky_core::exec::ExecParams;
+use seeky_core::exec::SandboxType;
+use seeky_core::exec::process_exec_tool_call;
+use seeky_core::exec_env::create_env;
+use seeky_core::protocol::SandboxPermission;
+use seeky_core::protocol::SandboxPolicy;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Runs `bash -c script` in `cwd` under the namespace backend and returns the
+/// exit code and stdout.
+async fn run_script(script: &str, cwd: &Path, sandbox_policy: &SandboxPolicy) -> (i32, String) {
+    let params = ExecParams {
+        command: vec!["bash".to_string(), "-c".to_string(), script.to_string()],
+        cwd: cwd.to_path_buf(),
+        timeout_ms: Some(2_000),
+        env: create_env(&ShellEnvironmentPolicy::default()),
+        limits: ResourceLimits::default(),
+    };
+    let sandbox_program = env!("CARGO_BIN_EXE_seeky-linux-sandbox");
+    let result = process_exec_tool_call(
+        params,
+        SandboxType::LinuxNamespaces,
+        Arc::new(Notify::new()),
+        sandbox_policy,
+        &Some(PathBuf::from(sandbox_program)),
+    )
+    .await;
+    match result {
+        Ok(output) => (output.exit_code, output.stdout),
+        Err(SeekyErr::Sandbox(SandboxErr::Denied(exit_code, stdout, _, _))) => (exit_code, stdout),
+        Err(e) => panic!("unexpected error: {e:?}"),
+    }
+}
+
+/// The backend silently falls back to Landlock when user namespaces are not
+/// available; the namespace-specific assertions are skipped in that case.
+async fn namespaces_available(cwd: &Path) -> bool {
+    let policy = SandboxPolicy::new_read_only_policy();
+    // In a fresh PID namespace the command runs as PID 2, below a minimal init.
+    let (_, stdout) = run_script("echo $$", cwd, &policy).await;
+    stdout.trim() == "2"
+}
+
+#[tokio::test]
+async fn writes_are_confined_to_writable_roots() {
+    let project = tempfile::tempdir().unwrap();
+    let outside = tempfile::tempdir_in(env!("CARGO_TARGET_TMPDIR")).unwrap();
+    // The default protected paths are missing from the project, which would
+    // keep the script from creating files in it.
+    let policy = SandboxPolicy::from(vec![
+        SandboxPermission::DiskFullReadAccess,
+        SandboxPermission::DiskWriteCwd,
+    ])
+    .with_protected_paths(Vec::new());
+
+    let (exit_code, _) = run_script("echo inside > file.txt", project.path(), &policy).await;
+    assert_eq!(0, exit_code);
+    assert_eq!(
+        "inside\n",
+        std::fs::read_to_string(project.path().join("file.txt")).unwrap()
+    );
+
+    let target = outside.path().join("file.txt");
+    let (exit_code, _) = run_script(
+        &format!("echo outside > {}", target.display()),
+        project.path(),
+        &policy,
+    )
+    .await;
+    assert_ne!(0, exit_code);
+    assert!(!target.exists());
+}
+
+#[tokio::test]
+async fn tmp_and_pids_are_private() {
+    let project = tempfile::tempdir().unwrap();
+    if !namespaces_available(project.path()).await {
+        return;
+    }
+    let policy = SandboxPolicy::from(vec![
+        SandboxPermission::DiskFullReadAccess,
+        SandboxPermission::DiskWriteCwd,
+    ]);
+    let marker = format!("seeky-namespaces-test-{}", std::process::id());
+
+    let (exit_code, stdout) = run_script(
+        &format!("echo hi > /tmp/{marker} && ls /proc | grep -c '^[0-9]'"),
+        project.path(),
+        &policy,
+    )
+    .await;
+    assert_eq!(0, exit_code);
+    // Only the init process, the shell, `ls` and `grep`.
+    assert!(stdout.trim().parse::<u32>().unwrap() <= 4, "{stdout}");
+    assert!(!Path::new("/tmp").join(&marker).exists());
+}
+
+#[tokio::test]
+async fn network_is_unreachable_without_network_access() {
+    let project = tempfile::tempdir().unwrap();
+    if !namespaces_available(project.path()).await {
+        return;
+    }
+    let listener = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let policy = SandboxPolicy::from(vec![
+        SandboxPermission::DiskFullReadAccess,
+        SandboxPermission::DiskWriteCwd,
+    ]);
+
+    // Inside the new network namespace even the loopback interface is down.
+    let (exit_code, _) = run_script(
+        &format!("exec 3<>/dev/tcp/127.0.0.1/{port}"),
+        project.path(),
+        &policy,
+    )
+    .await;
+    assert_ne!(0, exit_code);
+}
@@ -10,6 +10,7 @@ use tokio::io::AsyncWriteExt;
 use tokio::io::BufReader;
 use tokio::io::{self};
 use tokio::sync::mpsc;
+use toml::Value as TomlValue;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
@@ -25,7 +26,13 @@ use crate::message_processor::MessageProcessor;
 /// plenty for an interactive CLI.
 const CHANNEL_CAPACITY: usize = 128;
 
-pub async fn run_main(seeky_linux_sandbox_exe: Option<PathBuf>) -> IoResult<()> {
+/// Runs the MCP server over stdin/stdout. `cli_overrides` are generic
+/// `-c key=value` config overrides that are applied to every Seeky session
+/// started by a `seeky` tool call.
+pub async fn run_main(
+    seeky_linux_sandbox_exe: Option<PathBuf>,
+    cli_overrides: Vec<(String, TomlValue)>,
+) -> IoResult<()> {
     // Install a simple subscriber so `tracing` output is visible.  Users can
     // control the log level with `RUST_LOG`.
     tracing_subscriber::fmt()
@@ -62,7 +69,8 @@ pub async fn run_main(seeky_linux_sandbox_exe: Option<PathBuf>) -> IoResult<()>
 
     // Task: process incoming messages.
     let processor_handle = tokio::spawn({
-        let mut processor = MessageProcessor::new(outgoing_tx.clone(), seeky_linux_sandbox_exe);
+        let mut processor =
+            MessageProcessor::new(outgoing_tx.clone(), seeky_linux_sandbox_exe, cli_overrides);
         async move {
             while let Some(msg) = incoming_rx.recv().await {
                 match msg {
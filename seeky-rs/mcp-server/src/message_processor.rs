@@ -26,11 +26,13 @@ use seeky_core::config::Config as SeekyConfig;
 use serde_json::json;
 use tokio::sync::mpsc;
 use tokio::task;
+use toml::Value as TomlValue;
 
 pub(crate) struct MessageProcessor {
     outgoing: mpsc::Sender<JSONRPCMessage>,
     initialized: bool,
     seeky_linux_sandbox_exe: Option<PathBuf>,
+    cli_overrides: Vec<(String, TomlValue)>,
 }
 
 impl MessageProcessor {
@@ -39,11 +41,13 @@ impl MessageProcessor {
     pub(crate) fn new(
         outgoing: mpsc::Sender<JSONRPCMessage>,
         seeky_linux_sandbox_exe: Option<PathBuf>,
+        cli_overrides: Vec<(String, TomlValue)>,
     ) -> Self {
         Self {
             outgoing,
             initialized: false,
             seeky_linux_sandbox_exe,
+            cli_overrides,
         }
     }
 
@@ -346,7 +350,10 @@ impl MessageProcessor {
 
         let (initial_prompt, config): (String, SeekyConfig) = match arguments {
             Some(json_val) => match serde_json::from_value::<SeekyToolCallParam>(json_val) {
-                Ok(tool_cfg) => match tool_cfg.into_config(self.seeky_linux_sandbox_exe.clone()) {
+                Ok(tool_cfg) => match tool_cfg.into_config(
+                    self.seeky_linux_sandbox_exe.clone(),
+                    self.cli_overrides.clone(),
+                ) {
                     Ok(cfg) => cfg,
                     Err(e) => {
                         let result = CallToolResult {
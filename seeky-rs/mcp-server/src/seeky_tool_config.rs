@@ -7,6 +7,7 @@ use mcp_types::ToolInputSchema;
 use schemars::JsonSchema;
 use schemars::r#gen::SchemaSettings;
 use serde::Deserialize;
+use toml::Value as TomlValue;
 
 use seeky_core::protocol::AskForApproval;
 use seeky_core::protocol::SandboxPolicy;
@@ -147,6 +148,7 @@ impl SeekyToolCallParam {
     pub fn into_config(
         self,
         seeky_linux_sandbox_exe: Option<PathBuf>,
+        cli_overrides: Vec<(String, TomlValue)>,
     ) -> std::io::Result<(String, seeky_core::config::Config)> {
         let Self {
             prompt,
@@ -173,7 +175,7 @@ impl SeekyToolCallParam {
             seeky_linux_sandbox_exe,
         };
 
-        let cfg = seeky_core::config::Config::load_with_overrides(overrides)?;
+        let cfg = seeky_core::config::Config::load_with_cli_overrides(cli_overrides, overrides)?;
 
         Ok((prompt, cfg))
     }
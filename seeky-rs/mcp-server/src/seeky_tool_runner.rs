@@ -170,7 +170,9 @@ pub async fn run_seeky_tool_session(
                     | EventMsg::BackgroundEvent(_)
                     | EventMsg::PatchApplyBegin(_)
                     | EventMsg::PatchApplyEnd(_)
-                    | EventMsg::GetHistoryEntryResponse(_) => {
+                    | EventMsg::GetHistoryEntryResponse(_)
+                    | EventMsg::SearchHistoryResponse(_)
+                    | EventMsg::HistoryCompacted(_) => {
                         // For now, we do not do anything extra for these
                         // events. Note that
                         // send(seeky_event_to_notification(&event)) above has
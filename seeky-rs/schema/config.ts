@@ -0,0 +1,340 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+/**
+ * Base config deserialized from ~/.seeky/config.toml.
+ */
+export type ConfigToml = {
+  /**
+   * Default approval policy for executing commands.
+   */
+  approval_policy?: AskForApproval | null;
+  /**
+   * Settings for the audit log of commands and patches.
+   */
+  audit?: AuditToml | null;
+  /**
+   * Disable server-side response storage (sends the full conversation context with every request). Currently necessary for OpenAI customers who have opted into Zero Data Retention (ZDR).
+   */
+  disable_response_storage?: boolean | null;
+  /**
+   * Additional `.policy` files for command safety, loaded after the ones in `$SEEKY_HOME/policies`. Relative paths are resolved against `$SEEKY_HOME`.
+   */
+  exec_policy_files?: Array<string> | null;
+  /**
+   * Optional URI-based file opener. If set, citations to files in the model output will be hyperlinked using the specified URI scheme.
+   */
+  file_opener?: UriBasedFileOpener | null;
+  /**
+   * Settings that govern if and what will be written to `~/.seeky/history.jsonl`.
+   */
+  history?: HistoryToml | null;
+  /**
+   * System instructions.
+   */
+  instructions?: string | null;
+  /**
+   * Definition for MCP servers that Seeky can reach out to for tool calls.
+   */
+  mcp_servers?: {
+    [key: string]: McpServerConfig;
+  };
+  /**
+   * Optional override of model selection.
+   */
+  model?: string | null;
+  /**
+   * Provider to use from the model_providers map.
+   */
+  model_provider?: string | null;
+  /**
+   * User-defined provider entries that extend/override the built-in list.
+   */
+  model_providers?: {
+    [key: string]: ModelProviderInfo;
+  };
+  /**
+   * How much effort reasoning models should spend before responding.
+   */
+  model_reasoning_effort?: ReasoningEffort | null;
+  /**
+   * Level of detail requested for reasoning summaries.
+   */
+  model_reasoning_summary?: ReasoningSummary | null;
+  /**
+   * Optional external command to spawn for end-user notifications.
+   */
+  notify?: Array<string> | null;
+  /**
+   * Profile to use from the `profiles` map.
+   */
+  profile?: string | null;
+  /**
+   * Named profiles to facilitate switching between different configurations.
+   */
+  profiles?: {
+    [key: string]: ConfigProfile;
+  };
+  /**
+   * Maximum number of bytes to include from an AGENTS.md project doc file.
+   */
+  project_doc_max_bytes?: number | null;
+  /**
+   * Settings for scrubbing secrets from tool output, history and rollouts.
+   */
+  redaction?: RedactionToml | null;
+  /**
+   * Settings for the sandbox in which commands are executed.
+   */
+  sandbox?: SandboxToml | null;
+  sandbox_permissions?: Array<string> | null;
+  shell_environment_policy?: ShellEnvironmentPolicyToml;
+  /**
+   * Collection of settings that are specific to the TUI.
+   */
+  tui?: Tui | null;
+};
+
+/**
+ * Determines how liberally commands are auto‑approved by the system.
+ */
+export type AskForApproval = "unless-allow-listed" | "auto-edit" | "on-failure" | "never";
+
+/**
+ * `[audit]` as written in `config.toml` or a profile. Fields left unset fall back to the table this one is layered on.
+ */
+export type AuditToml = {
+  /**
+   * Set to `true` to write `audit.jsonl`.
+   */
+  enabled?: boolean | null;
+  /**
+   * Write the log to `.seeky/audit.jsonl` in the project (the root of the Git repository, or the cwd outside of one) instead of `~/.seeky/audit.jsonl`.
+   */
+  per_project?: boolean | null;
+};
+
+/**
+ * Collection of common configuration options that a user can define as a unit in `config.toml`. Every field is optional: anything left unset falls back to the profile named by `extends` (if any) and then to the top-level value in `config.toml`.
+ */
+export type ConfigProfile = {
+  approval_policy?: AskForApproval | null;
+  audit?: AuditToml | null;
+  disable_response_storage?: boolean | null;
+  /**
+   * Names of MCP servers from the top-level `mcp_servers` map (or from an inherited profile) that should not be started with this profile.
+   */
+  disabled_mcp_servers?: Array<string>;
+  /**
+   * Name of another profile whose values this profile inherits. Values set directly on this profile take precedence over inherited ones.
+   */
+  extends?: string | null;
+  file_opener?: UriBasedFileOpener | null;
+  /**
+   * Like `shell_environment_policy`, the `history`, `redaction`, `audit` and `sandbox` tables override the inherited ones field by field.
+   */
+  history?: HistoryToml | null;
+  instructions?: string | null;
+  /**
+   * MCP servers to add to (or replace in) the top-level `mcp_servers` map.
+   */
+  mcp_servers?: {
+    [key: string]: McpServerConfig;
+  };
+  model?: string | null;
+  /**
+   * The key in the `model_providers` map identifying the [`ModelProviderInfo`] to use.
+   */
+  model_provider?: string | null;
+  model_reasoning_effort?: ReasoningEffort | null;
+  model_reasoning_summary?: ReasoningSummary | null;
+  notify?: Array<string> | null;
+  project_doc_max_bytes?: number | null;
+  redaction?: RedactionToml | null;
+  sandbox?: SandboxToml | null;
+  sandbox_permissions?: Array<string> | null;
+  /**
+   * Individual fields set here override the corresponding fields of the top-level `shell_environment_policy`.
+   */
+  shell_environment_policy?: ShellEnvironmentPolicyToml | null;
+  tui?: Tui | null;
+};
+
+export type HistoryPersistence = "save-all" | "none";
+
+/**
+ * `[history]` as written in `config.toml` or a profile. Fields left unset fall back to the table this one is layered on.
+ */
+export type HistoryToml = {
+  /**
+   * If set, entries older than this many days are dropped.
+   */
+  max_age_days?: number | null;
+  /**
+   * If set, the maximum size of the history file in bytes. Once the file grows past this size, the oldest entries are dropped.
+   */
+  max_bytes?: number | null;
+  /**
+   * If true, history entries will not be written to disk.
+   */
+  persistence?: HistoryPersistence | null;
+};
+
+export type LinuxSandboxBackend = "landlock" | "namespaces";
+
+export type McpServerConfig = {
+  args?: Array<string>;
+  command: string;
+  env?: {
+    [key: string]: string;
+  } | null;
+};
+
+/**
+ * Serializable representation of a provider definition.
+ */
+export type ModelProviderInfo = {
+  /**
+   * Base URL for the provider's OpenAI-compatible API.
+   */
+  base_url: string;
+  /**
+   * Environment variable that stores the user's API key for this provider.
+   */
+  env_key?: string | null;
+  /**
+   * Optional instructions to help the user get a valid value for the variable and set it.
+   */
+  env_key_instructions?: string | null;
+  /**
+   * Friendly display name.
+   */
+  name: string;
+  /**
+   * Which wire protocol this provider expects.
+   */
+  wire_api: WireApi;
+};
+
+/**
+ * Controls how much effort reasoning models spend before responding. See https://platform.openai.com/docs/guides/reasoning for details.
+ */
+export type ReasoningEffort = "low" | "medium" | "high" | "none";
+
+/**
+ * Level of detail of the reasoning summary returned by reasoning models.
+ */
+export type ReasoningSummary = "auto" | "concise" | "detailed" | "none";
+
+/**
+ * `[redaction]` as written in `config.toml` or a profile. Fields left unset fall back to the table this one is layered on.
+ */
+export type RedactionToml = {
+  /**
+   * Set to `false` to disable redaction, including the built-in detectors for AWS keys, GitHub tokens, JWTs, private keys and `*_KEY=`-style assignments.
+   */
+  enabled?: boolean | null;
+  /**
+   * Additional regular expressions to redact. If a pattern has a capture group named `secret`, only that group is replaced.
+   */
+  patterns?: Array<string> | null;
+};
+
+/**
+ * Upper bounds on the resources a command and its children may use. Unset limits are inherited from Seeky. On Linux, `memory_bytes` and `max_processes` are enforced by a cgroup v2 leaf when a delegated cgroup is available, and by `setrlimit` otherwise.
+ */
+export type ResourceLimits = {
+  /**
+   * CPU time in seconds (`RLIMIT_CPU`).
+   */
+  cpu_seconds?: number | null;
+  /**
+   * Size in bytes of the largest file the command may write (`RLIMIT_FSIZE`).
+   */
+  file_size_bytes?: number | null;
+  /**
+   * Number of processes: `pids.max` of the cgroup, or `RLIMIT_NPROC` as a fallback, which counts all processes of the current user.
+   */
+  max_processes?: number | null;
+  /**
+   * Memory in bytes: `memory.max` of the cgroup, or the address space (`RLIMIT_AS`) as a fallback. Note that some runtimes reserve far more address space than they use. Not enforced on macOS.
+   */
+  memory_bytes?: number | null;
+  /**
+   * Number of open file descriptors per process (`RLIMIT_NOFILE`).
+   */
+  open_files?: number | null;
+};
+
+/**
+ * `[sandbox]` as written in `config.toml` or a profile. Fields left unset, including the individual limits, fall back to the table this one is layered on.
+ */
+export type SandboxToml = {
+  /**
+   * Resource limits applied to every command, sandboxed or not.
+   */
+  limits?: ResourceLimits | null;
+  /**
+   * Mechanism used by `seeky-linux-sandbox` to enforce the sandbox policy.
+   */
+  linux_backend?: LinuxSandboxBackend | null;
+  /**
+   * Paths that stay read-only inside the writable folders. Relative entries apply inside every writable folder; entries starting with `!` keep a path beneath a protected one writable. Defaults to `.git/hooks`, `.git/config`, `.git/info` and `.seeky`.
+   */
+  protected_paths?: Array<string> | null;
+};
+
+export type ShellEnvironmentPolicyInherit = "Core" | "All" | "None";
+
+/**
+ * Policy for building the `env` when spawning a process via either the `shell` or `local_shell` tool.
+ */
+export type ShellEnvironmentPolicyToml = {
+  /**
+   * Files with `NAME=value` lines (dotenv syntax) to load before `set`. Relative paths are resolved against the root of the Git repository, or against the cwd outside of one.
+   */
+  env_files?: Array<string> | null;
+  /**
+   * List of regular expressions.
+   */
+  exclude?: Array<string> | null;
+  ignore_default_excludes?: boolean | null;
+  /**
+   * List of regular expressions.
+   */
+  include_only?: Array<string> | null;
+  inherit?: ShellEnvironmentPolicyInherit | null;
+  /**
+   * Variables whose values must never appear in output sent to the model.
+   */
+  secrets?: Array<string> | null;
+  set?: {
+    [key: string]: string;
+  } | null;
+  /**
+   * Start from the environment of the user's login shell instead of the environment Seeky was started with.
+   */
+  snapshot_login_shell?: boolean | null;
+  /**
+   * Run `bash -lc` commands with the user's shell, if it is bash or zsh, with the aliases and functions of its login shell. Implies `snapshot_login_shell`.
+   */
+  use_user_shell?: boolean | null;
+};
+
+/**
+ * Collection of settings that are specific to the TUI.
+ */
+export type Tui = {
+  /**
+   * By default, mouse capture is enabled in the TUI so that it is possible to scroll the conversation history with a mouse. This comes at the cost of not being able to use the mouse to select text in the TUI. (Most terminals support a modifier key to allow this. For example, text selection works in iTerm if you hold down the `Option` key while clicking and dragging.)
+   *
+   * Setting this option to `true` disables mouse capture, so scrolling with the mouse is not possible, though the keyboard shortcuts e.g. `b` and `space` still work. This allows the user to select text in the TUI using the mouse without needing to hold down a modifier key.
+   */
+  disable_mouse_capture: boolean;
+};
+
+export type UriBasedFileOpener = "vscode" | "vscode-insiders" | "windsurf" | "cursor" | "none";
+
+/**
+ * Wire protocol that the provider speaks. Most third-party services only implement the classic OpenAI Chat Completions JSON schema, whereas OpenAI itself (and a handful of others) additionally expose the more modern *Responses* API. The two protocols use different request/response shapes and *cannot* be auto-detected at runtime, therefore each provider entry must declare which one it expects.
+ */
+export type WireApi = "responses" | "chat";
@@ -0,0 +1,446 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+/**
+ * A message on the Seeky protocol: a `Submission` sent by the client or an `Event` sent by the agent.
+ */
+export type SeekyProtocol = Submission | Event;
+
+/**
+ * Determines how liberally commands are auto‑approved by the system.
+ */
+export type AskForApproval = "unless-allow-listed" | "auto-edit" | "on-failure" | "never";
+
+/**
+ * Event Queue Entry - events from agent
+ */
+export type Event = {
+  /**
+   * Submission `id` that this event is correlated with.
+   */
+  id: string;
+  /**
+   * Payload
+   */
+  msg: EventMsg;
+};
+
+/**
+ * Response event from the agent
+ */
+export type EventMsg = {
+  message: string;
+  type: "error";
+} | {
+  type: "task_started";
+} | {
+  last_agent_message?: string | null;
+  type: "task_complete";
+} | {
+  message: string;
+  type: "agent_message";
+} | {
+  text: string;
+  type: "agent_reasoning";
+} | {
+  /**
+   * Current number of entries in the history log.
+   */
+  history_entry_count: number;
+  /**
+   * Identifier of the history log file (inode on Unix, 0 otherwise).
+   */
+  history_log_id: number;
+  /**
+   * Tell the client what model is being queried.
+   */
+  model: string;
+  /**
+   * Paths of the `AGENTS.md` files that were loaded into the instructions for this session, in the order they were included.
+   */
+  project_docs?: Array<string>;
+  /**
+   * Unique id for this session.
+   */
+  session_id: string;
+  type: "session_configured";
+} | {
+  /**
+   * Arguments to the tool call.
+   */
+  arguments?: unknown;
+  /**
+   * Identifier so this can be paired with the McpToolCallEnd event.
+   */
+  call_id: string;
+  /**
+   * Name of the MCP server as defined in the config.
+   */
+  server: string;
+  /**
+   * Name of the tool as given by the MCP server.
+   */
+  tool: string;
+  type: "mcp_tool_call_begin";
+} | {
+  /**
+   * Identifier for the corresponding McpToolCallBegin that finished.
+   */
+  call_id: string;
+  /**
+   * Result of the tool call. Note this could be an error.
+   */
+  result?: unknown;
+  /**
+   * Whether the tool call was successful. If `false`, `result` might not be present.
+   */
+  success: boolean;
+  type: "mcp_tool_call_end";
+} | {
+  /**
+   * Identifier so this can be paired with the ExecCommandEnd event.
+   */
+  call_id: string;
+  /**
+   * The command to be executed.
+   */
+  command: Array<string>;
+  /**
+   * The command's working directory if not the default cwd for the agent.
+   */
+  cwd: string;
+  type: "exec_command_begin";
+} | {
+  /**
+   * Identifier for the ExecCommandBegin that finished.
+   */
+  call_id: string;
+  /**
+   * The command's exit code.
+   */
+  exit_code: number;
+  /**
+   * Captured stderr
+   */
+  stderr: string;
+  /**
+   * Captured stdout
+   */
+  stdout: string;
+  type: "exec_command_end";
+} | {
+  /**
+   * The command to be executed.
+   */
+  command: Array<string>;
+  /**
+   * The command's working directory.
+   */
+  cwd: string;
+  /**
+   * What the sandbox blocked when this is a request to retry a failed command without the sandbox.
+   */
+  denial?: SandboxDenial | null;
+  /**
+   * Optional human-readable reason for the approval (e.g. retry without sandbox).
+   */
+  reason?: string | null;
+  /**
+   * Whether this is a request to retry a failed command without the sandbox. Approval rules do not lift the sandbox, so [`ReviewDecision::ApprovedAlways`] only approves such a command for the session.
+   */
+  retry_without_sandbox?: boolean;
+  type: "exec_approval_request";
+} | {
+  changes: {
+    [key: string]: FileChange;
+  };
+  /**
+   * When set, the agent is asking the user to allow writes under this root for the remainder of the session.
+   */
+  grant_root?: string | null;
+  /**
+   * Optional explanatory reason (e.g. request for extra write access).
+   */
+  reason?: string | null;
+  type: "apply_patch_approval_request";
+} | {
+  message: string;
+  type: "background_event";
+} | {
+  /**
+   * If true, there was no ApplyPatchApprovalRequest for this patch.
+   */
+  auto_approved: boolean;
+  /**
+   * Identifier so this can be paired with the PatchApplyEnd event.
+   */
+  call_id: string;
+  /**
+   * The changes to be applied.
+   */
+  changes: {
+    [key: string]: FileChange;
+  };
+  type: "patch_apply_begin";
+} | {
+  /**
+   * Identifier for the PatchApplyBegin that finished.
+   */
+  call_id: string;
+  /**
+   * Captured stderr (parser errors, IO failures, etc.).
+   */
+  stderr: string;
+  /**
+   * Captured stdout (summary printed by apply_patch).
+   */
+  stdout: string;
+  /**
+   * Whether the patch was applied successfully.
+   */
+  success: boolean;
+  type: "patch_apply_end";
+} | {
+  /**
+   * The entry at the requested offset, if available and parseable.
+   */
+  entry?: HistoryEntry | null;
+  log_id: number;
+  offset: number;
+  type: "get_history_entry_response";
+} | {
+  /**
+   * The matching entry, if any.
+   */
+  entry?: HistoryEntry | null;
+  log_id: number;
+  /**
+   * Offset of the matching entry, if any.
+   */
+  offset?: number | null;
+  query: string;
+  type: "search_history_response";
+} | {
+  /**
+   * Number of entries in the compacted history log.
+   */
+  history_entry_count: number;
+  /**
+   * Identifier of the compacted history log.
+   */
+  history_log_id: number;
+  type: "history_compacted";
+};
+
+export type FileChange = "delete" | {
+  add: {
+    content: string;
+  };
+} | {
+  update: {
+    move_path?: string | null;
+    unified_diff: string;
+  };
+};
+
+export type HistoryEntry = {
+  session_id: string;
+  text: string;
+  ts: number;
+};
+
+/**
+ * How [`search`] matches the query against the text of an entry.
+ */
+export type HistorySearchMode = "substring" | "prefix";
+
+/**
+ * User input
+ */
+export type InputItem = {
+  text: string;
+  type: "text";
+} | {
+  image_url: string;
+  type: "image";
+} | {
+  path: string;
+  type: "local_image";
+};
+
+/**
+ * Serializable representation of a provider definition.
+ */
+export type ModelProviderInfo = {
+  /**
+   * Base URL for the provider's OpenAI-compatible API.
+   */
+  base_url: string;
+  /**
+   * Environment variable that stores the user's API key for this provider.
+   */
+  env_key?: string | null;
+  /**
+   * Optional instructions to help the user get a valid value for the variable and set it.
+   */
+  env_key_instructions?: string | null;
+  /**
+   * Friendly display name.
+   */
+  name: string;
+  /**
+   * Which wire protocol this provider expects.
+   */
+  wire_api: WireApi;
+};
+
+/**
+ * Submission operation
+ */
+export type Op = {
+  /**
+   * When to escalate for approval for execution
+   */
+  approval_policy: AskForApproval;
+  /**
+   * Working directory that should be treated as the *root* of the session. All relative paths supplied by the model as well as the execution sandbox are resolved against this directory **instead** of the process-wide current working directory. CLI front-ends are expected to expand this to an absolute path before sending the `ConfigureSession` operation so that the business-logic layer can operate deterministically.
+   */
+  cwd: string;
+  /**
+   * Disable server-side response storage (send full context each request)
+   */
+  disable_response_storage?: boolean;
+  /**
+   * Model instructions
+   */
+  instructions?: string | null;
+  /**
+   * If not specified, server will use its default model.
+   */
+  model: string;
+  /**
+   * Optional external notifier command tokens. Present only when the client wants the agent to spawn a program after each completed turn.
+   */
+  notify?: Array<string> | null;
+  /**
+   * Paths of the `AGENTS.md` files whose contents are included in `instructions`.
+   */
+  project_docs?: Array<string>;
+  /**
+   * Provider identifier ("openai", "openrouter", ...).
+   */
+  provider: ModelProviderInfo;
+  /**
+   * How to sandbox commands executed in the system
+   */
+  sandbox_policy: SandboxPolicy;
+  type: "configure_session";
+} | {
+  type: "interrupt";
+} | {
+  /**
+   * User input items, see `InputItem`
+   */
+  items: Array<InputItem>;
+  type: "user_input";
+} | {
+  /**
+   * The user's decision in response to the request.
+   */
+  decision: ReviewDecision;
+  /**
+   * The id of the submission we are approving
+   */
+  id: string;
+  type: "exec_approval";
+} | {
+  /**
+   * The user's decision in response to the request.
+   */
+  decision: ReviewDecision;
+  /**
+   * The id of the submission we are approving
+   */
+  id: string;
+  type: "patch_approval";
+} | {
+  /**
+   * The message text to be stored.
+   */
+  text: string;
+  type: "add_to_history";
+} | {
+  log_id: number;
+  offset: number;
+  type: "get_history_entry_request";
+} | {
+  before_offset?: number | null;
+  log_id: number;
+  mode?: HistorySearchMode;
+  query: string;
+  type: "search_history_request";
+};
+
+/**
+ * User's decision in response to an ExecApprovalRequest.
+ */
+export type ReviewDecision = "approved" | "approved_for_session" | "approved_always" | "denied" | "abort";
+
+/**
+ * An access that the sandbox blocked, as far as it can be told from the output of the failed command.
+ */
+export type SandboxDenial = {
+  kind: SandboxDenialKind;
+  /**
+   * The file or folder the command tried to access, if known.
+   */
+  path?: string | null;
+};
+
+export type SandboxDenialKind = "read" | "write" | "network" | "protected_write";
+
+/**
+ * Permissions that should be granted to the sandbox in which the agent operates.
+ */
+export type SandboxPermission = "disk-full-read-access" | {
+  "disk-read-folder": {
+    folder: string;
+  };
+} | "disk-write-platform-user-temp-folder" | "disk-write-platform-global-temp-folder" | "disk-write-cwd" | {
+  "disk-write-folder": {
+    folder: string;
+  };
+} | "disk-full-write-access" | "network-full-access" | {
+  "network-allow-domains": {
+    domains: Array<string>;
+  };
+};
+
+/**
+ * Determines execution restrictions for model shell commands
+ */
+export type SandboxPolicy = {
+  permissions: Array<SandboxPermission>;
+  /**
+   * Paths that stay read-only even inside the writable roots, see [`SandboxPolicy::get_protected_paths`].
+   */
+  "protected-paths"?: Array<string>;
+};
+
+/**
+ * Submission Queue Entry - requests from user
+ */
+export type Submission = {
+  /**
+   * Unique id for this Submission to correlate with Events
+   */
+  id: string;
+  /**
+   * Payload
+   */
+  op: Op;
+};
+
+/**
+ * Wire protocol that the provider speaks. Most third-party services only implement the classic OpenAI Chat Completions JSON schema, whereas OpenAI itself (and a handful of others) additionally expose the more modern *Responses* API. The two protocols use different request/response shapes and *cannot* be auto-detected at runtime, therefore each provider entry must declare which one it expects.
+ */
+export type WireApi = "responses" | "chat";
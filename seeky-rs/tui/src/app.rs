@@ -177,10 +177,13 @@ impl App<'_> {
                         self.chat_widget.update_latest_log(line);
                     }
                 }
-                AppEvent::DispatchCommand(command) => match command {
+                AppEvent::DispatchCommand(command, args) => match command {
                     SlashCommand::Clear => {
                         self.chat_widget.clear_conversation_history();
                     }
+                    SlashCommand::Approvals => {
+                        self.chat_widget.manage_approval_rules(&args);
+                    }
                     SlashCommand::ToggleMouseMode => {
                         if let Err(e) = mouse_capture.toggle() {
                             tracing::error!("Failed to toggle mouse mode: {e}");
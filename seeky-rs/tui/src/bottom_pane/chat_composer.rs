@@ -53,8 +53,9 @@ impl ChatComposer<'_> {
         this
     }
 
-    /// Record the history metadata advertised by `SessionConfiguredEvent` so
-    /// that the composer can navigate cross-session history.
+    /// Record the history metadata advertised by `SessionConfiguredEvent` or
+    /// `HistoryCompactedEvent` so that the composer can navigate cross-session
+    /// history.
     pub(crate) fn set_history_metadata(&mut self, log_id: u64, entry_count: usize) {
         self.history.set_metadata(log_id, entry_count);
     }
@@ -133,8 +134,19 @@ impl ChatComposer<'_> {
                 ctrl: false,
             } => {
                 if let Some(cmd) = popup.selected_command() {
+                    // Everything after the command token on the first line.
+                    let args = self
+                        .textarea
+                        .lines()
+                        .first()
+                        .and_then(|line| line.trim_start().strip_prefix('/'))
+                        .and_then(|line| line.split_once(char::is_whitespace))
+                        .map(|(_, args)| args.trim().to_string())
+                        .unwrap_or_default();
+
                     // Send command to the app layer.
-                    self.app_event_tx.send(AppEvent::DispatchCommand(*cmd));
+                    self.app_event_tx
+                        .send(AppEvent::DispatchCommand(*cmd, args));
 
                     // Clear textarea so no residual text remains.
                     self.textarea.select_all();
@@ -45,7 +45,8 @@ impl ChatComposerHistory {
         }
     }
 
-    /// Update metadata when a new session is configured.
+    /// Update metadata when a new session is configured or the history log was
+    /// compacted.
     pub fn set_metadata(&mut self, log_id: u64, entry_count: usize) {
         self.history_log_id = Some(log_id);
         self.history_entry_count = entry_count;
@@ -9,6 +9,8 @@ use ratatui::layout::Layout;
 use ratatui::layout::Rect;
 use ratatui::widgets::Widget;
 use ratatui::widgets::WidgetRef;
+use seeky_core::approval_rules::ApprovalRules;
+use seeky_core::approval_rules::RuleDecision;
 use seeky_core::config::Config;
 use seeky_core::protocol::AgentMessageEvent;
 use seeky_core::protocol::AgentReasoningEvent;
@@ -212,6 +214,60 @@ impl ChatWidget<'_> {
         self.request_redraw();
     }
 
+    /// Handles `/approvals`, which lists the saved approval rules, and
+    /// `/approvals remove <n>`. The session reads the rules for every
+    /// command, so removals take effect right away.
+    pub(crate) fn manage_approval_rules(&mut self, args: &str) {
+        let rules = ApprovalRules::new(&self.config.seeky_home, &self.config.cwd);
+        let mut words = args.split_whitespace();
+        let result = match (words.next(), words.next(), words.next()) {
+            (None, _, _) => rules.load().map(|saved| {
+                if saved.is_empty() {
+                    return "no approval rules saved".to_string();
+                }
+                let mut message = "approval rules:".to_string();
+                for (index, saved) in saved.iter().enumerate() {
+                    let decision = match saved.rule.decision {
+                        RuleDecision::Allow => "allow",
+                        RuleDecision::Deny => "deny",
+                    };
+                    message.push_str(&format!(
+                        "\n{}. {decision} {}",
+                        index + 1,
+                        saved.rule.pattern
+                    ));
+                    if let Some(cwd) = &saved.rule.cwd {
+                        message.push_str(&format!(" in {}", cwd.display()));
+                    }
+                }
+                message
+            }),
+            (Some("remove"), Some(number), None) => match number.parse::<usize>() {
+                Ok(number) if number > 0 => rules.remove(number - 1).map(|removed| {
+                    format!(
+                        "removed approval rule #{number} ({}) from {}",
+                        removed.rule.pattern,
+                        rules.file(removed.source).display()
+                    )
+                }),
+                _ => Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("invalid rule number `{number}`"),
+                )),
+            },
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "usage: /approvals [remove <n>]",
+            )),
+        };
+        match result {
+            Ok(message) => self.conversation_history.add_background_event(message),
+            Err(e) => self.conversation_history.add_error(e.to_string()),
+        }
+        self.conversation_history.scroll_to_bottom();
+        self.request_redraw();
+    }
+
     pub(crate) fn handle_seeky_event(&mut self, event: Event) {
         let Event { id, msg } = event;
         match msg {
@@ -261,12 +317,16 @@ impl ChatWidget<'_> {
                 command,
                 cwd,
                 reason,
+                denial,
+                retry_without_sandbox,
             }) => {
                 let request = ApprovalRequest::Exec {
                     id,
                     command,
                     cwd,
                     reason,
+                    denial,
+                    retry_without_sandbox,
                 };
                 self.bottom_pane.push_approval_request(request);
             }
@@ -363,6 +423,15 @@ impl ChatWidget<'_> {
                 self.bottom_pane
                     .on_history_entry_response(log_id, offset, entry.map(|e| e.text));
             }
+            EventMsg::SearchHistoryResponse(_) => {
+                // The composer does not issue history searches yet.
+            }
+            EventMsg::HistoryCompacted(event) => {
+                // Offsets into the old log no longer resolve; the entries
+                // submitted so far are part of the compacted log.
+                self.bottom_pane
+                    .set_history_metadata(event.history_log_id, event.history_entry_count);
+            }
             event => {
                 self.conversation_history
                     .add_background_event(format!("{event:?}"));
@@ -1,5 +1,6 @@
 use clap::Parser;
 use seeky_common::ApprovalModeCliArg;
+use seeky_common::CliConfigOverrides;
 use seeky_common::SandboxPermissionOption;
 use std::path::PathBuf;
 
@@ -43,4 +44,7 @@ pub struct Cli {
     /// Disable server‑side response storage (sends the full conversation context with every request)
     #[arg(long = "disable-response-storage", default_value_t = false)]
     pub disable_response_storage: bool,
+
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
 }
@@ -105,6 +105,7 @@ impl HistoryCell {
             session_id,
             history_log_id: _,
             history_entry_count: _,
+            project_docs,
         } = event;
         if is_first_event {
             let mut lines: Vec<Line<'static>> = vec![
@@ -131,6 +132,12 @@ impl HistoryCell {
             for (key, value) in entries {
                 lines.push(Line::from(vec![format!("{key}: ").bold(), value.into()]));
             }
+            for doc in project_docs {
+                lines.push(Line::from(vec![
+                    "AGENTS.md: ".bold(),
+                    doc.display().to_string().into(),
+                ]));
+            }
             lines.push(Line::from(""));
             HistoryCell::WelcomeMessage { lines }
         } else if config.model == model {
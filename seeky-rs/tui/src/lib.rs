@@ -65,7 +65,15 @@ pub fn run_main(cli: Cli, seeky_linux_sandbox_exe: Option<PathBuf>) -> std::io::
             seeky_linux_sandbox_exe,
         };
         #[allow(clippy::print_stderr)]
-        match Config::load_with_overrides(overrides) {
+        let cli_overrides = match cli.config_overrides.parse_overrides() {
+            Ok(cli_overrides) => cli_overrides,
+            Err(err) => {
+                eprintln!("Error parsing -c overrides: {err}");
+                std::process::exit(1);
+            }
+        };
+        #[allow(clippy::print_stderr)]
+        match Config::load_with_cli_overrides(cli_overrides, overrides) {
             Ok(config) => config,
             Err(err) => {
                 eprintln!("Error loading configuration: {err}");
@@ -24,6 +24,7 @@ use ratatui::widgets::Widget;
 use ratatui::widgets::WidgetRef;
 use seeky_core::protocol::Op;
 use seeky_core::protocol::ReviewDecision;
+use seeky_core::protocol::SandboxDenial;
 use tui_input::Input;
 use tui_input::backend::crossterm::EventHandler;
 
@@ -39,6 +40,8 @@ pub(crate) enum ApprovalRequest {
         command: Vec<String>,
         cwd: PathBuf,
         reason: Option<String>,
+        denial: Option<SandboxDenial>,
+        retry_without_sandbox: bool,
     },
     ApplyPatch {
         id: String,
@@ -69,6 +72,12 @@ const SELECT_OPTIONS: &[SelectOption] = &[
 
         enters_input_mode: false,
     },
+    SelectOption {
+        label: "Yes, and always approve commands starting with this in this project (p)",
+        decision: Some(ReviewDecision::ApprovedAlways),
+
+        enters_input_mode: false,
+    },
     SelectOption {
         label: "Edit or give feedback (e)",
         decision: None,
@@ -128,6 +137,7 @@ impl UserApprovalWidget<'_> {
                 command,
                 cwd,
                 reason,
+                denial,
                 ..
             } => {
                 let cmd = strip_bash_lc_and_escape(command);
@@ -150,6 +160,15 @@ impl UserApprovalWidget<'_> {
                     contents.push(Line::from(reason.clone().italic()));
                     contents.push(Line::from(""));
                 }
+                if let Some(permission) = denial.as_ref().and_then(|d| d.suggested_permission()) {
+                    contents.push(Line::from(
+                        format!(
+                            "To allow just this instead, restart with `-s {permission}` or add it to `sandbox_permissions`."
+                        )
+                        .dim(),
+                    ));
+                    contents.push(Line::from(""));
+                }
                 contents.extend(vec![Line::from("Allow command?"), Line::from("")]);
                 Paragraph::new(contents)
             }
@@ -196,7 +215,7 @@ impl UserApprovalWidget<'_> {
 
         match self.mode {
             Mode::Select => {
-                let num_option_lines = SELECT_OPTIONS.len() as u16;
+                let num_option_lines = self.select_options().len() as u16;
                 confirmation_prompt_height + num_option_lines + BORDER_LINES
             }
             Mode::Input => {
@@ -212,6 +231,29 @@ impl UserApprovalWidget<'_> {
         }
     }
 
+    /// Approval rules only cover commands, and still run them in the sandbox,
+    /// so neither patches nor retries without the sandbox can be approved
+    /// always.
+    fn can_approve_always(&self) -> bool {
+        matches!(
+            self.approval_request,
+            ApprovalRequest::Exec {
+                retry_without_sandbox: false,
+                ..
+            }
+        )
+    }
+
+    fn select_options(&self) -> Vec<&'static SelectOption> {
+        let can_approve_always = self.can_approve_always();
+        SELECT_OPTIONS
+            .iter()
+            .filter(|opt| {
+                can_approve_always || opt.decision != Some(ReviewDecision::ApprovedAlways)
+            })
+            .collect()
+    }
+
     fn get_confirmation_prompt_height(&self, width: u16) -> u16 {
         // Should cache this for last value of width.
         self.confirmation_prompt.line_count(width) as u16
@@ -230,16 +272,17 @@ impl UserApprovalWidget<'_> {
     }
 
     fn handle_select_key(&mut self, key_event: KeyEvent) {
+        let options = self.select_options();
         match key_event.code {
             KeyCode::Up => {
                 if self.selected_option == 0 {
-                    self.selected_option = SELECT_OPTIONS.len() - 1;
+                    self.selected_option = options.len() - 1;
                 } else {
                     self.selected_option -= 1;
                 }
             }
             KeyCode::Down => {
-                self.selected_option = (self.selected_option + 1) % SELECT_OPTIONS.len();
+                self.selected_option = (self.selected_option + 1) % options.len();
             }
             KeyCode::Char('y') => {
                 self.send_decision(ReviewDecision::Approved);
@@ -247,6 +290,9 @@ impl UserApprovalWidget<'_> {
             KeyCode::Char('a') => {
                 self.send_decision(ReviewDecision::ApprovedForSession);
             }
+            KeyCode::Char('p') if self.can_approve_always() => {
+                self.send_decision(ReviewDecision::ApprovedAlways);
+            }
             KeyCode::Char('n') => {
                 self.send_decision(ReviewDecision::Denied);
             }
@@ -254,7 +300,7 @@ impl UserApprovalWidget<'_> {
                 self.mode = Mode::Input;
             }
             KeyCode::Enter => {
-                let opt = &SELECT_OPTIONS[self.selected_option];
+                let opt = options[self.selected_option];
                 if opt.enters_input_mode {
                     self.mode = Mode::Input;
                 } else if let Some(decision) = opt.decision {
@@ -345,8 +391,9 @@ impl WidgetRef for &UserApprovalWidget<'_> {
         // non-wrapping lines rather than a Paragraph because get_height(Rect)
         // depends on this behavior for its calculation.
         let lines = match self.mode {
-            Mode::Select => SELECT_OPTIONS
-                .iter()
+            Mode::Select => self
+                .select_options()
+                .into_iter()
                 .enumerate()
                 .map(|(idx, opt)| {
                     let (prefix, style) = if idx == self.selected_option {